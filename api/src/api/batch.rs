@@ -0,0 +1,241 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, OnceLock};
+
+use actix_web::{web, HttpResponse};
+
+use crate::{
+    api::solve::{request_solver, solve_stored},
+    config::Config,
+    error::AppError,
+    model_registry::ModelRegistry,
+    models::{
+        ActionFrequency, BatchBoardResult, BatchSolveRequest, BatchSolveResponse, SolveRequest,
+        SolveResponse, TextureReport,
+    },
+    solver::{
+        cards::parse_board,
+        texture::{flop_subset, isomorphism_count},
+        BoardTexture, GameState, Solver,
+    },
+    store::{join_cards, StrategyStore},
+};
+
+/// Batch solve endpoint - solve one spot on many boards
+///
+/// Boards are solved concurrently, at most `BATCH_THREADS` at once across
+/// all batches. Each board gets its own result or error; the report
+/// averages root action frequencies and EVs per board texture, weighting
+/// each board by the raw boards it stands for.
+#[utoipa::path(
+    post,
+    path = "/v1/solve/batch",
+    request_body = BatchSolveRequest,
+    responses(
+        (status = 200, description = "Per-board results and texture report", body = BatchSolveResponse),
+        (status = 422, description = "Validation error", body = ErrorDetail)
+    ),
+    tag = "Solver"
+)]
+pub async fn solve_batch(
+    req: web::Json<BatchSolveRequest>,
+    config: web::Data<Config>,
+    models: Option<web::Data<ModelRegistry>>,
+    store: Option<web::Data<StrategyStore>>,
+) -> Result<HttpResponse, AppError> {
+    let req = req.into_inner();
+
+    let boards = match (&req.boards, &req.flop_subset) {
+        (Some(boards), None) => boards.clone(),
+        (None, Some(name)) => flop_subset(name)
+            .map_err(AppError::ValidationError)?
+            .iter()
+            .map(|flop| join_cards(flop))
+            .collect(),
+        _ => {
+            return Err(AppError::ValidationError(
+                "Give exactly one of 'boards' or 'flop_subset'".to_string(),
+            ))
+        }
+    };
+    if boards.is_empty() {
+        return Err(AppError::ValidationError("Batch has no boards".to_string()));
+    }
+    if boards.len() > config.batch_max_boards {
+        return Err(AppError::ValidationError(format!(
+            "Batch has {} boards (maximum {})",
+            boards.len(),
+            config.batch_max_boards
+        )));
+    }
+
+    // Validate every spot before spending time on any of them
+    let spots: Vec<SolveRequest> = boards.into_iter().map(|board| req.spot(board)).collect();
+    for spot in &spots {
+        GameState::from_request(spot)
+            .map_err(|e| AppError::ValidationError(format!("Board '{}': {}", spot.board, e)))?;
+    }
+
+    let threads = req
+        .max_concurrency
+        .unwrap_or(config.batch_threads)
+        .clamp(1, config.batch_threads.max(1));
+    // One network for the whole batch, even if it is reloaded meanwhile
+    let solver = request_solver(&config, models.as_ref().map(|m| m.get_ref()));
+    let slots = BATCH_SLOTS.get_or_init(|| Slots::new(config.batch_threads));
+    let response = web::block(move || run_batch(spots, threads, slots, store.as_ref().map(|s| s.get_ref()), &solver))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Boards being solved by all batches, sized by the first batch's config
+static BATCH_SLOTS: OnceLock<Slots> = OnceLock::new();
+
+/// Counting semaphore over a thread budget
+struct Slots {
+    free: Mutex<usize>,
+    freed: Condvar,
+}
+
+impl Slots {
+    fn new(count: usize) -> Self {
+        Slots {
+            free: Mutex::new(count.max(1)),
+            freed: Condvar::new(),
+        }
+    }
+
+    /// Wait for a free slot, held until the guard is dropped
+    fn acquire(&self) -> SlotGuard<'_> {
+        let mut free = self.free.lock().unwrap();
+        while *free == 0 {
+            free = self.freed.wait(free).unwrap();
+        }
+        *free -= 1;
+        SlotGuard(self)
+    }
+}
+
+struct SlotGuard<'a>(&'a Slots);
+
+impl Drop for SlotGuard<'_> {
+    fn drop(&mut self) {
+        *self.0.free.lock().unwrap() += 1;
+        self.0.freed.notify_one();
+    }
+}
+
+/// Solve all spots on `threads` threads, each board taking one of the
+/// shared `slots` while it solves, and build the report
+fn run_batch(
+    spots: Vec<SolveRequest>,
+    threads: usize,
+    slots: &Slots,
+    store: Option<&StrategyStore>,
+    solver: &Solver,
+) -> BatchSolveResponse {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<SolveResponse, AppError>>>> =
+        Mutex::new((0..spots.len()).map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.min(spots.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(spot) = spots.get(i) else {
+                    break;
+                };
+                let slot = slots.acquire();
+                let result = solve_stored(spot.clone(), store, solver);
+                drop(slot);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    let boards: Vec<BatchBoardResult> = spots
+        .iter()
+        .zip(results.into_inner().unwrap())
+        .map(|(spot, result)| {
+            let (textures, weight) = parse_board(&spot.board)
+                .map(|cards| (BoardTexture::of(&cards).labels(), isomorphism_count(&cards)))
+                .unwrap_or_default();
+            match result.expect("every board is solved") {
+                Ok(result) => BatchBoardResult {
+                    board: result.board.clone(),
+                    textures,
+                    weight,
+                    ev: result.ev,
+                    result: Some(result),
+                    error: None,
+                },
+                Err(e) => BatchBoardResult {
+                    board: spot.board.clone(),
+                    textures,
+                    weight,
+                    ev: None,
+                    result: None,
+                    error: Some(e.to_string()),
+                },
+            }
+        })
+        .collect();
+
+    BatchSolveResponse {
+        num_boards: boards.len(),
+        textures: texture_reports(&boards),
+        boards,
+    }
+}
+
+/// Average root action frequencies and EVs per texture label, weighting
+/// each board by the raw boards it stands for
+fn texture_reports(boards: &[BatchBoardResult]) -> Vec<TextureReport> {
+    let mut labels = vec!["all".to_string()];
+    for board in boards {
+        for label in &board.textures {
+            if !labels.contains(label) {
+                labels.push(label.clone());
+            }
+        }
+    }
+
+    labels
+        .into_iter()
+        .map(|label| {
+            let group: Vec<(&BatchBoardResult, &SolveResponse)> = boards
+                .iter()
+                .filter(|b| label == "all" || b.textures.contains(&label))
+                .filter_map(|b| Some((b, b.result.as_ref()?)))
+                .collect();
+
+            let total_weight: usize = group.iter().map(|(b, _)| b.weight).sum();
+            let mut actions: Vec<ActionFrequency> = Vec::new();
+            for (board, result) in &group {
+                for action in &result.actions {
+                    let share = action.frequency * board.weight as f64 / total_weight as f64;
+                    match actions.iter_mut().find(|a| a.name == action.name) {
+                        Some(a) => a.frequency += share,
+                        None => actions.push(ActionFrequency {
+                            name: action.name.clone(),
+                            frequency: share,
+                        }),
+                    }
+                }
+            }
+
+            let evs: Vec<(f64, f64)> = group
+                .iter()
+                .filter_map(|(b, _)| Some((b.ev?, b.weight as f64)))
+                .collect();
+            let ev_weight: f64 = evs.iter().map(|(_, w)| w).sum();
+            TextureReport {
+                texture: label,
+                num_boards: group.len(),
+                actions,
+                average_ev: (ev_weight > 0.0).then(|| evs.iter().map(|(ev, w)| ev * w).sum::<f64>() / ev_weight),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,111 @@
+use actix_web::{web, HttpResponse};
+
+use crate::{
+    api::solve::{request_solver, solve_stored},
+    config::Config,
+    error::AppError,
+    hand_history::{hand_id, parse_hand, split_hands},
+    model_registry::ModelRegistry,
+    models::{DecisionReview, HandHistoryResponse, HandReview},
+    solver::{cards::Combo, Solver},
+    store::StrategyStore,
+};
+
+/// Hand history review endpoint - solver recommendations for hero's decisions
+///
+/// Accepts a text file of PokerStars hand histories. Every heads-up postflop
+/// pot hero played is turned into one solve request per hero decision, with
+/// the betting history leading to it. Hands that end preflop or go to the
+/// flop multiway are reported with an error.
+#[utoipa::path(
+    post,
+    path = "/v1/hand-history/review",
+    request_body(content = String, content_type = "text/plain"),
+    responses(
+        (status = 200, description = "Per-hand review", body = HandHistoryResponse),
+        (status = 422, description = "No hands found", body = ErrorDetail)
+    ),
+    tag = "Solver"
+)]
+pub async fn review_hands(
+    body: String,
+    config: web::Data<Config>,
+    models: Option<web::Data<ModelRegistry>>,
+    store: Option<web::Data<StrategyStore>>,
+) -> Result<HttpResponse, AppError> {
+    let hands: Vec<String> = split_hands(&body).into_iter().map(str::to_string).collect();
+    if hands.is_empty() {
+        return Err(AppError::ValidationError("No hand histories found".to_string()));
+    }
+
+    let solver = request_solver(&config, models.as_ref().map(|m| m.get_ref()));
+    let response = web::block(move || {
+        let store = store.as_ref().map(|s| s.get_ref());
+        let reviews: Vec<HandReview> = hands.iter().map(|text| review_hand(text, store, &solver)).collect();
+        HandHistoryResponse {
+            num_hands: reviews.len(),
+            hands: reviews,
+        }
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Solve each of hero's decisions in one hand
+fn review_hand(text: &str, store: Option<&StrategyStore>, solver: &Solver) -> HandReview {
+    let hand = match parse_hand(text) {
+        Ok(hand) => hand,
+        Err(e) => {
+            return HandReview {
+                hand_id: hand_id(text).unwrap_or_default(),
+                hero: None,
+                hero_hand: None,
+                hero_position: None,
+                decisions: Vec::new(),
+                error: Some(e),
+            }
+        }
+    };
+
+    let hero_combo = hand.hero_cards.as_deref().and_then(|c| c.parse::<Combo>().ok());
+    let decisions = hand
+        .hero_decisions()
+        .into_iter()
+        .map(|decision| {
+            let (actions, hero_strategy, error) = match solve_stored(decision.request.clone(), store, solver) {
+                Ok(response) => {
+                    let hero_strategy = response
+                        .combos
+                        .iter()
+                        .find(|c| c.hand.parse::<Combo>().ok().map(|c| c.id) == hero_combo.map(|c| c.id))
+                        .map(|c| c.strategy.clone());
+                    (response.actions, hero_strategy, None)
+                }
+                Err(e) => (Vec::new(), None, Some(e.to_string())),
+            };
+            DecisionReview {
+                board: decision.board,
+                action_taken: decision.action,
+                request: decision.request,
+                actions,
+                hero_strategy,
+                error,
+            }
+        })
+        .collect();
+
+    let error = hand
+        .hero_position()
+        .is_none()
+        .then(|| "Hero did not play the postflop pot".to_string());
+    HandReview {
+        hand_id: hand.hand_id.clone(),
+        hero: hand.hero.clone(),
+        hero_hand: hand.hero_cards.clone(),
+        hero_position: hand.hero_position(),
+        decisions,
+        error,
+    }
+}
@@ -1,8 +1,8 @@
-use actix_web::HttpResponse;
-use crate::models::HealthResponse;
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
+use crate::{config::Config, error::AppError, model_registry::ModelRegistry, models::HealthResponse};
 
 /// Health check endpoint
-/// Returns service status, model availability, and version
+/// Returns service status, loaded models, and version
 #[utoipa::path(
     get,
     path = "/health",
@@ -11,6 +11,76 @@ use crate::models::HealthResponse;
     ),
     tag = "System"
 )]
-pub async fn health() -> HttpResponse {
-    HttpResponse::Ok().json(HealthResponse::default())
+pub async fn health(models: Option<web::Data<ModelRegistry>>) -> HttpResponse {
+    HttpResponse::Ok().json(health_response(models.as_ref().map(|m| m.get_ref())))
+}
+
+/// Reload the value network from its configured file
+///
+/// The new weights are swapped in atomically once they load: solves already
+/// running finish on the previous network. On failure the previous network
+/// stays loaded. Sending the server SIGHUP does the same.
+///
+/// Requires `Authorization: Bearer <ADMIN_TOKEN>`; without `ADMIN_TOKEN`
+/// set the endpoint is disabled and only SIGHUP reloads.
+#[utoipa::path(
+    post,
+    path = "/v1/admin/models/reload",
+    responses(
+        (status = 200, description = "Value network reloaded", body = HealthResponse),
+        (status = 401, description = "Missing or wrong admin token", body = ErrorDetail),
+        (status = 422, description = "No value network configured", body = ErrorDetail),
+        (status = 500, description = "Weights file failed to load", body = ErrorDetail)
+    ),
+    tag = "System"
+)]
+pub async fn reload_models(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    models: Option<web::Data<ModelRegistry>>,
+) -> Result<HttpResponse, AppError> {
+    authorize_admin(&req, &config)?;
+    let Some(models) = models.filter(|m| m.path().is_some()) else {
+        return Err(AppError::ValidationError(
+            "No value network is configured (VALUE_NETWORK_PATH)".to_string(),
+        ));
+    };
+    let registry = models.clone();
+    let model = web::block(move || registry.reload())
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    tracing::info!("Reloaded value network from {} ({})", model.path, model.sha256);
+    Ok(HttpResponse::Ok().json(health_response(Some(&models))))
+}
+
+/// Check the request's bearer token against `ADMIN_TOKEN`
+fn authorize_admin(req: &HttpRequest, config: &Config) -> Result<(), AppError> {
+    let Some(expected) = &config.admin_token else {
+        return Err(AppError::Unauthorized(
+            "Admin endpoints are disabled (set ADMIN_TOKEN)".to_string(),
+        ));
+    };
+    let given = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    // Compare every byte so the time taken doesn't reveal the matching prefix
+    let matches = given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0;
+    if !matches {
+        return Err(AppError::Unauthorized("Missing or wrong admin token".to_string()));
+    }
+    Ok(())
+}
+
+fn health_response(models: Option<&ModelRegistry>) -> HealthResponse {
+    let models = models.map(ModelRegistry::status).unwrap_or_default();
+    HealthResponse {
+        model_loaded: !models.is_empty(),
+        tabular_only: models.is_empty(),
+        models,
+        ..HealthResponse::default()
+    }
 }
@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use actix_web::{web, HttpResponse};
+use futures_util::stream;
+use serde::Serialize;
+
+use crate::{
+    error::AppError,
+    jobs::JobManager,
+    models::{JobStatus, SolveRequest},
+};
+
+/// Interval between progress events on the event stream
+const EVENT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Look up the job manager registered as app data
+fn manager(jobs: Option<web::Data<JobManager>>) -> Result<web::Data<JobManager>, AppError> {
+    jobs.ok_or_else(|| AppError::ServiceUnavailable("Solve jobs are not enabled".to_string()))
+}
+
+/// Create job endpoint - enqueue a solve to run in the background
+#[utoipa::path(
+    post,
+    path = "/v1/jobs",
+    request_body = SolveRequest,
+    responses(
+        (status = 202, description = "Job accepted", body = JobResponse),
+        (status = 429, description = "Job queue is full", body = ErrorDetail)
+    ),
+    tag = "Jobs"
+)]
+pub async fn create_job(
+    req: web::Json<SolveRequest>,
+    jobs: Option<web::Data<JobManager>>,
+) -> Result<HttpResponse, AppError> {
+    let job = manager(jobs)?.submit(req.into_inner())?;
+    Ok(HttpResponse::Accepted().json(job))
+}
+
+/// Get job endpoint - status, progress and, once completed, the result
+#[utoipa::path(
+    get,
+    path = "/v1/jobs/{id}",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Job state", body = JobResponse),
+        (status = 404, description = "Unknown job", body = ErrorDetail)
+    ),
+    tag = "Jobs"
+)]
+pub async fn get_job(
+    id: web::Path<String>,
+    jobs: Option<web::Data<JobManager>>,
+) -> Result<HttpResponse, AppError> {
+    let job = manager(jobs)?.get(&id)?;
+    Ok(HttpResponse::Ok().json(job))
+}
+
+/// Cancel job endpoint - cancel a pending or running job, or delete a finished one
+#[utoipa::path(
+    delete,
+    path = "/v1/jobs/{id}",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Job state after cancellation", body = JobResponse),
+        (status = 404, description = "Unknown job", body = ErrorDetail)
+    ),
+    tag = "Jobs"
+)]
+pub async fn cancel_job(
+    id: web::Path<String>,
+    jobs: Option<web::Data<JobManager>>,
+) -> Result<HttpResponse, AppError> {
+    let job = manager(jobs)?.cancel(&id)?;
+    Ok(HttpResponse::Ok().json(job))
+}
+
+/// Job events endpoint - stream progress as Server-Sent Events
+///
+/// Emits a `progress` event (a `JobProgress` snapshot) every 250ms while the
+/// job is queued or running, then a single final event and closes: `result`
+/// with the full `SolveResponse`, `error` with the failure message, or
+/// `cancelled`.
+#[utoipa::path(
+    get,
+    path = "/v1/jobs/{id}/events",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Event stream (text/event-stream)"),
+        (status = 404, description = "Unknown job", body = ErrorDetail)
+    ),
+    tag = "Jobs"
+)]
+pub async fn job_events(
+    id: web::Path<String>,
+    jobs: Option<web::Data<JobManager>>,
+) -> Result<HttpResponse, AppError> {
+    let manager = manager(jobs)?;
+    let id = id.into_inner();
+    // Reject unknown IDs with a 404 before opening the stream
+    manager.get(&id)?;
+
+    let events = stream::unfold(Some(true), move |state| {
+        let manager = manager.clone();
+        let id = id.clone();
+        async move {
+            let first = state?;
+            if !first {
+                tokio::time::sleep(EVENT_INTERVAL).await;
+            }
+
+            let (event, next) = match manager.get(&id) {
+                Ok(job) => match job.status {
+                    JobStatus::Queued | JobStatus::Running => {
+                        (sse_event("progress", &job.progress), Some(false))
+                    }
+                    JobStatus::Completed => (sse_event("result", &job.result), None),
+                    JobStatus::Failed => (sse_event("error", &job.error), None),
+                    JobStatus::Cancelled => (sse_event("cancelled", &job.progress), None),
+                },
+                // Forgotten via DELETE while streaming
+                Err(e) => (sse_event("error", &e.to_string()), None),
+            };
+            Some((Ok::<_, actix_web::Error>(event), next))
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(events))
+}
+
+/// Format one Server-Sent Event with a JSON payload
+fn sse_event(event: &str, data: &impl Serialize) -> web::Bytes {
+    let data = serde_json::to_string(data).unwrap_or_else(|_| "null".to_string());
+    web::Bytes::from(format!("event: {}\ndata: {}\n\n", event, data))
+}
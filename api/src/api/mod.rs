@@ -1,6 +1,18 @@
+pub mod batch;
+pub mod hand_history;
 pub mod health;
+pub mod jobs;
+pub mod pio;
+pub mod preflop;
 pub mod solve;
+pub mod tree;
 
 // Re-export handlers
-pub use health::health;
+pub use batch::solve_batch;
+pub use hand_history::review_hands;
+pub use health::{health, reload_models};
+pub use jobs::{cancel_job, create_job, get_job, job_events};
+pub use pio::import_pio;
+pub use preflop::solve_preflop;
 pub use solve::solve;
+pub use tree::{export_solve_node, get_solve_node};
@@ -0,0 +1,35 @@
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{error::AppError, models::Player, pio::import_config};
+
+/// Query parameters for POST /v1/import/pio
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ImportQuery {
+    /// Whose strategy the resulting request asks for (default OOP)
+    pub player: Option<Player>,
+}
+
+/// Pio import endpoint - convert a PioSOLVER/GTO+ script or config to a solve request
+///
+/// Accepts a tree-building script (`set_board`, `set_pot`, `set_eff_stack`,
+/// `set_range`, `set_bet_sizes`) or a `#Key#Value` tree config as plain
+/// text. Amounts are read as big blinds and bet sizes are taken from the
+/// board's street.
+#[utoipa::path(
+    post,
+    path = "/v1/import/pio",
+    params(ImportQuery),
+    request_body(content = String, content_type = "text/plain"),
+    responses(
+        (status = 200, description = "Equivalent solve request", body = SolveRequest),
+        (status = 422, description = "Invalid or incomplete config", body = ErrorDetail)
+    ),
+    tag = "Solver"
+)]
+pub async fn import_pio(body: String, query: web::Query<ImportQuery>) -> Result<HttpResponse, AppError> {
+    let request =
+        import_config(&body, query.player.unwrap_or(Player::OOP)).map_err(AppError::ValidationError)?;
+    Ok(HttpResponse::Ok().json(request))
+}
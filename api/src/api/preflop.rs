@@ -0,0 +1,66 @@
+use actix_web::{web, HttpResponse};
+
+use crate::{
+    error::AppError,
+    models::PreflopSolveRequest,
+    solver::{preflop::PreflopTree, EquityTable, PreflopConfig, PreflopSolver, Range, SolverConfig},
+};
+
+/// Preflop solve endpoint - heads-up preflop strategy by hand class
+///
+/// Solves the preflop tree over the 169 hand classes using the equity
+/// table loaded at startup and returns the strategy at the node reached
+/// by `path`.
+#[utoipa::path(
+    post,
+    path = "/v1/solve/preflop",
+    request_body = PreflopSolveRequest,
+    responses(
+        (status = 200, description = "Successfully computed strategy", body = PreflopSolveResponse),
+        (status = 422, description = "Validation error", body = ErrorDetail),
+        (status = 503, description = "No preflop equity table loaded", body = ErrorDetail)
+    ),
+    tag = "Solver"
+)]
+pub async fn solve_preflop(
+    req: web::Json<PreflopSolveRequest>,
+    equity: Option<web::Data<EquityTable>>,
+) -> Result<HttpResponse, AppError> {
+    let req = req.into_inner();
+    let equity = equity.ok_or_else(|| {
+        AppError::ServiceUnavailable(
+            "No preflop equity table loaded (set PREFLOP_EQUITY_TABLE)".to_string(),
+        )
+    })?;
+
+    // Validate everything before starting the solve
+    let config = PreflopConfig::from_request(&req).map_err(AppError::ValidationError)?;
+    PreflopTree::build(&config)
+        .find(&req.path)
+        .map_err(AppError::ValidationError)?;
+
+    let parse_range = |range: &Option<String>| -> Result<Range, AppError> {
+        let range = match range {
+            Some(s) => Range::parse(s).map_err(AppError::ValidationError)?,
+            None => Range::full(),
+        };
+        if range.is_empty() {
+            return Err(AppError::ValidationError("Range cannot be empty".to_string()));
+        }
+        Ok(range)
+    };
+    let sb_range = parse_range(&req.sb_range)?;
+    let bb_range = parse_range(&req.bb_range)?;
+    let iterations = req.iterations.unwrap_or(SolverConfig::default().iterations);
+
+    let response = web::block(move || {
+        let mut solver = PreflopSolver::new(config, [&sb_range, &bb_range], &equity);
+        solver.solve(iterations);
+        solver.node_result(&req.path)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .map_err(AppError::ValidationError)?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -1,16 +1,20 @@
 use actix_web::{web, HttpResponse};
 
 use crate::{
+    config::Config,
     error::AppError,
-    mock_data::{get_mock_actions, get_mock_combos},
+    model_registry::ModelRegistry,
     models::{SolveRequest, SolveResponse},
+    solver::{cards::parse_board, Solver, SolverTables},
+    store::{relabel_response, SpotKey, StrategyStore},
 };
 
 /// Solve endpoint - returns Nash-equilibrium strategy for a game state
 ///
-/// Currently returns **mock data** — all 46 combos from the example
-/// OOP range on board Ah Kd Qc. The real solver will be wired in later
-/// without changing the API shape.
+/// Runs CFR for the configured number of iterations (`SOLVER_ITERATIONS`).
+/// With a value network loaded, flop and turn trees stop where the next
+/// card is dealt; without one turn trees are solved to the river and flop
+/// spots are refused, as their full trees belong in `/v1/jobs`.
 #[utoipa::path(
     post,
     path = "/v1/solve",
@@ -23,24 +27,91 @@ use crate::{
 )]
 pub async fn solve(
     req: web::Json<SolveRequest>,
+    config: web::Data<Config>,
+    models: Option<web::Data<ModelRegistry>>,
+    store: Option<web::Data<StrategyStore>>,
 ) -> Result<HttpResponse, AppError> {
-    // Get mock data
-    let actions = get_mock_actions();
-    let combos = get_mock_combos();
+    let solver = request_solver(&config, models.as_ref().map(|m| m.get_ref()));
+    if !solver.has_value_network() && parse_board(&req.board).is_ok_and(|board| board.len() == 3) {
+        return Err(AppError::ValidationError(
+            "Flop spots are solved to the river without a value network; submit them to /v1/jobs".to_string(),
+        ));
+    }
+    let response = web::block(move || {
+        solve_stored(req.into_inner(), store.as_ref().map(|s| s.get_ref()), &solver)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    Ok(HttpResponse::Ok().json(response))
+}
 
-    // Extract inner SolveRequest
-    let req = req.into_inner();
+/// Serve a request from the strategy store, solving and storing it on a miss
+pub fn solve_stored(
+    req: SolveRequest,
+    store: Option<&StrategyStore>,
+    solver: &Solver,
+) -> Result<SolveResponse, AppError> {
+    let Some(store) = store else {
+        if req.warm_start_from.is_some() {
+            return Err(AppError::ValidationError(
+                "warm_start_from needs a strategy store".to_string(),
+            ));
+        }
+        return solve_request(&req, solver);
+    };
 
-    // Build response matching the request
-    let response = SolveResponse {
-        player: req.player,
-        board: req.board,
-        pot: req.starting_pot,
-        effective_stack: req.effective_stack,
-        num_combos: combos.len(),
-        actions,
-        combos,
+    let key = SpotKey::of(&req, &solver.settings_key()).map_err(AppError::ValidationError)?;
+    // Stored tables are in canonical suits; this spot's suits are the inverse
+    let warm_start = match &req.warm_start_from {
+        Some(id) => {
+            if store.get(id).is_none() {
+                return Err(AppError::NotFound(format!("Stored solve '{}' not found", id)));
+            }
+            store.tables(id).map(|t| t.relabel(&key.permutation.inverse()))
+        }
+        None => None,
     };
+    if let Some(stored) = store.get(&key.hash) {
+        let mut response = relabel_response(&stored, &key.permutation.inverse());
+        response.board = req.board;
+        response.solve_id = Some(key.hash);
+        return Ok(response);
+    }
 
-    Ok(HttpResponse::Ok().json(response))
+    let (mut response, cfr) = solver.solve_from(&req, warm_start.as_ref())?;
+    response.solve_id = Some(key.hash.clone());
+    let stored = store
+        .put(&key.hash, &relabel_response(&response, &key.permutation))
+        .and_then(|_| match cfr {
+            Some(mut cfr) => {
+                let tables = SolverTables::capture(&cfr);
+                store.put_tables(&key.hash, &tables.relabel(&key.permutation))?;
+                store.put_tree(&key.hash, &cfr.solved_tree().relabel(&key.permutation))
+            }
+            None => Ok(()),
+        });
+    if let Err(e) = stored {
+        tracing::warn!("Failed to store solve {}: {}", key.hash, e);
+    }
+    Ok(response)
+}
+
+/// Solver for one request: the configured settings and the value network
+/// loaded right now, so a reload applies from the next solve on
+pub fn request_solver(config: &Config, models: Option<&ModelRegistry>) -> Solver {
+    let solver = Solver::new(config.solver_config());
+    match models.and_then(ModelRegistry::current) {
+        Some(model) => solver
+            .with_value_network(model.network.clone())
+            .with_network_sha256(model.sha256.clone()),
+        None => solver,
+    }
+}
+
+/// Validate a solve request and compute its strategy
+///
+/// Shared by the synchronous endpoint, batches, hand history reviews and
+/// the job workers.
+pub fn solve_request(req: &SolveRequest, solver: &Solver) -> Result<SolveResponse, AppError> {
+    solver.solve_from(req, None).map(|(response, _)| response)
 }
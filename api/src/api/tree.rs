@@ -0,0 +1,222 @@
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    error::AppError,
+    pio::node_csv,
+    models::{HandStrategy, NodeChild, NodeType, SolveNodeResponse, SolveResponse},
+    solver::{
+        cards::Combo,
+        solved_tree::{parse_path, PathStep},
+        strategy_file::PathNode,
+        utils::chips_to_bb,
+        SolvedNodeKind, StrategyFile,
+    },
+    store::{join_cards, StrategyStore},
+};
+
+/// Query parameters for GET /v1/solves/{id}/node
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct NodeQuery {
+    /// Node path from the root, e.g. `x/b33/r50/c/Td` (empty for the root)
+    #[serde(default)]
+    pub path: String,
+}
+
+/// Solve node endpoint - browse any node of a stored solve without re-solving
+///
+/// Path steps are `x` (check), `c` (call), `f` (fold), `a` (all-in),
+/// `b<pct>` / `r<pct>` (bet or raise of that percentage of the pot, matched
+/// to the closest size in the tree) and cards for chance nodes. Stored solves
+/// are kept in canonical suits, so boards and combos may be relabelled
+/// relative to the original request.
+#[utoipa::path(
+    get,
+    path = "/v1/solves/{id}/node",
+    params(
+        ("id" = String, Path, description = "Solve ID returned by /v1/solve"),
+        NodeQuery
+    ),
+    responses(
+        (status = 200, description = "Node strategy and children", body = SolveNodeResponse),
+        (status = 404, description = "Unknown solve", body = ErrorDetail),
+        (status = 422, description = "Invalid path", body = ErrorDetail),
+        (status = 503, description = "Strategy store not enabled", body = ErrorDetail)
+    ),
+    tag = "Solver"
+)]
+pub async fn get_solve_node(
+    id: web::Path<String>,
+    query: web::Query<NodeQuery>,
+    store: Option<web::Data<StrategyStore>>,
+) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+    let solve_id = id.clone();
+    let response = with_node(store, id, &query.path, move |file, found| {
+        Ok(node_response(solve_id, file, found))
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Node export endpoint - a node's strategy as Pio-style CSV
+///
+/// One row per combo reaching the node with its weight, EV and action
+/// frequencies. Paths are as for `/v1/solves/{id}/node`.
+#[utoipa::path(
+    get,
+    path = "/v1/solves/{id}/export",
+    params(
+        ("id" = String, Path, description = "Solve ID returned by /v1/solve"),
+        NodeQuery
+    ),
+    responses(
+        (status = 200, description = "Node strategy as CSV", content_type = "text/csv", body = String),
+        (status = 404, description = "Unknown solve", body = ErrorDetail),
+        (status = 422, description = "Invalid path or not a decision node", body = ErrorDetail),
+        (status = 503, description = "Strategy store not enabled", body = ErrorDetail)
+    ),
+    tag = "Solver"
+)]
+pub async fn export_solve_node(
+    id: web::Path<String>,
+    query: web::Query<NodeQuery>,
+    store: Option<web::Data<StrategyStore>>,
+) -> Result<HttpResponse, AppError> {
+    let csv = with_node(store, id.into_inner(), &query.path, |file, found| {
+        node_csv(&found.node, file.combos(), &found.weights, &found.board).map_err(AppError::ValidationError)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().content_type("text/csv").body(csv))
+}
+
+/// Load a stored solve's tree and run `f` on the node at `path`
+async fn with_node<T, F>(
+    store: Option<web::Data<StrategyStore>>,
+    id: String,
+    path: &str,
+    f: F,
+) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce(&StrategyFile, PathNode) -> Result<T, AppError> + Send + 'static,
+{
+    let store = store
+        .ok_or_else(|| AppError::ServiceUnavailable("The strategy store is not enabled".to_string()))?;
+    let steps = parse_path(path).map_err(AppError::ValidationError)?;
+
+    web::block(move || {
+        let tree = store
+            .tree(&id)
+            .ok_or_else(|| AppError::NotFound(format!("No stored tree for solve '{}'", id)))?;
+        let file = StrategyFile::parse(&tree)?;
+        let found = file.find(&steps).map_err(AppError::ValidationError)?;
+        f(&file, found)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Describe a node and the actions leading out of it
+fn node_response(solve_id: String, file: &StrategyFile, found: PathNode) -> SolveNodeResponse {
+    let PathNode {
+        node,
+        path,
+        board,
+        weights,
+    } = found;
+    let child_path = |step: PathStep| join_path(path.iter().chain(std::iter::once(&step)));
+
+    let (node_type, strategy, children) = match node.kind {
+        SolvedNodeKind::Decision(player) => {
+            let p = player as usize;
+            let mut actions: Vec<_> = node
+                .actions
+                .iter()
+                .filter_map(|a| a.to_action_info(node.pot))
+                .collect();
+
+            // Combos still reaching this node, and the overall action frequencies
+            let mut combos = Vec::new();
+            let mut total_weight = 0.0;
+            let mut ev = 0.0;
+            for (c, (&id, &weight)) in file.combos()[p].iter().zip(&weights[p]).enumerate() {
+                let Some(combo) = Combo::from_id(id) else { continue };
+                if weight <= 0.0 || combo.is_blocked_by(&board) {
+                    continue;
+                }
+                let strategy: Vec<f64> = node.combo_strategy(c).iter().map(|&x| x as f64).collect();
+                for (action, &prob) in actions.iter_mut().zip(&strategy) {
+                    action.frequency += weight * prob;
+                }
+                total_weight += weight;
+                ev += weight * node.evs[c] as f64;
+                combos.push(HandStrategy {
+                    hand: combo.to_string(),
+                    hand_id: id as u32,
+                    strategy,
+                });
+            }
+            if total_weight > 0.0 {
+                for action in &mut actions {
+                    action.frequency /= total_weight;
+                }
+            }
+
+            let children = node
+                .actions
+                .iter()
+                .zip(&actions)
+                .map(|(action, info)| NodeChild {
+                    name: info.name.clone(),
+                    path: child_path(PathStep::of(action, &node)),
+                    frequency: info.frequency,
+                })
+                .collect();
+            let strategy = SolveResponse {
+                player,
+                board: join_cards(&board),
+                pot: chips_to_bb(node.pot),
+                effective_stack: chips_to_bb(node.stack),
+                num_combos: combos.len(),
+                actions,
+                combos,
+                ev: (total_weight > 0.0).then(|| ev / total_weight),
+                hand_classes: None,
+                solve_id: Some(solve_id.clone()),
+            };
+            (NodeType::Decision, Some(strategy), children)
+        }
+        SolvedNodeKind::Chance => {
+            let children = node
+                .actions
+                .iter()
+                .map(|action| {
+                    let step = PathStep::of(action, &node);
+                    NodeChild {
+                        name: step.to_string(),
+                        path: child_path(step),
+                        frequency: 1.0 / node.actions.len() as f64,
+                    }
+                })
+                .collect();
+            (NodeType::Chance, None, children)
+        }
+        SolvedNodeKind::Terminal => (NodeType::Terminal, None, Vec::new()),
+    };
+
+    SolveNodeResponse {
+        solve_id,
+        path: join_path(path.iter()),
+        node_type,
+        node: strategy,
+        children,
+    }
+}
+
+fn join_path<'a>(steps: impl Iterator<Item = &'a PathStep>) -> String {
+    steps.map(|s| s.to_string()).collect::<Vec<_>>().join("/")
+}
@@ -0,0 +1,440 @@
+//! Offline command-line interface to the solver
+//!
+//! Solves spots into strategy files and queries them, checks ranges and
+//! computes preflop equities without running the HTTP server.
+
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use deeppdcfr_mock_server::{
+    models::{BetSizes, Player, SolveRequest},
+    solver::{
+        cards::{parse_board, Combo},
+        class_weights,
+        datagen::{generate_sample, load_dataset},
+        solved_tree::{parse_path, PathStep},
+        strategy_file::{self, WriteOptions},
+        trainer::train,
+        utils::chips_to_bb,
+        DataGenConfig, DatasetWriter, EquityTable, Range, Rng, SolvedNodeKind, Solver, SolverConfig,
+        StrategyFile, TrainConfig, ValueNetwork,
+    },
+    store::join_cards,
+    pio::node_csv,
+};
+
+#[derive(Parser)]
+#[command(name = "deeppdcfr-cli", version, about = "Solve and query poker spots offline")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve a spot and write its strategy file
+    Solve(SolveArgs),
+    /// Print the strategy table of a node in a strategy file
+    Show(ShowArgs),
+    /// Preflop equity of one range against another
+    Equity(EquityArgs),
+    /// Parse a range and print it normalised with its combo counts
+    Range(RangeArgs),
+    /// Solve random subgames into a value network training dataset
+    Datagen(DatagenArgs),
+    /// Train a value network on a dataset
+    Train(TrainArgs),
+}
+
+#[derive(Args)]
+struct SolveArgs {
+    /// SolveRequest JSON file ('-' for stdin). Overrides the spot flags.
+    #[arg(long, conflicts_with_all = ["board", "pot", "stack"])]
+    request: Option<PathBuf>,
+    /// Board cards, e.g. "Ah Kd Qc"
+    #[arg(long, required_unless_present = "request")]
+    board: Option<String>,
+    /// Starting pot in bb
+    #[arg(long, required_unless_present = "request")]
+    pot: Option<f64>,
+    /// Effective stack in bb
+    #[arg(long, required_unless_present = "request")]
+    stack: Option<f64>,
+    /// Player whose strategy is solved (oop or ip)
+    #[arg(long, default_value = "oop", value_parser = parse_player)]
+    player: Player,
+    /// OOP range in Pio syntax (default: all combos)
+    #[arg(long)]
+    oop_range: Option<String>,
+    /// IP range in Pio syntax (default: all combos)
+    #[arg(long)]
+    ip_range: Option<String>,
+    /// CFR iterations
+    #[arg(long, default_value_t = SolverConfig::default().iterations)]
+    iterations: usize,
+    /// Bets and raises before only checks, calls and folds remain
+    #[arg(long, default_value_t = SolverConfig::default().max_depth)]
+    max_depth: usize,
+    /// Value network weights; trees then stop at the next street instead of
+    /// being solved in full
+    #[arg(long, env = "VALUE_NETWORK_PATH")]
+    value_network: Option<PathBuf>,
+    /// Strategy file to write
+    #[arg(short, long)]
+    out: PathBuf,
+    /// Compress the strategy file
+    #[arg(long)]
+    compress: bool,
+    /// Store probabilities in 8 bits instead of 16
+    #[arg(long)]
+    narrow: bool,
+}
+
+#[derive(Args)]
+struct ShowArgs {
+    /// Strategy file written by `solve` or the strategy store
+    file: PathBuf,
+    /// Node path from the root, e.g. `x/b33/c/Td` (empty for the root)
+    #[arg(long, default_value = "")]
+    path: String,
+    /// Print Pio-style CSV instead of a table
+    #[arg(long)]
+    csv: bool,
+}
+
+#[derive(Args)]
+struct EquityArgs {
+    /// Hero range in Pio syntax
+    hero: String,
+    /// Villain range in Pio syntax
+    villain: String,
+    /// Preflop equity table (169x169 text format)
+    #[arg(long, env = "PREFLOP_EQUITY_TABLE")]
+    table: PathBuf,
+}
+
+#[derive(Args)]
+struct RangeArgs {
+    /// Range in Pio syntax
+    range: String,
+    /// Board cards; combos blocked by them are removed
+    #[arg(long)]
+    board: Option<String>,
+}
+
+#[derive(Args)]
+struct DatagenArgs {
+    /// Dataset directory; shards are added after any already there
+    #[arg(short, long)]
+    out: PathBuf,
+    /// Number of subgames to solve
+    #[arg(long, default_value_t = 100)]
+    samples: usize,
+    /// Samples per shard file
+    #[arg(long, default_value_t = 1000)]
+    shard_size: usize,
+    /// Board cards at the street boundary (3 or 4)
+    #[arg(long, default_value_t = 4)]
+    board_cards: usize,
+    /// Smallest and largest pot in bb
+    #[arg(long, num_args = 2, default_values_t = [4.0, 40.0])]
+    pot: Vec<f64>,
+    /// Smallest and largest effective stack in bb
+    #[arg(long, num_args = 2, default_values_t = [5.0, 100.0])]
+    stack: Vec<f64>,
+    /// Chance of each combo being in a sampled range
+    #[arg(long, default_value_t = 0.05)]
+    density: f64,
+    /// Bet sizes for both players (Pio syntax)
+    #[arg(long, default_value = "50, a")]
+    bets: String,
+    /// Raise sizes for both players (Pio syntax)
+    #[arg(long, default_value = "a")]
+    raises: String,
+    /// Bets and raises per subgame
+    #[arg(long, default_value_t = 3)]
+    max_depth: usize,
+    /// Exploitability to solve to, in % of the pot
+    #[arg(long, default_value_t = 1.0)]
+    exploitability: f64,
+    /// Iteration limit per subgame
+    #[arg(long, default_value_t = 1000)]
+    max_iterations: usize,
+    /// River value network, so 3-card subgames stop before the river
+    #[arg(long)]
+    value_network: Option<PathBuf>,
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+#[derive(Args)]
+struct TrainArgs {
+    /// Dataset directory written by `datagen`
+    #[arg(long)]
+    data: PathBuf,
+    /// Weights file to write
+    #[arg(short, long)]
+    out: PathBuf,
+    /// Hidden layer sizes, e.g. "256,256"
+    #[arg(long, value_delimiter = ',', default_value = "256,256")]
+    hidden: Vec<usize>,
+    #[arg(long, default_value_t = 20)]
+    epochs: usize,
+    #[arg(long, default_value_t = 32)]
+    batch_size: usize,
+    #[arg(long, default_value_t = 1e-3)]
+    learning_rate: f64,
+    /// Share of the samples held out for validation
+    #[arg(long, default_value_t = 0.1)]
+    validation: f64,
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Solve(args) => solve(args),
+        Command::Show(args) => show(args),
+        Command::Equity(args) => equity(args),
+        Command::Range(args) => range(args),
+        Command::Datagen(args) => datagen(args),
+        Command::Train(args) => train_network(args),
+    }
+}
+
+fn parse_player(s: &str) -> Result<Player, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "oop" => Ok(Player::OOP),
+        "ip" => Ok(Player::IP),
+        _ => Err(format!("Unknown player '{}' (expected oop or ip)", s)),
+    }
+}
+
+fn solve(args: SolveArgs) -> Result<()> {
+    let request = match &args.request {
+        Some(path) => {
+            let json = if path.as_os_str() == "-" {
+                let mut json = String::new();
+                std::io::stdin().read_to_string(&mut json)?;
+                json
+            } else {
+                fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?
+            };
+            serde_json::from_str(&json).context("Invalid SolveRequest JSON")?
+        }
+        None => SolveRequest {
+            player: args.player,
+            board: args.board.unwrap_or_default(),
+            effective_stack: args.stack.unwrap_or_default(),
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: args.pot.unwrap_or_default(),
+            bet_sizes: None,
+            root_bet: None,
+            betting_history: None,
+            oop_range: args.oop_range,
+            ip_range: args.ip_range,
+            rake: None,
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        },
+    };
+
+    let mut solver = Solver::new(SolverConfig {
+        iterations: args.iterations,
+        max_depth: args.max_depth,
+        ..SolverConfig::default()
+    });
+    if let Some(path) = &args.value_network {
+        solver = solver.with_value_network(Arc::new(ValueNetwork::load(path).map_err(|e| anyhow!(e))?));
+    }
+    let (response, cfr) = solver.solve_from(&request, None)?;
+    let tree = cfr.context("The solve kept no strategy tree")?.solved_tree();
+    let options = WriteOptions {
+        compress: args.compress,
+        wide: !args.narrow,
+    };
+    let bytes = strategy_file::write(&tree, options).map_err(|e| anyhow!(e))?;
+    fs::write(&args.out, &bytes).with_context(|| format!("Cannot write {}", args.out.display()))?;
+
+    println!(
+        "{:?} on {}: {} combos, pot {} bb, stack {} bb",
+        response.player, response.board, response.num_combos, response.pot, response.effective_stack
+    );
+    for action in &response.actions {
+        println!("  {:<12} {:>6.1}%", action.name, action.frequency * 100.0);
+    }
+    println!("Wrote {} nodes ({} bytes) to {}", tree.nodes.len(), bytes.len(), args.out.display());
+    Ok(())
+}
+
+fn show(args: ShowArgs) -> Result<()> {
+    let bytes = fs::read(&args.file).with_context(|| format!("Cannot read {}", args.file.display()))?;
+    let file = StrategyFile::parse(&bytes).map_err(|e| anyhow!(e))?;
+    let steps = parse_path(&args.path).map_err(|e| anyhow!(e))?;
+    let found = file.find(&steps).map_err(|e| anyhow!(e))?;
+    let node = &found.node;
+
+    if args.csv {
+        print!("{}", node_csv(node, file.combos(), &found.weights, &found.board).map_err(|e| anyhow!(e))?);
+        return Ok(());
+    }
+
+    println!(
+        "Board {}, pot {} bb, stack {} bb",
+        join_cards(&found.board),
+        chips_to_bb(node.pot),
+        chips_to_bb(node.stack)
+    );
+    let player = match node.kind {
+        SolvedNodeKind::Decision(player) => player,
+        SolvedNodeKind::Chance => {
+            let cards: Vec<String> = node.actions.iter().map(|a| PathStep::of(a, node).to_string()).collect();
+            println!("Chance node: {}", cards.join(" "));
+            return Ok(());
+        }
+        SolvedNodeKind::Terminal => {
+            println!("Terminal node");
+            return Ok(());
+        }
+    };
+
+    let names: Vec<String> = node
+        .actions
+        .iter()
+        .filter_map(|a| a.to_action_info(node.pot))
+        .map(|info| info.name)
+        .collect();
+    println!("{:?} to act", player);
+    print!("{:<6} {:>7} {:>8}", "Hand", "Weight", "EV");
+    for name in &names {
+        print!(" {:>9}", name);
+    }
+    println!();
+
+    let p = player as usize;
+    let mut totals = vec![0.0; names.len()];
+    let mut total_weight = 0.0;
+    for (c, (&id, &weight)) in file.combos()[p].iter().zip(&found.weights[p]).enumerate() {
+        let Some(combo) = Combo::from_id(id) else { continue };
+        if weight <= 0.0 || combo.is_blocked_by(&found.board) {
+            continue;
+        }
+        print!("{:<6} {:>7.4} {:>8.3}", combo.to_string(), weight, node.evs[c]);
+        for (total, &prob) in totals.iter_mut().zip(node.combo_strategy(c)) {
+            *total += weight * prob as f64;
+            print!(" {:>8.1}%", prob * 100.0);
+        }
+        println!();
+        total_weight += weight;
+    }
+    if total_weight > 0.0 {
+        print!("{:<6} {:>7.2} {:>8}", "Total", total_weight, "");
+        for total in totals {
+            print!(" {:>8.1}%", total / total_weight * 100.0);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+fn equity(args: EquityArgs) -> Result<()> {
+    let table = EquityTable::load(&args.table).map_err(|e| anyhow!(e))?;
+    let hero = Range::parse(&args.hero).map_err(|e| anyhow!(e))?;
+    let villain = Range::parse(&args.villain).map_err(|e| anyhow!(e))?;
+    let Some(equity) = table.range_equity(&class_weights(&hero), &class_weights(&villain)) else {
+        bail!("Both ranges must contain at least one combo");
+    };
+
+    println!("Hero    ({:.1} combos): {:.2}%", hero.total_weight(), equity * 100.0);
+    println!("Villain ({:.1} combos): {:.2}%", villain.total_weight(), (1.0 - equity) * 100.0);
+    Ok(())
+}
+
+fn range(args: RangeArgs) -> Result<()> {
+    let mut range = Range::parse(&args.range).map_err(|e| anyhow!(e))?;
+    let mut available = 1326;
+    if let Some(board) = &args.board {
+        let cards = parse_board(board).map_err(|e| anyhow!(e))?;
+        range = range.filter_blocked(&cards);
+        available = Range::full().filter_blocked(&cards).len();
+    }
+
+    println!("{}", range);
+    println!(
+        "{} combos ({:.1} weighted), {:.1}% of {} possible",
+        range.get_combos().iter().filter(|&&(_, freq)| freq > 0.0).count(),
+        range.total_weight(),
+        range.total_weight() * 100.0 / available as f64,
+        available
+    );
+    Ok(())
+}
+
+fn datagen(args: DatagenArgs) -> Result<()> {
+    let value_network = match &args.value_network {
+        Some(path) => Some(Arc::new(ValueNetwork::load(path).map_err(|e| anyhow!(e))?)),
+        None => None,
+    };
+    let config = DataGenConfig {
+        board_cards: args.board_cards,
+        pot: (args.pot[0], args.pot[1]),
+        stack: (args.stack[0], args.stack[1]),
+        range_density: args.density,
+        bet_sizes: BetSizes {
+            oop_bet: args.bets.clone(),
+            oop_raise: args.raises.clone(),
+            ip_bet: args.bets,
+            ip_raise: args.raises,
+        },
+        max_depth: args.max_depth,
+        target_exploitability: args.exploitability,
+        max_iterations: args.max_iterations,
+        value_network,
+        ..DataGenConfig::default()
+    };
+
+    let mut rng = Rng::new(args.seed);
+    let mut writer = DatasetWriter::create(&args.out, args.shard_size).map_err(|e| anyhow!(e))?;
+    for i in 1..=args.samples {
+        let (sample, stats) = generate_sample(&config, &mut rng).map_err(|e| anyhow!(e))?;
+        writer.push(sample).map_err(|e| anyhow!(e))?;
+        println!(
+            "Sample {}/{}: {} iterations, exploitability {:.2}% of the pot",
+            i, args.samples, stats.iterations, stats.exploitability
+        );
+    }
+    let shards = writer.finish().map_err(|e| anyhow!(e))?;
+    println!("{} now holds {} shards", args.out.display(), shards);
+    Ok(())
+}
+
+fn train_network(args: TrainArgs) -> Result<()> {
+    let samples = load_dataset(&args.data).map_err(|e| anyhow!(e))?;
+    println!("Loaded {} samples from {}", samples.len(), args.data.display());
+    let config = TrainConfig {
+        hidden: args.hidden,
+        epochs: args.epochs,
+        batch_size: args.batch_size,
+        learning_rate: args.learning_rate,
+        validation_fraction: args.validation,
+        seed: args.seed,
+    };
+
+    let (network, _) = train(&samples, &config, |report| match report.validation_loss {
+        Some(validation) => println!(
+            "Epoch {:>3}: train loss {:.6}, validation loss {:.6}",
+            report.epoch, report.train_loss, validation
+        ),
+        None => println!("Epoch {:>3}: train loss {:.6}", report.epoch, report.train_loss),
+    })
+    .map_err(|e| anyhow!(e))?;
+    fs::write(&args.out, network.to_bytes()).with_context(|| format!("Cannot write {}", args.out.display()))?;
+    println!("Wrote {} parameters to {}", network.num_parameters(), args.out.display());
+    Ok(())
+}
@@ -1,5 +1,7 @@
 use std::net::SocketAddr;
 
+use crate::solver::SolverConfig;
+
 /// Server configuration
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -11,6 +13,38 @@ pub struct Config {
     pub description: String,
     /// API version
     pub version: String,
+    /// Preflop equity table file (`PREFLOP_EQUITY_TABLE`). Preflop solving is disabled without it.
+    pub preflop_equity_path: Option<String>,
+    /// Value network weights file (`VALUE_NETWORK_PATH`). Without it trees are solved in full.
+    pub value_network_path: Option<String>,
+    /// Bearer token for the admin endpoints (`ADMIN_TOKEN`). They are disabled without it.
+    pub admin_token: Option<String>,
+    /// CFR iterations per solve (`SOLVER_ITERATIONS`)
+    pub solver_iterations: usize,
+    /// Bets and raises before only checks, calls and folds remain (`SOLVER_MAX_DEPTH`)
+    pub solver_max_depth: usize,
+    /// Number of solve worker threads for async jobs (`JOB_WORKERS`)
+    pub job_workers: usize,
+    /// Maximum number of queued jobs before POST /v1/jobs returns 429 (`JOB_QUEUE_CAPACITY`)
+    pub job_queue_capacity: usize,
+    /// Iterations between progress reports of running jobs (`JOB_PROGRESS_EVERY`)
+    pub job_progress_every: usize,
+    /// Seconds a finished job's result is kept (`JOB_RESULT_TTL_SECS`)
+    pub job_result_ttl_secs: usize,
+    /// Directory of checkpoints of running jobs (`JOB_CHECKPOINT_DIR`). Jobs can't resume without it.
+    pub job_checkpoint_dir: Option<String>,
+    /// Iterations between job checkpoints (`JOB_CHECKPOINT_EVERY`)
+    pub job_checkpoint_every: usize,
+    /// Boards solved at once across all batch solves (`BATCH_THREADS`)
+    pub batch_threads: usize,
+    /// Maximum boards per batch solve (`BATCH_MAX_BOARDS`)
+    pub batch_max_boards: usize,
+    /// Directory of stored solves (`STRATEGY_STORE_DIR`). Solves are not stored without it.
+    pub strategy_store_dir: Option<String>,
+    /// Maximum total size of stored solves in bytes (`STRATEGY_STORE_MAX_BYTES`)
+    pub strategy_store_max_bytes: usize,
+    /// Maximum age of a stored solve in seconds (`STRATEGY_STORE_MAX_AGE_SECS`)
+    pub strategy_store_max_age_secs: usize,
 }
 
 impl Default for Config {
@@ -20,6 +54,25 @@ impl Default for Config {
             title: "DeepPDCFR Solver API".to_string(),
             description: "REST API for querying Nash-equilibrium strategies in No-Limit Hold'em. Uses PioSOLVER syntax for bet sizes and hand ranges.".to_string(),
             version: "0.1.0".to_string(),
+            preflop_equity_path: std::env::var("PREFLOP_EQUITY_TABLE").ok(),
+            value_network_path: std::env::var("VALUE_NETWORK_PATH").ok(),
+            admin_token: std::env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty()),
+            solver_iterations: env_or("SOLVER_ITERATIONS", SolverConfig::default().iterations),
+            solver_max_depth: env_or("SOLVER_MAX_DEPTH", SolverConfig::default().max_depth),
+            job_workers: env_or("JOB_WORKERS", 2),
+            job_queue_capacity: env_or("JOB_QUEUE_CAPACITY", 16),
+            job_progress_every: env_or("JOB_PROGRESS_EVERY", 10),
+            job_result_ttl_secs: env_or("JOB_RESULT_TTL_SECS", 3600),
+            job_checkpoint_dir: std::env::var("JOB_CHECKPOINT_DIR").ok(),
+            job_checkpoint_every: env_or("JOB_CHECKPOINT_EVERY", 100),
+            batch_threads: env_or(
+                "BATCH_THREADS",
+                std::thread::available_parallelism().map_or(1, |n| n.get()),
+            ),
+            batch_max_boards: env_or("BATCH_MAX_BOARDS", 1755),
+            strategy_store_dir: std::env::var("STRATEGY_STORE_DIR").ok(),
+            strategy_store_max_bytes: env_or("STRATEGY_STORE_MAX_BYTES", 1 << 30),
+            strategy_store_max_age_secs: env_or("STRATEGY_STORE_MAX_AGE_SECS", 7 * 24 * 3600),
         }
     }
 }
@@ -27,8 +80,26 @@ impl Default for Config {
 impl Config {
     /// Create a new config with custom port
     pub fn with_port(port: u16) -> Self {
-        let mut config = Self::default();
-        config.addr = format!("0.0.0.0:{}", port).parse().unwrap();
-        config
+        Self {
+            addr: format!("0.0.0.0:{}", port).parse().unwrap(),
+            ..Self::default()
+        }
+    }
+
+    /// Settings every solve runs with
+    pub fn solver_config(&self) -> SolverConfig {
+        SolverConfig {
+            iterations: self.solver_iterations,
+            max_depth: self.solver_max_depth,
+            ..SolverConfig::default()
+        }
     }
 }
+
+/// Read a numeric setting from the environment, falling back to a default
+fn env_or(name: &str, default: usize) -> usize {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
@@ -8,6 +8,9 @@ use utoipa::ToSchema;
 pub enum AppError {
     ValidationError(String),
     NotFound(String),
+    Unauthorized(String),
+    ServiceUnavailable(String),
+    TooManyRequests(String),
     Internal(String),
 }
 
@@ -16,6 +19,9 @@ impl fmt::Display for AppError {
         match self {
             AppError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
+            AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            AppError::ServiceUnavailable(msg) => write!(f, "Service unavailable: {}", msg),
+            AppError::TooManyRequests(msg) => write!(f, "Too many requests: {}", msg),
             AppError::Internal(msg) => write!(f, "Internal error: {}", msg),
         }
     }
@@ -35,6 +41,9 @@ impl ResponseError for AppError {
         match self {
             AppError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
             AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -43,6 +52,9 @@ impl ResponseError for AppError {
         let (error_code, message) = match self {
             AppError::ValidationError(msg) => ("validation_error", msg.clone()),
             AppError::NotFound(msg) => ("not_found", msg.clone()),
+            AppError::Unauthorized(msg) => ("unauthorized", msg.clone()),
+            AppError::ServiceUnavailable(msg) => ("service_unavailable", msg.clone()),
+            AppError::TooManyRequests(msg) => ("too_many_requests", msg.clone()),
             AppError::Internal(msg) => ("internal_error", msg.clone()),
         };
 
@@ -0,0 +1,485 @@
+//! Hand history parsing
+//!
+//! Extracts heads-up postflop spots from PokerStars-style text hand
+//! histories: positions, stacks and pot at the flop, the board, and the
+//! postflop action sequence as a `betting_history`. Amounts are converted to
+//! big blinds using the blinds in the hand header.
+//!
+//! Bets are recorded as a percentage of the pot before the bet, raises as
+//! the raise increment over a call as a percentage of the pot after calling
+//! (the same convention as raise sizes), and all-ins as `allin`.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    models::{ActionType, HistoryAction, Player, SolveRequest},
+    solver::{cards::parse_board, Card},
+    store::join_cards,
+};
+
+/// A heads-up postflop spot read from a hand history
+#[derive(Debug, Clone)]
+pub struct ParsedHand {
+    pub hand_id: String,
+    /// Player the hole cards were dealt to, if shown
+    pub hero: Option<String>,
+    /// Hero's hole cards. e.g. 'AhKd'
+    pub hero_cards: Option<String>,
+    /// Names of the OOP and IP players
+    pub players: [String; 2],
+    /// Spot at the start of the flop (for OOP, without history)
+    pub spot: SolveRequest,
+    /// Postflop actions in order, including turn and river deals
+    pub actions: Vec<HistoryAction>,
+}
+
+/// One of hero's postflop decisions
+#[derive(Debug, Clone)]
+pub struct HeroDecision {
+    /// Spot for hero with the history leading to the decision
+    pub request: SolveRequest,
+    /// Board at the decision
+    pub board: String,
+    /// What hero did
+    pub action: HistoryAction,
+}
+
+impl ParsedHand {
+    /// Hero's position, if hero played the postflop pot
+    pub fn hero_position(&self) -> Option<Player> {
+        let hero = self.hero.as_ref()?;
+        if *hero == self.players[0] {
+            Some(Player::OOP)
+        } else if *hero == self.players[1] {
+            Some(Player::IP)
+        } else {
+            None
+        }
+    }
+
+    /// A solve request for each of hero's postflop decisions
+    pub fn hero_decisions(&self) -> Vec<HeroDecision> {
+        let Some(position) = self.hero_position() else {
+            return Vec::new();
+        };
+
+        let mut board = self.spot.board.clone();
+        let mut decisions = Vec::new();
+        for (i, action) in self.actions.iter().enumerate() {
+            if action.action == ActionType::Deal {
+                if let Some(card) = &action.card {
+                    board = format!("{} {}", board, card);
+                }
+                continue;
+            }
+            if action.position != position {
+                continue;
+            }
+
+            let mut request = self.spot.clone();
+            request.player = position;
+            request.betting_history = (i > 0).then(|| self.actions[..i].to_vec());
+            decisions.push(HeroDecision {
+                request,
+                board: board.clone(),
+                action: action.clone(),
+            });
+        }
+        decisions
+    }
+}
+
+/// Split a file into individual hand histories
+pub fn split_hands(text: &str) -> Vec<&str> {
+    let mut starts: Vec<usize> = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim_start().starts_with("PokerStars ") {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+    starts.push(text.len());
+    starts.windows(2).map(|w| text[w[0]..w[1]].trim()).collect()
+}
+
+/// Hand number from the header line
+pub fn hand_id(text: &str) -> Option<String> {
+    let header = text.lines().next()?;
+    let rest = &header[header.find('#')? + 1..];
+    let id: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    (!id.is_empty()).then_some(id)
+}
+
+/// Big blind from the last `(small/big` group in the header
+fn big_blind(header: &str) -> Option<f64> {
+    header.match_indices('(').rev().find_map(|(i, _)| {
+        let (_, big) = header[i + 1..].split_once('/')?;
+        let big = big.split(|c: char| c == ')' || c.is_whitespace()).next()?;
+        parse_amount(big).filter(|&bb| bb > 0.0)
+    })
+}
+
+/// Parse an amount such as `$1,250.50` or `1500`
+fn parse_amount(s: &str) -> Option<f64> {
+    let digits: String = s
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '$' | '€' | '£' | ','))
+        .collect();
+    digits.parse().ok().filter(|v: &f64| v.is_finite() && *v >= 0.0)
+}
+
+/// First amount in a string
+fn first_amount(s: &str) -> Result<f64, String> {
+    s.split_whitespace()
+        .next()
+        .and_then(parse_amount)
+        .ok_or_else(|| format!("Invalid amount in '{}'", s))
+}
+
+/// Cards of the last `[...]` group on a line
+fn last_cards(line: &str) -> Result<Vec<Card>, String> {
+    let start = line.rfind('[').ok_or_else(|| format!("No cards in '{}'", line))?;
+    let end = line[start..].find(']').ok_or_else(|| format!("No cards in '{}'", line))?;
+    parse_board(&line[start + 1..start + end])
+}
+
+/// Running state of a hand
+#[derive(Default)]
+struct Table {
+    /// Seat number, name and starting stack in chips
+    seats: Vec<(u32, String, f64)>,
+    button: Option<u32>,
+    /// Chips put in by each player over the hand
+    contributed: HashMap<String, f64>,
+    /// Chips put in by each player on the current street
+    street_bets: HashMap<String, f64>,
+    folded: HashSet<String>,
+}
+
+impl Table {
+    fn pot(&self) -> f64 {
+        self.contributed.values().sum()
+    }
+
+    fn put_in(&mut self, name: &str, amount: f64, street_bet: bool) {
+        *self.contributed.entry(name.to_string()).or_default() += amount;
+        if street_bet {
+            *self.street_bets.entry(name.to_string()).or_default() += amount;
+        }
+    }
+
+    fn street_bet(&self, name: &str) -> f64 {
+        self.street_bets.get(name).copied().unwrap_or(0.0)
+    }
+
+    /// Player whose name prefixes an action line, preferring the longest name
+    fn actor<'a>(&self, line: &'a str) -> Option<(String, &'a str)> {
+        self.seats
+            .iter()
+            .filter_map(|(_, name, _)| Some((name, line.strip_prefix(name.as_str())?.strip_prefix(": ")?)))
+            .max_by_key(|(name, _)| name.len())
+            .map(|(name, rest)| (name.clone(), rest))
+    }
+
+    /// Order the two remaining players as [OOP, IP]: OOP sits first after the button
+    fn positions(&self, players: &[&(u32, String, f64)]) -> Result<[String; 2], String> {
+        let button = self.button.ok_or("Missing button seat")?;
+        let max_seat = self.seats.iter().map(|s| s.0).max().unwrap_or(button).max(button);
+        let distance = |seat: u32| (seat + max_seat - button - 1) % max_seat;
+        let mut players = players.to_vec();
+        players.sort_by_key(|(seat, _, _)| distance(*seat));
+        Ok([players[0].1.clone(), players[1].1.clone()])
+    }
+}
+
+/// Parse one hand into its heads-up postflop spot
+///
+/// Fails for hands that end before the flop or see it multiway.
+pub fn parse_hand(text: &str) -> Result<ParsedHand, String> {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    let header = lines.next().ok_or("Empty hand history")?;
+    let hand_id = hand_id(header).ok_or("Missing hand number in the header")?;
+    let big_blind = big_blind(header).ok_or("Missing blinds in the header")?;
+    let bb = |chips: f64| (chips / big_blind * 100.0).round() / 100.0;
+
+    let mut table = Table::default();
+    let mut hero = None;
+    let mut hero_cards = None;
+    let mut dealt = false;
+    // Set once the flop is dealt: players, board and spot
+    let mut flop: Option<([String; 2], Vec<Card>, SolveRequest)> = None;
+    let mut actions: Vec<HistoryAction> = Vec::new();
+    let mut push = |position, action, amount_percent, card: Option<Card>| {
+        actions.push(HistoryAction {
+            order: actions.len() as u32 + 1,
+            position,
+            action,
+            amount_percent,
+            card: card.map(|c| c.to_string()),
+        });
+    };
+
+    for line in lines {
+        if let Some(marker) = line.strip_prefix("*** ") {
+            if marker.starts_with("HOLE CARDS") {
+                dealt = true;
+            } else if marker.starts_with("FLOP") {
+                let board = last_cards(line)?;
+                let remaining: Vec<_> = table
+                    .seats
+                    .iter()
+                    .filter(|(_, name, _)| !table.folded.contains(name))
+                    .collect();
+                if remaining.len() != 2 {
+                    return Err(format!("{} players saw the flop; only heads-up pots are supported", remaining.len()));
+                }
+                let players = table.positions(&remaining)?;
+                let stack = |name: &String| {
+                    let (_, _, chips) = table.seats.iter().find(|(_, n, _)| n == name).unwrap();
+                    bb(chips - table.contributed.get(name).copied().unwrap_or(0.0))
+                };
+                let stacks = [stack(&players[0]), stack(&players[1])];
+                let spot = SolveRequest {
+                    player: Player::OOP,
+                    board: join_cards(&board),
+                    effective_stack: stacks[0].min(stacks[1]),
+                    oop_stack: Some(stacks[0]),
+                    ip_stack: Some(stacks[1]),
+                    starting_pot: bb(table.pot()),
+                    bet_sizes: None,
+                    root_bet: None,
+                    betting_history: None,
+                    oop_range: None,
+                    ip_range: None,
+                    rake: None,
+                    node_locks: None,
+                    group_by_class: false,
+                    warm_start_from: None,
+                };
+                flop = Some((players, board, spot));
+                table.street_bets.clear();
+            } else if marker.starts_with("TURN") || marker.starts_with("RIVER") {
+                let (_, board, _) = flop.as_mut().ok_or("Turn or river without a flop")?;
+                let card = *last_cards(line)?.last().ok_or("Missing dealt card")?;
+                board.push(card);
+                push(Player::OOP, ActionType::Deal, None, Some(card));
+                table.street_bets.clear();
+            } else if marker.starts_with("SHOW DOWN") || marker.starts_with("SUMMARY") {
+                break;
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("Table ") {
+            table.button = rest
+                .split("Seat #")
+                .nth(1)
+                .and_then(|s| s.split_whitespace().next())
+                .and_then(|s| s.parse().ok());
+            continue;
+        }
+        if !dealt {
+            if let Some(rest) = line.strip_prefix("Seat ") {
+                let (seat, rest) = rest.split_once(": ").ok_or_else(|| format!("Invalid seat line '{}'", line))?;
+                let chips_at = rest.find(" in chips").ok_or_else(|| format!("Invalid seat line '{}'", line))?;
+                let open = rest[..chips_at].rfind(" (").ok_or_else(|| format!("Invalid seat line '{}'", line))?;
+                let seat = seat.parse().map_err(|_| format!("Invalid seat line '{}'", line))?;
+                let stack = first_amount(&rest[open + 2..chips_at])?;
+                table.seats.push((seat, rest[..open].to_string(), stack));
+                continue;
+            }
+        }
+        if let Some(rest) = line.strip_prefix("Dealt to ") {
+            if let Some(open) = rest.find(" [") {
+                let cards = last_cards(rest)?;
+                hero = Some(rest[..open].to_string());
+                hero_cards = Some(cards.iter().map(|c| c.to_string()).collect());
+            }
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+            let amount = first_amount(rest.split(')').next().unwrap_or(""))?;
+            if let Some(name) = rest.split(" returned to ").nth(1) {
+                table.put_in(name.trim(), -amount, true);
+            }
+            continue;
+        }
+
+        let Some((name, verb)) = table.actor(line) else {
+            continue;
+        };
+        let all_in = verb.ends_with("and is all-in");
+        let verb = verb.trim_end_matches("and is all-in").trim();
+        let position = match &flop {
+            Some((players, _, _)) => players.iter().position(|p| *p == name).map(|i| [Player::OOP, Player::IP][i]),
+            None => None,
+        };
+
+        if let Some(rest) = verb.strip_prefix("posts ") {
+            let is_ante = rest.starts_with("the ante");
+            let amount = rest.rsplit(' ').next().and_then(parse_amount).ok_or_else(|| format!("Invalid post '{}'", line))?;
+            table.put_in(&name, amount, !is_ante);
+        } else if verb == "folds" {
+            table.folded.insert(name);
+            if let Some(position) = position {
+                push(position, ActionType::Fold, None, None);
+            }
+        } else if verb == "checks" {
+            if let Some(position) = position {
+                push(position, ActionType::Check, None, None);
+            }
+        } else if let Some(rest) = verb.strip_prefix("calls ") {
+            if let Some(position) = position {
+                push(position, if all_in { ActionType::Allin } else { ActionType::Call }, None, None);
+            }
+            table.put_in(&name, first_amount(rest)?, true);
+        } else if let Some(rest) = verb.strip_prefix("bets ") {
+            let amount = first_amount(rest)?;
+            if let Some(position) = position {
+                let pct = amount / table.pot().max(f64::EPSILON) * 100.0;
+                if all_in {
+                    push(position, ActionType::Allin, None, None);
+                } else {
+                    push(position, ActionType::Bet, Some((pct * 10.0).round() / 10.0), None);
+                }
+            }
+            table.put_in(&name, amount, true);
+        } else if let Some(rest) = verb.strip_prefix("raises ") {
+            let to = rest
+                .split(" to ")
+                .nth(1)
+                .ok_or_else(|| format!("Invalid raise '{}'", line))
+                .and_then(first_amount)?;
+            let own = table.street_bet(&name);
+            if let Some(position) = position {
+                let facing = table.street_bets.values().copied().fold(0.0, f64::max);
+                let pot_after_call = table.pot() + (facing - own);
+                let pct = (to - facing) / pot_after_call.max(f64::EPSILON) * 100.0;
+                if all_in {
+                    push(position, ActionType::Allin, None, None);
+                } else {
+                    push(position, ActionType::Raise, Some((pct * 10.0).round() / 10.0), None);
+                }
+            }
+            table.put_in(&name, to - own, true);
+        }
+    }
+
+    let (players, _, spot) = flop.ok_or("The hand ended before the flop")?;
+    Ok(ParsedHand {
+        hand_id,
+        hero,
+        hero_cards,
+        players,
+        spot,
+        actions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAND: &str = "\
+PokerStars Hand #245871234567: Hold'em No Limit ($0.50/$1.00 USD) - 2024/03/01 20:15:00 ET
+Table 'Alcor II' 6-max Seat #3 is the button
+Seat 1: Villain A ($104.50 in chips)
+Seat 2: Hero ($100 in chips)
+Seat 3: btn_player ($87.25 in chips)
+Seat 4: Fish ($250 in chips)
+Villain A: posts small blind $0.50
+Hero: posts big blind $1
+*** HOLE CARDS ***
+Dealt to Hero [Ah Kd]
+btn_player: raises $1.50 to $2.50
+Fish: folds
+Villain A: folds
+Hero: calls $1.50
+*** FLOP *** [Ac 7d 2h]
+Hero: checks
+btn_player: bets $3
+Hero: raises $7 to $10
+btn_player: calls $7
+*** TURN *** [Ac 7d 2h] [9s]
+Hero: bets $15.50
+btn_player: folds
+Uncalled bet ($15.50) returned to Hero
+Hero collected $25.50 from pot
+*** SUMMARY ***
+Total pot $25.50 | Rake $0
+Seat 1: Villain A (small blind) folded before Flop
+";
+
+    #[test]
+    fn test_parse_hand() {
+        let hand = parse_hand(HAND).unwrap();
+        assert_eq!(hand.hand_id, "245871234567");
+        assert_eq!(hand.hero.as_deref(), Some("Hero"));
+        assert_eq!(hand.hero_cards.as_deref(), Some("AhKd"));
+        // Big blind is first to act after the button
+        assert_eq!(hand.players, ["Hero".to_string(), "btn_player".to_string()]);
+        assert_eq!(hand.hero_position(), Some(Player::OOP));
+
+        assert_eq!(hand.spot.board, "Ac 7d 2h");
+        assert_eq!(hand.spot.starting_pot, 5.5);
+        assert_eq!(hand.spot.oop_stack, Some(97.5));
+        assert_eq!(hand.spot.ip_stack, Some(84.75));
+        assert_eq!(hand.spot.effective_stack, 84.75);
+
+        let actions: Vec<(Player, ActionType, Option<f64>)> = hand
+            .actions
+            .iter()
+            .map(|a| (a.position, a.action.clone(), a.amount_percent))
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                (Player::OOP, ActionType::Check, None),
+                (Player::IP, ActionType::Bet, Some(54.5)),
+                // 7 over a call, into 5.5 + 3 + 3
+                (Player::OOP, ActionType::Raise, Some(60.9)),
+                (Player::IP, ActionType::Call, None),
+                (Player::OOP, ActionType::Deal, None),
+                (Player::OOP, ActionType::Bet, Some(60.8)),
+                (Player::IP, ActionType::Fold, None),
+            ]
+        );
+        assert_eq!(hand.actions[4].card.as_deref(), Some("9s"));
+    }
+
+    #[test]
+    fn test_hero_decisions() {
+        let hand = parse_hand(HAND).unwrap();
+        let decisions = hand.hero_decisions();
+        assert_eq!(decisions.len(), 3);
+        assert!(decisions[0].request.betting_history.is_none());
+        assert_eq!(decisions[1].action.action, ActionType::Raise);
+        assert_eq!(decisions[1].request.betting_history.as_ref().unwrap().len(), 2);
+        assert_eq!(decisions[2].board, "Ac 7d 2h 9s");
+        assert_eq!(decisions[2].request.betting_history.as_ref().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_split_and_reject_hands() {
+        let preflop = "\
+PokerStars Hand #1: Tournament #9, $1+$0.10 USD Hold'em No Limit - Level I (10/20) - 2024/03/01
+Table '9 1' 9-max Seat #1 is the button
+Seat 1: A (1500 in chips)
+Seat 2: B (1500 in chips)
+A: posts small blind 10
+B: posts big blind 20
+*** HOLE CARDS ***
+A: folds
+Uncalled bet (10) returned to B
+*** SUMMARY ***
+";
+        let text = format!("{}\n\n{}", HAND, preflop);
+        let hands = split_hands(&text);
+        assert_eq!(hands.len(), 2);
+        assert_eq!(hand_id(hands[1]).as_deref(), Some("1"));
+        assert_eq!(parse_hand(hands[1]).unwrap_err(), "The hand ended before the flop");
+        assert_eq!(big_blind(hands[1].lines().next().unwrap()), Some(20.0));
+    }
+}
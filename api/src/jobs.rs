@@ -0,0 +1,387 @@
+//! Asynchronous solve jobs
+//!
+//! Jobs are queued on a bounded channel and run by a fixed pool of worker
+//! threads. Status and progress live in a shared table polled by the HTTP
+//! handlers; cancellation is cooperative through `JobControl`. Finished,
+//! failed and cancelled jobs are dropped from the table by the first
+//! lookup or submission after their result TTL.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{
+    error::AppError,
+    models::{ActionInfo, JobProgress, JobResponse, JobStatus, SolveRequest, SolveResponse},
+};
+
+/// Function that runs one solve, reporting progress through the control
+///
+/// The control is shared so solver callbacks can hold on to it.
+pub type SolveFn = dyn Fn(SolveRequest, &Arc<JobControl>) -> Result<SolveResponse, AppError> + Send + Sync;
+
+/// Progress reporting and cancellation for a running job
+#[derive(Debug)]
+pub struct JobControl {
+    cancelled: AtomicBool,
+    progress: Mutex<JobProgress>,
+    started: Mutex<Option<Instant>>,
+}
+
+impl JobControl {
+    fn new(total_iterations: usize) -> Self {
+        JobControl {
+            cancelled: AtomicBool::new(false),
+            progress: Mutex::new(JobProgress {
+                iteration: 0,
+                total_iterations,
+                exploitability: None,
+                eta_seconds: None,
+                root_actions: None,
+            }),
+            started: Mutex::new(None),
+        }
+    }
+
+    /// Check if the job has been cancelled (solvers should stop early)
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Record the latest iteration and exploitability
+    ///
+    /// The ETA is extrapolated from the time spent so far.
+    pub fn report(&self, iteration: usize, exploitability: Option<f64>) {
+        let elapsed = self
+            .started
+            .lock()
+            .unwrap()
+            .map(|start| start.elapsed().as_secs_f64());
+
+        let mut progress = self.progress.lock().unwrap();
+        progress.iteration = iteration.min(progress.total_iterations);
+        progress.exploitability = exploitability;
+        progress.eta_seconds = match elapsed {
+            Some(elapsed) if iteration > 0 => {
+                let remaining = progress.total_iterations - progress.iteration;
+                Some(elapsed / iteration as f64 * remaining as f64)
+            }
+            _ => None,
+        };
+    }
+
+    /// Record the current root strategy (actions with their frequencies)
+    pub fn report_root_strategy(&self, actions: Vec<ActionInfo>) {
+        self.progress.lock().unwrap().root_actions = Some(actions);
+    }
+
+    /// Snapshot of the current progress
+    pub fn progress(&self) -> JobProgress {
+        self.progress.lock().unwrap().clone()
+    }
+
+    fn start(&self) {
+        *self.started.lock().unwrap() = Some(Instant::now());
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A queued, running or finished job
+struct Job {
+    status: JobStatus,
+    control: Arc<JobControl>,
+    result: Option<SolveResponse>,
+    error: Option<String>,
+    /// When the job completed, failed or was cancelled
+    finished: Option<Instant>,
+}
+
+impl Job {
+    fn to_response(&self, id: &str) -> JobResponse {
+        JobResponse {
+            job_id: id.to_string(),
+            status: self.status,
+            progress: self.control.progress(),
+            result: self.result.clone(),
+            error: self.error.clone(),
+        }
+    }
+}
+
+type JobTable = Arc<Mutex<HashMap<String, Job>>>;
+
+/// Bounded job queue with a fixed worker pool
+#[derive(Clone)]
+pub struct JobManager {
+    jobs: JobTable,
+    queue: SyncSender<(String, SolveRequest)>,
+    next_id: Arc<AtomicU64>,
+    total_iterations: usize,
+    result_ttl: Duration,
+}
+
+impl JobManager {
+    /// Start `workers` worker threads behind a queue of `capacity` pending jobs
+    ///
+    /// `total_iterations` is the iteration count reported for progress.
+    pub fn new(workers: usize, capacity: usize, total_iterations: usize, solve: Arc<SolveFn>) -> Self {
+        let (queue, receiver) = mpsc::sync_channel(capacity.max(1));
+        let receiver = Arc::new(Mutex::new(receiver));
+        let jobs: JobTable = Arc::new(Mutex::new(HashMap::new()));
+
+        for i in 0..workers.max(1) {
+            let receiver = Arc::clone(&receiver);
+            let jobs = Arc::clone(&jobs);
+            let solve = Arc::clone(&solve);
+            thread::Builder::new()
+                .name(format!("solve-worker-{}", i))
+                .spawn(move || worker_loop(receiver, jobs, solve))
+                .expect("failed to spawn solve worker");
+        }
+
+        JobManager {
+            jobs,
+            queue,
+            next_id: Arc::new(AtomicU64::new(1)),
+            total_iterations,
+            result_ttl: Duration::from_secs(3600),
+        }
+    }
+
+    /// Forget finished jobs this long after they finish (default one hour)
+    pub fn with_result_ttl(mut self, ttl: Duration) -> Self {
+        self.result_ttl = ttl;
+        self
+    }
+
+    /// Enqueue a solve and return its initial state
+    ///
+    /// Fails with `TooManyRequests` when the queue is full.
+    pub fn submit(&self, request: SolveRequest) -> Result<JobResponse, AppError> {
+        let id = format!("job-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let job = Job {
+            status: JobStatus::Queued,
+            control: Arc::new(JobControl::new(self.total_iterations)),
+            result: None,
+            error: None,
+            finished: None,
+        };
+        let response = job.to_response(&id);
+
+        // Insert before sending so a fast worker always finds the job
+        let mut jobs = self.live_jobs();
+        jobs.insert(id.clone(), job);
+        drop(jobs);
+        match self.queue.try_send((id.clone(), request)) {
+            Ok(()) => Ok(response),
+            Err(e) => {
+                self.jobs.lock().unwrap().remove(&id);
+                Err(match e {
+                    TrySendError::Full(_) => {
+                        AppError::TooManyRequests("Job queue is full, retry later".to_string())
+                    }
+                    TrySendError::Disconnected(_) => {
+                        AppError::Internal("Job workers have stopped".to_string())
+                    }
+                })
+            }
+        }
+    }
+
+    /// Current state of a job
+    pub fn get(&self, id: &str) -> Result<JobResponse, AppError> {
+        let jobs = self.live_jobs();
+        let job = jobs.get(id).ok_or_else(|| job_not_found(id))?;
+        Ok(job.to_response(id))
+    }
+
+    /// Cancel a queued or running job, or forget a finished one
+    ///
+    /// Running jobs stop at the solver's next cancellation check.
+    pub fn cancel(&self, id: &str) -> Result<JobResponse, AppError> {
+        let mut jobs = self.live_jobs();
+        let job = jobs.get_mut(id).ok_or_else(|| job_not_found(id))?;
+        match job.status {
+            JobStatus::Queued | JobStatus::Running => {
+                job.control.cancel();
+                job.status = JobStatus::Cancelled;
+                job.finished = Some(Instant::now());
+                Ok(job.to_response(id))
+            }
+            _ => Ok(jobs.remove(id).unwrap().to_response(id)),
+        }
+    }
+
+    /// Lock the job table with the jobs past their result TTL dropped
+    fn live_jobs(&self) -> MutexGuard<'_, HashMap<String, Job>> {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.retain(|_, job| job.finished.is_none_or(|at| at.elapsed() < self.result_ttl));
+        jobs
+    }
+}
+
+fn job_not_found(id: &str) -> AppError {
+    AppError::NotFound(format!("Job '{}' not found", id))
+}
+
+/// Pull jobs off the queue until the manager is dropped
+fn worker_loop(receiver: Arc<Mutex<Receiver<(String, SolveRequest)>>>, jobs: JobTable, solve: Arc<SolveFn>) {
+    loop {
+        // Hold the lock only while waiting, not while solving
+        let next = receiver.lock().unwrap().recv();
+        let Ok((id, request)) = next else {
+            return;
+        };
+
+        let control = {
+            let mut jobs = jobs.lock().unwrap();
+            match jobs.get_mut(&id) {
+                Some(job) if job.status == JobStatus::Queued => {
+                    job.status = JobStatus::Running;
+                    Arc::clone(&job.control)
+                }
+                // Cancelled (or forgotten) while queued
+                _ => continue,
+            }
+        };
+
+        control.start();
+        let result = solve(request, &control);
+
+        let mut jobs = jobs.lock().unwrap();
+        let Some(job) = jobs.get_mut(&id) else {
+            continue;
+        };
+        if job.status == JobStatus::Cancelled {
+            continue;
+        }
+        job.finished = Some(Instant::now());
+        match result {
+            Ok(response) => {
+                control.report(control.progress().total_iterations, control.progress().exploitability);
+                job.status = JobStatus::Completed;
+                job.result = Some(response);
+            }
+            Err(e) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{Solver, SolverConfig};
+    use std::time::Duration;
+
+    fn request() -> SolveRequest {
+        serde_json::from_value(serde_json::json!({
+            "player": "OOP",
+            "board": "Ah Kd Qc 7s 2h",
+            "effective_stack": 100,
+            "starting_pot": 20,
+            "oop_range": "AA,KK",
+            "ip_range": "TT,99"
+        }))
+        .unwrap()
+    }
+
+    /// Solve function that reports progress until cancelled or `iterations` are done
+    fn slow_solve(iterations: usize) -> Arc<SolveFn> {
+        Arc::new(move |req, control| {
+            for i in 1..=iterations {
+                if control.is_cancelled() {
+                    return Err(AppError::Internal("cancelled".to_string()));
+                }
+                thread::sleep(Duration::from_millis(5));
+                control.report(i, Some(1.0 / i as f64));
+            }
+            let solver = Solver::new(SolverConfig {
+                iterations: 5,
+                ..SolverConfig::default()
+            });
+            let response = solver.solve(&req)?;
+            control.report_root_strategy(response.actions.clone());
+            Ok(response)
+        })
+    }
+
+    fn wait_for(manager: &JobManager, id: &str, status: JobStatus) -> JobResponse {
+        for _ in 0..400 {
+            let job = manager.get(id).unwrap();
+            if job.status == status {
+                return job;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        panic!("job {} never reached {:?}", id, status);
+    }
+
+    #[test]
+    fn test_job_completes() {
+        let manager = JobManager::new(1, 4, 10, slow_solve(10));
+        let job = manager.submit(request()).unwrap();
+        assert_eq!(job.status, JobStatus::Queued);
+
+        let done = wait_for(&manager, &job.job_id, JobStatus::Completed);
+        assert_eq!(done.progress.iteration, 10);
+        assert_eq!(done.progress.eta_seconds, Some(0.0));
+        assert!(done.result.is_some());
+        assert_eq!(done.progress.root_actions.unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_queue_full_and_cancel() {
+        let manager = JobManager::new(1, 1, 1000, slow_solve(1000));
+        let running = manager.submit(request()).unwrap();
+        wait_for(&manager, &running.job_id, JobStatus::Running);
+
+        // One pending slot, then the queue is full
+        let queued = manager.submit(request()).unwrap();
+        assert!(matches!(
+            manager.submit(request()),
+            Err(AppError::TooManyRequests(_))
+        ));
+
+        let cancelled = manager.cancel(&queued.job_id).unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+        let cancelled = manager.cancel(&running.job_id).unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+
+        assert!(matches!(manager.get("job-999"), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_finished_jobs_expire() {
+        let manager = JobManager::new(1, 4, 1, slow_solve(1)).with_result_ttl(Duration::from_millis(50));
+        let done = manager.submit(request()).unwrap();
+        wait_for(&manager, &done.job_id, JobStatus::Completed);
+        let failed = manager.submit(SolveRequest { board: "Ah Ah Qc".to_string(), ..request() }).unwrap();
+        wait_for(&manager, &failed.job_id, JobStatus::Failed);
+
+        // Kept until the TTL has passed, then dropped by the next lookup
+        assert!(manager.get(&done.job_id).is_ok());
+        thread::sleep(Duration::from_millis(60));
+        assert!(matches!(manager.get(&done.job_id), Err(AppError::NotFound(_))));
+        assert!(matches!(manager.cancel(&failed.job_id), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_failed_job() {
+        let manager = JobManager::new(1, 4, 10, slow_solve(1));
+        let mut req = request();
+        req.board = "Ah Ah Qc".to_string();
+        let job = manager.submit(req).unwrap();
+
+        let failed = wait_for(&manager, &job.job_id, JobStatus::Failed);
+        assert!(failed.error.unwrap().contains("Duplicate"));
+    }
+}
@@ -1,23 +1,48 @@
 pub mod api;
 pub mod config;
 pub mod error;
-pub mod mock_data;
+pub mod hand_history;
+pub mod jobs;
+pub mod model_registry;
 pub mod models;
+pub mod pio;
 pub mod solver;
+pub mod store;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
 use actix_web::web;
 use actix_cors::Cors;
+use sha2::{Digest, Sha256};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::{
-    api::{health, solve},
+    api::{
+        cancel_job, create_job, export_solve_node, get_job, get_solve_node, health, import_pio,
+        job_events, reload_models, review_hands, solve, solve_batch, solve_preflop,
+    },
     config::Config,
     error::ErrorDetail,
+    jobs::{JobControl, JobManager},
+    model_registry::ModelRegistry,
+    solver::{CheckpointConfig, ProgressFn},
+    store::StrategyStore,
     models::{
-        health::HealthResponse,
-        request::{ActionType, BetSizes, HistoryAction, Player, SolveRequest},
-        response::{ActionInfo, ActionTypeResponse, HandStrategy, SolveResponse},
+        health::{HealthResponse, ModelStatus},
+        request::{
+            ActionType, BatchSolveRequest, BetSizes, ComboLock, HandCategory, HistoryAction, LockRule, NodeLock, Player,
+            PreflopPosition, PreflopSizes, PreflopSolveRequest, RakeConfig, RootBet,
+            SolveRequest,
+        },
+        response::{
+            ActionFrequency, ActionInfo, ActionTypeResponse, BatchBoardResult,
+            BatchSolveResponse, DecisionReview, HandClassStrategy, HandHistoryResponse, HandReview, HandStrategy, JobProgress,
+            JobResponse, JobStatus, NodeChild, NodeType, PreflopSolveResponse, SolveNodeResponse,
+            SolveResponse, TextureReport,
+        },
     },
 };
 
@@ -31,26 +56,64 @@ use crate::{
     ),
     paths(
         api::health::health,
+        api::health::reload_models,
         api::solve::solve,
+        api::preflop::solve_preflop,
+        api::batch::solve_batch,
+        api::tree::get_solve_node,
+        api::tree::export_solve_node,
+        api::pio::import_pio,
+        api::hand_history::review_hands,
+        api::jobs::create_job,
+        api::jobs::get_job,
+        api::jobs::cancel_job,
+        api::jobs::job_events,
     ),
     components(
         schemas(
             HealthResponse,
+            ModelStatus,
             SolveRequest,
             SolveResponse,
             BetSizes,
             HistoryAction,
+            NodeLock,
+            ComboLock,
+            LockRule,
+            HandCategory,
+            RakeConfig,
+            RootBet,
             Player,
             ActionType,
             ActionInfo,
             ActionTypeResponse,
             HandStrategy,
+            PreflopSolveRequest,
+            PreflopSolveResponse,
+            PreflopSizes,
+            PreflopPosition,
+            HandClassStrategy,
+            BatchSolveRequest,
+            BatchSolveResponse,
+            BatchBoardResult,
+            TextureReport,
+            ActionFrequency,
+            JobResponse,
+            JobStatus,
+            JobProgress,
+            SolveNodeResponse,
+            NodeType,
+            NodeChild,
+            HandHistoryResponse,
+            HandReview,
+            DecisionReview,
             ErrorDetail,
         )
     ),
     tags(
         (name = "System", description = "System endpoints"),
-        (name = "Solver", description = "Poker solver endpoints")
+        (name = "Solver", description = "Poker solver endpoints"),
+        (name = "Jobs", description = "Asynchronous solve jobs")
     )
 )]
 struct ApiDoc;
@@ -60,7 +123,18 @@ pub fn configure_app(cfg: &mut web::ServiceConfig) {
     use actix_web::HttpResponse;
 
     cfg.route("/health", web::get().to(health))
+        .route("/v1/admin/models/reload", web::post().to(reload_models))
         .route("/v1/solve", web::post().to(solve))
+        .route("/v1/solve/preflop", web::post().to(solve_preflop))
+        .route("/v1/solve/batch", web::post().to(solve_batch))
+        .route("/v1/solves/{id}/node", web::get().to(get_solve_node))
+        .route("/v1/solves/{id}/export", web::get().to(export_solve_node))
+        .route("/v1/import/pio", web::post().to(import_pio))
+        .route("/v1/hand-history/review", web::post().to(review_hands))
+        .route("/v1/jobs", web::post().to(create_job))
+        .route("/v1/jobs/{id}", web::get().to(get_job))
+        .route("/v1/jobs/{id}", web::delete().to(cancel_job))
+        .route("/v1/jobs/{id}/events", web::get().to(job_events))
         // Redirect /docs to /docs/
         .route("/docs", web::get().to(|| async {
             HttpResponse::PermanentRedirect()
@@ -82,6 +156,97 @@ pub fn create_cors() -> Cors {
         .allow_any_header()
 }
 
+/// Open the strategy store if one is configured
+pub fn create_strategy_store(config: &Config) -> Result<Option<web::Data<StrategyStore>>, String> {
+    let Some(dir) = &config.strategy_store_dir else {
+        return Ok(None);
+    };
+    let store = StrategyStore::open(
+        dir,
+        config.strategy_store_max_bytes as u64,
+        Duration::from_secs(config.strategy_store_max_age_secs as u64),
+    )?;
+    Ok(Some(web::Data::new(store)))
+}
+
+/// Load the configured value network, if any
+pub fn create_model_registry(config: &Config) -> Result<web::Data<ModelRegistry>, String> {
+    ModelRegistry::open(config.value_network_path.clone()).map(web::Data::new)
+}
+
+/// Create the solve job queue and its worker pool
+///
+/// Each job takes the value network loaded when it starts and reports its
+/// iteration, exploitability and root strategy every `JOB_PROGRESS_EVERY`
+/// iterations, stopping there once cancelled. With
+/// `JOB_CHECKPOINT_DIR` set, jobs checkpoint as they solve: a job for the
+/// same request after a crash or cancellation resumes where that one
+/// stopped. Finished and failed jobs remove their checkpoint, so a retry of
+/// a failed job starts over.
+pub fn create_job_manager(
+    config: &Config,
+    store: Option<web::Data<StrategyStore>>,
+    models: Option<web::Data<ModelRegistry>>,
+) -> JobManager {
+    let config = config.clone();
+    if let Some(dir) = &config.job_checkpoint_dir {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            tracing::warn!("Cannot create job checkpoint directory {}: {}", dir, e);
+        }
+    }
+    let result_ttl = Duration::from_secs(config.job_result_ttl_secs as u64);
+    JobManager::new(
+        config.job_workers,
+        config.job_queue_capacity,
+        config.solver_iterations,
+        Arc::new(move |req, control| {
+            let mut solver = api::solve::request_solver(&config, models.as_ref().map(|m| m.get_ref()));
+            solver = solver.with_progress(config.job_progress_every, job_progress(control, config.solver_iterations));
+            let checkpoint = config.job_checkpoint_dir.as_ref().map(|dir| job_checkpoint_path(dir, &req));
+            if let Some(path) = &checkpoint {
+                solver = solver.with_checkpoint(CheckpointConfig {
+                    path: path.clone(),
+                    every: config.job_checkpoint_every,
+                });
+            }
+            let result = api::solve::solve_stored(req, store.as_ref().map(|s| s.get_ref()), &solver);
+            if let Some(path) = &checkpoint {
+                if !control.is_cancelled() {
+                    let _ = std::fs::remove_file(path);
+                }
+            }
+            result
+        }),
+    )
+    .with_result_ttl(result_ttl)
+}
+
+/// Progress callback of a job's solver
+///
+/// Reports the iteration out of `total`, the exploitability and the root
+/// strategy so far, and stops the solve once the job is cancelled.
+pub fn job_progress(control: &Arc<JobControl>, total: usize) -> Arc<ProgressFn> {
+    let control = Arc::clone(control);
+    Arc::new(move |cfr, remaining| {
+        control.report(total.saturating_sub(remaining), Some(cfr.exploitability()));
+        if let Ok((root, results)) = cfr.node_strategy(&[]) {
+            let actions = solver::action_frequencies(&root.state, &root.actions(), &results);
+            control.report_root_strategy(actions);
+        }
+        !control.is_cancelled()
+    })
+}
+
+/// Checkpoint file of a job, named by a hash of its request
+///
+/// The request as sent, suits included, since checkpoints hold the tables
+/// of one exact tree.
+pub fn job_checkpoint_path(dir: impl AsRef<Path>, request: &SolveRequest) -> PathBuf {
+    let bytes = serde_json::to_vec(request).unwrap_or_default();
+    let hash: String = Sha256::digest(&bytes).iter().map(|b| format!("{:02x}", b)).collect();
+    dir.as_ref().join(format!("{}.ckpt", hash))
+}
+
 /// Get server configuration
 pub fn get_config() -> Config {
     Config::default()
@@ -1,5 +1,9 @@
-use deeppdcfr_mock_server::{configure_app, create_cors, create_swagger, get_config};
-use actix_web::{App, HttpServer};
+use deeppdcfr_mock_server::{
+    configure_app, create_cors, create_job_manager, create_model_registry, create_strategy_store,
+    create_swagger, get_config,
+    solver::EquityTable,
+};
+use actix_web::{web, App, HttpServer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[actix_web::main]
@@ -17,15 +21,83 @@ async fn main() -> std::io::Result<()> {
     let config = get_config();
     let addr = config.addr;
 
+    // Load the preflop equity table if configured
+    let equity = match &config.preflop_equity_path {
+        Some(path) => {
+            let table = EquityTable::load(path).map_err(std::io::Error::other)?;
+            tracing::info!("Loaded preflop equity table from {}", path);
+            Some(web::Data::new(table))
+        }
+        None => {
+            tracing::warn!("PREFLOP_EQUITY_TABLE not set; preflop solving is disabled");
+            None
+        }
+    };
+
+    // Load the value network if configured
+    let models = create_model_registry(&config).map_err(std::io::Error::other)?;
+    match models.current() {
+        Some(model) => tracing::info!(
+            "Loaded value network from {} ({} parameters, sha256 {})",
+            model.path,
+            model.network.num_parameters(),
+            model.sha256
+        ),
+        None => tracing::info!("VALUE_NETWORK_PATH not set; trees are solved without a value network"),
+    }
+
+    // Reload the value network on SIGHUP
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut hangups = signal(SignalKind::hangup())?;
+        let models = models.clone();
+        actix_web::rt::spawn(async move {
+            while hangups.recv().await.is_some() {
+                let registry = models.clone();
+                match web::block(move || registry.reload()).await {
+                    Ok(Ok(model)) => tracing::info!("Reloaded value network from {} ({})", model.path, model.sha256),
+                    Ok(Err(e)) => tracing::error!("Value network reload failed: {}", e),
+                    Err(e) => tracing::error!("Value network reload failed: {}", e),
+                }
+            }
+        });
+    }
+
+    // Open the strategy store if configured
+    let store = create_strategy_store(&config).map_err(std::io::Error::other)?;
+    match &config.strategy_store_dir {
+        Some(dir) => tracing::info!("Storing solves in {}", dir),
+        None => tracing::info!("STRATEGY_STORE_DIR not set; solves are not stored"),
+    }
+
+    let jobs = web::Data::new(create_job_manager(&config, store.clone(), Some(models.clone())));
+    tracing::info!(
+        "Solve jobs: {} workers, queue capacity {}",
+        config.job_workers,
+        config.job_queue_capacity
+    );
+
     tracing::info!("🚀 Server starting on http://{}", addr);
     tracing::info!("📚 Swagger UI available at http://{}/docs/", addr);
 
     // Run server
+    let config = web::Data::new(config);
     HttpServer::new(move || {
-        App::new()
+        let mut app = App::new()
             .wrap(create_cors())
             .service(create_swagger())
-            .configure(configure_app)
+            .app_data(config.clone())
+            .app_data(jobs.clone())
+            .app_data(models.clone())
+            .configure(configure_app);
+        if let Some(equity) = &equity {
+            app = app.app_data(equity.clone());
+        }
+        if let Some(store) = &store {
+            app = app.app_data(store.clone());
+        }
+        app
     })
     .bind(&addr)?
     .run()
@@ -0,0 +1,150 @@
+//! Loaded value network with hot reload
+//!
+//! The registry holds the current network behind an `Arc`. Solves take a
+//! clone when they start, so a reload swaps the network for new solves
+//! while in-flight ones finish on the one they started with. A weights file
+//! that fails to load leaves the current network in place.
+
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use sha2::{Digest, Sha256};
+
+use crate::{models::ModelStatus, solver::ValueNetwork};
+
+/// A value network with where and when it was loaded from
+#[derive(Debug)]
+pub struct LoadedModel {
+    pub network: Arc<ValueNetwork>,
+    pub path: String,
+    /// Hex SHA-256 of the weights file
+    pub sha256: String,
+    pub loaded_at: SystemTime,
+}
+
+impl LoadedModel {
+    /// Read and parse a weights file
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Cannot read value network '{}': {}", path, e))?;
+        let network = ValueNetwork::parse(&bytes).map_err(|e| format!("Invalid value network '{}': {}", path, e))?;
+        Ok(LoadedModel {
+            network: Arc::new(network),
+            path: path.to_string(),
+            sha256: Sha256::digest(&bytes).iter().map(|b| format!("{:02x}", b)).collect(),
+            loaded_at: SystemTime::now(),
+        })
+    }
+
+    /// Status reported by the health endpoint
+    pub fn status(&self) -> ModelStatus {
+        ModelStatus {
+            kind: "value_network".to_string(),
+            path: self.path.clone(),
+            feature_version: self.network.feature_version,
+            num_parameters: self.network.num_parameters(),
+            sha256: self.sha256.clone(),
+            loaded_at: self
+                .loaded_at
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        }
+    }
+}
+
+/// The value network solves use, reloadable from its configured path
+#[derive(Debug, Default)]
+pub struct ModelRegistry {
+    path: Option<String>,
+    current: RwLock<Option<Arc<LoadedModel>>>,
+}
+
+impl ModelRegistry {
+    /// Registry for the weights file at `path`, loading it now
+    ///
+    /// Without a path there is no network and trees are solved in full.
+    pub fn open(path: Option<String>) -> Result<Self, String> {
+        let current = path.as_deref().map(LoadedModel::load).transpose()?;
+        Ok(ModelRegistry {
+            path,
+            current: RwLock::new(current.map(Arc::new)),
+        })
+    }
+
+    /// Configured weights file
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// The loaded model, if any; holding it keeps it alive across reloads
+    pub fn current(&self) -> Option<Arc<LoadedModel>> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// The loaded network, if any
+    pub fn network(&self) -> Option<Arc<ValueNetwork>> {
+        self.current().map(|model| model.network.clone())
+    }
+
+    /// Load the configured weights file again and swap it in
+    pub fn reload(&self) -> Result<Arc<LoadedModel>, String> {
+        let Some(path) = &self.path else {
+            return Err("No value network is configured (VALUE_NETWORK_PATH)".to_string());
+        };
+        // Load outside the lock so readers are never blocked on the file
+        let model = Arc::new(LoadedModel::load(path)?);
+        *self.current.write().unwrap() = Some(model.clone());
+        Ok(model)
+    }
+
+    /// Status of every loaded model
+    pub fn status(&self) -> Vec<ModelStatus> {
+        self.current().map(|model| model.status()).into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::value_net::{Dense, INPUT_SIZE, OUTPUT_SIZE};
+
+    fn write_network(path: &std::path::Path, bias: f32) {
+        let mut output = Dense::zeros(1, OUTPUT_SIZE);
+        output.biases.fill(bias);
+        let network = ValueNetwork::new(vec![Dense::zeros(INPUT_SIZE, 1), output]).unwrap();
+        std::fs::write(path, network.to_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_reload_swaps_network() {
+        let dir = std::env::temp_dir().join(format!("model-registry-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("value.net");
+        write_network(&path, 0.25);
+
+        let registry = ModelRegistry::open(Some(path.to_string_lossy().into_owned())).unwrap();
+        let before = registry.current().unwrap();
+        assert_eq!(before.status().sha256.len(), 64);
+
+        // In-flight holders keep the old network; new readers get the new one
+        write_network(&path, 0.75);
+        let after = registry.reload().unwrap();
+        assert_ne!(after.sha256, before.sha256);
+        assert_eq!(before.network.layers[1].biases[0], 0.25);
+        assert_eq!(registry.network().unwrap().layers[1].biases[0], 0.75);
+
+        // A broken file leaves the current network loaded
+        std::fs::write(&path, b"not a network").unwrap();
+        assert!(registry.reload().is_err());
+        assert_eq!(registry.current().unwrap().sha256, after.sha256);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tabular_only() {
+        let registry = ModelRegistry::open(None).unwrap();
+        assert!(registry.current().is_none());
+        assert!(registry.status().is_empty());
+        assert!(registry.path().is_none());
+        assert!(registry.reload().is_err());
+    }
+}
@@ -8,10 +8,17 @@ pub struct HealthResponse {
     #[schema(example = "ok")]
     pub status: String,
 
-    /// Whether the solver model is loaded
+    /// Whether a value network is loaded (otherwise trees are solved in full)
     #[schema(example = true)]
     pub model_loaded: bool,
 
+    /// Whether solves run without any network, on full tabular trees
+    #[schema(example = false)]
+    pub tabular_only: bool,
+
+    /// Loaded model files
+    pub models: Vec<ModelStatus>,
+
     /// API version string
     #[schema(example = "0.1.0")]
     pub version: String,
@@ -21,8 +28,37 @@ impl Default for HealthResponse {
     fn default() -> Self {
         Self {
             status: "ok".to_string(),
-            model_loaded: true,
-            version: "0.1.0".to_string(),
+            model_loaded: false,
+            tabular_only: true,
+            models: Vec::new(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
         }
     }
 }
+
+/// A loaded model file
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ModelStatus {
+    /// What the model is used for
+    #[schema(example = "value_network")]
+    pub kind: String,
+
+    /// File the model was loaded from
+    #[schema(example = "/models/river.net")]
+    pub path: String,
+
+    /// Version of the input features the model was trained on
+    #[schema(example = 2)]
+    pub feature_version: u16,
+
+    /// Number of weights and biases
+    #[schema(example = 1454908)]
+    pub num_parameters: usize,
+
+    /// Hex SHA-256 of the file
+    pub sha256: String,
+
+    /// When the file was loaded, in seconds since the Unix epoch
+    #[schema(example = 1760000000)]
+    pub loaded_at: u64,
+}
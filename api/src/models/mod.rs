@@ -3,6 +3,13 @@ pub mod request;
 pub mod response;
 
 // Re-export commonly used types
-pub use health::HealthResponse;
-pub use request::{ActionType, BetSizes, HistoryAction, Player, SolveRequest};
-pub use response::{ActionInfo, ActionTypeResponse, HandStrategy, SolveResponse};
+pub use health::{HealthResponse, ModelStatus};
+pub use request::{
+    ActionType, BatchSolveRequest, BetSizes, ComboLock, HandCategory, HistoryAction, LockRule, NodeLock, Player, PreflopPosition,
+    PreflopSizes, PreflopSolveRequest, RakeConfig, RootBet, SolveRequest,
+};
+pub use response::{
+    ActionFrequency, ActionInfo, ActionTypeResponse, BatchBoardResult, BatchSolveResponse, DecisionReview, HandClassStrategy,
+    HandHistoryResponse, HandReview, HandStrategy, JobProgress, JobResponse,
+    JobStatus, NodeChild, NodeType, PreflopSolveResponse, SolveNodeResponse, SolveResponse, TextureReport,
+};
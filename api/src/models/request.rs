@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 /// Player position type
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Player {
     /// Out of position
@@ -60,7 +60,7 @@ impl Default for BetSizes {
 }
 
 /// Action type in betting history
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ActionType {
     Check,
@@ -87,7 +87,7 @@ pub struct HistoryAction {
     #[schema(example = "check")]
     pub action: ActionType,
 
-    /// Bet/raise size as percentage of pot (required for bet/raise, omit otherwise)
+    /// Bet size as percentage of pot, or for a raise the chips beyond a call as percentage of the pot after calling, as in bet sizes (required for bet/raise, omit otherwise)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub amount_percent: Option<f64>,
 
@@ -96,6 +96,128 @@ pub struct HistoryAction {
     pub card: Option<String>,
 }
 
+/// Outstanding bet at the root that the player to act must respond to
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RootBet {
+    /// Which position made the bet. The other position acts first.
+    #[schema(example = "IP")]
+    pub position: Player,
+
+    /// Bet size in big blinds (bb), not included in 'starting_pot'. Decimals allowed.
+    /// Anything above the caller's stack is uncalled and stays with the bettor.
+    #[schema(example = 6.6, minimum = 0.01, maximum = 1000000)]
+    pub amount: f64,
+}
+
+/// Rake configuration applied to terminal payoffs
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RakeConfig {
+    /// Rake as percentage of the final pot. e.g. 5 for 5%
+    #[schema(example = 5.0, minimum = 0, maximum = 100)]
+    pub percent: f64,
+
+    /// Maximum rake per pot in big blinds (bb). Null = uncapped.
+    #[schema(example = 3.0)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cap_big_blinds: Option<f64>,
+
+    /// No flop, no drop: pots that end preflop are not raked
+    #[schema(example = true)]
+    #[serde(default = "default_no_flop_no_drop")]
+    pub no_flop_no_drop: bool,
+}
+
+fn default_no_flop_no_drop() -> bool {
+    true
+}
+
+/// Explicit action probabilities for a single locked combo
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ComboLock {
+    /// Hand in card notation. e.g. 'AhKd'
+    #[schema(example = "AhKd")]
+    pub hand: String,
+
+    /// Action probabilities matching the node's 'actions' array order. Must sum to 1.0.
+    #[schema(example = json!([1.0, 0.0, 0.0, 0.0]))]
+    pub strategy: Vec<f64>,
+}
+
+/// Made hand on the board of a locked node, judged by the best five cards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HandCategory {
+    StraightFlush,
+    Quads,
+    FullHouse,
+    Flush,
+    Straight,
+    /// Pocket pair matching a board card
+    Set,
+    /// One hole card matching a paired board
+    Trips,
+    TwoPair,
+    /// Pocket pair above every board card
+    Overpair,
+    /// Hole card pairing the highest board card
+    TopPair,
+    /// Hole card pairing the second highest board card
+    SecondPair,
+    /// Any other pair using a hole card, including pocket pairs below the top card
+    WeakPair,
+    /// No pair made with a hole card
+    HighCard,
+}
+
+/// Rule locking one action's frequency for every combo of a hand group
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LockRule {
+    /// Hands the rule applies to, in Pio range syntax. e.g. 'AA,KK,AQs-ATs'.
+    /// Empty = every hand.
+    #[schema(example = "JJ-99")]
+    #[serde(default)]
+    pub hands: String,
+
+    /// Only lock the hands that make this category on the node's board
+    #[schema(example = "top_pair")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<HandCategory>,
+
+    /// Action to lock
+    #[schema(example = "check")]
+    pub action: ActionType,
+
+    /// Bet size as percentage of pot, or for a raise the chips beyond a call as percentage of the pot after calling, as in bet sizes (required for bet/raise, omit otherwise)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_percent: Option<f64>,
+
+    /// Frequency the action is taken with (0.0–1.0). The remaining actions are solved freely.
+    #[schema(example = 1.0)]
+    #[serde(default = "default_lock_frequency")]
+    pub frequency: f64,
+}
+
+fn default_lock_frequency() -> f64 {
+    1.0
+}
+
+/// Strategy lock for the acting player at one node of the tree
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NodeLock {
+    /// Betting actions leading to the locked node (same format as 'betting_history').
+    /// Empty = the root node.
+    #[serde(default)]
+    pub betting_history: Vec<HistoryAction>,
+
+    /// Per-combo locks. Take precedence over 'rules' for the listed combos.
+    #[serde(default)]
+    pub combos: Vec<ComboLock>,
+
+    /// Hand-group rules, applied in order; later rules override earlier ones
+    #[serde(default)]
+    pub rules: Vec<LockRule>,
+}
+
 /// Request body for the POST /v1/solve endpoint
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SolveRequest {
@@ -107,18 +229,32 @@ pub struct SolveRequest {
     #[schema(example = "Ah Kd Qc")]
     pub board: String,
 
-    /// Effective stack size in big blinds (bb)
-    #[schema(example = 100, minimum = 1)]
-    pub effective_stack: u32,
+    /// Effective stack size in big blinds (bb). Decimals allowed.
+    #[schema(example = 100, minimum = 0.01, maximum = 1000000)]
+    pub effective_stack: f64,
+
+    /// OOP stack in big blinds (bb) at the start of the street. Defaults to 'effective_stack'.
+    #[schema(example = 100, minimum = 0.01, maximum = 1000000)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oop_stack: Option<f64>,
+
+    /// IP stack in big blinds (bb) at the start of the street. Defaults to 'effective_stack'.
+    #[schema(example = 150, minimum = 0.01, maximum = 1000000)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_stack: Option<f64>,
 
-    /// Pot size at the start of the current street in big blinds (bb)
-    #[schema(example = 20, minimum = 1)]
-    pub starting_pot: u32,
+    /// Pot size at the start of the current street in big blinds (bb). Decimals allowed.
+    #[schema(example = 20, minimum = 0.01, maximum = 1000000)]
+    pub starting_pot: f64,
 
     /// Bet sizing configuration (Pio syntax). Defaults apply if omitted.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bet_sizes: Option<BetSizes>,
 
+    /// Bet already made at the root, so the solve starts facing it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_bet: Option<RootBet>,
+
     /// Betting actions to replay to reach the target node
     #[serde(skip_serializing_if = "Option::is_none")]
     pub betting_history: Option<Vec<HistoryAction>>,
@@ -132,4 +268,232 @@ pub struct SolveRequest {
     #[schema(example = "22+,A2s+,K9s+,Q9s+,J9s+,T8s+,97s+,87s,76s,65s,ATo+,KJo+")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ip_range: Option<String>,
+
+    /// Rake taken from the pot at showdown and fold terminals. Null = no rake.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rake: Option<RakeConfig>,
+
+    /// Strategy locks. Locked combos play the given frequencies and the
+    /// opponent's strategy is solved as a best adjustment around them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_locks: Option<Vec<NodeLock>>,
+
+    /// Also return the strategy aggregated by the 169 hand classes
+    #[serde(default)]
+    pub group_by_class: bool,
+
+    /// `solve_id` of a stored solve of a similar spot to start from. Its
+    /// regrets and average strategy seed the nodes reached by the same
+    /// action path. Null = solve from scratch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warm_start_from: Option<String>,
+}
+
+/// Request body for the POST /v1/solve/batch endpoint
+///
+/// Shares one spot configuration across many boards. Give either `boards`
+/// or `flop_subset`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchSolveRequest {
+    /// Which player's strategy to return
+    #[schema(example = "OOP")]
+    pub player: Player,
+
+    /// Boards to solve. e.g. ["Ah Kd Qc", "7s 7d 2c"]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boards: Option<Vec<String>>,
+
+    /// Named subset of the 1755 canonical flops: all, rainbow, two_tone, monotone or paired
+    #[schema(example = "monotone")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flop_subset: Option<String>,
+
+    /// Effective stack size in big blinds (bb). Decimals allowed.
+    #[schema(example = 100, minimum = 0.01, maximum = 1000000)]
+    pub effective_stack: f64,
+
+    /// OOP stack in big blinds (bb). Defaults to 'effective_stack'.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oop_stack: Option<f64>,
+
+    /// IP stack in big blinds (bb). Defaults to 'effective_stack'.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_stack: Option<f64>,
+
+    /// Pot size in big blinds (bb). Decimals allowed.
+    #[schema(example = 20, minimum = 0.01, maximum = 1000000)]
+    pub starting_pot: f64,
+
+    /// Bet sizing configuration (Pio syntax). Defaults apply if omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bet_sizes: Option<BetSizes>,
+
+    /// OOP range in Pio syntax. Null = all combos (uniform).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oop_range: Option<String>,
+
+    /// IP range in Pio syntax. Null = all combos (uniform).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_range: Option<String>,
+
+    /// Rake taken from the pot at showdown and fold terminals. Null = no rake.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rake: Option<RakeConfig>,
+
+    /// Maximum boards solved at once (capped by the server's batch thread budget)
+    #[schema(example = 4)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+}
+
+impl BatchSolveRequest {
+    /// Single-board solve request with this batch's shared settings
+    pub fn spot(&self, board: String) -> SolveRequest {
+        SolveRequest {
+            player: self.player,
+            board,
+            effective_stack: self.effective_stack,
+            oop_stack: self.oop_stack,
+            ip_stack: self.ip_stack,
+            starting_pot: self.starting_pot,
+            bet_sizes: self.bet_sizes.clone(),
+            root_bet: None,
+            betting_history: None,
+            oop_range: self.oop_range.clone(),
+            ip_range: self.ip_range.clone(),
+            rake: self.rake.clone(),
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        }
+    }
+}
+
+/// Heads-up preflop position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PreflopPosition {
+    /// Small blind (button)
+    SB,
+    /// Big blind
+    BB,
+}
+
+/// Preflop raise sizes. Each is a comma-separated list of raise-to amounts:
+/// bb (e.g. "2.5"), a multiple of the previous bet (e.g. "3x"), or all-in ("a").
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PreflopSizes {
+    /// First raise (open) sizes. e.g. "2.5"
+    #[schema(example = "2.5")]
+    #[serde(default = "default_open")]
+    pub open: String,
+
+    /// 3-bet sizes. e.g. "3x"
+    #[schema(example = "3x")]
+    #[serde(default = "default_three_bet")]
+    pub three_bet: String,
+
+    /// 4-bet sizes. e.g. "2.2x"
+    #[schema(example = "2.2x")]
+    #[serde(default = "default_four_bet")]
+    pub four_bet: String,
+
+    /// 5-bet and later sizes. e.g. "a"
+    #[schema(example = "a")]
+    #[serde(default = "default_five_bet")]
+    pub five_bet: String,
+}
+
+fn default_open() -> String {
+    "2.5".to_string()
+}
+fn default_three_bet() -> String {
+    "3x".to_string()
+}
+fn default_four_bet() -> String {
+    "2.2x".to_string()
+}
+fn default_five_bet() -> String {
+    "a".to_string()
+}
+
+impl Default for PreflopSizes {
+    fn default() -> Self {
+        Self {
+            open: default_open(),
+            three_bet: default_three_bet(),
+            four_bet: default_four_bet(),
+            five_bet: default_five_bet(),
+        }
+    }
+}
+
+/// Request body for the POST /v1/solve/preflop endpoint
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PreflopSolveRequest {
+    /// Starting stack of each player in big blinds (bb), before blinds and antes
+    #[schema(example = 100, minimum = 0.01, maximum = 1000000)]
+    pub effective_stack: f64,
+
+    /// Small blind in big blinds (bb)
+    #[schema(example = 0.5)]
+    #[serde(default = "default_small_blind")]
+    pub small_blind: f64,
+
+    /// Big blind in big blinds (bb)
+    #[schema(example = 1.0)]
+    #[serde(default = "default_big_blind")]
+    pub big_blind: f64,
+
+    /// Ante posted by each player in big blinds (bb)
+    #[schema(example = 0.0)]
+    #[serde(default)]
+    pub ante: f64,
+
+    /// Button straddle in big blinds (bb), posted by the SB instead of the small blind.
+    /// The BB then acts first and the straddler last.
+    #[schema(example = 2.0)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub straddle: Option<f64>,
+
+    /// Raise sizes per raise level. Defaults apply if omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sizes: Option<PreflopSizes>,
+
+    /// Raises committing at least this percentage of the stack become all-in
+    #[schema(example = 67.0)]
+    #[serde(default = "default_allin_threshold")]
+    pub allin_threshold: f64,
+
+    /// SB range in Pio syntax. Null = all hands.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sb_range: Option<String>,
+
+    /// BB range in Pio syntax. Null = all hands.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bb_range: Option<String>,
+
+    /// Rake taken at showdown and, unless no-flop-no-drop, on preflop folds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rake: Option<RakeConfig>,
+
+    /// Actions leading to the node to return, '/'-separated: f, x, c, r<bb> (raise to), a.
+    /// e.g. "r2.5/r7.5". Empty = the first decision.
+    #[schema(example = "r2.5")]
+    #[serde(default)]
+    pub path: String,
+
+    /// Number of CFR iterations. Defaults to the solver default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iterations: Option<usize>,
+}
+
+fn default_small_blind() -> f64 {
+    0.5
+}
+fn default_big_blind() -> f64 {
+    1.0
+}
+fn default_allin_threshold() -> f64 {
+    67.0
 }
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use super::request::Player;
+use super::request::{HistoryAction, Player, PreflopPosition, SolveRequest};
 
 /// Action type
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -70,11 +70,11 @@ pub struct SolveResponse {
 
     /// Current pot size in big blinds (bb)
     #[schema(example = 20)]
-    pub pot: u32,
+    pub pot: f64,
 
     /// Effective stack in big blinds (bb)
     #[schema(example = 100)]
-    pub effective_stack: u32,
+    pub effective_stack: f64,
 
     /// Number of combos returned
     #[schema(example = 15)]
@@ -85,4 +85,310 @@ pub struct SolveResponse {
 
     /// Per-combo strategy
     pub combos: Vec<HandStrategy>,
+
+    /// Range-weighted EV of the acting player at this node in bb
+    #[schema(example = 7.5)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ev: Option<f64>,
+
+    /// Strategy aggregated by hand class (only when `group_by_class` is set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hand_classes: Option<Vec<HandClassStrategy>>,
+
+    /// ID of the stored solve (only when the strategy store is enabled)
+    #[schema(example = "3f2a9c...")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solve_id: Option<String>,
+}
+
+/// Strategy and value of one of the 169 starting hand classes
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HandClassStrategy {
+    /// Hand class. e.g. 'AKs'
+    #[schema(example = "AKs")]
+    pub hand: String,
+
+    /// Flush suit held by the combos of this subgroup. Set only on boards
+    /// with flush draws, where a class is split into the combos that hold
+    /// cards of the flush suit and the rest.
+    #[schema(example = "h")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flush_suit: Option<String>,
+
+    /// Number of combos of this class in the range
+    #[schema(example = 4)]
+    pub num_combos: u32,
+
+    /// Range weight reaching this node (sum of combo frequencies times reach probability)
+    #[schema(example = 3.2)]
+    pub weight: f64,
+
+    /// Average action probabilities matching the 'actions' array order
+    #[schema(example = json!([0.0, 0.3, 0.7]))]
+    pub strategy: Vec<f64>,
+
+    /// Expected value in big blinds (bb)
+    #[schema(example = 1.25)]
+    pub ev: f64,
+}
+
+/// Response body for POST /v1/solve/preflop
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PreflopSolveResponse {
+    /// Acting player at this node
+    #[schema(example = "SB")]
+    pub player: PreflopPosition,
+
+    /// Current pot size in big blinds (bb), including blinds and antes
+    #[schema(example = 1.5)]
+    pub pot: f64,
+
+    /// Available actions at this node
+    pub actions: Vec<ActionInfo>,
+
+    /// Per-class strategy in 13x13 grid order (AA, AKs, ..., 22)
+    pub hands: Vec<HandClassStrategy>,
+}
+
+/// Lifecycle state of a solve job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Progress of a solve job
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JobProgress {
+    /// CFR iterations completed so far
+    #[schema(example = 40)]
+    pub iteration: usize,
+
+    /// Total CFR iterations the job will run
+    #[schema(example = 100)]
+    pub total_iterations: usize,
+
+    /// Latest exploitability in percent of the pot (null until measured)
+    #[schema(example = 0.8)]
+    pub exploitability: Option<f64>,
+
+    /// Estimated seconds until completion (null until the first iteration)
+    #[schema(example = 12.5)]
+    pub eta_seconds: Option<f64>,
+
+    /// Current average root strategy, if the solver reports one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_actions: Option<Vec<ActionInfo>>,
+}
+
+/// Response body for the /v1/jobs endpoints
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JobResponse {
+    /// Job identifier
+    #[schema(example = "job-1")]
+    pub job_id: String,
+
+    /// Current status
+    pub status: JobStatus,
+
+    /// Solver progress
+    pub progress: JobProgress,
+
+    /// Solve result once the job has completed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<SolveResponse>,
+
+    /// Error message if the job failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result for one board of a batch solve
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchBoardResult {
+    /// Board cards (space-separated)
+    #[schema(example = "Ah Kd Qc")]
+    pub board: String,
+
+    /// Texture labels this board is reported under
+    #[schema(example = json!(["rainbow", "unpaired", "A-high"]))]
+    pub textures: Vec<String>,
+
+    /// Raw boards this one stands for: the boards suit-isomorphic to it
+    /// (24 for a rainbow flop). Texture reports are weighted by it.
+    #[schema(example = 24)]
+    pub weight: usize,
+
+    /// Range-weighted EV of the acting player at the spot's node in bb
+    pub ev: Option<f64>,
+
+    /// Solve result, if the board solved successfully
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<SolveResponse>,
+
+    /// Error message if this board failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Average frequency of an action over a group of boards
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ActionFrequency {
+    /// Action name. e.g. 'Bet 33%'
+    #[schema(example = "Bet 33%")]
+    pub name: String,
+
+    /// Average frequency weighted by board (0.0–1.0); boards without this action count as 0
+    #[schema(example = 0.42)]
+    pub frequency: f64,
+}
+
+/// Aggregate report for one board texture
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TextureReport {
+    /// Texture label. e.g. 'monotone', 'paired', 'A-high', or 'all'
+    #[schema(example = "rainbow")]
+    pub texture: String,
+
+    /// Number of solved boards with this texture
+    #[schema(example = 12)]
+    pub num_boards: usize,
+
+    /// Average action frequencies at the root, weighted by board
+    pub actions: Vec<ActionFrequency>,
+
+    /// Average EV in bb over boards that report one, weighted by board
+    pub average_ev: Option<f64>,
+}
+
+/// Response body for POST /v1/solve/batch
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchSolveResponse {
+    /// Number of boards in the batch
+    #[schema(example = 3)]
+    pub num_boards: usize,
+
+    /// Per-board results, in request order
+    pub boards: Vec<BatchBoardResult>,
+
+    /// Aggregate report per texture, starting with 'all'
+    pub textures: Vec<TextureReport>,
+}
+
+/// Kind of a node in a stored strategy tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeType {
+    /// A player acts
+    Decision,
+    /// A card is dealt
+    Chance,
+    /// Fold, showdown, or the end of the solved tree
+    Terminal,
+}
+
+/// An action leading out of a tree node
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NodeChild {
+    /// Action name, or the dealt card at chance nodes
+    #[schema(example = "Bet 33%")]
+    pub name: String,
+
+    /// Path of the child node
+    #[schema(example = "x/b33")]
+    pub path: String,
+
+    /// Range-weighted frequency of the action (deal probability at chance nodes)
+    #[schema(example = 0.45)]
+    pub frequency: f64,
+}
+
+/// Response body for GET /v1/solves/{id}/node
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SolveNodeResponse {
+    /// ID of the stored solve
+    #[schema(example = "3f2a9c...")]
+    pub solve_id: String,
+
+    /// Normalised path of this node (empty at the root)
+    #[schema(example = "x/b33")]
+    pub path: String,
+
+    /// Kind of node
+    pub node_type: NodeType,
+
+    /// Strategy of the acting player (decision nodes only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<SolveResponse>,
+
+    /// Actions leading out of this node
+    pub children: Vec<NodeChild>,
+}
+
+/// Solver recommendation for one of hero's decisions
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DecisionReview {
+    /// Board at the decision
+    #[schema(example = "Ac 7d 2h 9s")]
+    pub board: String,
+
+    /// Action hero took
+    pub action_taken: HistoryAction,
+
+    /// Solve request for the decision
+    pub request: SolveRequest,
+
+    /// Available actions with their overall frequencies
+    pub actions: Vec<ActionInfo>,
+
+    /// Recommended strategy for hero's hand, matching the 'actions' order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hero_strategy: Option<Vec<f64>>,
+
+    /// Error message if this decision could not be solved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Review of one hand
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HandReview {
+    /// Hand number from the history
+    #[schema(example = "245871234567")]
+    pub hand_id: String,
+
+    /// Player the hole cards were dealt to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hero: Option<String>,
+
+    /// Hero's hole cards
+    #[schema(example = "AhKd")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hero_hand: Option<String>,
+
+    /// Hero's postflop position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hero_position: Option<Player>,
+
+    /// Hero's postflop decisions, in order
+    pub decisions: Vec<DecisionReview>,
+
+    /// Why the hand could not be reviewed (e.g. multiway or no flop)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response body for POST /v1/hand-history/review
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HandHistoryResponse {
+    /// Number of hands in the file
+    #[schema(example = 12)]
+    pub num_hands: usize,
+
+    /// Review of each hand, in file order
+    pub hands: Vec<HandReview>,
 }
@@ -0,0 +1,479 @@
+//! PioSOLVER and GTO+ interoperability
+//!
+//! Reads tree-building scripts (`set_pot`, `set_eff_stack`, `set_bet_sizes`,
+//! ...) and `#Key#Value` tree configs into a `SolveRequest`, writes a
+//! request back out as a script, and exports solved nodes as Pio-style CSV.
+//! Amounts are read and written as big blinds. Bet sizes are taken from the
+//! street of the board; sizes that are not given keep our defaults.
+
+use std::fmt::Write;
+
+use crate::{
+    models::{BetSizes, Player, RootBet, SolveRequest},
+    solver::{
+        cards::{parse_board, Combo},
+        solved_tree::PathStep,
+        BetSizeConfig, Card, SolvedNode, SolvedNodeKind, SolvedTree, Street,
+    },
+    store::join_cards,
+};
+
+/// Bet sizes of one player as read from a config
+#[derive(Debug, Default)]
+struct PlayerSizes {
+    bet: Vec<String>,
+    raise: Vec<String>,
+    allin: bool,
+}
+
+/// Our size list syntax, or `None` if nothing was given
+fn size_list(sizes: &[String], allin: bool) -> Option<String> {
+    let mut list = sizes.to_vec();
+    if allin {
+        list.push("a".to_string());
+    }
+    (!list.is_empty()).then(|| list.join(", "))
+}
+
+/// Spot settings collected while reading a config
+#[derive(Debug, Default)]
+struct Config {
+    board: Option<String>,
+    /// OOP and IP amounts put in on the current street, and the pot before it
+    pot: Option<(f64, f64, f64)>,
+    effective_stack: Option<f64>,
+    ranges: [Option<String>; 2],
+    /// Sizes per street and player, indexed [street][OOP, IP]
+    sizes: [[PlayerSizes; 2]; 3],
+}
+
+/// Read a Pio/GTO+ tree-building script or `#Key#Value` config
+///
+/// `player` selects whose strategy the request asks for.
+pub fn import_config(text: &str, player: Player) -> Result<SolveRequest, String> {
+    let mut config = Config::default();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let result = match line.strip_prefix('#').and_then(|l| l.split_once('#')) {
+            Some((key, value)) => config.read_key(key.trim(), value.trim()),
+            None if line.is_empty() || line.starts_with('#') => Ok(()),
+            None => config.read_command(line),
+        };
+        result.map_err(|e| format!("Line {}: {}", number + 1, e))?;
+    }
+    config.into_request(player)
+}
+
+fn parse_amount(s: &str) -> Result<f64, String> {
+    s.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite() && *v >= 0.0)
+        .ok_or_else(|| format!("Invalid amount '{}'", s))
+}
+
+fn parse_player(s: &str) -> Result<usize, String> {
+    match s.trim().to_ascii_uppercase().as_str() {
+        "OOP" | "0" => Ok(0),
+        "IP" | "1" => Ok(1),
+        _ => Err(format!("Invalid player '{}'", s)),
+    }
+}
+
+fn parse_street(s: &str) -> Result<usize, String> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "flop" => Ok(Street::Flop as usize),
+        "turn" => Ok(Street::Turn as usize),
+        "river" => Ok(Street::River as usize),
+        _ => Err(format!("Invalid street '{}'", s)),
+    }
+}
+
+/// Sizes separated by commas or spaces
+fn split_sizes(s: &str) -> Vec<String> {
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl Config {
+    /// One line of a Pio script; solver control commands are ignored
+    fn read_command(&mut self, line: &str) -> Result<(), String> {
+        let (command, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let args = args.trim();
+        match command {
+            "set_board" => self.board = Some(args.to_string()),
+            "set_eff_stack" => self.effective_stack = Some(parse_amount(args)?),
+            "set_pot" => {
+                let values = args
+                    .split_whitespace()
+                    .map(parse_amount)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let [oop, ip, start] = values[..] else {
+                    return Err("set_pot expects OOP, IP and starting pot amounts".to_string());
+                };
+                self.pot = Some((oop, ip, start));
+            }
+            "set_range" => {
+                let (player, range) = args
+                    .split_once(char::is_whitespace)
+                    .ok_or("set_range expects a player and a range")?;
+                self.ranges[parse_player(player)?] = Some(range.trim().to_string());
+            }
+            "set_bet_sizes" => {
+                let parts: Vec<&str> = args.split(',').map(str::trim).collect();
+                let [player, street, kind, sizes @ ..] = &parts[..] else {
+                    return Err("set_bet_sizes expects player,street,type[,sizes]".to_string());
+                };
+                let sizes = sizes.iter().flat_map(|s| split_sizes(s)).collect();
+                let entry = &mut self.sizes[parse_street(street)?][parse_player(player)?];
+                match *kind {
+                    "bet" | "donk" => entry.bet = sizes,
+                    "raise" => entry.raise = sizes,
+                    "allin" => entry.allin = true,
+                    _ => return Err(format!("Invalid bet size type '{}'", kind)),
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// One `#Key#Value` line of a tree config; unknown keys are ignored
+    fn read_key(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "Board" => self.board = Some(value.to_string()),
+            "Pot" => self.pot = Some((0.0, 0.0, parse_amount(value)?)),
+            "EffectiveStacks" => self.effective_stack = Some(parse_amount(value)?),
+            "Range0" => self.ranges[0] = Some(value.to_string()),
+            "Range1" => self.ranges[1] = Some(value.to_string()),
+            _ => {
+                // e.g. FlopConfig.BetSize (OOP) or TurnConfigIP.RaiseSize
+                let Some((section, field)) = key.split_once('.') else {
+                    return Ok(());
+                };
+                let (street, player) = match section.strip_suffix("IP") {
+                    Some(street) => (street, 1),
+                    None => (section, 0),
+                };
+                let Some(street) = street.strip_suffix("Config") else {
+                    return Ok(());
+                };
+                let entry = &mut self.sizes[parse_street(street)?][player];
+                match field {
+                    "BetSize" | "DonkBetSize" => entry.bet = split_sizes(value),
+                    "RaiseSize" => entry.raise = split_sizes(value),
+                    "AddAllin" => entry.allin = value.eq_ignore_ascii_case("true"),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn into_request(self, player: Player) -> Result<SolveRequest, String> {
+        let board = self.board.ok_or("No board given")?;
+        let cards = parse_board(&board)?;
+        let street = Street::from_board_len(cards.len())
+            .ok_or_else(|| format!("Board must have 3 to 5 cards, got {}", cards.len()))?;
+        let (oop, ip, start) = self.pot.ok_or("No pot given")?;
+        let effective_stack = self.effective_stack.ok_or("No effective stack given")?;
+
+        // Unequal amounts on the current street become a bet to respond to
+        let root_bet = (oop != ip).then(|| RootBet {
+            position: if oop > ip { Player::OOP } else { Player::IP },
+            amount: (oop - ip).abs(),
+        });
+
+        let mut bet_sizes = BetSizes::default();
+        let [oop_sizes, ip_sizes] = &self.sizes[street as usize];
+        for (sizes, bet, raise) in [
+            (oop_sizes, &mut bet_sizes.oop_bet, &mut bet_sizes.oop_raise),
+            (ip_sizes, &mut bet_sizes.ip_bet, &mut bet_sizes.ip_raise),
+        ] {
+            if let Some(list) = size_list(&sizes.bet, sizes.allin) {
+                *bet = list;
+            }
+            if let Some(list) = size_list(&sizes.raise, sizes.allin) {
+                *raise = list;
+            }
+        }
+        BetSizeConfig::from_bet_sizes(&bet_sizes)?;
+
+        let [oop_range, ip_range] = self.ranges;
+        Ok(SolveRequest {
+            player,
+            board: join_cards(&cards),
+            effective_stack,
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: start + 2.0 * oop.min(ip),
+            bet_sizes: Some(bet_sizes),
+            root_bet,
+            betting_history: None,
+            oop_range,
+            ip_range,
+            rake: None,
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        })
+    }
+}
+
+/// Write a request as a Pio tree-building script
+pub fn export_script(request: &SolveRequest) -> Result<String, String> {
+    let cards = parse_board(&request.board)?;
+    let street = match Street::from_board_len(cards.len()) {
+        Some(Street::Flop) => "flop",
+        Some(Street::Turn) => "turn",
+        Some(Street::River) => "river",
+        None => return Err(format!("Board must have 3 to 5 cards, got {}", cards.len())),
+    };
+
+    // A root bet is part of the pot in Pio, put in by the bettor
+    let (mut oop, mut ip) = (0.0, 0.0);
+    if let Some(bet) = &request.root_bet {
+        match bet.position {
+            Player::OOP => oop = bet.amount,
+            Player::IP => ip = bet.amount,
+        }
+    }
+
+    let mut script = String::new();
+    let _ = writeln!(script, "set_board {}", cards.iter().map(|c| c.to_string()).collect::<String>());
+    let _ = writeln!(script, "set_pot {} {} {}", oop, ip, request.starting_pot);
+    let _ = writeln!(script, "set_eff_stack {}", request.effective_stack);
+    for (name, range) in [("OOP", &request.oop_range), ("IP", &request.ip_range)] {
+        if let Some(range) = range {
+            let _ = writeln!(script, "set_range {} {}", name, range);
+        }
+    }
+
+    let sizes = request.bet_sizes.clone().unwrap_or_default();
+    for (name, kind, list) in [
+        ("OOP", "bet", &sizes.oop_bet),
+        ("OOP", "raise", &sizes.oop_raise),
+        ("IP", "bet", &sizes.ip_bet),
+        ("IP", "raise", &sizes.ip_raise),
+    ] {
+        let tokens = split_sizes(list);
+        let (allin, percents): (Vec<_>, Vec<_>) = tokens
+            .iter()
+            .partition(|t| t.eq_ignore_ascii_case("a") || t.eq_ignore_ascii_case("allin"));
+        let mut line = format!("set_bet_sizes {},{},{}", name, street, kind);
+        for size in percents {
+            line.push(',');
+            line.push_str(size);
+        }
+        let _ = writeln!(script, "{}", line);
+        // Pio's all-in flag covers both bets and raises
+        if !allin.is_empty() && kind == "raise" {
+            let _ = writeln!(script, "set_bet_sizes {},{},allin", name, street);
+        }
+    }
+    script.push_str("build_tree\n");
+    Ok(script)
+}
+
+/// Pio-style CSV of a decision node: one row per combo reaching it
+///
+/// Columns are hand, weight (range weight times reach), EV in bb and the
+/// frequency of each action.
+pub fn node_csv(
+    node: &SolvedNode,
+    combos: &[Vec<u16>; 2],
+    weights: &[Vec<f64>; 2],
+    board: &[Card],
+) -> Result<String, String> {
+    let SolvedNodeKind::Decision(player) = node.kind else {
+        return Err("Only decision nodes have a strategy".to_string());
+    };
+    let p = player as usize;
+
+    let mut csv = String::from("Hand,Weight,EV");
+    for action in &node.actions {
+        if let Some(info) = action.to_action_info(node.pot) {
+            let _ = write!(csv, ",{}", info.name);
+        }
+    }
+    csv.push('\n');
+
+    for (c, (&id, &weight)) in combos[p].iter().zip(&weights[p]).enumerate() {
+        let Some(combo) = Combo::from_id(id) else { continue };
+        if weight <= 0.0 || combo.is_blocked_by(board) {
+            continue;
+        }
+        let _ = write!(csv, "{},{:.4},{:.3}", combo, weight, node.evs[c]);
+        for prob in node.combo_strategy(c) {
+            let _ = write!(csv, ",{:.4}", prob);
+        }
+        csv.push('\n');
+    }
+    Ok(csv)
+}
+
+/// CSV of every decision node of a tree, keyed by node path
+pub fn export_tree_csv(tree: &SolvedTree) -> Result<Vec<(String, String)>, String> {
+    tree.validate()?;
+    let weights = tree
+        .weights
+        .clone()
+        .map(|w| w.iter().map(|&x| x as f64).collect::<Vec<f64>>());
+
+    let mut files = Vec::new();
+    let mut stack = vec![(0usize, Vec::<PathStep>::new(), weights, tree.board.clone())];
+    while let Some((index, path, weights, board)) = stack.pop() {
+        let node = &tree.nodes[index];
+        if matches!(node.kind, SolvedNodeKind::Decision(_)) {
+            let name = path.iter().map(|s| s.to_string()).collect::<Vec<_>>().join("/");
+            files.push((name, node_csv(node, &tree.combos, &weights, &board)?));
+        }
+
+        // Push in reverse so nodes come out in depth-first action order
+        for (a, &child) in node.children.iter().enumerate().rev() {
+            let (mut weights, mut board) = (weights.clone(), board.clone());
+            node.advance(a, &tree.combos, &mut weights, &mut board);
+            let mut path = path.clone();
+            path.push(PathStep::of(&node.actions[a], node));
+            stack.push((child as usize, path, weights, board));
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Action;
+
+    #[test]
+    fn test_import_script() {
+        let script = "\
+set_range OOP AA,KK,QQ:0.5
+set_range IP AKs,AKo
+set_board AhKdQc
+set_pot 0 0 20
+set_eff_stack 100
+set_bet_sizes OOP,flop,bet,50
+set_bet_sizes OOP,flop,allin
+set_bet_sizes IP,flop,bet,33,75
+set_bet_sizes OOP,turn,bet,120
+build_tree
+go
+";
+        let req = import_config(script, Player::OOP).unwrap();
+        assert_eq!(req.board, "Ah Kd Qc");
+        assert_eq!(req.starting_pot, 20.0);
+        assert_eq!(req.effective_stack, 100.0);
+        assert_eq!(req.oop_range.as_deref(), Some("AA,KK,QQ:0.5"));
+        let sizes = req.bet_sizes.unwrap();
+        assert_eq!(sizes.oop_bet, "50, a");
+        assert_eq!(sizes.oop_raise, "a");
+        assert_eq!(sizes.ip_bet, "33, 75");
+        assert_eq!(sizes.ip_raise, BetSizes::default().ip_raise);
+        assert!(req.root_bet.is_none());
+    }
+
+    #[test]
+    fn test_import_config_keys_and_root_bet() {
+        let config = "\
+#TreeBuilding#
+#Board#9h8h2c
+#Pot#30
+#EffectiveStacks#85
+#Range0#JJ+
+#Range1#TT-88
+#FlopConfig.BetSize#33 67
+#FlopConfigIP.RaiseSize#60
+#FlopConfigIP.AddAllin#True
+";
+        let req = import_config(config, Player::IP).unwrap();
+        assert_eq!(req.player, Player::IP);
+        assert_eq!(req.ip_range.as_deref(), Some("TT-88"));
+        let sizes = req.bet_sizes.unwrap();
+        assert_eq!(sizes.oop_bet, "33, 67");
+        assert_eq!(sizes.ip_raise, "60, a");
+
+        let req = import_config("set_board AhKdQc\nset_pot 10 0 20\nset_eff_stack 90", Player::IP).unwrap();
+        assert_eq!(req.starting_pot, 20.0);
+        let bet = req.root_bet.unwrap();
+        assert_eq!((bet.position, bet.amount), (Player::OOP, 10.0));
+    }
+
+    #[test]
+    fn test_import_errors() {
+        assert!(import_config("set_pot 0 0 20\nset_eff_stack 100", Player::OOP).is_err());
+        let err = import_config("set_board AhKdQc\nset_pot 0 20", Player::OOP).unwrap_err();
+        assert!(err.starts_with("Line 2"));
+        let err = import_config(
+            "set_board AhKdQc\nset_pot 0 0 20\nset_eff_stack 100\nset_bet_sizes OOP,flop,bet,2.5x",
+            Player::OOP,
+        );
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_script_roundtrip() {
+        let script = "set_board AhKdQc\nset_pot 0 6.6 20\nset_eff_stack 100\nset_range OOP AA\nset_bet_sizes IP,flop,bet,33\nset_bet_sizes IP,flop,allin\n";
+        let req = import_config(script, Player::OOP).unwrap();
+        let again = import_config(&export_script(&req).unwrap(), Player::OOP).unwrap();
+        assert_eq!(serde_json::to_value(&req).unwrap(), serde_json::to_value(&again).unwrap());
+    }
+
+    #[test]
+    fn test_export_tree_csv() {
+        let aa: Combo = "AsAh".parse().unwrap();
+        let kk: Combo = "KsKh".parse().unwrap();
+        let terminal = |pot| SolvedNode {
+            kind: SolvedNodeKind::Terminal,
+            pot,
+            stack: 0,
+            to_call: 0,
+            actions: vec![],
+            children: vec![],
+            strategy: vec![],
+            evs: vec![],
+        };
+        let tree = SolvedTree {
+            board: parse_board("Qd 7c 2h").unwrap(),
+            combos: [vec![aa.id, kk.id], vec![kk.id]],
+            weights: [vec![1.0, 0.5], vec![1.0]],
+            nodes: vec![
+                SolvedNode {
+                    kind: SolvedNodeKind::Decision(Player::OOP),
+                    pot: 1000,
+                    stack: 5000,
+                    to_call: 0,
+                    actions: vec![Action::Check, Action::Bet(500)],
+                    children: vec![1, 2],
+                    strategy: vec![0.5, 0.5, 1.0, 0.0],
+                    evs: vec![7.5, 2.0],
+                },
+                terminal(1000),
+                SolvedNode {
+                    kind: SolvedNodeKind::Decision(Player::IP),
+                    pot: 1500,
+                    stack: 4500,
+                    to_call: 500,
+                    actions: vec![Action::Fold, Action::Call],
+                    children: vec![3, 4],
+                    strategy: vec![0.25, 0.75],
+                    evs: vec![1.0],
+                },
+                terminal(1500),
+                terminal(2000),
+            ],
+        };
+
+        let files = export_tree_csv(&tree).unwrap();
+        let paths: Vec<&str> = files.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, vec!["", "b50"]);
+        assert_eq!(
+            files[0].1,
+            "Hand,Weight,EV,Check,Bet 50%\nAsAh,1.0000,7.500,0.5000,0.5000\nKsKh,0.5000,2.000,1.0000,0.0000\n"
+        );
+        assert_eq!(files[1].1, "Hand,Weight,EV,Fold,Call\nKsKh,1.0000,1.000,0.2500,0.7500\n");
+    }
+}
@@ -5,6 +5,7 @@
 //! - All-in: "a" or "allin"
 //! - Multiple sizes: "33, 67, a"
 
+use super::utils::Chips;
 use crate::models::BetSizes;
 
 /// A bet size specification
@@ -17,11 +18,11 @@ pub enum BetSize {
 }
 
 impl BetSize {
-    /// Calculate the actual bet amount in big blinds
-    pub fn calculate(&self, pot: u32, stack: u32) -> u32 {
+    /// Calculate the actual bet amount in chips
+    pub fn calculate(&self, pot: Chips, stack: Chips) -> Chips {
         match self {
             BetSize::Percent(pct) => {
-                let amount = (pot as f64 * pct / 100.0).round() as u32;
+                let amount = (pot as f64 * pct / 100.0).round() as Chips;
                 amount.min(stack) // Cap at stack
             }
             BetSize::AllIn => stack,
@@ -54,7 +55,7 @@ impl BetSizeConfig {
     }
 
     /// Get bet amounts for a given pot and stack
-    pub fn get_bet_amounts(&self, oop: bool, pot: u32, stack: u32) -> Vec<u32> {
+    pub fn get_bet_amounts(&self, oop: bool, pot: Chips, stack: Chips) -> Vec<Chips> {
         let sizes = if oop { &self.oop_bet } else { &self.ip_bet };
         sizes
             .iter()
@@ -64,7 +65,13 @@ impl BetSizeConfig {
     }
 
     /// Get raise amounts for a given pot, amount to call, and stack
-    pub fn get_raise_amounts(&self, oop: bool, pot: u32, to_call: u32, stack: u32) -> Vec<u32> {
+    pub fn get_raise_amounts(
+        &self,
+        oop: bool,
+        pot: Chips,
+        to_call: Chips,
+        stack: Chips,
+    ) -> Vec<Chips> {
         let sizes = if oop {
             &self.oop_raise
         } else {
@@ -78,7 +85,7 @@ impl BetSizeConfig {
             .map(|size| match size {
                 BetSize::Percent(pct) => {
                     // Raise is: call + (pot_after_call * percentage)
-                    let raise_amount = (pot_after_call as f64 * pct / 100.0).round() as u32;
+                    let raise_amount = (pot_after_call as f64 * pct / 100.0).round() as Chips;
                     to_call + raise_amount
                 }
                 BetSize::AllIn => stack,
@@ -176,11 +183,14 @@ mod tests {
     #[test]
     fn test_calculate_percent() {
         let size = BetSize::Percent(33.0);
-        // 33% of pot=100 = 33bb
+        // 33% of pot=1bb = 0.33bb
         assert_eq!(size.calculate(100, 200), 33);
 
         // Capped at stack
         assert_eq!(size.calculate(100, 20), 20);
+
+        // 33% of 5bb = 1.65bb, no longer rounded to whole bb
+        assert_eq!(size.calculate(500, 10000), 165);
     }
 
     #[test]
@@ -192,28 +202,23 @@ mod tests {
     #[test]
     fn test_get_bet_amounts() {
         let config = BetSizeConfig::default();
-        let amounts = config.get_bet_amounts(true, 20, 100);
+        let amounts = config.get_bet_amounts(true, 2000, 10000);
 
-        // 33% of 20 = 6.6 ≈ 7bb
-        // 67% of 20 = 13.4 ≈ 13bb
+        // 33% of 20bb = 6.6bb
+        // 67% of 20bb = 13.4bb
         // a = 100bb
-        assert_eq!(amounts.len(), 3);
-        assert!(amounts.contains(&7) || amounts.contains(&6)); // Rounding
-        assert!(amounts.contains(&13) || amounts.contains(&14));
-        assert!(amounts.contains(&100));
+        assert_eq!(amounts, vec![660, 1340, 10000]);
     }
 
     #[test]
     fn test_get_raise_amounts() {
         let config = BetSizeConfig::default();
-        // Pot=20, to_call=10, stack=100
-        // pot_after_call = 20 + 10 = 30
-        // 50% raise = call(10) + 50% of pot_after_call(15) = 25
-        let amounts = config.get_raise_amounts(true, 20, 10, 100);
-
-        assert!(amounts.len() >= 1);
-        // Should include all-in
-        assert!(amounts.contains(&100));
+        // Pot=20bb, to_call=10bb, stack=100bb
+        // pot_after_call = 20 + 10 = 30bb
+        // 50% raise = call(10) + 50% of pot_after_call(15) = 25bb
+        let amounts = config.get_raise_amounts(true, 2000, 1000, 10000);
+
+        assert_eq!(amounts, vec![2500, 10000]);
     }
 
     #[test]
@@ -0,0 +1,114 @@
+//! Bounds-checked little-endian reading for the binary file formats
+//!
+//! Strategy files, checkpoints, value networks and dataset shards are all
+//! parsed from untrusted bytes, so every read checks its length and counts
+//! taken from a file are never trusted to fit.
+
+/// Little-endian reader over a byte slice
+///
+/// `what` names the file in errors, e.g. "Checkpoint file is truncated".
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    what: &'static str,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8], what: &'static str) -> Self {
+        Self::at(bytes, 0, what)
+    }
+
+    /// Reader starting `pos` bytes in
+    pub fn at(bytes: &'a [u8], pos: usize, what: &'static str) -> Self {
+        Reader { bytes, pos, what }
+    }
+
+    /// Bytes read so far
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Check if every byte has been read
+    pub fn is_done(&self) -> bool {
+        self.pos == self.bytes.len()
+    }
+
+    pub fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len());
+        let end = end.ok_or_else(|| format!("{} is truncated", self.what))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    /// Skip `count` records of `len` bytes each
+    pub fn skip(&mut self, count: usize, len: usize) -> Result<(), String> {
+        self.take_records(count, len).map(|_| ())
+    }
+
+    pub fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, String> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, String> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    pub fn u64(&mut self) -> Result<u64, String> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    pub fn f32(&mut self) -> Result<f32, String> {
+        self.array().map(f32::from_le_bytes)
+    }
+
+    pub fn f32s(&mut self, n: usize) -> Result<Vec<f32>, String> {
+        let bytes = self.take_records(n, 4)?;
+        Ok(bytes.chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect())
+    }
+
+    pub fn f64s(&mut self, n: usize) -> Result<Vec<f64>, String> {
+        let bytes = self.take_records(n, 8)?;
+        Ok(bytes.chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect())
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn take_records(&mut self, count: usize, len: usize) -> Result<&'a [u8], String> {
+        let n = count
+            .checked_mul(len)
+            .ok_or_else(|| format!("{} is truncated", self.what))?;
+        self.take(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_little_endian() {
+        let bytes = [7, 1, 2, 0, 0, 0, 0, 0, 0, 128, 63];
+        let mut reader = Reader::new(&bytes, "Test file");
+        assert_eq!(reader.u8().unwrap(), 7);
+        assert_eq!(reader.u16().unwrap(), 0x0201);
+        reader.skip(1, 4).unwrap();
+        assert_eq!(reader.f32s(1).unwrap(), vec![1.0]);
+        assert!(reader.is_done());
+        assert_eq!(reader.u8().unwrap_err(), "Test file is truncated");
+    }
+
+    #[test]
+    fn test_huge_counts_are_truncation() {
+        let mut reader = Reader::new(&[0; 16], "Test file");
+        assert!(reader.f64s(usize::MAX / 4).is_err());
+        assert!(reader.skip(usize::MAX, 2).is_err());
+        assert_eq!(reader.pos(), 0);
+    }
+}
@@ -218,6 +218,33 @@ impl Combo {
         Combo { card1, card2, id }
     }
 
+    /// Combo made of two distinct cards (either order), with its ID
+    pub fn from_cards(a: Card, b: Card) -> Self {
+        // Position in the highest-first card order used by `generate_all_combos`
+        let (i, j) = {
+            let (pa, pb) = (51 - a.value() as u16, 51 - b.value() as u16);
+            (pa.min(pb), pa.max(pb))
+        };
+        let id = i * 51 - i * i.saturating_sub(1) / 2 + (j - i - 1);
+        let (card1, card2) = if a > b { (a, b) } else { (b, a) };
+        Combo::new(card1, card2, id)
+    }
+
+    /// Combo with the given ID (inverse of `from_cards`)
+    pub fn from_id(id: u16) -> Option<Self> {
+        let mut rest = id;
+        for i in 0..51u16 {
+            let row = 51 - i;
+            if rest < row {
+                let a = Card::from_value((51 - i) as u8)?;
+                let b = Card::from_value((51 - (i + 1 + rest)) as u8)?;
+                return Some(Combo::new(a, b, id));
+            }
+            rest -= row;
+        }
+        None
+    }
+
     /// Check if this combo is blocked by any of the given cards
     pub fn is_blocked_by(&self, cards: &[Card]) -> bool {
         cards.contains(&self.card1) || cards.contains(&self.card2)
@@ -235,6 +262,20 @@ impl fmt::Display for Combo {
     }
 }
 
+impl FromStr for Combo {
+    type Err = String;
+
+    /// Parse a combo from string like "AhKd" (either card order)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards = parse_board(s)?;
+        if cards.len() != 2 || cards[0] == cards[1] {
+            return Err(format!("Invalid hand: '{}' (expected 2 distinct cards)", s));
+        }
+
+        Ok(Combo::from_cards(cards[0], cards[1]))
+    }
+}
+
 /// Generate all 1326 possible two-card combinations
 ///
 /// Combos are ordered from highest to lowest (AA first, 22 last)
@@ -286,7 +327,7 @@ pub fn parse_board(s: &str) -> Result<Vec<Card>, String> {
             .collect()
     } else {
         // Try concatenated format (every 2 characters)
-        if s.len() % 2 != 0 {
+        if !s.len().is_multiple_of(2) {
             return Err(format!("Invalid board string length: {}", s.len()));
         }
 
@@ -405,6 +446,26 @@ mod tests {
         assert_eq!(board[2].to_string(), "Qc");
     }
 
+    #[test]
+    fn test_combo_from_cards() {
+        for combo in generate_all_combos() {
+            assert_eq!(Combo::from_cards(combo.card2, combo.card1), combo);
+            assert_eq!(Combo::from_id(combo.id), Some(combo));
+        }
+        assert_eq!(Combo::from_id(1326), None);
+    }
+
+    #[test]
+    fn test_parse_combo() {
+        let combo: Combo = "AhKd".parse().unwrap();
+        let reversed: Combo = "KdAh".parse().unwrap();
+        assert_eq!(combo, reversed);
+        assert_eq!(combo.to_string(), "AhKd");
+
+        assert!("AhAh".parse::<Combo>().is_err());
+        assert!("AhKdQc".parse::<Combo>().is_err());
+    }
+
     #[test]
     fn test_parse_board_invalid() {
         assert!(parse_board("AhKdQ").is_err()); // Odd length
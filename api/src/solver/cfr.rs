@@ -1,7 +1,31 @@
 //! CFR (Counterfactual Regret Minimization) algorithm
+//!
+//! Full-width CFR+ over the game tree: every pass walks the whole tree with
+//! a reach vector per player over their combos. Terminal values account for
+//! card removal between the two hands, and the leaves of depth-limited
+//! trees are valued by the value network. Iterations can instead sample
+//! cards, hands and actions (see [`mccfr`](super::mccfr)); external and
+//! outcome sampling run the generic walk over [`HoldemGame`].
 
-use super::game_tree::GameTree;
+use super::cards::{Card, Combo};
+use super::checkpoint::Checkpoint;
+use super::game_state::{Action, GameState};
+use super::game_tree::{GameNode, GameTree};
+use super::hand_class::ComboResult;
+use super::hand_eval::{HandEvaluator, HandStrength};
+use super::game_cfr;
+use super::holdem_game::HoldemGame;
+use super::mccfr::Sampling;
+use super::node_lock::{apply_lock, LockedNode, LockedStrategy, NodeLocks};
+use super::range::Range;
+use super::rng::Rng;
+use super::solved_tree::{SolvedNode, SolvedNodeKind, SolvedTree};
+use super::utils::chips_to_bb;
+use super::value_net::{ValueNetwork, NUM_COMBOS};
+use crate::models::{ActionType, HistoryAction, Player};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// CFR solver
 pub struct CFRSolver {
@@ -9,22 +33,1094 @@ pub struct CFRSolver {
     pub regret_sum: HashMap<String, Vec<f64>>,
     pub strategy_sum: HashMap<String, Vec<f64>>,
     pub iteration: usize,
+    /// Locked strategies by info set key
+    pub locked: HashMap<String, LockedStrategy>,
+    /// Combos of each player [OOP, IP] with their range weights, by combo ID
+    pub(super) hands: [Vec<(Combo, f64)>; 2],
+    /// Values at the leaves of depth-limited trees
+    value_network: Option<Arc<ValueNetwork>>,
+    evaluator: HandEvaluator,
+    /// Hand strengths of each player's combos by river board
+    strengths: HashMap<Vec<Card>, [Vec<HandStrength>; 2]>,
+    sampling: Sampling,
+    rng: Rng,
 }
 
 impl CFRSolver {
     /// Create a new CFR solver
     pub fn new(tree: GameTree) -> Self {
+        let hands = range_hands(&tree.root.state);
         CFRSolver {
             tree,
             regret_sum: HashMap::new(),
             strategy_sum: HashMap::new(),
             iteration: 0,
+            locked: HashMap::new(),
+            hands,
+            value_network: None,
+            evaluator: HandEvaluator::new(),
+            strengths: HashMap::new(),
+            sampling: Sampling::FullWidth,
+            rng: Rng::new(0),
         }
     }
 
+    /// Sample each iteration instead of walking the full tree
+    ///
+    /// External and outcome sampling deal single hands, so they need a full
+    /// tree rather than value network leaves.
+    pub fn with_sampling(mut self, sampling: Sampling, seed: u64) -> Self {
+        self.sampling = sampling;
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    /// State to resume this solve from
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            fingerprint: self.fingerprint(),
+            iteration: self.iteration,
+            rng_state: self.rng.state(),
+            sampling: self.sampling,
+            regret_sum: self.regret_sum.clone(),
+            strategy_sum: self.strategy_sum.clone(),
+        }
+    }
+
+    /// Continue from a checkpoint of a solve of the same tree
+    ///
+    /// Fails if the checkpoint was taken of another tree or ranges, or under
+    /// a different sampling scheme.
+    pub fn restore(&mut self, checkpoint: Checkpoint) -> Result<(), String> {
+        if checkpoint.fingerprint != self.fingerprint() {
+            return Err("Checkpoint was taken of a different game tree or ranges".to_string());
+        }
+        if checkpoint.sampling != self.sampling {
+            return Err(format!(
+                "Checkpoint was taken with {:?} sampling, not {:?}",
+                checkpoint.sampling, self.sampling
+            ));
+        }
+        self.iteration = checkpoint.iteration;
+        self.rng = Rng::new(checkpoint.rng_state);
+        self.regret_sum = checkpoint.regret_sum;
+        self.strategy_sum = checkpoint.strategy_sum;
+        Ok(())
+    }
+
+    /// Hash of the tree and both players' weighted combos
+    ///
+    /// Identifies what a checkpoint's tables belong to: the same fingerprint
+    /// means the same info sets with the same actions.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Sha256::new();
+        for hands in &self.hands {
+            hasher.update((hands.len() as u32).to_le_bytes());
+            for (combo, weight) in hands {
+                hasher.update(combo.id.to_le_bytes());
+                hasher.update(weight.to_bits().to_le_bytes());
+            }
+        }
+        hasher.update(self.tree.root.state.board.iter().map(|c| c.value()).collect::<Vec<_>>());
+        hash_node(&mut hasher, &self.tree.root);
+        u64::from_le_bytes(hasher.finalize()[..8].try_into().unwrap())
+    }
+
+    /// Value the leaves of a depth-limited tree with a value network
+    pub fn with_value_network(mut self, network: Arc<ValueNetwork>) -> Self {
+        self.value_network = Some(network);
+        self
+    }
+
+    /// Resolve node locks against the tree and enforce them during solving
+    ///
+    /// Returns the locks whose history runs on past a leaf of a
+    /// depth-limited tree, for `subgame_after` to hand on to the subgame.
+    pub fn lock_nodes(&mut self, locks: &NodeLocks) -> Result<NodeLocks, String> {
+        let mut past_leaves = NodeLocks::default();
+        for locked_node in &locks.nodes {
+            let (path, rest) = self.tree.root.follow(&locked_node.history)?;
+            if !rest.is_empty() {
+                past_leaves.nodes.push(locked_node.clone());
+                continue;
+            }
+            let node = self.tree.root.at(&path);
+            let actions = node.actions();
+            if actions.is_empty() {
+                return Err("Cannot lock a terminal node".to_string());
+            }
+
+            for (&combo_id, constraint) in &locked_node.combos {
+                let locked = constraint.resolve(&actions, node.state.pot, node.state.to_call())?;
+                self.locked
+                    .insert(info_set_key(&node.state, combo_id), locked);
+            }
+        }
+        Ok(past_leaves)
+    }
+
+    /// Current strategy of an info set (regret matching, then locks)
+    pub fn get_strategy(&self, info_set: &str, num_actions: usize) -> Vec<f64> {
+        current_strategy(&self.regret_sum, &self.locked, info_set, num_actions)
+    }
+
+    /// Average strategy of an info set (uniform if it was never reached)
+    pub fn average_strategy(&self, info_set: &str, num_actions: usize) -> Vec<f64> {
+        average_strategy(&self.strategy_sum, &self.locked, info_set, num_actions)
+    }
+
     /// Run CFR iterations
-    pub fn solve(&mut self, _iterations: usize) -> HashMap<String, Vec<f64>> {
-        // TODO: Implement CFR algorithm
-        HashMap::new()
+    ///
+    /// Returns the average strategy of every info set.
+    ///
+    /// # Panics
+    ///
+    /// If the tree is depth limited and no value network was given, or
+    /// external or outcome sampling is used on a depth-limited tree.
+    pub fn solve(&mut self, iterations: usize) -> HashMap<String, Vec<f64>> {
+        let game = HoldemGame::new(&self.tree);
+        for _ in 0..iterations {
+            self.iteration += 1;
+            if let Sampling::External | Sampling::Outcome = self.sampling {
+                game_cfr::iterate(
+                    &game,
+                    self.sampling,
+                    &mut self.regret_sum,
+                    &mut self.strategy_sum,
+                    &self.locked,
+                    &mut self.rng,
+                    self.iteration,
+                );
+                continue;
+            }
+            for traverser in [Player::OOP, Player::IP] {
+                let reach = self.initial_reach();
+                let mut pass = Pass {
+                    hands: &self.hands,
+                    regret_sum: &mut self.regret_sum,
+                    strategy_sum: &mut self.strategy_sum,
+                    locked: &self.locked,
+                    value_network: self.value_network.as_deref(),
+                    evaluator: &self.evaluator,
+                    strengths: &mut self.strengths,
+                    rng: (self.sampling == Sampling::Chance).then_some(&mut self.rng),
+                    iteration: self.iteration,
+                    traverser,
+                    mode: PassMode::Update,
+                };
+                pass.traverse(&self.tree.root, &reach);
+            }
+        }
+
+        self.strategy_sum
+            .keys()
+            .map(|key| {
+                let n = self.strategy_sum[key].len();
+                (key.clone(), self.average_strategy(key, n))
+            })
+            .collect()
+    }
+
+    /// Average strategy and EV of each combo at the node reached by `history`
+    ///
+    /// Combo weights are the range weights times the reach of the actions
+    /// leading to the node; EVs are in bb.
+    pub fn node_result(&mut self, history: &[HistoryAction]) -> Result<(&GameNode, Vec<ComboResult>), String> {
+        let path = self.decision_path(history)?;
+        let reach = self.reach_along(&path);
+        let evs = self.combo_evs(&path, &reach);
+        Ok(self.combo_results(&path, &reach, &evs))
+    }
+
+    /// Like [`node_result`](Self::node_result) with every EV left at zero,
+    /// skipping the walk of the subtree the EVs need
+    pub fn node_strategy(&self, history: &[HistoryAction]) -> Result<(&GameNode, Vec<ComboResult>), String> {
+        let path = self.decision_path(history)?;
+        let reach = self.reach_along(&path);
+        let evs = vec![0.0; self.hands[self.tree.root.at(&path).state.to_act as usize].len()];
+        Ok(self.combo_results(&path, &reach, &evs))
+    }
+
+    /// Path of child indices to the decision node reached by `history`
+    fn decision_path(&self, history: &[HistoryAction]) -> Result<Vec<usize>, String> {
+        let path = self.tree.root.find_path(history)?;
+        let node = self.tree.root.at(&path);
+        if node.is_terminal || node.is_leaf || node.is_chance() {
+            return Err("The betting history does not lead to a decision node".to_string());
+        }
+        Ok(path)
+    }
+
+    /// Reaching combos of the acting player at a decision node with their
+    /// average strategy and the given EVs
+    fn combo_results(&self, path: &[usize], reach: &[Vec<f64>; 2], evs: &[f64]) -> (&GameNode, Vec<ComboResult>) {
+        let node = self.tree.root.at(path);
+        let p = node.state.to_act as usize;
+
+        let prefix = history_key(&node.state);
+        let results = self.hands[p]
+            .iter()
+            .enumerate()
+            .filter(|&(i, (combo, _))| reach[p][i] > 0.0 && !combo.is_blocked_by(&node.state.board))
+            .map(|(i, &(combo, _))| ComboResult {
+                combo,
+                weight: reach[p][i],
+                strategy: average_strategy(
+                    &self.strategy_sum,
+                    &self.locked,
+                    &format!("{}|{}", prefix, combo.id),
+                    node.children.len(),
+                ),
+                ev: evs[i],
+            })
+            .collect();
+        (node, results)
+    }
+
+    /// Strategy tree of the solve: the average strategy and EV of the
+    /// acting player's combos at every decision node
+    ///
+    /// Combos the board blocks are left out. Depth-limited leaves become
+    /// terminal nodes.
+    pub fn solved_tree(&mut self) -> SolvedTree {
+        let board = self.tree.root.state.board.clone();
+        let playable: [Vec<usize>; 2] = [0, 1].map(|p| {
+            (0..self.hands[p].len())
+                .filter(|&i| !self.hands[p][i].0.is_blocked_by(&board))
+                .collect()
+        });
+
+        let mut paths = Vec::new();
+        flatten(&self.tree.root, &mut Vec::new(), &mut paths);
+        let mut nodes = Vec::with_capacity(paths.len());
+        for (path, children) in paths {
+            let node = self.tree.root.at(&path);
+            let actions = node.actions();
+            let mut solved = SolvedNode {
+                kind: SolvedNodeKind::Terminal,
+                pot: node.state.pot,
+                stack: node.state.effective_stack(),
+                // As strategy files derive it, from the call
+                to_call: if actions.contains(&Action::Call) { node.state.to_call() } else { 0 },
+                actions,
+                children,
+                strategy: Vec::new(),
+                evs: Vec::new(),
+            };
+            if node.is_chance() {
+                solved.kind = SolvedNodeKind::Chance;
+            } else if !node.is_terminal && !node.is_leaf {
+                let player = node.state.to_act;
+                let p = player as usize;
+                let prefix = history_key(&node.state);
+                for &i in &playable[p] {
+                    let key = format!("{}|{}", prefix, self.hands[p][i].0.id);
+                    let strategy = self.average_strategy(&key, solved.actions.len());
+                    solved.strategy.extend(strategy.iter().map(|&x| x as f32));
+                }
+                let reach = self.reach_along(&path);
+                let evs = self.combo_evs(&path, &reach);
+                solved.evs = playable[p].iter().map(|&i| evs[i] as f32).collect();
+                solved.kind = SolvedNodeKind::Decision(player);
+            }
+            nodes.push(solved);
+        }
+
+        SolvedTree {
+            combos: [0, 1].map(|p| playable[p].iter().map(|&i| self.hands[p][i].0.id).collect()),
+            weights: [0, 1].map(|p| playable[p].iter().map(|&i| self.hands[p][i].1 as f32).collect()),
+            board,
+            nodes,
+        }
+    }
+
+    /// EV in bb of each of the acting player's combos at the decision node
+    /// on `path`, given both players' reach there
+    fn combo_evs(&mut self, path: &[usize], reach: &[Vec<f64>; 2]) -> Vec<f64> {
+        let node = self.tree.root.at(path);
+        let player = node.state.to_act;
+        let p = player as usize;
+        let mut pass = Pass {
+            hands: &self.hands,
+            regret_sum: &mut self.regret_sum,
+            strategy_sum: &mut self.strategy_sum,
+            locked: &self.locked,
+            value_network: self.value_network.as_deref(),
+            evaluator: &self.evaluator,
+            strengths: &mut self.strengths,
+            rng: None,
+            iteration: self.iteration,
+            traverser: player,
+            mode: PassMode::Average,
+        };
+        let values = pass.traverse(node, reach);
+        let mass = opponent_mass(&self.hands, player, &reach[1 - p]);
+        values
+            .iter()
+            .zip(mass)
+            .map(|(v, m)| if m > 0.0 { v / m } else { 0.0 })
+            .collect()
+    }
+
+    /// Subgame after the depth-limited leaf that `history` runs on past
+    ///
+    /// The state has the card dealt at the leaf and each range weighted by
+    /// how often the average strategy reaches the leaf. Returns the rest of
+    /// the history with it, or `None` if the history ends inside this tree.
+    ///
+    /// `locks` are the locks past this tree's leaves from `lock_nodes`. They
+    /// are returned with the steps into the subgame taken off their history,
+    /// and are an error unless they lie in that subgame: other subgames are
+    /// valued by the network and never solved.
+    pub fn subgame_after(
+        &self,
+        history: &[HistoryAction],
+        locks: &NodeLocks,
+    ) -> Result<Option<(GameState, Vec<HistoryAction>, NodeLocks)>, String> {
+        let off_line = |lock: &HistoryAction| {
+            format!(
+                "Node lock at action {} lies past the depth limit, off the line of the betting history",
+                lock.order
+            )
+        };
+        let (path, rest) = self.tree.root.follow(history)?;
+        let Some((deal, rest)) = rest.split_first() else {
+            return match locks.nodes.first() {
+                Some(lock) => Err(off_line(lock.history.iter().max_by_key(|h| h.order).unwrap())),
+                None => Ok(None),
+            };
+        };
+        let reach = self.reach_along(&path);
+        let node = self.tree.root.at(&path);
+        let card = match (&deal.action, deal.card.as_deref()) {
+            (ActionType::Deal, Some(card)) => card.parse::<Card>()?,
+            _ => return Err(format!("Action {} ({:?}) is not available in the tree", deal.order, deal.action)),
+        };
+        if node.state.board.contains(&card) {
+            return Err(format!("Action {} deals {}, which is already on the board", deal.order, card));
+        }
+
+        let mut state = node.state.apply_action(Action::Deal(card));
+        let [oop_range, ip_range] = [0, 1].map(|p| {
+            let mut range = Range::new();
+            for ((combo, _), &r) in self.hands[p].iter().zip(&reach[p]) {
+                if !combo.is_blocked_by(&[card]) {
+                    range.set_frequency(combo.id, r);
+                }
+            }
+            Arc::new(range)
+        });
+        state.oop_range = oop_range;
+        state.ip_range = ip_range;
+
+        let mut subgame_locks = NodeLocks::default();
+        for lock in &locks.nodes {
+            let (lock_path, lock_rest) = self.tree.root.follow(&lock.history)?;
+            let (lock_deal, lock_rest) = lock_rest.split_first().unwrap();
+            if lock_path != path || lock_deal.card.as_deref().map(str::parse::<Card>) != Some(Ok(card)) {
+                return Err(off_line(lock_deal));
+            }
+            subgame_locks.nodes.push(LockedNode {
+                history: lock_rest.to_vec(),
+                combos: lock.combos.clone(),
+            });
+        }
+        Ok(Some((state, rest.to_vec(), subgame_locks)))
+    }
+
+    /// EV in bb of every root combo of each player [OOP, IP] under the
+    /// average strategy, indexed by combo ID
+    ///
+    /// Combos outside a range are zero. The root may be a chance node, so
+    /// this also values the start of a street.
+    pub fn root_values(&mut self) -> [Vec<f64>; 2] {
+        [Player::OOP, Player::IP].map(|player| {
+            let p = player as usize;
+            let values = self.root_pass(player, PassMode::Average);
+            let mass = opponent_mass(&self.hands, player, &self.initial_reach()[1 - p]);
+            let mut by_id = vec![0.0; NUM_COMBOS];
+            for (((combo, _), v), m) in self.hands[p].iter().zip(values).zip(mass) {
+                if m > 0.0 {
+                    by_id[combo.id as usize] = v / m;
+                }
+            }
+            by_id
+        })
+    }
+
+    /// Exploitability of the average strategy in bb
+    ///
+    /// The mean of what each player gains by switching to a best response
+    /// against the other's average strategy; zero at a Nash equilibrium.
+    pub fn exploitability(&mut self) -> f64 {
+        let gains = [Player::OOP, Player::IP].map(|player| {
+            self.expected_value(player, PassMode::BestResponse) - self.expected_value(player, PassMode::Average)
+        });
+        (gains[0] + gains[1]) / 2.0
+    }
+
+    /// Expected value in bb of a player's whole range at the root
+    fn expected_value(&mut self, player: Player, mode: PassMode) -> f64 {
+        let p = player as usize;
+        let reach = self.initial_reach();
+        let values = self.root_pass(player, mode);
+        let mass = opponent_mass(&self.hands, player, &reach[1 - p]);
+        let pairs: f64 = reach[p].iter().zip(&mass).map(|(r, m)| r * m).sum();
+        if pairs <= 0.0 {
+            return 0.0;
+        }
+        reach[p].iter().zip(&values).map(|(r, v)| r * v).sum::<f64>() / pairs
+    }
+
+    /// Counterfactual values of `player`'s combos at the root without updating
+    fn root_pass(&mut self, player: Player, mode: PassMode) -> Vec<f64> {
+        let reach = self.initial_reach();
+        let mut pass = Pass {
+            hands: &self.hands,
+            regret_sum: &mut self.regret_sum,
+            strategy_sum: &mut self.strategy_sum,
+            locked: &self.locked,
+            value_network: self.value_network.as_deref(),
+            evaluator: &self.evaluator,
+            strengths: &mut self.strengths,
+            rng: None,
+            iteration: self.iteration,
+            traverser: player,
+            mode,
+        };
+        pass.traverse(&self.tree.root, &reach)
+    }
+
+    /// Each player's range weights times the average strategy's reach of
+    /// the node at the end of a path of child indices
+    fn reach_along(&self, path: &[usize]) -> [Vec<f64>; 2] {
+        let mut reach = self.initial_reach();
+        let mut node = &self.tree.root;
+        for &index in path {
+            let (action, child) = &node.children[index];
+            match action {
+                Action::Deal(card) => zero_blocked(&self.hands, &mut reach, *card),
+                _ => {
+                    let p = node.state.to_act as usize;
+                    let prefix = history_key(&node.state);
+                    for (r, (combo, _)) in reach[p].iter_mut().zip(&self.hands[p]) {
+                        let key = format!("{}|{}", prefix, combo.id);
+                        *r *= self.average_strategy(&key, node.children.len())[index];
+                    }
+                }
+            }
+            node = child;
+        }
+        reach
+    }
+
+    /// Range weights of both players at the root
+    fn initial_reach(&self) -> [Vec<f64>; 2] {
+        [0, 1].map(|p| self.hands[p].iter().map(|&(_, w)| w).collect())
+    }
+}
+
+/// What a walk over the tree does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PassMode {
+    /// CFR+ iteration: update regrets and strategy sums
+    Update,
+    /// Evaluate the average strategy
+    Average,
+    /// Best response of the traverser to the opponent's average strategy
+    BestResponse,
+}
+
+/// One walk over the tree for a traverser
+struct Pass<'a> {
+    hands: &'a [Vec<(Combo, f64)>; 2],
+    regret_sum: &'a mut HashMap<String, Vec<f64>>,
+    strategy_sum: &'a mut HashMap<String, Vec<f64>>,
+    locked: &'a HashMap<String, LockedStrategy>,
+    value_network: Option<&'a ValueNetwork>,
+    evaluator: &'a HandEvaluator,
+    strengths: &'a mut HashMap<Vec<Card>, [Vec<HandStrength>; 2]>,
+    /// Deals one card per chance node when updating (chance sampling)
+    rng: Option<&'a mut Rng>,
+    iteration: usize,
+    traverser: Player,
+    mode: PassMode,
+}
+
+impl Pass<'_> {
+    /// Traverser's counterfactual values per combo below `node`
+    fn traverse(&mut self, node: &GameNode, reach: &[Vec<f64>; 2]) -> Vec<f64> {
+        if node.is_terminal || node.is_leaf {
+            return self.terminal_values(node, reach);
+        }
+        if node.is_chance() {
+            return self.chance_values(node, reach);
+        }
+
+        let t = self.traverser as usize;
+        let p = node.state.to_act as usize;
+        let n = node.children.len();
+        let prefix = history_key(&node.state);
+        let keys: Vec<String> = self.hands[p]
+            .iter()
+            .map(|(combo, _)| format!("{}|{}", prefix, combo.id))
+            .collect();
+        let strategies: Vec<Vec<f64>> = keys
+            .iter()
+            .map(|key| match self.mode {
+                PassMode::Update => current_strategy(self.regret_sum, self.locked, key, n),
+                _ => average_strategy(self.strategy_sum, self.locked, key, n),
+            })
+            .collect();
+
+        let mut values = vec![0.0; self.hands[t].len()];
+        let mut action_values = Vec::with_capacity(n);
+        for (a, (_, child)) in node.children.iter().enumerate() {
+            let mut child_reach = reach.clone();
+            for (r, strategy) in child_reach[p].iter_mut().zip(&strategies) {
+                *r *= strategy[a];
+            }
+
+            let child_values = self.traverse(child, &child_reach);
+            if p == t {
+                for (i, v) in values.iter_mut().enumerate() {
+                    *v += strategies[i][a] * child_values[i];
+                }
+                action_values.push(child_values);
+            } else {
+                for (v, cv) in values.iter_mut().zip(&child_values) {
+                    *v += cv;
+                }
+            }
+        }
+
+        if p == t && self.mode == PassMode::BestResponse {
+            for (i, v) in values.iter_mut().enumerate() {
+                *v = action_values
+                    .iter()
+                    .map(|av| av[i])
+                    .fold(f64::NEG_INFINITY, f64::max);
+            }
+        }
+
+        if p == t && self.mode == PassMode::Update {
+            let weight = self.iteration as f64;
+            for (i, key) in keys.into_iter().enumerate() {
+                let regrets = self.regret_sum.entry(key.clone()).or_insert_with(|| vec![0.0; n]);
+                for (a, regret) in regrets.iter_mut().enumerate() {
+                    // CFR+: regrets floored at zero, linear strategy averaging
+                    *regret = (*regret + action_values[a][i] - values[i]).max(0.0);
+                }
+                let sums = self.strategy_sum.entry(key).or_insert_with(|| vec![0.0; n]);
+                for (sum, prob) in sums.iter_mut().zip(&strategies[i]) {
+                    *sum += weight * reach[p][i] * prob;
+                }
+            }
+        }
+
+        values
+    }
+
+    /// Average over the dealt cards, each equally likely given both hands
+    ///
+    /// With chance sampling one card stands in for all of them.
+    fn chance_values(&mut self, node: &GameNode, reach: &[Vec<f64>; 2]) -> Vec<f64> {
+        let t = self.traverser as usize;
+        // Cards left once both players' hole cards are removed
+        let mut outcomes = (node.children.len() - 4) as f64;
+        let mut values = vec![0.0; self.hands[t].len()];
+        let children = match (self.mode, self.rng.as_deref_mut()) {
+            (PassMode::Update, Some(rng)) => {
+                let index = rng.below(node.children.len());
+                outcomes /= node.children.len() as f64;
+                &node.children[index..=index]
+            }
+            _ => &node.children[..],
+        };
+        for (action, child) in children {
+            let Action::Deal(card) = *action else { continue };
+            let mut child_reach = reach.clone();
+            zero_blocked(self.hands, &mut child_reach, card);
+
+            let child_values = self.traverse(child, &child_reach);
+            for (i, (combo, _)) in self.hands[t].iter().enumerate() {
+                if !combo.is_blocked_by(&[card]) {
+                    values[i] += child_values[i] / outcomes;
+                }
+            }
+        }
+        values
+    }
+
+    /// Traverser's values at a fold, showdown or leaf, weighted by opponent reach
+    fn terminal_values(&mut self, node: &GameNode, reach: &[Vec<f64>; 2]) -> Vec<f64> {
+        let state = &node.state;
+        let t = self.traverser as usize;
+        let pot = chips_to_bb(state.pot);
+        let invested = state.put_in().map(chips_to_bb);
+        let put_in = invested[t];
+        let mass = opponent_mass(self.hands, self.traverser, &reach[1 - t]);
+
+        if let Some(folder) = state.folded() {
+            let payoff = state.rake.fold_payoffs(pot, invested, folder, true)[t];
+            return mass.iter().map(|m| payoff * m).collect();
+        }
+
+        if node.is_leaf {
+            let network = self
+                .value_network
+                .expect("depth-limited trees need a value network");
+            let by_id = [0, 1].map(|p| {
+                let mut weights = vec![0.0; NUM_COMBOS];
+                for ((combo, _), &r) in self.hands[p].iter().zip(&reach[p]) {
+                    weights[combo.id as usize] = r;
+                }
+                weights
+            });
+            let shares = network.predict(
+                &state.board,
+                state.pot,
+                state.effective_stack(),
+                [&by_id[0], &by_id[1]],
+            );
+            return self.hands[t]
+                .iter()
+                .zip(&mass)
+                .map(|((combo, _), m)| (shares[t][combo.id as usize] * pot - put_in) * m)
+                .collect();
+        }
+
+        // Showdown
+        let win = state.rake.showdown_payoffs(pot, invested, Some(self.traverser))[t];
+        let tie = state.rake.showdown_payoffs(pot, invested, None)[t];
+        let strengths = showdown_strengths(self.strengths, self.hands, self.evaluator, &state.board);
+        let (own, other) = (&strengths[t], &strengths[1 - t]);
+        self.hands[t]
+            .iter()
+            .zip(own)
+            .map(|((combo, _), &strength)| {
+                let mut value = 0.0;
+                for (((o, _), &r), &other_strength) in self.hands[1 - t].iter().zip(&reach[1 - t]).zip(other) {
+                    if r == 0.0 || o.is_blocked_by(&combo.cards()) {
+                        continue;
+                    }
+                    value += r * match strength.cmp(&other_strength) {
+                        std::cmp::Ordering::Less => win,
+                        std::cmp::Ordering::Equal => tie,
+                        std::cmp::Ordering::Greater => -put_in,
+                    };
+                }
+                value
+            })
+            .collect()
+    }
+}
+
+/// Each player's combos with positive weight, ordered by combo ID
+pub(super) fn range_hands(state: &GameState) -> [Vec<(Combo, f64)>; 2] {
+    [&state.oop_range, &state.ip_range].map(|range| {
+        let mut hands: Vec<(Combo, f64)> = range
+            .get_combos()
+            .into_iter()
+            .filter(|&(_, weight)| weight > 0.0)
+            .filter_map(|(id, weight)| Some((Combo::from_id(id)?, weight)))
+            .collect();
+        hands.sort_by_key(|(combo, _)| combo.id);
+        hands
+    })
+}
+
+/// Hand strengths of each player's combos on a river board, cached
+///
+/// Combos the board blocks get the weakest strength.
+pub(super) fn showdown_strengths<'s>(
+    cache: &'s mut HashMap<Vec<Card>, [Vec<HandStrength>; 2]>,
+    hands: &[Vec<(Combo, f64)>; 2],
+    evaluator: &HandEvaluator,
+    board: &[Card],
+) -> &'s [Vec<HandStrength>; 2] {
+    cache.entry(board.to_vec()).or_insert_with(|| {
+        [0, 1].map(|p| {
+            hands[p]
+                .iter()
+                .map(|(combo, _)| match combo.is_blocked_by(board) {
+                    true => HandStrength::MAX,
+                    false => evaluator.evaluate_hand(combo.cards(), board),
+                })
+                .collect()
+        })
+    })
+}
+
+/// Opponent reach not sharing a card with each of `player`'s combos
+fn opponent_mass(hands: &[Vec<(Combo, f64)>; 2], player: Player, opponent_reach: &[f64]) -> Vec<f64> {
+    let p = player as usize;
+    let mut card_sums = [0.0; 52];
+    let mut by_id = vec![0.0; NUM_COMBOS];
+    let mut total = 0.0;
+    for ((combo, _), &r) in hands[1 - p].iter().zip(opponent_reach) {
+        card_sums[combo.card1.value() as usize] += r;
+        card_sums[combo.card2.value() as usize] += r;
+        by_id[combo.id as usize] = r;
+        total += r;
+    }
+
+    hands[p]
+        .iter()
+        .map(|(combo, _)| {
+            // The opponent's copy of the same combo was subtracted twice
+            total - card_sums[combo.card1.value() as usize] - card_sums[combo.card2.value() as usize]
+                + by_id[combo.id as usize]
+        })
+        .collect()
+}
+
+/// Zero the reach of every combo holding a dealt card
+fn zero_blocked(hands: &[Vec<(Combo, f64)>; 2], reach: &mut [Vec<f64>; 2], card: Card) {
+    for (player_hands, player_reach) in hands.iter().zip(reach.iter_mut()) {
+        for ((combo, _), r) in player_hands.iter().zip(player_reach.iter_mut()) {
+            if combo.is_blocked_by(&[card]) {
+                *r = 0.0;
+            }
+        }
+    }
+}
+
+pub(super) fn current_strategy(
+    regret_sum: &HashMap<String, Vec<f64>>,
+    locked: &HashMap<String, LockedStrategy>,
+    info_set: &str,
+    num_actions: usize,
+) -> Vec<f64> {
+    let strategy = match regret_sum.get(info_set) {
+        Some(regrets) => regret_matching(regrets),
+        None => vec![1.0 / num_actions as f64; num_actions],
+    };
+
+    match locked.get(info_set) {
+        Some(lock) => apply_lock(&strategy, lock),
+        None => strategy,
+    }
+}
+
+fn average_strategy(
+    strategy_sum: &HashMap<String, Vec<f64>>,
+    locked: &HashMap<String, LockedStrategy>,
+    info_set: &str,
+    num_actions: usize,
+) -> Vec<f64> {
+    let strategy = match strategy_sum.get(info_set) {
+        Some(sums) if sums.iter().sum::<f64>() > 0.0 => {
+            let total: f64 = sums.iter().sum();
+            sums.iter().map(|s| s / total).collect()
+        }
+        _ => vec![1.0 / num_actions as f64; num_actions],
+    };
+
+    match locked.get(info_set) {
+        Some(lock) => apply_lock(&strategy, lock),
+        None => strategy,
+    }
+}
+
+/// Paths of a subtree's nodes in preorder, each with its children's
+/// indices; returns the index of `node`
+fn flatten(node: &GameNode, path: &mut Vec<usize>, out: &mut Vec<(Vec<usize>, Vec<u32>)>) -> u32 {
+    let index = out.len();
+    out.push((path.clone(), Vec::new()));
+    for (i, (_, child)) in node.children.iter().enumerate() {
+        path.push(i);
+        let child = flatten(child, path, out);
+        path.pop();
+        out[index].1.push(child);
+    }
+    index as u32
+}
+
+/// Feed a subtree's shape into a fingerprint: pots, stacks and actions
+fn hash_node(hasher: &mut Sha256, node: &GameNode) {
+    hasher.update(node.state.pot.to_le_bytes());
+    for stack in node.state.stacks {
+        hasher.update(stack.to_le_bytes());
+    }
+    hasher.update([node.is_terminal as u8, node.is_leaf as u8]);
+    hasher.update((node.children.len() as u32).to_le_bytes());
+    for (action, child) in &node.children {
+        hasher.update(format!("{:?}", action));
+        hash_node(hasher, child);
+    }
+}
+
+/// Public part of an info set key: the action history
+pub(super) fn history_key(state: &GameState) -> String {
+    let history: Vec<String> = state.history.iter().map(|a| format!("{:?}", a)).collect();
+    history.join("/")
+}
+
+/// Info set key for a combo at a game state
+///
+/// The acting player's private information is the combo; the public
+/// information is the action history (which includes dealt cards).
+pub fn info_set_key(state: &GameState, combo_id: u16) -> String {
+    format!("{}|{}", history_key(state), combo_id)
+}
+
+/// Regret matching: strategy proportional to positive regrets
+pub fn regret_matching(regrets: &[f64]) -> Vec<f64> {
+    let positive_sum: f64 = regrets.iter().map(|r| r.max(0.0)).sum();
+    if positive_sum > 0.0 {
+        regrets.iter().map(|r| r.max(0.0) / positive_sum).collect()
+    } else {
+        vec![1.0 / regrets.len() as f64; regrets.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BetSizes, HandCategory, LockRule, NodeLock, SolveRequest};
+    use crate::solver::value_net::{Dense, INPUT_SIZE, OUTPUT_SIZE};
+    use crate::solver::Street;
+
+    fn state(board: &str, oop_range: &str, ip_range: &str) -> GameState {
+        GameState::from_request(&SolveRequest {
+            player: Player::OOP,
+            board: board.to_string(),
+            effective_stack: 100.0,
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: 10.0,
+            bet_sizes: Some(BetSizes {
+                oop_bet: "a".to_string(),
+                oop_raise: "a".to_string(),
+                ip_bet: "a".to_string(),
+                ip_raise: "a".to_string(),
+            }),
+            root_bet: None,
+            betting_history: None,
+            oop_range: Some(oop_range.to_string()),
+            ip_range: Some(ip_range.to_string()),
+            rake: None,
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        })
+        .unwrap()
+    }
+
+    fn root_frequency(solver: &mut CFRSolver, hand: &str, action: usize) -> f64 {
+        let (_, results) = solver.node_result(&[]).unwrap();
+        let hand: Combo = hand.parse().unwrap();
+        results.iter().find(|r| r.combo.id == hand.id).unwrap().strategy[action]
+    }
+
+    #[test]
+    fn test_regret_matching() {
+        assert_eq!(regret_matching(&[1.0, 3.0, -2.0]), vec![0.25, 0.75, 0.0]);
+        assert_eq!(regret_matching(&[-1.0, 0.0]), vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_opponent_mass() {
+        let hands = [
+            vec![("AsAh".parse().unwrap(), 1.0)],
+            vec![("AsKs".parse().unwrap(), 1.0), ("KdKc".parse().unwrap(), 1.0)],
+        ];
+        assert_eq!(opponent_mass(&hands, Player::OOP, &[0.5, 2.0]), vec![2.0]);
+        assert_eq!(opponent_mass(&hands, Player::IP, &[3.0]), vec![0.0, 3.0]);
+    }
+
+    #[test]
+    fn test_river_nuts_shove() {
+        // OOP holds the nuts or air against a bluff catcher on the river.
+        // The nuts always shove; IP calls air's shove often enough that
+        // air's EV of shoving matches checking.
+        let tree = GameTree::build(state("Ah Kd Qc 7s 2h", "JsTs,3d3c", "AsQs"), 10);
+        let mut solver = CFRSolver::new(tree);
+        solver.solve(300);
+
+        assert!(root_frequency(&mut solver, "JsTs", 1) > 0.95);
+        let (_, results) = solver.node_result(&[]).unwrap();
+        let nuts = results.iter().find(|r| r.combo.to_string() == "JsTs").unwrap();
+        assert!(nuts.ev > 10.0, "EV {}", nuts.ev);
+    }
+
+    #[test]
+    fn test_solved_tree_matches_node_results() {
+        // Turn to river: decisions, a chance node and showdowns
+        let tree = GameTree::build(state("Ah Kd Qc 7s", "JsTs,3d3c", "AsQs,KsKh"), 10);
+        let mut solver = CFRSolver::new(tree);
+        solver.solve(50);
+        let solved = solver.solved_tree();
+        solved.validate().unwrap();
+
+        // Root, then OOP shoves and IP acts
+        let (_, results) = solver.node_result(&[]).unwrap();
+        let root = &solved.nodes[0];
+        assert_eq!(root.kind, SolvedNodeKind::Decision(Player::OOP));
+        for (c, result) in results.iter().enumerate() {
+            assert_eq!(solved.combos[0][c], result.combo.id);
+            assert!((root.evs[c] as f64 - result.ev).abs() < 1e-4);
+            for (&p, &q) in root.combo_strategy(c).iter().zip(&result.strategy) {
+                assert!((p as f64 - q).abs() < 1e-6);
+            }
+        }
+        let shove = &solved.nodes[root.children[1] as usize];
+        assert_eq!(shove.kind, SolvedNodeKind::Decision(Player::IP));
+        assert_eq!(shove.evs.len(), 2);
+
+        // Check, check reaches the river card
+        let checked = &solved.nodes[solved.nodes[root.children[0] as usize].children[0] as usize];
+        assert_eq!(checked.kind, SolvedNodeKind::Chance);
+        assert!(checked.actions.iter().all(|a| matches!(a, Action::Deal(_))));
+    }
+
+    #[test]
+    fn test_fold_dominated_hand() {
+        // Facing a shove with the worst hand and no bluffs in the range: fold
+        let tree = GameTree::build(state("Ah Kd Qc 7s 2h", "AsAd", "3d3c"), 10);
+        let mut solver = CFRSolver::new(tree);
+        solver.solve(200);
+
+        let history = vec![HistoryAction {
+            order: 1,
+            position: Player::OOP,
+            action: crate::models::ActionType::Allin,
+            amount_percent: None,
+            card: None,
+        }];
+        let (node, results) = solver.node_result(&history).unwrap();
+        assert_eq!(node.actions()[0], Action::Fold);
+        assert!(results[0].strategy[0] > 0.99);
+    }
+
+    #[test]
+    fn test_exploitability() {
+        let tree = GameTree::build(state("Ah Kd Qc 7s 2h", "JsTs,3d3c", "AsQs"), 10);
+        let mut solver = CFRSolver::new(tree);
+        let uniform = solver.exploitability();
+        solver.solve(300);
+        let solved = solver.exploitability();
+        assert!(uniform > 1.0, "uniform {}", uniform);
+        assert!(solved < 0.05, "solved {}", solved);
+    }
+
+    #[test]
+    fn test_best_adjustment_to_lock() {
+        // OOP is locked into shoving every weak pair, so half the shoves are
+        // bluffs: IP calls them all and gains nothing more by deviating,
+        // while OOP would gain a lot by checking its air
+        let tree = GameTree::build(state("Ah Kd Qc 7s 2h", "JsTs,3d3c", "AsQs"), 10);
+        let mut solver = CFRSolver::new(tree);
+        let lock = NodeLock {
+            betting_history: vec![],
+            combos: vec![],
+            rules: vec![LockRule {
+                hands: String::new(),
+                category: Some(HandCategory::WeakPair),
+                action: ActionType::Allin,
+                amount_percent: None,
+                frequency: 1.0,
+            }],
+        };
+        let locks = NodeLocks::from_request(&[lock], &solver.tree.root.state.board).unwrap();
+        solver.lock_nodes(&locks).unwrap();
+        solver.solve(300);
+
+        assert!(root_frequency(&mut solver, "3d3c", 1) > 0.999);
+        let gain = |solver: &mut CFRSolver, player| {
+            solver.expected_value(player, PassMode::BestResponse) - solver.expected_value(player, PassMode::Average)
+        };
+        let ip_gain = gain(&mut solver, Player::IP);
+        let oop_gain = gain(&mut solver, Player::OOP);
+        assert!(ip_gain < 0.05, "IP gain {}", ip_gain);
+        assert!(oop_gain > 10.0, "OOP gain {}", oop_gain);
+    }
+
+    #[test]
+    fn test_kuhn_poker_spot() {
+        // Kuhn poker as a river spot: three disjoint combos, so the players
+        // hold different ones (AK > AQ > KQ high), the 1bb antes are dead
+        // money and the only bet is an all-in for 1bb
+        let kuhn = "AdKd,AcQc,KhQh".to_string();
+        let state = GameState::from_request(&SolveRequest {
+            player: Player::OOP,
+            board: "2c 3h 5s 8c 9h".to_string(),
+            effective_stack: 1.0,
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: 2.0,
+            bet_sizes: Some(BetSizes {
+                oop_bet: "a".to_string(),
+                oop_raise: "a".to_string(),
+                ip_bet: "a".to_string(),
+                ip_raise: "a".to_string(),
+            }),
+            root_bet: None,
+            betting_history: None,
+            oop_range: Some(kuhn.clone()),
+            ip_range: Some(kuhn),
+            rake: None,
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        })
+        .unwrap();
+        let mut solver = CFRSolver::new(GameTree::build(state, 10));
+        solver.solve(1000);
+
+        // Kuhn's first player is worth -1/18 net of the ante, which counts
+        // as dead money here
+        let value = solver.expected_value(Player::OOP, PassMode::Average);
+        assert!((value - (1.0 - 1.0 / 18.0)).abs() < 1e-3, "value {}", value);
+        assert!(solver.exploitability() < 1e-3, "exploitability {}", solver.exploitability());
+
+        // Facing a bet, IP calls with the king, folds the jack and calls
+        // the queen a third of the time
+        let bet = vec![HistoryAction {
+            order: 1,
+            position: Player::OOP,
+            action: ActionType::Allin,
+            amount_percent: None,
+            card: None,
+        }];
+        let (node, results) = solver.node_result(&bet).unwrap();
+        assert_eq!(node.actions(), vec![Action::Fold, Action::Call]);
+        let call = |hand: &str| results.iter().find(|r| r.combo.to_string() == hand).unwrap().strategy[1];
+        assert!(call("AdKd") > 0.99);
+        assert!(call("KhQh") < 0.01);
+        assert!((call("AcQc") - 1.0 / 3.0).abs() < 0.02, "call {}", call("AcQc"));
+    }
+
+    #[test]
+    fn test_root_values() {
+        // The best hand wins the whole pot whatever IP does
+        let tree = GameTree::build(state("Ah Kd Qc 7s 2h", "AsAd", "3d3c"), 10);
+        let mut solver = CFRSolver::new(tree);
+        solver.solve(200);
+
+        let [oop, ip] = solver.root_values();
+        let aces: Combo = "AsAd".parse().unwrap();
+        let threes: Combo = "3d3c".parse().unwrap();
+        let (aces, threes) = (oop[aces.id as usize], ip[threes.id as usize]);
+        assert!((aces - 10.0).abs() < 0.05, "EV {}", aces);
+        // Without rake the pot is shared out exactly
+        assert!((aces + threes - 10.0).abs() < 1e-9);
+        assert_eq!(oop.iter().filter(|&&v| v != 0.0).count(), 1);
+    }
+
+    #[test]
+    fn test_value_network_leaves() {
+        // A network claiming OOP wins the whole pot after the flop: IP folds to any bet
+        let mut output = Dense::zeros(1, OUTPUT_SIZE);
+        output.biases[..NUM_COMBOS].fill(1.0);
+        let network = ValueNetwork::new(vec![Dense::zeros(INPUT_SIZE, 1), output]).unwrap();
+
+        let tree = GameTree::build_depth_limited(state("Ah Kd Qc", "JsTs", "AsQs"), 10, Street::Turn);
+        let mut solver = CFRSolver::new(tree).with_value_network(Arc::new(network));
+        solver.solve(100);
+
+        let (_, results) = solver.node_result(&[]).unwrap();
+        assert!(results[0].ev > 9.9, "EV {}", results[0].ev);
     }
 }
@@ -0,0 +1,237 @@
+//! Checkpoints of tabular CFR solves
+//!
+//! Everything [`CFRSolver`](super::CFRSolver) carries from one iteration to
+//! the next: regrets, strategy sums, the iteration counter (which sets the
+//! linear averaging weights) and the sampling RNG. Floats are stored as
+//! their exact bits, so a solve resumed from a checkpoint continues
+//! bit-for-bit as if it had never stopped.
+//!
+//! File layout (little-endian): magic, format version, fingerprint of the
+//! solved tree, CRC-32 of the body, then the body: iteration, RNG state,
+//! sampling variant and the two tables as (key, values) entries in key
+//! order.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::bytes::Reader;
+use super::mccfr::Sampling;
+
+/// Magic bytes at the start of a checkpoint file
+pub const MAGIC: &[u8; 8] = b"DPCFRCKP";
+
+/// Version of the checkpoint layout
+pub const FORMAT_VERSION: u16 = 2;
+
+const HEADER_LEN: usize = 8 + 2 + 8 + 4;
+
+/// Where and how often a solve writes checkpoints
+#[derive(Debug, Clone)]
+pub struct CheckpointConfig {
+    pub path: PathBuf,
+    /// Iterations between checkpoints
+    pub every: usize,
+}
+
+/// Solver state between two iterations
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    /// [`CFRSolver::fingerprint`](super::CFRSolver::fingerprint) of the solve
+    pub fingerprint: u64,
+    pub iteration: usize,
+    pub rng_state: u64,
+    pub sampling: Sampling,
+    pub regret_sum: HashMap<String, Vec<f64>>,
+    pub strategy_sum: HashMap<String, Vec<f64>>,
+}
+
+impl Checkpoint {
+    /// Read a checkpoint file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|e| format!("Cannot read checkpoint '{}': {}", path.display(), e))?;
+        Self::parse(&bytes)
+    }
+
+    /// Write to a file, replacing it only once the new one is complete
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, self.to_bytes())
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|e| format!("Cannot write checkpoint '{}': {}", path.display(), e))
+    }
+
+    /// Parse a checkpoint file
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let mut header = Reader::new(bytes, "Checkpoint file");
+        if header.take(8)? != MAGIC {
+            return Err("Not a checkpoint file".to_string());
+        }
+        let version = header.u16()?;
+        if version != FORMAT_VERSION {
+            return Err(format!("Unsupported checkpoint version {}", version));
+        }
+        let fingerprint = header.u64()?;
+        let checksum = header.u32()?;
+        if crc32fast::hash(&bytes[HEADER_LEN..]) != checksum {
+            return Err("Checkpoint checksum mismatch".to_string());
+        }
+
+        let mut reader = Reader::at(bytes, HEADER_LEN, "Checkpoint file");
+        let iteration = reader.u64()? as usize;
+        let rng_state = reader.u64()?;
+        let sampling = match reader.u8()? {
+            0 => Sampling::FullWidth,
+            1 => Sampling::Chance,
+            2 => Sampling::External,
+            3 => Sampling::Outcome,
+            other => return Err(format!("Unknown sampling variant {} in checkpoint", other)),
+        };
+        let regret_sum = read_table(&mut reader)?;
+        let strategy_sum = read_table(&mut reader)?;
+        if !reader.is_done() {
+            return Err("Trailing bytes after the checkpoint tables".to_string());
+        }
+        Ok(Checkpoint {
+            fingerprint,
+            iteration,
+            rng_state,
+            sampling,
+            regret_sum,
+            strategy_sum,
+        })
+    }
+
+    /// Serialise to the checkpoint file format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend((self.iteration as u64).to_le_bytes());
+        body.extend(self.rng_state.to_le_bytes());
+        body.push(match self.sampling {
+            Sampling::FullWidth => 0,
+            Sampling::Chance => 1,
+            Sampling::External => 2,
+            Sampling::Outcome => 3,
+        });
+        write_table(&mut body, &self.regret_sum);
+        write_table(&mut body, &self.strategy_sum);
+
+        let mut file = Vec::with_capacity(HEADER_LEN + body.len());
+        file.extend(MAGIC);
+        file.extend(FORMAT_VERSION.to_le_bytes());
+        file.extend(self.fingerprint.to_le_bytes());
+        file.extend(crc32fast::hash(&body).to_le_bytes());
+        file.extend(body);
+        file
+    }
+}
+
+/// Entries in key order, so equal tables give equal files
+fn write_table(bytes: &mut Vec<u8>, table: &HashMap<String, Vec<f64>>) {
+    let mut keys: Vec<&String> = table.keys().collect();
+    keys.sort();
+    bytes.extend((keys.len() as u32).to_le_bytes());
+    for key in keys {
+        bytes.extend((key.len() as u32).to_le_bytes());
+        bytes.extend(key.as_bytes());
+        let values = &table[key];
+        bytes.extend((values.len() as u32).to_le_bytes());
+        for v in values {
+            bytes.extend(v.to_bits().to_le_bytes());
+        }
+    }
+}
+
+/// Read a table written by `write_table`
+fn read_table(reader: &mut Reader) -> Result<HashMap<String, Vec<f64>>, String> {
+    let entries = reader.u32()? as usize;
+    let mut table = HashMap::with_capacity(entries.min(1 << 20));
+    for _ in 0..entries {
+        let len = reader.u32()? as usize;
+        let key = std::str::from_utf8(reader.take(len)?).map_err(|_| "Checkpoint key is not UTF-8")?;
+        let n = reader.u32()? as usize;
+        table.insert(key.to_string(), reader.f64s(n)?);
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BetSizes, Player, SolveRequest};
+    use crate::solver::{CFRSolver, GameState, GameTree};
+
+    fn solver(sampling: Sampling) -> CFRSolver {
+        solver_of("JsTs,3d3c", sampling)
+    }
+
+    fn solver_of(oop_range: &str, sampling: Sampling) -> CFRSolver {
+        let state = GameState::from_request(&SolveRequest {
+            player: Player::OOP,
+            board: "Ah Kd Qc 7s".to_string(),
+            effective_stack: 100.0,
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: 10.0,
+            bet_sizes: Some(BetSizes {
+                oop_bet: "a".to_string(),
+                oop_raise: "a".to_string(),
+                ip_bet: "a".to_string(),
+                ip_raise: "a".to_string(),
+            }),
+            root_bet: None,
+            betting_history: None,
+            oop_range: Some(oop_range.to_string()),
+            ip_range: Some("AsQs,KsKh".to_string()),
+            rake: None,
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        })
+        .unwrap();
+        CFRSolver::new(GameTree::build(state, 10)).with_sampling(sampling, 11)
+    }
+
+    #[test]
+    fn test_resume_is_bit_identical() {
+        let path = std::env::temp_dir().join(format!("checkpoint-{}.bin", std::process::id()));
+        for sampling in [Sampling::FullWidth, Sampling::Chance, Sampling::External, Sampling::Outcome] {
+            let mut original = solver(sampling);
+            original.solve(10);
+            original.checkpoint().save(&path).unwrap();
+            original.solve(10);
+
+            let mut resumed = solver(sampling);
+            resumed.restore(Checkpoint::load(&path).unwrap()).unwrap();
+            assert_eq!(resumed.iteration, 10);
+            resumed.solve(10);
+
+            // Same bits, not just close values
+            assert_eq!(resumed.checkpoint().to_bytes(), original.checkpoint().to_bytes(), "{:?}", sampling);
+        }
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_invalid_checkpoints() {
+        let mut cfr = solver(Sampling::External);
+        cfr.solve(5);
+        let bytes = cfr.checkpoint().to_bytes();
+        assert_eq!(Checkpoint::parse(&bytes).unwrap(), cfr.checkpoint());
+
+        assert!(Checkpoint::parse(&bytes[..bytes.len() - 1]).is_err());
+        let mut corrupt = bytes.clone();
+        corrupt[HEADER_LEN + 3] ^= 1;
+        assert!(Checkpoint::parse(&corrupt).unwrap_err().contains("checksum"));
+
+        // Resuming under another sampling scheme would not continue the same run
+        let mut other = solver(Sampling::Outcome);
+        assert!(other.restore(cfr.checkpoint()).is_err());
+        // Nor would one of another spot
+        let mut other = solver_of("JsTs,4d4c", Sampling::External);
+        assert!(other.restore(cfr.checkpoint()).unwrap_err().contains("different game tree"));
+    }
+}
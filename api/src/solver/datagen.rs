@@ -0,0 +1,413 @@
+//! Training data for the value network
+//!
+//! Random subgames are sampled at a street boundary (board, both ranges,
+//! pot and stack), solved with CFR+ to a target exploitability, and every
+//! combo's value there becomes a training target. Samples are written to
+//! numbered shard files in a dataset directory.
+//!
+//! Shard format (little-endian): magic `DPCFRVDS`, u16 format version, u16
+//! feature version, u32 input size, u32 output size, u32 sample count, then
+//! per sample the input f32s, the target f32s and one mask byte per target
+//! (1 when the combo was in the range, so its target is meaningful).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::bet_sizing::BetSizeConfig;
+use super::bytes::Reader;
+use super::cards::{Card, Combo};
+use super::cfr::CFRSolver;
+use super::game_state::{Action, GameState, Street};
+use super::game_tree::GameTree;
+use super::rake::Rake;
+use super::range::Range;
+use super::rng::Rng;
+use super::utils::{bb_to_chips, chips_to_bb};
+use super::features::{FeatureEncoder, FEATURE_VERSION, NUM_COMBOS};
+use super::value_net::{ValueNetwork, INPUT_SIZE, OUTPUT_SIZE};
+use crate::models::{BetSizes, Player};
+
+/// Magic bytes at the start of a shard file
+pub const MAGIC: &[u8; 8] = b"DPCFRVDS";
+
+/// Version of the shard file layout
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Subgame sampling and solving parameters
+#[derive(Debug, Clone)]
+pub struct DataGenConfig {
+    /// Board cards at the boundary: 4 values the river, 3 the turn and river
+    pub board_cards: usize,
+    /// Pot range in bb
+    pub pot: (f64, f64),
+    /// Effective stack range in bb
+    pub stack: (f64, f64),
+    /// Chance of each combo being in a sampled range
+    pub range_density: f64,
+    pub bet_sizes: BetSizes,
+    /// Bets and raises per subgame
+    pub max_depth: usize,
+    /// Exploitability to solve to, in % of the pot
+    pub target_exploitability: f64,
+    pub max_iterations: usize,
+    /// Iterations between exploitability checks
+    pub check_every: usize,
+    /// Values river boundaries, so 3-card subgames stop before the river
+    pub value_network: Option<Arc<ValueNetwork>>,
+}
+
+impl Default for DataGenConfig {
+    fn default() -> Self {
+        Self {
+            board_cards: 4,
+            pot: (4.0, 40.0),
+            stack: (5.0, 100.0),
+            range_density: 0.05,
+            bet_sizes: BetSizes {
+                oop_bet: "50, a".to_string(),
+                oop_raise: "a".to_string(),
+                ip_bet: "50, a".to_string(),
+                ip_raise: "a".to_string(),
+            },
+            max_depth: 3,
+            target_exploitability: 1.0,
+            max_iterations: 1000,
+            check_every: 25,
+            value_network: None,
+        }
+    }
+}
+
+/// One training example
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// Encoded public state, see [`FeatureEncoder::encode_public_state`]
+    pub input: Vec<f32>,
+    /// Pot share of every combo for each player [OOP, IP], by combo ID
+    pub target: Vec<f32>,
+    /// Whether each target is meaningful (the combo was in the range)
+    pub mask: Vec<bool>,
+}
+
+/// How a sampled subgame was solved
+#[derive(Debug, Clone, Copy)]
+pub struct SolveStats {
+    pub iterations: usize,
+    /// Exploitability reached, in % of the pot
+    pub exploitability: f64,
+}
+
+/// Sample a random subgame, solve it and return its training example
+pub fn generate_sample(config: &DataGenConfig, rng: &mut Rng) -> Result<(Sample, SolveStats), String> {
+    let street = match config.board_cards {
+        3 | 4 => Street::from_board_len(config.board_cards).unwrap(),
+        n => return Err(format!("Subgames start on a 3 or 4 card board, got {}", n)),
+    };
+    let mut deck: Vec<Card> = (0..52).filter_map(Card::from_value).collect();
+    rng.shuffle(&mut deck);
+    let board = deck[..config.board_cards].to_vec();
+
+    let pot = bb_to_chips(rng.range(config.pot.0, config.pot.1)).max(1);
+    let stack = bb_to_chips(rng.range(config.stack.0, config.stack.1)).max(1);
+    let ranges = [0, 1].map(|_| random_range(&board, config.range_density, rng));
+
+    // Both players checked: the street is over and the next card is dealt
+    let state = GameState {
+        street,
+        board: board.clone(),
+        pot,
+        stacks: [stack; 2],
+        bets: [0, 0],
+        starting_stacks: [stack; 2],
+        to_act: Player::OOP,
+        oop_range: Arc::new(ranges[0].clone()),
+        ip_range: Arc::new(ranges[1].clone()),
+        history: vec![Action::Check, Action::Check],
+        bet_config: BetSizeConfig::from_bet_sizes(&config.bet_sizes)?,
+        rake: Rake::none(),
+    };
+    let mut solver = match (&config.value_network, street) {
+        (Some(network), Street::Flop) => {
+            let tree = GameTree::build_depth_limited(state, config.max_depth, Street::River);
+            CFRSolver::new(tree).with_value_network(network.clone())
+        }
+        _ => CFRSolver::new(GameTree::build(state, config.max_depth)),
+    };
+
+    let pot_bb = chips_to_bb(pot);
+    let target = config.target_exploitability * pot_bb / 100.0;
+    let mut iterations = 0;
+    let mut exploitability = solver.exploitability();
+    while exploitability > target && iterations < config.max_iterations {
+        let batch = config.check_every.max(1).min(config.max_iterations - iterations);
+        solver.solve(batch);
+        iterations += batch;
+        exploitability = solver.exploitability();
+    }
+
+    let weights = ranges.map(|range| {
+        let mut by_id = vec![0.0; NUM_COMBOS];
+        for (id, weight) in range.get_combos() {
+            by_id[id as usize] = weight;
+        }
+        by_id
+    });
+    let (input, permutation) =
+        FeatureEncoder::current().encode_public_state(&board, pot, stack, [&weights[0], &weights[1]]);
+    // Targets are indexed like the network outputs, by the relabelled combos
+    let values = solver.root_values();
+    let mut target = vec![0.0; OUTPUT_SIZE];
+    let mut mask = vec![false; OUTPUT_SIZE];
+    for id in 0..NUM_COMBOS {
+        let mapped = permutation.apply_combo(&Combo::from_id(id as u16).unwrap()).id as usize;
+        for p in 0..2 {
+            target[p * NUM_COMBOS + mapped] = (values[p][id] / pot_bb) as f32;
+            mask[p * NUM_COMBOS + mapped] = weights[p][id] > 0.0;
+        }
+    }
+
+    let sample = Sample { input, target, mask };
+    let stats = SolveStats {
+        iterations,
+        exploitability: exploitability * 100.0 / pot_bb,
+    };
+    Ok((sample, stats))
+}
+
+/// Random weights on a random subset of the combos not blocked by the board
+fn random_range(board: &[Card], density: f64, rng: &mut Rng) -> Range {
+    let available: Vec<Combo> = (0..NUM_COMBOS as u16)
+        .filter_map(Combo::from_id)
+        .filter(|combo| !combo.is_blocked_by(board))
+        .collect();
+    let mut range = Range::new();
+    for combo in &available {
+        if rng.next_f64() < density {
+            range.set_frequency(combo.id, 1.0 - rng.next_f64());
+        }
+    }
+    if range.is_empty() {
+        range.set_frequency(available[rng.below(available.len())].id, 1.0);
+    }
+    range
+}
+
+/// Writes samples to numbered shards in a dataset directory
+pub struct DatasetWriter {
+    dir: PathBuf,
+    shard_size: usize,
+    buffer: Vec<Sample>,
+    next_shard: usize,
+}
+
+impl DatasetWriter {
+    /// Open a dataset directory, creating it if needed
+    ///
+    /// New shards are numbered after any already there, so repeated runs
+    /// add to the same dataset.
+    pub fn create(dir: impl AsRef<Path>, shard_size: usize) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| format!("Cannot create '{}': {}", dir.display(), e))?;
+        let next_shard = shard_paths(&dir)?.len();
+        Ok(DatasetWriter {
+            dir,
+            shard_size: shard_size.max(1),
+            buffer: Vec::new(),
+            next_shard,
+        })
+    }
+
+    /// Add a sample, writing a shard once enough are buffered
+    pub fn push(&mut self, sample: Sample) -> Result<(), String> {
+        self.buffer.push(sample);
+        if self.buffer.len() >= self.shard_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write the remaining samples and return the number of shards written
+    pub fn finish(mut self) -> Result<usize, String> {
+        self.flush()?;
+        Ok(self.next_shard)
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let path = self.dir.join(format!("shard-{:05}.bin", self.next_shard));
+        fs::write(&path, encode_shard(&self.buffer))
+            .map_err(|e| format!("Cannot write '{}': {}", path.display(), e))?;
+        self.buffer.clear();
+        self.next_shard += 1;
+        Ok(())
+    }
+}
+
+/// Shard files of a dataset directory in order
+pub fn shard_paths(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>, String> {
+    let dir = dir.as_ref();
+    let entries = fs::read_dir(dir).map_err(|e| format!("Cannot read '{}': {}", dir.display(), e))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("shard-") && name.ends_with(".bin"))
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Read every shard of a dataset directory
+pub fn load_dataset(dir: impl AsRef<Path>) -> Result<Vec<Sample>, String> {
+    let mut samples = Vec::new();
+    for path in shard_paths(dir)? {
+        let bytes = fs::read(&path).map_err(|e| format!("Cannot read '{}': {}", path.display(), e))?;
+        samples.extend(parse_shard(&bytes).map_err(|e| format!("{}: {}", path.display(), e))?);
+    }
+    Ok(samples)
+}
+
+/// Serialise samples to the shard format
+pub fn encode_shard(samples: &[Sample]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(24 + samples.len() * (4 * INPUT_SIZE + 5 * OUTPUT_SIZE));
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend(FORMAT_VERSION.to_le_bytes());
+    bytes.extend(FEATURE_VERSION.to_le_bytes());
+    bytes.extend((INPUT_SIZE as u32).to_le_bytes());
+    bytes.extend((OUTPUT_SIZE as u32).to_le_bytes());
+    bytes.extend((samples.len() as u32).to_le_bytes());
+    for sample in samples {
+        for x in sample.input.iter().chain(&sample.target) {
+            bytes.extend(x.to_le_bytes());
+        }
+        bytes.extend(sample.mask.iter().map(|&m| m as u8));
+    }
+    bytes
+}
+
+/// Parse a shard file
+pub fn parse_shard(bytes: &[u8]) -> Result<Vec<Sample>, String> {
+    let mut reader = Reader::new(bytes, "Shard");
+    if reader.take(8)? != MAGIC {
+        return Err("Not a dataset shard".to_string());
+    }
+    let version = reader.u16()?;
+    if version != FORMAT_VERSION {
+        return Err(format!("Unsupported shard format version {}", version));
+    }
+    let features = reader.u16()?;
+    if features != FEATURE_VERSION {
+        return Err(format!(
+            "Shard was generated with feature version {} (expected {})",
+            features, FEATURE_VERSION
+        ));
+    }
+    let (inputs, outputs) = (reader.u32()? as usize, reader.u32()? as usize);
+    if inputs != INPUT_SIZE || outputs != OUTPUT_SIZE {
+        return Err(format!(
+            "Shard samples map {} inputs to {} outputs (expected {} to {})",
+            inputs, outputs, INPUT_SIZE, OUTPUT_SIZE
+        ));
+    }
+
+    let count = reader.u32()? as usize;
+    let mut samples = Vec::with_capacity(count.min(4096));
+    for _ in 0..count {
+        samples.push(Sample {
+            input: reader.f32s(INPUT_SIZE)?,
+            target: reader.f32s(OUTPUT_SIZE)?,
+            mask: reader.take(OUTPUT_SIZE)?.iter().map(|&b| b != 0).collect(),
+        });
+    }
+    if !reader.is_done() {
+        return Err("Trailing bytes after the last sample".to_string());
+    }
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_config() -> DataGenConfig {
+        DataGenConfig {
+            range_density: 0.01,
+            bet_sizes: BetSizes {
+                oop_bet: "a".to_string(),
+                oop_raise: "a".to_string(),
+                ip_bet: "a".to_string(),
+                ip_raise: "a".to_string(),
+            },
+            max_depth: 1,
+            target_exploitability: 2.0,
+            max_iterations: 50,
+            check_every: 10,
+            ..DataGenConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_generate_sample() {
+        let mut rng = Rng::new(3);
+        let (sample, stats) = generate_sample(&small_config(), &mut rng).unwrap();
+        assert_eq!(sample.input.len(), INPUT_SIZE);
+        assert_eq!(sample.target.len(), OUTPUT_SIZE);
+        // Rank and suit of each of the four board cards
+        let board_size = INPUT_SIZE - 2 - 2 * NUM_COMBOS;
+        assert_eq!(sample.input[..board_size].iter().sum::<f32>(), 8.0);
+        assert!(stats.iterations <= 50);
+
+        // Pot shares of combos in the ranges; the rest are masked out
+        let masked = sample.mask.iter().filter(|&&m| m).count();
+        assert!(masked >= 2);
+        for (&share, &m) in sample.target.iter().zip(&sample.mask) {
+            if m {
+                assert!(share.is_finite());
+            } else {
+                assert_eq!(share, 0.0);
+            }
+        }
+
+        // Same seed, same sample
+        let (again, _) = generate_sample(&small_config(), &mut Rng::new(3)).unwrap();
+        assert_eq!(again, sample);
+    }
+
+    #[test]
+    fn test_shard_roundtrip() {
+        let mut rng = Rng::new(1);
+        let samples: Vec<Sample> = (0..3)
+            .map(|_| generate_sample(&small_config(), &mut rng).unwrap().0)
+            .collect();
+        let bytes = encode_shard(&samples);
+        assert_eq!(parse_shard(&bytes).unwrap(), samples);
+        assert!(parse_shard(&bytes[..bytes.len() - 1]).is_err());
+        assert!(parse_shard(b"DPCFRVNN").is_err());
+    }
+
+    #[test]
+    fn test_dataset_writer() {
+        let dir = std::env::temp_dir().join(format!("deeppdcfr-datagen-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let sample = generate_sample(&small_config(), &mut Rng::new(5)).unwrap().0;
+
+        let mut writer = DatasetWriter::create(&dir, 2).unwrap();
+        for _ in 0..5 {
+            writer.push(sample.clone()).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), 3);
+        assert_eq!(load_dataset(&dir).unwrap().len(), 5);
+
+        // A second run adds shards after the existing ones
+        let mut writer = DatasetWriter::create(&dir, 2).unwrap();
+        writer.push(sample).unwrap();
+        assert_eq!(writer.finish().unwrap(), 4);
+        assert_eq!(load_dataset(&dir).unwrap().len(), 6);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
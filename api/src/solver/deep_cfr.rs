@@ -0,0 +1,582 @@
+//! Deep CFR
+//!
+//! An alternative to [`CFRSolver`](super::CFRSolver) for games too big to
+//! hold regrets per info set. Nothing is stored per node: each iteration
+//! deals hands from the ranges and walks the game with external sampling
+//! (every traverser action, one sampled opponent action and card), adding
+//! (info set features, regrets) to a reservoir buffer per player. An
+//! advantage network is then trained from scratch on each buffer, and
+//! regret matching on its outputs is the next iteration's strategy. The
+//! opponents' strategies seen along the way train the average-strategy
+//! network at the end. Samples are weighted by iteration, as in Linear CFR.
+
+use std::cmp::Ordering;
+
+use super::cards::{Card, Combo};
+use super::features::FeatureEncoder;
+use super::game_state::{find_action, Action, GameState};
+use super::game_tree::tree_actions;
+use super::hand_class::ComboResult;
+use super::hand_eval::HandEvaluator;
+use super::rng::{sample_index, Rng};
+use super::trainer::{batch_step, init_layers, Adam, Example};
+use super::utils::chips_to_bb;
+use super::value_net::{forward_layers, Dense};
+use crate::models::{HistoryAction, Player};
+
+/// Network outputs: the most actions any decision can have
+pub const MAX_ACTIONS: usize = 8;
+
+/// Deep CFR parameters
+#[derive(Debug, Clone)]
+pub struct DeepCfrConfig {
+    /// Traversals per player per iteration
+    pub traversals: usize,
+    /// Capacity of each reservoir buffer
+    pub buffer_size: usize,
+    /// Hidden layer sizes of the advantage and strategy networks
+    pub hidden: Vec<usize>,
+    /// Minibatches per network training
+    pub train_steps: usize,
+    pub batch_size: usize,
+    pub learning_rate: f64,
+    /// Sampled playouts per combo when estimating EVs
+    pub ev_samples: usize,
+    pub seed: u64,
+}
+
+impl Default for DeepCfrConfig {
+    fn default() -> Self {
+        Self {
+            traversals: 500,
+            buffer_size: 100_000,
+            hidden: vec![64, 64],
+            train_steps: 200,
+            batch_size: 128,
+            learning_rate: 1e-3,
+            ev_samples: 16,
+            seed: 0,
+        }
+    }
+}
+
+/// Uniform sample of everything pushed into it, up to a capacity
+#[derive(Debug, Clone)]
+pub struct Reservoir<T> {
+    capacity: usize,
+    items: Vec<T>,
+    seen: usize,
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(capacity: usize) -> Self {
+        Reservoir {
+            capacity,
+            items: Vec::new(),
+            seen: 0,
+        }
+    }
+
+    /// Offer an item; once full it replaces a random one with falling odds
+    pub fn push(&mut self, item: T, rng: &mut Rng) {
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+        } else {
+            let slot = rng.below(self.seen);
+            if slot < self.capacity {
+                self.items[slot] = item;
+            }
+        }
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Number of items offered so far
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+}
+
+/// A training target recorded during traversal
+#[derive(Debug, Clone)]
+struct Memory {
+    features: Vec<f32>,
+    /// Regrets or strategy, padded to `MAX_ACTIONS`
+    target: Vec<f32>,
+    /// Legal actions
+    mask: Vec<bool>,
+    iteration: usize,
+}
+
+impl Memory {
+    fn new(features: Vec<f32>, values: &[f64], iteration: usize) -> Self {
+        let mut target = vec![0.0; MAX_ACTIONS];
+        let mut mask = vec![false; MAX_ACTIONS];
+        for (a, &v) in values.iter().enumerate() {
+            target[a] = v as f32;
+            mask[a] = true;
+        }
+        Memory {
+            features,
+            target,
+            mask,
+            iteration,
+        }
+    }
+}
+
+/// Deep CFR solver
+pub struct DeepCfrSolver {
+    root: GameState,
+    max_depth: usize,
+    config: DeepCfrConfig,
+    /// Combos of each player [OOP, IP] with their range weights, by combo ID
+    hands: [Vec<(Combo, f64)>; 2],
+    /// Advantage network per player (empty until first trained: uniform play)
+    advantage: [Vec<Dense>; 2],
+    advantage_memory: [Reservoir<Memory>; 2],
+    strategy_memory: Reservoir<Memory>,
+    /// Average-strategy network (empty until trained)
+    policy: Vec<Dense>,
+    pub iteration: usize,
+    rng: Rng,
+    evaluator: HandEvaluator,
+    encoder: FeatureEncoder,
+}
+
+impl DeepCfrSolver {
+    /// Solver for the game from `root`, with at most `max_depth` bets and raises
+    pub fn new(root: GameState, max_depth: usize, config: DeepCfrConfig) -> Result<Self, String> {
+        let bets = &root.bet_config;
+        let sizes = [&bets.oop_bet, &bets.oop_raise, &bets.ip_bet, &bets.ip_raise];
+        if sizes.iter().any(|s| s.len() + 2 > MAX_ACTIONS) {
+            return Err(format!("Deep CFR supports at most {} bet sizes per situation", MAX_ACTIONS - 2));
+        }
+
+        let hands = [&root.oop_range, &root.ip_range].map(|range| {
+            let mut hands: Vec<(Combo, f64)> = range
+                .get_combos()
+                .into_iter()
+                .filter(|&(_, weight)| weight > 0.0)
+                .filter_map(|(id, weight)| Some((Combo::from_id(id)?, weight)))
+                .collect();
+            hands.sort_by_key(|(combo, _)| combo.id);
+            hands
+        });
+        if hands.iter().any(|h| h.is_empty()) {
+            return Err("Both ranges need at least one combo".to_string());
+        }
+
+        Ok(DeepCfrSolver {
+            root,
+            max_depth,
+            hands,
+            advantage: [Vec::new(), Vec::new()],
+            advantage_memory: [Reservoir::new(config.buffer_size), Reservoir::new(config.buffer_size)],
+            strategy_memory: Reservoir::new(config.buffer_size),
+            policy: Vec::new(),
+            iteration: 0,
+            rng: Rng::new(config.seed),
+            evaluator: HandEvaluator::new(),
+            encoder: FeatureEncoder::current(),
+            config,
+        })
+    }
+
+    /// Run Deep CFR iterations, then train the average-strategy network
+    pub fn solve(&mut self, iterations: usize) -> Result<(), String> {
+        let root = self.root.clone();
+        for _ in 0..iterations {
+            self.iteration += 1;
+            for traverser in [Player::OOP, Player::IP] {
+                for _ in 0..self.config.traversals {
+                    let hands = self.deal()?;
+                    self.traverse(&root, 0, hands, traverser);
+                }
+                let t = traverser as usize;
+                self.advantage[t] = self.train(Memories::Advantage(t));
+            }
+        }
+        self.policy = self.train(Memories::Strategy);
+        Ok(())
+    }
+
+    /// Average strategy of a combo at a decision (uniform before solving)
+    pub fn average_strategy(&self, state: &GameState, combo: &Combo, num_actions: usize) -> Vec<f64> {
+        if self.policy.is_empty() {
+            return vec![1.0 / num_actions as f64; num_actions];
+        }
+        let output = forward_layers(&self.policy, &self.encoder.encode_infoset(state, combo));
+        let probs: Vec<f64> = output[..num_actions].iter().map(|&p| p.max(0.0) as f64).collect();
+        let total: f64 = probs.iter().sum();
+        if total > 0.0 {
+            probs.iter().map(|p| p / total).collect()
+        } else {
+            vec![1.0 / num_actions as f64; num_actions]
+        }
+    }
+
+    /// Average strategy and estimated EV of each combo at the node reached
+    /// by `history`, with the node's state and actions
+    ///
+    /// Combo weights are the range weights times the reach of the actions
+    /// leading to the node. EVs in bb are averaged over sampled playouts.
+    pub fn node_result(
+        &mut self,
+        history: &[HistoryAction],
+    ) -> Result<(GameState, Vec<Action>, Vec<ComboResult>), String> {
+        let mut sorted: Vec<&HistoryAction> = history.iter().collect();
+        sorted.sort_by_key(|h| h.order);
+
+        let mut reach: [Vec<f64>; 2] = [0, 1].map(|p| self.hands[p].iter().map(|&(_, w)| w).collect());
+        let mut state = self.root.clone();
+        let mut depth = 0;
+        for step in sorted {
+            let actions = self.actions(&state, depth);
+            let card = step.card.as_deref().map(|c| c.parse::<Card>()).transpose()?;
+            let index = find_action(&actions, &step.action, step.amount_percent, card, state.pot, state.to_call())
+                .ok_or_else(|| format!("Action {} ({:?}) is not available in the tree", step.order, step.action))?;
+
+            let action = &actions[index];
+            if let Action::Deal(card) = action {
+                for (hands, reach) in self.hands.iter().zip(reach.iter_mut()) {
+                    for ((combo, _), r) in hands.iter().zip(reach.iter_mut()) {
+                        if combo.is_blocked_by(&[*card]) {
+                            *r = 0.0;
+                        }
+                    }
+                }
+            } else {
+                let p = state.to_act as usize;
+                for ((combo, _), r) in self.hands[p].iter().zip(reach[p].iter_mut()) {
+                    *r *= self.average_strategy(&state, combo, actions.len())[index];
+                }
+            }
+            depth += action.is_aggressive() as usize;
+            state = state.apply_action(action.clone());
+        }
+
+        if state.is_terminal() || state.street_closed() {
+            return Err("The betting history does not lead to a decision node".to_string());
+        }
+        let actions = self.actions(&state, depth);
+        let p = state.to_act as usize;
+        let mut results = Vec::new();
+        for (i, &(combo, _)) in self.hands[p].clone().iter().enumerate() {
+            if reach[p][i] <= 0.0 || combo.is_blocked_by(&state.board) {
+                continue;
+            }
+            results.push(ComboResult {
+                combo,
+                weight: reach[p][i],
+                strategy: self.average_strategy(&state, &combo, actions.len()),
+                ev: self.estimate_ev(&state, depth, combo, &reach[1 - p]),
+            });
+        }
+        Ok((state, actions, results))
+    }
+
+    /// Actions at a state: the deals once a street is over, else the tree's
+    fn actions(&self, state: &GameState, depth: usize) -> Vec<Action> {
+        match state.street_closed() {
+            true => state.get_available_actions(),
+            false => tree_actions(state, depth, self.max_depth),
+        }
+    }
+
+    /// Deal a hand to each player from their ranges
+    fn deal(&mut self) -> Result<[Combo; 2], String> {
+        for _ in 0..1000 {
+            let oop = sample_weighted(&self.hands[0], &mut self.rng);
+            let ip = sample_weighted(&self.hands[1], &mut self.rng);
+            if !oop.is_blocked_by(&ip.cards()) {
+                return Ok([oop, ip]);
+            }
+        }
+        Err("The ranges have no combos that can be dealt together".to_string())
+    }
+
+    /// External-sampling traversal; returns the traverser's value in bb
+    fn traverse(&mut self, state: &GameState, depth: usize, hands: [Combo; 2], traverser: Player) -> f64 {
+        if state.is_terminal() {
+            return self.utility(state, hands, traverser);
+        }
+        if state.street_closed() {
+            let card = self.deal_card(state, hands);
+            return self.traverse(&state.apply_action(Action::Deal(card)), depth, hands, traverser);
+        }
+
+        let actions = tree_actions(state, depth, self.max_depth);
+        let p = state.to_act as usize;
+        let features = self.encoder.encode_infoset(state, &hands[p]);
+        let strategy = advantage_strategy(&self.advantage[p], &features, actions.len());
+        let child = |action: &Action| (state.apply_action(action.clone()), depth + action.is_aggressive() as usize);
+
+        if state.to_act == traverser {
+            let values: Vec<f64> = actions
+                .iter()
+                .map(|action| {
+                    let (next, depth) = child(action);
+                    self.traverse(&next, depth, hands, traverser)
+                })
+                .collect();
+            let ev: f64 = values.iter().zip(&strategy).map(|(v, s)| v * s).sum();
+            // Regrets in units of the starting pot keep targets near one
+            let scale = chips_to_bb(self.root.pot.max(1));
+            let regrets: Vec<f64> = values.iter().map(|v| (v - ev) / scale).collect();
+            let memory = Memory::new(features, &regrets, self.iteration);
+            self.advantage_memory[p].push(memory, &mut self.rng);
+            ev
+        } else {
+            let memory = Memory::new(features, &strategy, self.iteration);
+            self.strategy_memory.push(memory, &mut self.rng);
+            let (next, depth) = child(&actions[sample_index(&strategy, &mut self.rng)]);
+            self.traverse(&next, depth, hands, traverser)
+        }
+    }
+
+    /// EV of `combo` at a state, averaged over playouts of the average strategy
+    fn estimate_ev(&mut self, state: &GameState, depth: usize, combo: Combo, opponent_reach: &[f64]) -> f64 {
+        let player = state.to_act;
+        let o = 1 - player as usize;
+        let opponents: Vec<(Combo, f64)> = self.hands[o]
+            .iter()
+            .zip(opponent_reach)
+            .filter(|((c, _), &r)| r > 0.0 && !c.is_blocked_by(&combo.cards()))
+            .map(|(&(c, _), &r)| (c, r))
+            .collect();
+        if opponents.is_empty() {
+            return 0.0;
+        }
+
+        let mut total = 0.0;
+        for _ in 0..self.config.ev_samples.max(1) {
+            let mut hands = [combo; 2];
+            hands[o] = sample_weighted(&opponents, &mut self.rng);
+            total += self.playout(state, depth, hands, player);
+        }
+        total / self.config.ev_samples.max(1) as f64
+    }
+
+    /// Play to the end with the average strategy; `player`'s value in bb
+    fn playout(&mut self, state: &GameState, depth: usize, hands: [Combo; 2], player: Player) -> f64 {
+        let mut state = state.clone();
+        let mut depth = depth;
+        loop {
+            if state.is_terminal() {
+                return self.utility(&state, hands, player);
+            }
+            let action = if state.street_closed() {
+                Action::Deal(self.deal_card(&state, hands))
+            } else {
+                let actions = tree_actions(&state, depth, self.max_depth);
+                let strategy = self.average_strategy(&state, &hands[state.to_act as usize], actions.len());
+                actions[sample_index(&strategy, &mut self.rng)].clone()
+            };
+            depth += action.is_aggressive() as usize;
+            state = state.apply_action(action);
+        }
+    }
+
+    /// Random card not on the board or in either hand
+    fn deal_card(&mut self, state: &GameState, hands: [Combo; 2]) -> Card {
+        loop {
+            let card = Card::from_value(self.rng.below(52) as u8).unwrap();
+            if !state.board.contains(&card) && !hands.iter().any(|h| h.is_blocked_by(&[card])) {
+                return card;
+            }
+        }
+    }
+
+    /// Value in bb for `player` at a terminal state
+    fn utility(&self, state: &GameState, hands: [Combo; 2], player: Player) -> f64 {
+        let p = player as usize;
+        let pot = chips_to_bb(state.pot);
+        let invested = state.put_in().map(chips_to_bb);
+        if let Some(folder) = state.folded() {
+            return state.rake.fold_payoffs(pot, invested, folder, true)[p];
+        }
+
+        let oop = self.evaluator.evaluate_hand(hands[0].cards(), &state.board);
+        let ip = self.evaluator.evaluate_hand(hands[1].cards(), &state.board);
+        let winner = match oop.cmp(&ip) {
+            Ordering::Less => Some(Player::OOP),
+            Ordering::Equal => None,
+            Ordering::Greater => Some(Player::IP),
+        };
+        state.rake.showdown_payoffs(pot, invested, winner)[p]
+    }
+
+    /// Train a fresh network on a buffer, weighting samples by iteration
+    fn train(&mut self, memories: Memories) -> Vec<Dense> {
+        let buffer = match memories {
+            Memories::Advantage(p) => &self.advantage_memory[p],
+            Memories::Strategy => &self.strategy_memory,
+        };
+        if buffer.items().is_empty() {
+            return Vec::new();
+        }
+
+        let mut sizes = vec![self.encoder.infoset_size()];
+        sizes.extend(&self.config.hidden);
+        sizes.push(MAX_ACTIONS);
+        let mut layers = init_layers(&sizes, &mut self.rng);
+        let mut adam = Adam::new(&layers, self.config.learning_rate);
+        let items = buffer.items();
+        for _ in 0..self.config.train_steps {
+            let batch: Vec<Example> = (0..self.config.batch_size.max(1))
+                .map(|_| {
+                    let memory = &items[self.rng.below(items.len())];
+                    Example {
+                        input: &memory.features,
+                        target: &memory.target,
+                        mask: &memory.mask,
+                        weight: memory.iteration as f32,
+                    }
+                })
+                .collect();
+            batch_step(&mut layers, &mut adam, &batch);
+        }
+        layers
+    }
+}
+
+/// Which reservoir to train on
+#[derive(Clone, Copy)]
+enum Memories {
+    Advantage(usize),
+    Strategy,
+}
+
+/// Regret matching on predicted advantages
+///
+/// With no positive advantage the best action is played, as in Deep CFR.
+fn advantage_strategy(network: &[Dense], features: &[f32], num_actions: usize) -> Vec<f64> {
+    if network.is_empty() {
+        return vec![1.0 / num_actions as f64; num_actions];
+    }
+    let output = forward_layers(network, features);
+    let advantages: Vec<f64> = output[..num_actions].iter().map(|&a| a as f64).collect();
+    let positive: f64 = advantages.iter().map(|a| a.max(0.0)).sum();
+    if positive > 0.0 {
+        return advantages.iter().map(|a| a.max(0.0) / positive).collect();
+    }
+    let best = advantages
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map_or(0, |(i, _)| i);
+    (0..num_actions).map(|a| (a == best) as u8 as f64).collect()
+}
+
+fn sample_weighted(hands: &[(Combo, f64)], rng: &mut Rng) -> Combo {
+    let weights: Vec<f64> = hands.iter().map(|&(_, w)| w).collect();
+    hands[sample_index(&weights, rng)].0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BetSizes, SolveRequest};
+
+    fn state(oop_range: &str, ip_range: &str) -> GameState {
+        GameState::from_request(&SolveRequest {
+            player: Player::OOP,
+            board: "Ah Kd Qc 7s 2h".to_string(),
+            effective_stack: 100.0,
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: 10.0,
+            bet_sizes: Some(BetSizes {
+                oop_bet: "a".to_string(),
+                oop_raise: "a".to_string(),
+                ip_bet: "a".to_string(),
+                ip_raise: "a".to_string(),
+            }),
+            root_bet: None,
+            betting_history: None,
+            oop_range: Some(oop_range.to_string()),
+            ip_range: Some(ip_range.to_string()),
+            rake: None,
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        })
+        .unwrap()
+    }
+
+    fn config() -> DeepCfrConfig {
+        DeepCfrConfig {
+            traversals: 100,
+            buffer_size: 2000,
+            hidden: vec![16],
+            train_steps: 100,
+            batch_size: 32,
+            learning_rate: 1e-2,
+            ev_samples: 8,
+            seed: 3,
+        }
+    }
+
+    #[test]
+    fn test_reservoir() {
+        let mut rng = Rng::new(1);
+        let mut reservoir = Reservoir::new(100);
+        for i in 0..10_000 {
+            reservoir.push(i, &mut rng);
+        }
+        assert_eq!(reservoir.items().len(), 100);
+        assert_eq!(reservoir.seen(), 10_000);
+        // A uniform sample of 0..10000 has a mean near 5000
+        let mean = reservoir.items().iter().sum::<i32>() as f64 / 100.0;
+        assert!((3500.0..6500.0).contains(&mean), "mean {}", mean);
+    }
+
+    #[test]
+    fn test_advantage_strategy() {
+        let network = vec![Dense {
+            inputs: 1,
+            outputs: MAX_ACTIONS,
+            weights: vec![0.0; MAX_ACTIONS],
+            biases: vec![1.0, 3.0, -1.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        }];
+        assert_eq!(advantage_strategy(&network, &[0.0], 3), vec![0.25, 0.75, 0.0]);
+
+        let mut negative = network.clone();
+        negative[0].biases = vec![-2.0, -1.0, -3.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        assert_eq!(advantage_strategy(&negative, &[0.0], 3), vec![0.0, 1.0, 0.0]);
+        assert_eq!(advantage_strategy(&[], &[0.0], 4), vec![0.25; 4]);
+    }
+
+    #[test]
+    fn test_fold_to_shove_with_air() {
+        // IP holds the worst hand facing the nuts' shove: fold
+        let mut solver = DeepCfrSolver::new(state("AsAd", "3d3c"), 2, config()).unwrap();
+        solver.solve(3).unwrap();
+
+        let history = vec![HistoryAction {
+            order: 1,
+            position: Player::OOP,
+            action: crate::models::ActionType::Allin,
+            amount_percent: None,
+            card: None,
+        }];
+        let (state, actions, results) = solver.node_result(&history).unwrap();
+        assert_eq!(state.to_act, Player::IP);
+        assert_eq!(actions[0], Action::Fold);
+        assert!(results[0].strategy[0] > 0.9, "{:?}", results[0].strategy);
+        // Folding loses nothing more; sampled calls lose the stack
+        assert!((-100.0..=0.0).contains(&results[0].ev), "EV {}", results[0].ev);
+    }
+
+    #[test]
+    fn test_rejects_too_many_sizes() {
+        let mut root = state("AsAd", "3d3c");
+        root.bet_config.oop_bet = (1..=7).map(|i| crate::solver::BetSize::Percent(i as f64 * 10.0)).collect();
+        assert!(DeepCfrSolver::new(root, 2, config()).is_err());
+        assert!(DeepCfrSolver::new(state("AsAd", ""), 2, config()).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Precomputed preflop hand-class equity tables
+//!
+//! A table holds the all-in equity of every hand class against every other
+//! (169 x 169, grid order, ties counted as half). The text format is one row
+//! per hero class with 169 whitespace-separated equities; blank lines and
+//! lines starting with '#' are ignored.
+
+use std::path::Path;
+
+use super::hand_class::{HandClass, NUM_HAND_CLASSES};
+
+/// Tolerance when checking that `eq(a, b) + eq(b, a) == 1`
+const SYMMETRY_TOLERANCE: f64 = 1e-3;
+
+/// Hand-class vs hand-class equity table
+#[derive(Debug, Clone)]
+pub struct EquityTable {
+    equities: Vec<f32>,
+}
+
+impl EquityTable {
+    /// Build a table from a function of (hero, villain) classes
+    pub fn from_fn(f: impl Fn(HandClass, HandClass) -> f64) -> Self {
+        let classes = HandClass::all();
+        let mut equities = Vec::with_capacity(NUM_HAND_CLASSES * NUM_HAND_CLASSES);
+        for &hero in &classes {
+            for &villain in &classes {
+                equities.push(f(hero, villain) as f32);
+            }
+        }
+        EquityTable { equities }
+    }
+
+    /// Parse a table from its text format
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut equities = Vec::with_capacity(NUM_HAND_CLASSES * NUM_HAND_CLASSES);
+
+        let rows = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        for (row, line) in rows.enumerate() {
+            let values = line
+                .split_whitespace()
+                .map(|v| {
+                    v.parse::<f32>()
+                        .map_err(|_| format!("Invalid equity '{}' in row {}", v, row + 1))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if values.len() != NUM_HAND_CLASSES {
+                return Err(format!(
+                    "Row {} has {} equities (expected {})",
+                    row + 1,
+                    values.len(),
+                    NUM_HAND_CLASSES
+                ));
+            }
+            if values.iter().any(|v| !(0.0..=1.0).contains(v)) {
+                return Err(format!("Row {} has equities outside 0.0-1.0", row + 1));
+            }
+            equities.extend(values);
+        }
+
+        if equities.len() != NUM_HAND_CLASSES * NUM_HAND_CLASSES {
+            return Err(format!(
+                "Equity table has {} rows (expected {})",
+                equities.len() / NUM_HAND_CLASSES,
+                NUM_HAND_CLASSES
+            ));
+        }
+
+        let table = EquityTable { equities };
+        table.check_symmetry()?;
+        Ok(table)
+    }
+
+    /// Load a table from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read equity table '{}': {}", path.display(), e))?;
+        Self::parse(&contents)
+    }
+
+    /// Equity of `hero` against `villain` (grid indices)
+    pub fn equity(&self, hero: usize, villain: usize) -> f64 {
+        self.equities[hero * NUM_HAND_CLASSES + villain] as f64
+    }
+
+    /// Equity of each hero class against a weighted villain range
+    ///
+    /// Returns, per hero class, `sum_j weights[j] * equity(i, j)`.
+    pub fn weighted_equities(&self, villain_weights: &[f64]) -> Vec<f64> {
+        (0..NUM_HAND_CLASSES)
+            .map(|hero| {
+                let row = &self.equities[hero * NUM_HAND_CLASSES..(hero + 1) * NUM_HAND_CLASSES];
+                row.iter()
+                    .zip(villain_weights)
+                    .map(|(&eq, &w)| eq as f64 * w)
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Equity of a hero range against a villain range, given as class weights
+    ///
+    /// Card removal between the two ranges is ignored. `None` if either
+    /// range is empty.
+    pub fn range_equity(&self, hero_weights: &[f64], villain_weights: &[f64]) -> Option<f64> {
+        let hero_total: f64 = hero_weights.iter().sum();
+        let villain_total: f64 = villain_weights.iter().sum();
+        if hero_total <= 0.0 || villain_total <= 0.0 {
+            return None;
+        }
+        let equity: f64 = self
+            .weighted_equities(villain_weights)
+            .iter()
+            .zip(hero_weights)
+            .map(|(eq, w)| eq * w)
+            .sum();
+        Some(equity / (hero_total * villain_total))
+    }
+
+    /// Check that `eq(a, b) + eq(b, a) == 1` for all pairs
+    fn check_symmetry(&self) -> Result<(), String> {
+        for a in 0..NUM_HAND_CLASSES {
+            for b in a..NUM_HAND_CLASSES {
+                let sum = self.equity(a, b) + self.equity(b, a);
+                if (sum - 1.0).abs() > SYMMETRY_TOLERANCE {
+                    return Err(format!(
+                        "Equities of {} vs {} do not sum to 1 ({:.4})",
+                        HandClass::from_index(a).unwrap(),
+                        HandClass::from_index(b).unwrap(),
+                        sum
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::hand_class::class_weights;
+    use crate::solver::Range;
+
+    /// Text table where the hand with the higher top rank always wins
+    fn high_card_table() -> String {
+        let classes = HandClass::all();
+        classes
+            .iter()
+            .map(|hero| {
+                classes
+                    .iter()
+                    .map(|villain| match hero.high.cmp(&villain.high) {
+                        std::cmp::Ordering::Greater => "1",
+                        std::cmp::Ordering::Less => "0",
+                        std::cmp::Ordering::Equal => "0.5",
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_parse_table() {
+        let table = EquityTable::parse(&format!("# high card wins\n{}", high_card_table())).unwrap();
+        let aa = "AA".parse::<HandClass>().unwrap().index();
+        let kk = "KK".parse::<HandClass>().unwrap().index();
+        assert_eq!(table.equity(aa, kk), 1.0);
+        assert_eq!(table.equity(kk, aa), 0.0);
+        assert_eq!(table.equity(aa, aa), 0.5);
+    }
+
+    #[test]
+    fn test_weighted_equities() {
+        let table = EquityTable::from_fn(|_, _| 0.5);
+        let weights = vec![2.0; NUM_HAND_CLASSES];
+        let equities = table.weighted_equities(&weights);
+        assert!((equities[0] - NUM_HAND_CLASSES as f64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_range_equity() {
+        let table = EquityTable::parse(&high_card_table()).unwrap();
+        let weights = |range: &str| class_weights(&Range::parse(range).unwrap());
+        assert_eq!(table.range_equity(&weights("AA"), &weights("KK")), Some(1.0));
+        assert_eq!(table.range_equity(&weights("AA,KK"), &weights("AA")), Some(0.25));
+        assert_eq!(table.range_equity(&weights("AA"), &weights("")), None);
+    }
+
+    #[test]
+    fn test_invalid_tables() {
+        assert!(EquityTable::parse("0.5 0.5").is_err());
+
+        let asymmetric = vec![vec!["0.9"; NUM_HAND_CLASSES].join(" "); NUM_HAND_CLASSES].join("\n");
+        assert!(EquityTable::parse(&asymmetric).is_err());
+    }
+}
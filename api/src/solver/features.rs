@@ -0,0 +1,358 @@
+//! Feature encoding for neural models
+//!
+//! One deterministic encoder turns game states into network inputs for the
+//! value network (public state and both ranges) and for regret and strategy
+//! networks (an info set: the state seen by the player to act and their
+//! combo). Every encoding has a version, stored with trained models, so a
+//! model keeps being fed the inputs it was trained on after the encoding
+//! changes.
+//!
+//! Version 2 (current) puts cards through the suit permutation that makes
+//! the board (then the hole cards) canonical, so isomorphic spots encode
+//! identically, and one-hot encodes each card by rank and by suit. Version 1
+//! one-hot encoded raw cards over all 52.
+
+use super::cards::{Card, Combo};
+use super::game_state::{Action, GameState};
+use super::isomorphism::SuitPermutation;
+use super::utils::Chips;
+use crate::models::Player;
+
+/// Encoding version produced for newly trained models
+pub const FEATURE_VERSION: u16 = 2;
+
+/// Versions that can still be encoded, oldest first
+pub const SUPPORTED_FEATURE_VERSIONS: [u16; 2] = [1, 2];
+
+/// Number of two-card combos, indexed by combo ID
+pub const NUM_COMBOS: usize = 1326;
+
+/// Features per card: rank one-hot, then suit one-hot
+const CARD_SIZE: usize = 13 + 4;
+
+/// Action slots kept per street (the most recent ones)
+pub const MAX_STREET_ACTIONS: usize = 6;
+
+/// Features per action slot: check, bet, call, raise, all-in, size
+const ACTION_SIZE: usize = 6;
+
+/// Length of an encoded info set in the current version
+pub const INFOSET_SIZE: usize = 2 * CARD_SIZE + 5 * CARD_SIZE + 2 + 3 + 3 + 3 * MAX_STREET_ACTIONS * ACTION_SIZE;
+
+/// Length of an encoded value network input in the current version
+pub const VALUE_INPUT_SIZE: usize = 5 * CARD_SIZE + 2 + 2 * NUM_COMBOS;
+
+/// Encoder for one feature version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeatureEncoder {
+    version: u16,
+}
+
+impl FeatureEncoder {
+    /// Encoder for a version, if it is still supported
+    pub fn new(version: u16) -> Result<Self, String> {
+        if !SUPPORTED_FEATURE_VERSIONS.contains(&version) {
+            return Err(format!(
+                "Unsupported feature version {} (supported: {:?})",
+                version, SUPPORTED_FEATURE_VERSIONS
+            ));
+        }
+        Ok(FeatureEncoder { version })
+    }
+
+    /// Encoder for the current version
+    pub fn current() -> Self {
+        FeatureEncoder {
+            version: FEATURE_VERSION,
+        }
+    }
+
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Length of an encoded info set
+    pub fn infoset_size(&self) -> usize {
+        match self.version {
+            1 => 52 + 52 + 1 + 3 + 4,
+            _ => INFOSET_SIZE,
+        }
+    }
+
+    /// Length of an encoded value network input
+    pub fn value_input_size(&self) -> usize {
+        match self.version {
+            1 => 52 + 2 + 2 * NUM_COMBOS,
+            _ => VALUE_INPUT_SIZE,
+        }
+    }
+
+    /// Encode the info set of the player to act holding `combo`
+    ///
+    /// Hole cards (high first) and board cards, then the position, street,
+    /// pot and stack ratios and amount to call, then the last
+    /// `MAX_STREET_ACTIONS` actions of each street with bet sizes as a share
+    /// of the starting effective stack.
+    pub fn encode_infoset(&self, state: &GameState, combo: &Combo) -> Vec<f32> {
+        if self.version == 1 {
+            return encode_infoset_v1(state, combo);
+        }
+
+        let permutation = SuitPermutation::canonical_for_hand(&state.board, &combo.cards());
+        let hole = permutation.apply_combo(combo);
+        let mut features = Vec::with_capacity(INFOSET_SIZE);
+        for card in hole.cards() {
+            push_card(&mut features, Some(card));
+        }
+        push_board(&mut features, &permutation.apply_board(&state.board));
+
+        let ip = state.to_act == Player::IP;
+        features.extend([!ip as u8 as f32, ip as u8 as f32]);
+        let mut street = [0.0; 3];
+        street[state.street as usize] = 1.0;
+        features.extend(street);
+        let (pot, stack) = (state.pot.max(1) as f32, state.effective_stack() as f32);
+        features.extend([pot / (pot + 2.0 * stack), stack / pot, state.to_call() as f32 / pot]);
+
+        let starting_stack = state.starting_stacks[0].min(state.starting_stacks[1]).max(1) as f32;
+        // The history starts on the root street, not necessarily the flop
+        let mut streets: [&[Action]; 3] = [&[]; 3];
+        let deals = state.history.iter().filter(|a| matches!(a, Action::Deal(_))).count();
+        let root_street = (state.street as usize).saturating_sub(deals);
+        for (i, actions) in state.history.split(|a| matches!(a, Action::Deal(_))).enumerate() {
+            if let Some(slot) = streets.get_mut(root_street + i) {
+                *slot = actions;
+            }
+        }
+        for actions in streets {
+            let recent = &actions[actions.len().saturating_sub(MAX_STREET_ACTIONS)..];
+            for slot in 0..MAX_STREET_ACTIONS {
+                let mut encoded = [0.0; ACTION_SIZE];
+                if let Some(action) = recent.get(slot) {
+                    let kind = match action {
+                        Action::Check => Some(0),
+                        Action::Bet(_) => Some(1),
+                        Action::Call => Some(2),
+                        Action::Raise(_) => Some(3),
+                        Action::AllIn(_) => Some(4),
+                        Action::Fold | Action::Deal(_) => None,
+                    };
+                    if let Some(kind) = kind {
+                        encoded[kind] = 1.0;
+                    }
+                    encoded[5] = action.amount() as f32 / starting_stack;
+                }
+                features.extend(encoded);
+            }
+        }
+        features
+    }
+
+    /// Encode a public state and both ranges as value network input
+    ///
+    /// Returns the input and the suit permutation applied to the cards.
+    /// Ranges are reach weights by combo ID and are normalised; the
+    /// network's outputs are indexed by the permuted combos' IDs.
+    pub fn encode_public_state(
+        &self,
+        board: &[Card],
+        pot: Chips,
+        stack: Chips,
+        ranges: [&[f64]; 2],
+    ) -> (Vec<f32>, SuitPermutation) {
+        let (permutation, mut input) = match self.version {
+            1 => {
+                let mut input = vec![0.0; 52];
+                for card in board {
+                    input[card.value() as usize] = 1.0;
+                }
+                (SuitPermutation::identity(), input)
+            }
+            _ => {
+                let permutation = SuitPermutation::canonical_for(board);
+                let mut input = Vec::with_capacity(VALUE_INPUT_SIZE);
+                push_board(&mut input, &permutation.apply_board(board));
+                (permutation, input)
+            }
+        };
+
+        let pot = pot.max(1) as f32;
+        input.push(pot / (pot + 2.0 * stack as f32));
+        input.push(stack as f32 / pot);
+
+        let offset = input.len();
+        input.resize(offset + 2 * NUM_COMBOS, 0.0);
+        for (p, range) in ranges.iter().enumerate() {
+            let total: f64 = range.iter().sum();
+            if total <= 0.0 {
+                continue;
+            }
+            for (id, &w) in range.iter().enumerate() {
+                if w > 0.0 {
+                    let combo = Combo::from_id(id as u16).unwrap();
+                    let mapped = permutation.apply_combo(&combo).id as usize;
+                    input[offset + p * NUM_COMBOS + mapped] = (w / total) as f32;
+                }
+            }
+        }
+        (input, permutation)
+    }
+}
+
+/// Rank and suit one-hots of a card (all zero for an empty slot)
+fn push_card(features: &mut Vec<f32>, card: Option<Card>) {
+    let mut encoded = [0.0; CARD_SIZE];
+    if let Some(card) = card {
+        encoded[card.rank() as usize] = 1.0;
+        encoded[13 + card.suit() as usize] = 1.0;
+    }
+    features.extend(encoded);
+}
+
+/// Five board slots, empty past the cards dealt
+fn push_board(features: &mut Vec<f32>, board: &[Card]) {
+    for slot in 0..5 {
+        push_card(features, board.get(slot).copied());
+    }
+}
+
+/// Version 1: raw one-hot cards and a count of this street's bets
+fn encode_infoset_v1(state: &GameState, combo: &Combo) -> Vec<f32> {
+    let mut features = vec![0.0; 52 + 52 + 1 + 3 + 4];
+    for card in combo.cards() {
+        features[card.value() as usize] = 1.0;
+    }
+    for card in &state.board {
+        features[52 + card.value() as usize] = 1.0;
+    }
+
+    let mut i = 104;
+    features[i] = (state.to_act == Player::IP) as u8 as f32;
+    i += 1;
+    features[i + state.street as usize] = 1.0;
+    i += 3;
+
+    let pot = state.pot.max(1) as f32;
+    let stack = state.effective_stack() as f32;
+    features[i] = pot / (pot + 2.0 * stack);
+    features[i + 1] = stack / pot;
+    features[i + 2] = state.to_call() as f32 / pot;
+    let street_start = state
+        .history
+        .iter()
+        .rposition(|a| matches!(a, Action::Deal(_)))
+        .map_or(0, |i| i + 1);
+    features[i + 3] = state.history[street_start..].iter().filter(|a| a.is_aggressive()).count() as f32;
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::cards::parse_board;
+    use crate::models::SolveRequest;
+
+    fn state(board: &str) -> GameState {
+        GameState::from_request(&SolveRequest {
+            player: Player::OOP,
+            board: board.to_string(),
+            effective_stack: 100.0,
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: 10.0,
+            bet_sizes: None,
+            root_bet: None,
+            betting_history: None,
+            oop_range: None,
+            ip_range: None,
+            rake: None,
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_encode_infoset() {
+        let state = state("Ah Kd Qc 7s").apply_action(Action::Bet(500));
+        let combo: Combo = "JsTs".parse().unwrap();
+
+        let features = FeatureEncoder::current().encode_infoset(&state, &combo);
+        assert_eq!(features.len(), INFOSET_SIZE);
+        // Two hole cards and four board cards, a rank and a suit each
+        assert_eq!(features[..7 * CARD_SIZE].iter().sum::<f32>(), 12.0);
+        assert_eq!(features[6 * CARD_SIZE..7 * CARD_SIZE].iter().sum::<f32>(), 0.0); // no river
+        let rest = &features[7 * CARD_SIZE..];
+        assert_eq!(rest[..2], [0.0, 1.0]); // IP to act
+        assert_eq!(rest[2..5], [0.0, 1.0, 0.0]); // turn
+        assert_eq!(rest[7], 1.0 / 3.0); // 5bb to call into 15bb
+
+        // Flop history is empty; the turn bet is the first turn slot
+        let history = &rest[8..];
+        assert!(history[..MAX_STREET_ACTIONS * ACTION_SIZE].iter().all(|&x| x == 0.0));
+        let turn = &history[MAX_STREET_ACTIONS * ACTION_SIZE..];
+        assert_eq!(turn[..ACTION_SIZE], [0.0, 1.0, 0.0, 0.0, 0.0, 0.05]);
+    }
+
+    #[test]
+    fn test_suit_isomorphic_infosets_match() {
+        let encoder = FeatureEncoder::current();
+        let a = encoder.encode_infoset(&state("Ah Kh 2c"), &"QhJd".parse().unwrap());
+        let b = encoder.encode_infoset(&state("As Ks 2d"), &"QsJh".parse().unwrap());
+        assert_eq!(a, b);
+
+        // Same board, but the queen no longer matches the flush suit
+        let c = encoder.encode_infoset(&state("Ah Kh 2c"), &"QdJh".parse().unwrap());
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_encode_public_state() {
+        let encoder = FeatureEncoder::current();
+        let board = parse_board("Ah Kd Qc").unwrap();
+        let mut oop = vec![0.0; NUM_COMBOS];
+        oop[0] = 2.0;
+        oop[1] = 2.0;
+        let ip = vec![0.0; NUM_COMBOS];
+        let (input, permutation) = encoder.encode_public_state(&board, 2000, 9000, [&oop, &ip]);
+        assert_eq!(input.len(), VALUE_INPUT_SIZE);
+        assert_eq!(input[..5 * CARD_SIZE].iter().sum::<f32>(), 6.0);
+        assert_eq!(input[5 * CARD_SIZE], 0.1);
+        assert_eq!(input[5 * CARD_SIZE + 1], 4.5);
+
+        let ranges = &input[5 * CARD_SIZE + 2..];
+        for id in [0, 1] {
+            let mapped = permutation.apply_combo(&Combo::from_id(id).unwrap()).id as usize;
+            assert_eq!(ranges[mapped], 0.5);
+        }
+        assert_eq!(ranges[NUM_COMBOS..].iter().sum::<f32>(), 0.0);
+    }
+
+    #[test]
+    fn test_version_1() {
+        let encoder = FeatureEncoder::new(1).unwrap();
+        let board = parse_board("Ah Kd Qc").unwrap();
+        let mut oop = vec![0.0; NUM_COMBOS];
+        oop[0] = 2.0;
+        oop[1] = 2.0;
+        let ip = vec![0.0; NUM_COMBOS];
+        let (input, permutation) = encoder.encode_public_state(&board, 2000, 9000, [&oop, &ip]);
+        assert!(permutation.is_identity());
+        assert_eq!(input.len(), encoder.value_input_size());
+        assert_eq!(input.iter().take(52).sum::<f32>(), 3.0);
+        assert_eq!(input[52], 0.1);
+        assert_eq!(input[53], 4.5);
+        assert_eq!(input[54], 0.5);
+        assert_eq!(input[54 + NUM_COMBOS..].iter().sum::<f32>(), 0.0);
+
+        let state = state("Ah Kd Qc 7s").apply_action(Action::Bet(500));
+        let features = encoder.encode_infoset(&state, &"JsTs".parse().unwrap());
+        assert_eq!(features.len(), encoder.infoset_size());
+        assert_eq!(features[104], 1.0);
+        assert_eq!(features[111], 1.0);
+
+        assert!(FeatureEncoder::new(0).is_err());
+        assert!(FeatureEncoder::new(FEATURE_VERSION + 1).is_err());
+    }
+}
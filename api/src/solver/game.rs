@@ -0,0 +1,71 @@
+//! Two-player extensive-form games
+//!
+//! The interface CFR ([`GameCfr`](super::game_cfr::GameCfr)), best
+//! responses and Monte Carlo CFR ([`mccfr`](super::mccfr)) are written
+//! against. Hold'em ([`HoldemGame`](super::holdem_game::HoldemGame)) is one
+//! implementation; small reference games with known solutions live in
+//! [`games`](super::games).
+//!
+//! [`CFRSolver`](super::CFRSolver) only goes through this trait for
+//! external and outcome sampling. Its full-width pass and best response
+//! walk the hold'em tree with a reach vector over every combo at once, and
+//! stay specific to hold'em.
+
+use super::rng::{sample_index, Rng};
+
+/// Who moves at a state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    /// Player 0 or 1 picks an action
+    Player(usize),
+    /// An outcome is drawn from [`Game::chance_outcomes`]
+    Chance,
+    /// The game is over and pays out [`Game::utility`]
+    Terminal,
+}
+
+/// A two-player game with chance and imperfect information
+pub trait Game {
+    type State: Clone;
+    type Action: Clone;
+
+    /// State before anything happens
+    fn root(&self) -> Self::State;
+
+    /// Who moves at a state
+    fn turn(&self, state: &Self::State) -> Turn;
+
+    /// Legal actions of the player to move
+    fn actions(&self, state: &Self::State) -> Vec<Self::Action>;
+
+    /// Outcomes of a chance node with their probabilities
+    fn chance_outcomes(&self, state: &Self::State) -> Vec<(Self::Action, f64)>;
+
+    /// Draw one chance outcome, or `None` if there are none
+    ///
+    /// Games with many outcomes can override this to avoid listing them.
+    fn sample_chance(&self, state: &Self::State, rng: &mut Rng) -> Option<Self::Action> {
+        let outcomes = self.chance_outcomes(state);
+        let probs: Vec<f64> = outcomes.iter().map(|(_, p)| *p).collect();
+        let index = (!outcomes.is_empty()).then(|| sample_index(&probs, rng))?;
+        outcomes.into_iter().nth(index).map(|(outcome, _)| outcome)
+    }
+
+    /// State after an action or chance outcome
+    fn apply(&self, state: &Self::State, action: &Self::Action) -> Self::State;
+
+    /// Key of the player to move's information set: equal for the states
+    /// they cannot tell apart
+    fn info_set_key(&self, state: &Self::State) -> String;
+
+    /// Key of what both players have seen: the information set without
+    /// the mover's private information
+    fn public_state(&self, state: &Self::State) -> String;
+
+    /// Payoff of `player` at a terminal state
+    ///
+    /// The two payoffs need not sum to zero (dead money, rake):
+    /// exploitability compares each player's best response with their own
+    /// value rather than with the opponent's.
+    fn utility(&self, state: &Self::State, player: usize) -> f64;
+}
@@ -0,0 +1,382 @@
+//! CFR for any [`Game`]
+//!
+//! Tabular CFR+ with regrets and strategy sums per info set key, plus the
+//! [`Sampling`] variants of [`mccfr`](super::mccfr): chance, external and
+//! outcome sampling. Best responses and exploitability are computed exactly
+//! by walking the whole game, so this suits games small enough to enumerate
+//! (Kuhn, Leduc) or short hold'em subgames.
+
+use std::collections::HashMap;
+
+use super::cfr::current_strategy;
+use super::game::{Game, Turn};
+use super::mccfr::Sampling;
+use super::node_lock::LockedStrategy;
+use super::rng::Rng;
+
+/// CFR solver for a [`Game`]
+pub struct GameCfr<G: Game> {
+    pub game: G,
+    pub regret_sum: HashMap<String, Vec<f64>>,
+    pub strategy_sum: HashMap<String, Vec<f64>>,
+    pub iteration: usize,
+    sampling: Sampling,
+    rng: Rng,
+}
+
+impl<G: Game> GameCfr<G> {
+    /// Full-width CFR+ solver
+    pub fn new(game: G) -> Self {
+        GameCfr {
+            game,
+            regret_sum: HashMap::new(),
+            strategy_sum: HashMap::new(),
+            iteration: 0,
+            sampling: Sampling::FullWidth,
+            rng: Rng::new(0),
+        }
+    }
+
+    /// Sample each iteration instead of walking the full game
+    pub fn with_sampling(mut self, sampling: Sampling, seed: u64) -> Self {
+        self.sampling = sampling;
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    /// Run iterations, each updating both players in turn
+    pub fn solve(&mut self, iterations: usize) {
+        let unlocked = HashMap::new();
+        for _ in 0..iterations {
+            self.iteration += 1;
+            iterate(
+                &self.game,
+                self.sampling,
+                &mut self.regret_sum,
+                &mut self.strategy_sum,
+                &unlocked,
+                &mut self.rng,
+                self.iteration,
+            );
+        }
+    }
+
+    /// Average strategy of an info set (uniform if it was never reached)
+    pub fn average_strategy(&self, info_set: &str, num_actions: usize) -> Vec<f64> {
+        average(&self.strategy_sum, info_set, num_actions)
+    }
+
+    /// Expected value of `player` when both play the average strategy
+    pub fn expected_value(&self, player: usize) -> f64 {
+        self.value(&self.game.root(), player, &mut None)
+    }
+
+    /// Value of `player`'s best response to the opponent's average strategy
+    pub fn best_response_value(&self, player: usize) -> f64 {
+        let mut response = BestResponse {
+            player,
+            info_sets: HashMap::new(),
+            actions: HashMap::new(),
+        };
+        self.collect(&self.game.root(), player, 1.0, &mut response.info_sets);
+        self.value(&self.game.root(), player, &mut Some(response))
+    }
+
+    /// Mean gain of the two players from best responding; zero at equilibrium
+    pub fn exploitability(&self) -> f64 {
+        let gains = [0, 1].map(|p| self.best_response_value(p) - self.expected_value(p));
+        (gains[0] + gains[1]) / 2.0
+    }
+
+    /// Value of `player` below `state`, following the best response's
+    /// actions at their info sets if one is given
+    fn value(&self, state: &G::State, player: usize, response: &mut Option<BestResponse<G::State>>) -> f64 {
+        match self.game.turn(state) {
+            Turn::Terminal => self.game.utility(state, player),
+            Turn::Chance => self
+                .game
+                .chance_outcomes(state)
+                .iter()
+                .map(|(outcome, p)| p * self.value(&self.game.apply(state, outcome), player, response))
+                .sum(),
+            Turn::Player(p) => {
+                let actions = self.game.actions(state);
+                if p == player && response.is_some() {
+                    let key = self.game.info_set_key(state);
+                    let a = self.best_action(&key, &actions, response);
+                    return self.value(&self.game.apply(state, &actions[a]), player, response);
+                }
+                let strategy = self.average_strategy(&self.game.info_set_key(state), actions.len());
+                actions
+                    .iter()
+                    .zip(&strategy)
+                    .filter(|(_, &s)| s > 0.0)
+                    .map(|(action, s)| s * self.value(&self.game.apply(state, action), player, response))
+                    .sum()
+            }
+        }
+    }
+
+    /// Best action at one of the responder's info sets, weighing its states
+    /// by the opponent's and chance's reach
+    fn best_action(&self, key: &str, actions: &[G::Action], response: &mut Option<BestResponse<G::State>>) -> usize {
+        if let Some(&a) = response.as_ref().and_then(|r| r.actions.get(key)) {
+            return a;
+        }
+        let states = response.as_ref().map_or(Vec::new(), |r| r.info_sets[key].clone());
+        let player = response.as_ref().map_or(0, |r| r.player);
+        let totals: Vec<f64> = actions
+            .iter()
+            .map(|action| {
+                states
+                    .iter()
+                    .map(|(state, reach)| reach * self.value(&self.game.apply(state, action), player, response))
+                    .sum()
+            })
+            .collect();
+        let best = (0..actions.len())
+            .max_by(|&a, &b| totals[a].total_cmp(&totals[b]))
+            .unwrap_or(0);
+        if let Some(r) = response.as_mut() {
+            r.actions.insert(key.to_string(), best);
+        }
+        best
+    }
+
+    /// States of each of `player`'s info sets with the reach of everyone else
+    fn collect(
+        &self,
+        state: &G::State,
+        player: usize,
+        reach: f64,
+        info_sets: &mut HashMap<String, Vec<(G::State, f64)>>,
+    ) {
+        if reach <= 0.0 {
+            return;
+        }
+        match self.game.turn(state) {
+            Turn::Terminal => {}
+            Turn::Chance => {
+                for (outcome, p) in self.game.chance_outcomes(state) {
+                    self.collect(&self.game.apply(state, &outcome), player, reach * p, info_sets);
+                }
+            }
+            Turn::Player(p) => {
+                let key = self.game.info_set_key(state);
+                let actions = self.game.actions(state);
+                let strategy = match p == player {
+                    true => vec![1.0; actions.len()],
+                    false => self.average_strategy(&key, actions.len()),
+                };
+                if p == player {
+                    info_sets.entry(key).or_default().push((state.clone(), reach));
+                }
+                for (action, s) in actions.iter().zip(strategy) {
+                    self.collect(&self.game.apply(state, action), player, reach * s, info_sets);
+                }
+            }
+        }
+    }
+}
+
+/// A best response being built, info set by info set
+struct BestResponse<S> {
+    player: usize,
+    /// States of each of the responder's info sets with their reach
+    info_sets: HashMap<String, Vec<(S, f64)>>,
+    /// Chosen action per info set
+    actions: HashMap<String, usize>,
+}
+
+/// One CFR iteration: a walk per traverser, then the regret update
+///
+/// Shared by [`GameCfr`] and the sampling variants of
+/// [`CFRSolver`](super::CFRSolver), which keep their own tables.
+pub(super) fn iterate<G: Game>(
+    game: &G,
+    sampling: Sampling,
+    regret_sum: &mut HashMap<String, Vec<f64>>,
+    strategy_sum: &mut HashMap<String, Vec<f64>>,
+    locked: &HashMap<String, LockedStrategy>,
+    rng: &mut Rng,
+    iteration: usize,
+) {
+    let root = game.root();
+    for traverser in 0..2 {
+        let mut walk = Walk {
+            game,
+            regret_sum,
+            locked,
+            regrets: HashMap::new(),
+            strategy_sum,
+            rng,
+            iteration,
+            traverser,
+        };
+        match sampling {
+            Sampling::FullWidth => walk.full_width(&root, [1.0, 1.0], 1.0, false),
+            Sampling::Chance => walk.full_width(&root, [1.0, 1.0], 1.0, true),
+            Sampling::External => walk.external(&root),
+            Sampling::Outcome => walk.outcome(&root, 1.0, 1.0, 1.0).0,
+        };
+
+        // CFR+: an info set's regrets are floored at zero once all of its
+        // states have added theirs
+        for (key, delta) in walk.regrets {
+            let regrets = regret_sum.entry(key).or_insert_with(|| vec![0.0; delta.len()]);
+            for (regret, d) in regrets.iter_mut().zip(delta) {
+                *regret = (*regret + d).max(0.0);
+            }
+        }
+    }
+}
+
+/// One iteration's walk for a traverser
+pub(super) struct Walk<'a, G: Game> {
+    pub game: &'a G,
+    pub regret_sum: &'a HashMap<String, Vec<f64>>,
+    pub locked: &'a HashMap<String, LockedStrategy>,
+    /// Regrets added by this walk
+    pub regrets: HashMap<String, Vec<f64>>,
+    pub strategy_sum: &'a mut HashMap<String, Vec<f64>>,
+    pub rng: &'a mut Rng,
+    pub iteration: usize,
+    pub traverser: usize,
+}
+
+impl<G: Game> Walk<'_, G> {
+    /// Current strategy of an info set (regret matching, then locks)
+    pub fn strategy(&self, key: &str, n: usize) -> Vec<f64> {
+        current_strategy(self.regret_sum, self.locked, key, n)
+    }
+
+    /// CFR+ over every action, and every chance outcome unless `sample_chance`
+    ///
+    /// `reach` holds both players' reach, `chance` chance's. Returns the
+    /// traverser's expected value below `state`.
+    pub fn full_width(&mut self, state: &G::State, reach: [f64; 2], chance: f64, sample_chance: bool) -> f64 {
+        match self.game.turn(state) {
+            Turn::Terminal => self.game.utility(state, self.traverser),
+            Turn::Chance => {
+                if sample_chance {
+                    // Sampled with its own probability, which cancels out
+                    let Some(outcome) = self.game.sample_chance(state, self.rng) else {
+                        return 0.0;
+                    };
+                    return self.full_width(&self.game.apply(state, &outcome), reach, chance, true);
+                }
+                self.game
+                    .chance_outcomes(state)
+                    .iter()
+                    .map(|(outcome, p)| {
+                        p * self.full_width(&self.game.apply(state, outcome), reach, chance * p, false)
+                    })
+                    .sum()
+            }
+            Turn::Player(p) => {
+                let key = self.game.info_set_key(state);
+                let actions = self.game.actions(state);
+                let n = actions.len();
+                let strategy = self.strategy(&key, n);
+                let values: Vec<f64> = actions
+                    .iter()
+                    .zip(&strategy)
+                    .map(|(action, s)| {
+                        let mut child_reach = reach;
+                        child_reach[p] *= s;
+                        self.full_width(&self.game.apply(state, action), child_reach, chance, sample_chance)
+                    })
+                    .collect();
+                let value: f64 = values.iter().zip(&strategy).map(|(v, s)| v * s).sum();
+
+                if p == self.traverser {
+                    let counterfactual = reach[1 - p] * chance;
+                    let regrets = self.regrets.entry(key.clone()).or_insert_with(|| vec![0.0; n]);
+                    for (regret, v) in regrets.iter_mut().zip(&values) {
+                        *regret += counterfactual * (v - value);
+                    }
+                    let weight = self.iteration as f64 * reach[p];
+                    let sums = self.strategy_sum.entry(key).or_insert_with(|| vec![0.0; n]);
+                    for (sum, s) in sums.iter_mut().zip(&strategy) {
+                        *sum += weight * s;
+                    }
+                }
+                value
+            }
+        }
+    }
+}
+
+fn average(strategy_sum: &HashMap<String, Vec<f64>>, info_set: &str, num_actions: usize) -> Vec<f64> {
+    match strategy_sum.get(info_set) {
+        Some(sums) if sums.iter().sum::<f64>() > 0.0 => {
+            let total: f64 = sums.iter().sum();
+            sums.iter().map(|s| s / total).collect()
+        }
+        _ => vec![1.0 / num_actions as f64; num_actions],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::games::{Kuhn, Leduc};
+
+    #[test]
+    fn test_kuhn_game_value() {
+        let mut cfr = GameCfr::new(Kuhn);
+        assert!(cfr.exploitability() > 0.4);
+        cfr.solve(1000);
+        assert!((cfr.expected_value(0) + 1.0 / 18.0).abs() < 1e-4, "value {}", cfr.expected_value(0));
+        assert!(cfr.exploitability() < 1e-3, "exploitability {}", cfr.exploitability());
+        assert_eq!(cfr.strategy_sum.len(), 12);
+
+        // Player 1 always calls a bet with the king and never with the jack
+        assert!(cfr.average_strategy("K|b", 2)[1] > 0.99);
+        assert!(cfr.average_strategy("J|b", 2)[0] > 0.99);
+    }
+
+    #[test]
+    fn test_kuhn_sampling_variants() {
+        for (sampling, iterations, target) in [
+            (Sampling::Chance, 9000, 0.02),
+            (Sampling::External, 30000, 0.02),
+            (Sampling::Outcome, 150000, 0.03),
+        ] {
+            let mut cfr = GameCfr::new(Kuhn).with_sampling(sampling, 3);
+            cfr.solve(iterations);
+            let exploitability = cfr.exploitability();
+            assert!(exploitability < target, "{:?}: exploitability {}", sampling, exploitability);
+            let value = cfr.expected_value(0);
+            assert!((value + 1.0 / 18.0).abs() < target, "{:?}: value {}", sampling, value);
+        }
+    }
+
+    #[test]
+    fn test_leduc_converges() {
+        let mut cfr = GameCfr::new(Leduc);
+        assert!(cfr.exploitability() > 2.0);
+        cfr.solve(100);
+        let exploitability = cfr.exploitability();
+        assert!(exploitability < 0.02, "exploitability {}", exploitability);
+        // The first player's equilibrium value is about -0.0856
+        assert!((cfr.expected_value(0) + 0.0856).abs() < 0.01, "value {}", cfr.expected_value(0));
+        // 6 betting histories by 3 ranks in the first round; 5 ways into the
+        // second round, then 6 histories by 3 private and 3 public ranks
+        assert_eq!(cfr.strategy_sum.len(), 6 * 3 + 5 * 6 * 9);
+    }
+
+    #[test]
+    fn test_leduc_sampling_variants() {
+        for (sampling, iterations, target) in [
+            (Sampling::Chance, 3000, 0.3),
+            (Sampling::External, 10000, 0.3),
+            (Sampling::Outcome, 50000, 0.6),
+        ] {
+            let mut cfr = GameCfr::new(Leduc).with_sampling(sampling, 3);
+            cfr.solve(iterations);
+            let exploitability = cfr.exploitability();
+            assert!(exploitability < target, "{:?}: exploitability {}", sampling, exploitability);
+        }
+    }
+}
@@ -1,7 +1,13 @@
 //! Game state representation
 
-use super::{BetSizeConfig, Card, Range};
-use crate::models::Player;
+use std::sync::Arc;
+
+use super::cards::parse_board;
+use super::utils::{chips_to_bb, parse_bb_amount, Chips};
+use super::{BetSizeConfig, Card, Rake, Range};
+use crate::models::{
+    ActionInfo, ActionType, ActionTypeResponse, BetSizes, Player, SolveRequest,
+};
 
 /// Poker street
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -17,42 +23,544 @@ pub enum Action {
     Fold,
     Check,
     Call,
-    Bet(u32),   // Amount in bb
-    Raise(u32), // Total amount to call
-    AllIn(u32),
+    Bet(Chips),   // Amount in chips
+    Raise(Chips), // Amount put in (call plus raise)
+    AllIn(Chips),
     Deal(Card), // Progress to next street
 }
 
+impl Action {
+    /// Check whether this action is of the given API action type
+    pub fn is_type(&self, action_type: &ActionType) -> bool {
+        matches!(
+            (self, action_type),
+            (Action::Fold, ActionType::Fold)
+                | (Action::Check, ActionType::Check)
+                | (Action::Call, ActionType::Call)
+                | (Action::Bet(_), ActionType::Bet)
+                | (Action::Raise(_), ActionType::Raise)
+                | (Action::AllIn(_), ActionType::Allin)
+                | (Action::Deal(_), ActionType::Deal)
+        )
+    }
+
+    /// Check whether this is a bet, raise or all-in
+    pub fn is_aggressive(&self) -> bool {
+        matches!(self, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_))
+    }
+
+    /// Amount put in by this action in chips (0 for check/fold/call/deal)
+    pub fn amount(&self) -> Chips {
+        match self {
+            Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => *amount,
+            _ => 0,
+        }
+    }
+
+    /// Size of a bet or raise the way bet sizes are given: the chips beyond
+    /// a call as a percentage of the pot after calling
+    pub fn size_percent(&self, pot: Chips, to_call: Chips) -> f64 {
+        let raise = self.amount().saturating_sub(to_call);
+        raise as f64 * 100.0 / (pot + to_call).max(1) as f64
+    }
+
+    /// Describe this action for the API, given the pot before it
+    ///
+    /// Returns `None` for deal actions, which are not player decisions.
+    pub fn to_action_info(&self, pot: Chips) -> Option<ActionInfo> {
+        let percent = self.amount() as f64 * 100.0 / pot.max(1) as f64;
+        let (name, action_type) = match self {
+            Action::Fold => ("Fold".to_string(), ActionTypeResponse::Fold),
+            Action::Check => ("Check".to_string(), ActionTypeResponse::Check),
+            Action::Call => ("Call".to_string(), ActionTypeResponse::Call),
+            Action::Bet(_) => (format!("Bet {:.0}%", percent), ActionTypeResponse::Bet),
+            Action::Raise(_) => (format!("Raise {:.0}%", percent), ActionTypeResponse::Raise),
+            Action::AllIn(_) => ("All-in".to_string(), ActionTypeResponse::Allin),
+            Action::Deal(_) => return None,
+        };
+
+        Some(ActionInfo {
+            name,
+            action_type,
+            amount_big_blinds: chips_to_bb(self.amount()),
+            amount_percent: if self.amount() > 0 { percent } else { 0.0 },
+            frequency: 0.0,
+        })
+    }
+}
+
+/// Find the action matching an API action description
+///
+/// Bets and raises are matched to the available size closest to
+/// `amount_percent`, measured by [`Action::size_percent`] against `pot` and
+/// the `to_call` the actions face, since sizes are rounded to whole chips.
+pub fn find_action(
+    actions: &[Action],
+    action_type: &ActionType,
+    amount_percent: Option<f64>,
+    card: Option<Card>,
+    pot: Chips,
+    to_call: Chips,
+) -> Option<usize> {
+    let candidates = actions
+        .iter()
+        .enumerate()
+        .filter(|(_, action)| action.is_type(action_type));
+
+    match (action_type, amount_percent, card) {
+        (ActionType::Deal, _, Some(card)) => actions.iter().position(|a| *a == Action::Deal(card)),
+        (ActionType::Bet | ActionType::Raise, Some(pct), _) => candidates
+            .map(|(i, action)| (i, (action.size_percent(pot, to_call) - pct).abs()))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i),
+        _ => candidates.map(|(i, _)| i).next(),
+    }
+}
+
+impl Street {
+    /// The street after this one
+    pub fn next(self) -> Option<Self> {
+        match self {
+            Street::Flop => Some(Street::Turn),
+            Street::Turn => Some(Street::River),
+            Street::River => None,
+        }
+    }
+
+    /// Street for a board with the given number of cards
+    pub fn from_board_len(len: usize) -> Option<Self> {
+        match len {
+            3 => Some(Street::Flop),
+            4 => Some(Street::Turn),
+            5 => Some(Street::River),
+            _ => None,
+        }
+    }
+}
+
 /// Game state (immutable)
 #[derive(Debug, Clone)]
 pub struct GameState {
     pub street: Street,
     pub board: Vec<Card>,
-    pub pot: u32,
-    pub stacks: [u32; 2], // [OOP, IP]
+    pub pot: Chips,         // Including bets on the current street
+    pub stacks: [Chips; 2], // [OOP, IP], chips behind
+    pub bets: [Chips; 2],   // [OOP, IP], bets on the current street
+    pub starting_stacks: [Chips; 2], // [OOP, IP], stacks before any root bet
     pub to_act: Player,
-    pub oop_range: Range,
-    pub ip_range: Range,
+    pub oop_range: Arc<Range>,
+    pub ip_range: Arc<Range>,
     pub history: Vec<Action>,
     pub bet_config: BetSizeConfig,
+    pub rake: Rake,
 }
 
 impl GameState {
+    /// Build the root state of a solve request
+    pub fn from_request(request: &SolveRequest) -> Result<Self, String> {
+        let board = parse_board(&request.board)?;
+        let street = Street::from_board_len(board.len())
+            .ok_or_else(|| format!("Board must have 3-5 cards, got {}", board.len()))?;
+        for (i, card) in board.iter().enumerate() {
+            if board[..i].contains(card) {
+                return Err(format!("Duplicate board card: '{}'", card));
+            }
+        }
+
+        let mut pot = parse_bb_amount(request.starting_pot, "Starting pot")?;
+        let mut stacks = [
+            parse_bb_amount(
+                request.oop_stack.unwrap_or(request.effective_stack),
+                "OOP stack",
+            )?,
+            parse_bb_amount(
+                request.ip_stack.unwrap_or(request.effective_stack),
+                "IP stack",
+            )?,
+        ];
+
+        // A root bet is already in the pot; the other player acts first.
+        // Only as much as the caller can match is in play: the rest would
+        // be returned uncalled, so the bettor keeps it.
+        let starting_stacks = stacks;
+        let mut bets = [0, 0];
+        let mut to_act = Player::OOP;
+        if let Some(root_bet) = &request.root_bet {
+            let bettor = match root_bet.position {
+                Player::OOP => 0,
+                Player::IP => 1,
+            };
+            let amount = parse_bb_amount(root_bet.amount, "Root bet")?;
+            if amount > stacks[bettor] {
+                return Err(format!(
+                    "Root bet of {}bb exceeds the bettor's stack ({}bb)",
+                    root_bet.amount,
+                    chips_to_bb(stacks[bettor])
+                ));
+            }
+            let amount = amount.min(stacks[1 - bettor]);
+            stacks[bettor] -= amount;
+            bets[bettor] = amount;
+            pot += amount;
+            to_act = match root_bet.position {
+                Player::OOP => Player::IP,
+                Player::IP => Player::OOP,
+            };
+        }
+
+        let parse_range = |range: &Option<String>| match range {
+            Some(s) => Range::parse(s),
+            None => Ok(Range::full()),
+        };
+        let oop_range = parse_range(&request.oop_range)?.filter_blocked(&board);
+        let ip_range = parse_range(&request.ip_range)?.filter_blocked(&board);
+
+        let bet_config = BetSizeConfig::from_bet_sizes(
+            request.bet_sizes.as_ref().unwrap_or(&BetSizes::default()),
+        )?;
+        let rake = match &request.rake {
+            Some(config) => Rake::from_config(config)?,
+            None => Rake::none(),
+        };
+
+        Ok(GameState {
+            street,
+            board,
+            pot,
+            stacks,
+            bets,
+            starting_stacks,
+            to_act,
+            oop_range: Arc::new(oop_range),
+            ip_range: Arc::new(ip_range),
+            history: Vec::new(),
+            bet_config,
+            rake,
+        })
+    }
+
+    /// Amount the player to act must call
+    pub fn to_call(&self) -> Chips {
+        let (own, other) = match self.to_act {
+            Player::OOP => (self.bets[0], self.bets[1]),
+            Player::IP => (self.bets[1], self.bets[0]),
+        };
+        other.saturating_sub(own)
+    }
+
+    /// Effective stack: the most either player can still lose
+    pub fn effective_stack(&self) -> Chips {
+        self.stacks[0].min(self.stacks[1])
+    }
+
+    /// Chips each player has put in since the root, any root bet included
+    pub fn put_in(&self) -> [Chips; 2] {
+        [
+            self.starting_stacks[0] - self.stacks[0],
+            self.starting_stacks[1] - self.stacks[1],
+        ]
+    }
+
+    /// Actions taken on the current street
+    fn street_actions(&self) -> &[Action] {
+        let start = self
+            .history
+            .iter()
+            .rposition(|a| matches!(a, Action::Deal(_)))
+            .map_or(0, |i| i + 1);
+        &self.history[start..]
+    }
+
+    /// Player who folded, if the hand ended with a fold
+    pub fn folded(&self) -> Option<Player> {
+        matches!(self.history.last(), Some(Action::Fold)).then_some(self.to_act)
+    }
+
+    /// Check if the betting on the current street is over
+    pub fn street_closed(&self) -> bool {
+        let actions = self.street_actions();
+        match actions.last() {
+            Some(Action::Fold | Action::Call) => true,
+            // A check can only follow a check
+            Some(Action::Check) => actions.len() >= 2,
+            // Nobody can bet once a player is all-in
+            None => self.to_call() == 0 && self.effective_stack() == 0,
+            _ => false,
+        }
+    }
+
     /// Check if this is a terminal state
     pub fn is_terminal(&self) -> bool {
-        // TODO: Implement terminal state check
-        false
+        self.folded().is_some() || (self.street_closed() && self.street == Street::River)
     }
 
     /// Get available actions
+    ///
+    /// Once the betting on a street is over the next card is dealt, so the
+    /// actions are the possible deals.
     pub fn get_available_actions(&self) -> Vec<Action> {
-        // TODO: Implement action generation
-        vec![]
+        if self.is_terminal() {
+            return vec![];
+        }
+        if self.street_closed() {
+            return (0..52)
+                .filter_map(Card::from_value)
+                .filter(|card| !self.board.contains(card))
+                .map(Action::Deal)
+                .collect();
+        }
+
+        let p = self.to_act as usize;
+        let oop = self.to_act == Player::OOP;
+        let stack = self.stacks[p];
+        let to_call = self.to_call();
+        let (mut actions, amounts, max) = if to_call > 0 {
+            // Raising is pointless once the opponent is all-in
+            let max = stack.min(to_call + self.stacks[1 - p]);
+            let raises = if max > to_call {
+                self.bet_config.get_raise_amounts(oop, self.pot, to_call, max)
+            } else {
+                vec![]
+            };
+            (vec![Action::Fold, Action::Call], raises, max)
+        } else {
+            let max = self.effective_stack();
+            (vec![Action::Check], self.bet_config.get_bet_amounts(oop, self.pot, max), max)
+        };
+
+        let mut amounts: Vec<Chips> = amounts.into_iter().map(|a| a.min(max)).collect();
+        amounts.sort_unstable();
+        amounts.dedup();
+        actions.extend(amounts.into_iter().map(|amount| match amount {
+            _ if amount == max => Action::AllIn(amount),
+            _ if to_call > 0 => Action::Raise(amount),
+            _ => Action::Bet(amount),
+        }));
+        actions
     }
 
     /// Apply an action to get a new state
-    pub fn apply_action(&self, _action: Action) -> GameState {
-        // TODO: Implement state transition
-        self.clone()
+    pub fn apply_action(&self, action: Action) -> GameState {
+        let mut next = self.clone();
+        let p = self.to_act as usize;
+        let amount = match &action {
+            Action::Fold => {
+                // The folder stays `to_act` so the fold can be attributed
+                next.history.push(action);
+                return next;
+            }
+            Action::Deal(card) => {
+                next.board.push(*card);
+                next.street = self.street.next().unwrap_or(self.street);
+                next.bets = [0, 0];
+                next.to_act = Player::OOP;
+                next.history.push(action);
+                return next;
+            }
+            Action::Check => 0,
+            Action::Call => self.to_call().min(self.stacks[p]),
+            Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => *amount,
+        };
+
+        next.stacks[p] -= amount;
+        next.bets[p] += amount;
+        next.pot += amount;
+        next.to_act = match self.to_act {
+            Player::OOP => Player::IP,
+            Player::IP => Player::OOP,
+        };
+        next.history.push(action);
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::RootBet;
+
+    fn request() -> SolveRequest {
+        SolveRequest {
+            player: Player::OOP,
+            board: "Ah Kd Qc".to_string(),
+            effective_stack: 100.0,
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: 20.0,
+            bet_sizes: None,
+            root_bet: None,
+            betting_history: None,
+            oop_range: Some("AA,KK".to_string()),
+            ip_range: None,
+            rake: None,
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        }
+    }
+
+    #[test]
+    fn test_from_request() {
+        let state = GameState::from_request(&request()).unwrap();
+        assert_eq!(state.street, Street::Flop);
+        assert_eq!(state.pot, 2000);
+        assert_eq!(state.stacks, [10000, 10000]);
+        assert_eq!(state.to_act, Player::OOP);
+        assert_eq!(state.to_call(), 0);
+        // AA loses 3 combos and KK 3 combos to the board
+        assert_eq!(state.oop_range.len(), 6);
+        // 1326 minus combos containing a board card
+        assert_eq!(state.ip_range.len(), 1176);
+    }
+
+    #[test]
+    fn test_asymmetric_stacks_and_root_bet() {
+        let mut req = request();
+        req.oop_stack = Some(80.0);
+        req.ip_stack = Some(150.0);
+        req.root_bet = Some(RootBet {
+            position: Player::IP,
+            amount: 6.6,
+        });
+
+        let state = GameState::from_request(&req).unwrap();
+        assert_eq!(state.stacks, [8000, 14340]);
+        assert_eq!(state.pot, 2660);
+        assert_eq!(state.to_act, Player::OOP);
+        assert_eq!(state.to_call(), 660);
+        assert_eq!(state.effective_stack(), 8000);
+    }
+
+    #[test]
+    fn test_root_bet_above_callers_stack() {
+        let mut req = request();
+        req.oop_stack = Some(80.0);
+        req.ip_stack = Some(150.0);
+        req.root_bet = Some(RootBet {
+            position: Player::IP,
+            amount: 100.0,
+        });
+
+        // OOP can only call 80bb; IP's other 20bb stay behind
+        let state = GameState::from_request(&req).unwrap();
+        assert_eq!(state.stacks, [8000, 7000]);
+        assert_eq!(state.pot, 10000);
+        assert_eq!(state.to_call(), 8000);
+        assert_eq!(state.get_available_actions(), vec![Action::Fold, Action::Call]);
+
+        let called = state.apply_action(Action::Call);
+        assert_eq!(called.pot, 18000);
+        assert_eq!(called.put_in(), [8000, 8000]);
+    }
+
+    #[test]
+    fn test_invalid_request() {
+        let mut req = request();
+        req.board = "Ah Kd".to_string();
+        assert!(GameState::from_request(&req).is_err());
+
+        let mut req = request();
+        req.board = "Ah Kd Ah".to_string();
+        assert!(GameState::from_request(&req).is_err());
+
+        let mut req = request();
+        req.ip_stack = Some(5.0);
+        req.root_bet = Some(RootBet {
+            position: Player::IP,
+            amount: 10.0,
+        });
+        assert!(GameState::from_request(&req).is_err());
+
+        let mut req = request();
+        req.starting_pot = 0.0;
+        assert!(GameState::from_request(&req).is_err());
+
+        // Stacks this deep would overflow the pot once all in
+        let mut req = request();
+        req.effective_stack = 30_000_000.0;
+        assert!(GameState::from_request(&req).is_err());
+    }
+
+    #[test]
+    fn test_find_action() {
+        let actions = vec![
+            Action::Check,
+            Action::Bet(660),
+            Action::Bet(1340),
+            Action::AllIn(10000),
+        ];
+
+        assert_eq!(find_action(&actions, &ActionType::Check, None, None, 2000, 0), Some(0));
+        assert_eq!(find_action(&actions, &ActionType::Bet, Some(33.0), None, 2000, 0), Some(1));
+        assert_eq!(find_action(&actions, &ActionType::Bet, Some(67.0), None, 2000, 0), Some(2));
+        assert_eq!(find_action(&actions, &ActionType::Allin, None, None, 2000, 0), Some(3));
+        assert_eq!(find_action(&actions, &ActionType::Fold, None, None, 2000, 0), None);
+
+        // Facing 660 into 2000, a 50% raise adds half the 3320 pot after calling
+        let raises = vec![Action::Fold, Action::Call, Action::Raise(2320), Action::Raise(3980)];
+        assert_eq!(raises[2].size_percent(2660, 660), 50.0);
+        assert_eq!(find_action(&raises, &ActionType::Raise, Some(50.0), None, 2660, 660), Some(2));
+        assert_eq!(find_action(&raises, &ActionType::Raise, Some(100.0), None, 2660, 660), Some(3));
+    }
+
+    #[test]
+    fn test_betting_round() {
+        let state = GameState::from_request(&request()).unwrap();
+        assert_eq!(
+            state.get_available_actions(),
+            vec![Action::Check, Action::Bet(660), Action::Bet(1340), Action::AllIn(10000)]
+        );
+
+        // Check, bet 6.6bb: IP faces fold, call or a raise to 6.6 + 50% of 33.2 = 23.2bb
+        let state = state.apply_action(Action::Check).apply_action(Action::Bet(660));
+        assert_eq!(state.to_act, Player::OOP);
+        assert_eq!(state.to_call(), 660);
+        assert_eq!(
+            state.get_available_actions(),
+            vec![Action::Fold, Action::Call, Action::Raise(2320), Action::AllIn(10000)]
+        );
+
+        let called = state.apply_action(Action::Call);
+        assert!(called.street_closed() && !called.is_terminal());
+        assert_eq!(called.pot, 3320);
+        assert_eq!(called.put_in(), [660, 660]);
+        assert_eq!(called.get_available_actions().len(), 49);
+
+        let turn = called.apply_action(Action::Deal("2c".parse().unwrap()));
+        assert_eq!(turn.street, Street::Turn);
+        assert_eq!(turn.to_act, Player::OOP);
+        assert!(!turn.street_closed());
+
+        let folded = state.apply_action(Action::Fold);
+        assert_eq!(folded.folded(), Some(Player::OOP));
+        assert!(folded.is_terminal());
+    }
+
+    #[test]
+    fn test_allin_runout() {
+        let mut req = request();
+        req.board = "Ah Kd Qc 7s".to_string();
+        let state = GameState::from_request(&req).unwrap();
+        let called = state
+            .apply_action(Action::AllIn(10000))
+            .apply_action(Action::Call);
+        assert!(called.street_closed());
+
+        // The river is dealt with nobody left to act
+        let river = called.apply_action(Action::Deal("2c".parse().unwrap()));
+        assert!(river.street_closed() && river.is_terminal());
+        assert!(river.get_available_actions().is_empty());
+    }
+
+    #[test]
+    fn test_action_info() {
+        let info = Action::Bet(660).to_action_info(2000).unwrap();
+        assert_eq!(info.name, "Bet 33%");
+        assert_eq!(info.amount_big_blinds, 6.6);
+        assert!((info.amount_percent - 33.0).abs() < 1e-9);
+
+        let info = Action::Check.to_action_info(2000).unwrap();
+        assert_eq!(info.amount_big_blinds, 0.0);
+        assert!(Action::Deal("2c".parse().unwrap()).to_action_info(2000).is_none());
     }
 }
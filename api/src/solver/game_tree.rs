@@ -1,6 +1,12 @@
 //! Game tree builder
+//!
+//! Trees can be built in full, dealing every turn and river card, or depth
+//! limited: betting stops where a later street would be dealt and those
+//! nodes become leaves valued by the value network.
 
-use super::game_state::{Action, GameState};
+use super::cards::Card;
+use super::game_state::{find_action, Action, GameState, Street};
+use crate::models::HistoryAction;
 
 /// A node in the game tree
 #[derive(Debug, Clone)]
@@ -8,6 +14,81 @@ pub struct GameNode {
     pub state: GameState,
     pub children: Vec<(Action, Box<GameNode>)>,
     pub is_terminal: bool,
+    /// Street boundary of a depth-limited tree, valued by the value network
+    pub is_leaf: bool,
+}
+
+impl GameNode {
+    /// Check if the next card is dealt here
+    pub fn is_chance(&self) -> bool {
+        matches!(self.children.first(), Some((Action::Deal(_), _)))
+    }
+
+    /// Get the actions available at this node
+    pub fn actions(&self) -> Vec<Action> {
+        self.children
+            .iter()
+            .map(|(action, _)| action.clone())
+            .collect()
+    }
+
+    /// Follow a betting history from this node to the node it reaches
+    pub fn find(&self, history: &[HistoryAction]) -> Result<&GameNode, String> {
+        Ok(self.at(&self.find_path(history)?))
+    }
+
+    /// Node reached by a path of child indices
+    pub fn at(&self, path: &[usize]) -> &GameNode {
+        path.iter().fold(self, |node, &index| &node.children[index].1)
+    }
+
+    /// Child indices taken when following a betting history from this node
+    pub fn find_path(&self, history: &[HistoryAction]) -> Result<Vec<usize>, String> {
+        let (path, rest) = self.follow(history)?;
+        match rest.first() {
+            Some(step) => Err(not_available(step)),
+            None => Ok(path),
+        }
+    }
+
+    /// Follow a betting history as far as the tree goes
+    ///
+    /// Returns the child indices taken and the steps left over where the
+    /// history runs on past a leaf of a depth-limited tree.
+    pub fn follow(&self, history: &[HistoryAction]) -> Result<(Vec<usize>, Vec<HistoryAction>), String> {
+        let mut sorted: Vec<&HistoryAction> = history.iter().collect();
+        sorted.sort_by_key(|h| h.order);
+
+        let mut node = self;
+        let mut path = Vec::with_capacity(sorted.len());
+        for (i, step) in sorted.iter().enumerate() {
+            if node.is_leaf {
+                return Ok((path, sorted[i..].iter().map(|&h| h.clone()).collect()));
+            }
+            let card = step
+                .card
+                .as_deref()
+                .map(|c| c.parse::<Card>())
+                .transpose()?;
+            let index = find_action(
+                &node.actions(),
+                &step.action,
+                step.amount_percent,
+                card,
+                node.state.pot,
+                node.state.to_call(),
+            )
+            .ok_or_else(|| not_available(step))?;
+            path.push(index);
+            node = &node.children[index].1;
+        }
+
+        Ok((path, Vec::new()))
+    }
+}
+
+fn not_available(step: &HistoryAction) -> String {
+    format!("Action {} ({:?}) is not available in the tree", step.order, step.action)
 }
 
 /// Game tree
@@ -18,8 +99,125 @@ pub struct GameTree {
 
 impl GameTree {
     /// Build a game tree from initial state
-    pub fn build(_initial_state: GameState, _max_depth: usize) -> Self {
-        // TODO: Implement tree building
-        unimplemented!("Game tree building not yet implemented")
+    ///
+    /// After `max_depth` bets and raises only checks, calls and folds remain.
+    pub fn build(initial_state: GameState, max_depth: usize) -> Self {
+        Self::build_until(initial_state, max_depth, None)
+    }
+
+    /// Build a tree that stops before `leaf_street` is dealt
+    pub fn build_depth_limited(initial_state: GameState, max_depth: usize, leaf_street: Street) -> Self {
+        Self::build_until(initial_state, max_depth, Some(leaf_street))
+    }
+
+    fn build_until(initial_state: GameState, max_depth: usize, leaf_street: Option<Street>) -> Self {
+        let mut node_count = 0;
+        let root = build_node(initial_state, 0, max_depth, leaf_street, &mut node_count);
+        GameTree { root, node_count }
+    }
+}
+
+/// Actions kept in a tree at a state reached after `depth` bets and raises
+///
+/// After `max_depth` bets and raises only checks, calls and folds remain.
+pub fn tree_actions(state: &GameState, depth: usize, max_depth: usize) -> Vec<Action> {
+    let mut actions = state.get_available_actions();
+    if depth >= max_depth {
+        actions.retain(|action| !action.is_aggressive());
+    }
+    actions
+}
+
+fn build_node(
+    state: GameState,
+    depth: usize,
+    max_depth: usize,
+    leaf_street: Option<Street>,
+    node_count: &mut usize,
+) -> GameNode {
+    *node_count += 1;
+    let is_terminal = state.is_terminal();
+    let is_leaf = !is_terminal
+        && state.street_closed()
+        && leaf_street.is_some_and(|leaf| state.street.next().is_some_and(|next| next as u8 >= leaf as u8));
+
+    let mut children = Vec::new();
+    if !is_terminal && !is_leaf {
+        for action in tree_actions(&state, depth, max_depth) {
+            let child = state.apply_action(action.clone());
+            let depth = depth + action.is_aggressive() as usize;
+            children.push((action, Box::new(build_node(child, depth, max_depth, leaf_street, node_count))));
+        }
+    }
+
+    GameNode {
+        state,
+        children,
+        is_terminal,
+        is_leaf,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BetSizes, Player, SolveRequest};
+
+    fn state(board: &str, bet_sizes: &str) -> GameState {
+        GameState::from_request(&SolveRequest {
+            player: Player::OOP,
+            board: board.to_string(),
+            effective_stack: 100.0,
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: 20.0,
+            bet_sizes: Some(BetSizes {
+                oop_bet: bet_sizes.to_string(),
+                oop_raise: "a".to_string(),
+                ip_bet: bet_sizes.to_string(),
+                ip_raise: "a".to_string(),
+            }),
+            root_bet: None,
+            betting_history: None,
+            oop_range: Some("AA".to_string()),
+            ip_range: Some("KK".to_string()),
+            rake: None,
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_river_tree() {
+        // x-x, x-a-f, x-a-c, a-f, a-c
+        let tree = GameTree::build(state("Ah Kd Qc 7s 2h", "a"), 10);
+        assert_eq!(tree.node_count, 9);
+        assert_eq!(tree.root.actions(), vec![Action::Check, Action::AllIn(10000)]);
+
+        let check_check = &tree.root.children[0].1.children[0].1;
+        assert!(check_check.is_terminal && check_check.state.folded().is_none());
+        let shove_fold = &tree.root.children[1].1.children[0].1;
+        assert_eq!(shove_fold.state.folded(), Some(Player::IP));
+    }
+
+    #[test]
+    fn test_depth_limited_tree() {
+        let tree = GameTree::build_depth_limited(state("Ah Kd Qc", "50"), 10, Street::Turn);
+        let check_check = &tree.root.children[0].1.children[0].1;
+        assert!(check_check.is_leaf && check_check.children.is_empty());
+
+        // The full tree deals every turn card instead
+        let tree = GameTree::build(state("Ah Kd Qc 7s", "a"), 10);
+        let check_check = &tree.root.children[0].1.children[0].1;
+        assert!(check_check.is_chance() && !check_check.is_leaf);
+        assert_eq!(check_check.children.len(), 48);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let tree = GameTree::build(state("Ah Kd Qc 7s 2h", "50"), 0);
+        assert_eq!(tree.root.actions(), vec![Action::Check]);
     }
 }
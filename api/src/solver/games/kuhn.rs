@@ -0,0 +1,124 @@
+//! Kuhn poker
+//!
+//! Three cards (J, Q, K), one each, antes of 1 and a single bet of 1. The
+//! first player's equilibrium value is -1/18.
+
+use crate::solver::game::{Game, Turn};
+
+/// Kuhn poker
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Kuhn;
+
+/// A bet (or call) or a pass (check or fold)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KuhnAction {
+    Pass,
+    Bet,
+    /// Deal cards to players 0 and 1
+    Deal(u8, u8),
+}
+
+/// Cards dealt and actions taken
+#[derive(Debug, Clone, Default)]
+pub struct KuhnState {
+    pub cards: Option<[u8; 2]>,
+    pub history: Vec<KuhnAction>,
+}
+
+impl Game for Kuhn {
+    type State = KuhnState;
+    type Action = KuhnAction;
+
+    fn root(&self) -> KuhnState {
+        KuhnState::default()
+    }
+
+    fn turn(&self, state: &KuhnState) -> Turn {
+        use KuhnAction::*;
+        match (state.cards, state.history.as_slice()) {
+            (None, _) => Turn::Chance,
+            (_, [Pass, Pass] | [Bet, _] | [Pass, Bet, _]) => Turn::Terminal,
+            (_, history) => Turn::Player(history.len() % 2),
+        }
+    }
+
+    fn actions(&self, _state: &KuhnState) -> Vec<KuhnAction> {
+        vec![KuhnAction::Pass, KuhnAction::Bet]
+    }
+
+    fn chance_outcomes(&self, _state: &KuhnState) -> Vec<(KuhnAction, f64)> {
+        let mut outcomes = Vec::with_capacity(6);
+        for a in 0..3 {
+            for b in (0..3).filter(|&b| b != a) {
+                outcomes.push((KuhnAction::Deal(a, b), 1.0 / 6.0));
+            }
+        }
+        outcomes
+    }
+
+    fn apply(&self, state: &KuhnState, action: &KuhnAction) -> KuhnState {
+        let mut next = state.clone();
+        match *action {
+            KuhnAction::Deal(a, b) => next.cards = Some([a, b]),
+            action => next.history.push(action),
+        }
+        next
+    }
+
+    fn info_set_key(&self, state: &KuhnState) -> String {
+        let player = state.history.len() % 2;
+        let card = state.cards.map_or(0, |cards| cards[player]);
+        format!("{}|{}", "JQK".as_bytes()[card as usize] as char, self.public_state(state))
+    }
+
+    fn public_state(&self, state: &KuhnState) -> String {
+        state
+            .history
+            .iter()
+            .map(|a| if *a == KuhnAction::Bet { 'b' } else { 'p' })
+            .collect()
+    }
+
+    fn utility(&self, state: &KuhnState, player: usize) -> f64 {
+        use KuhnAction::*;
+        let cards = state.cards.expect("terminal states have cards");
+        let showdown = |stake: f64| if cards[player] > cards[1 - player] { stake } else { -stake };
+        match state.history.as_slice() {
+            [Pass, Pass] => showdown(1.0),
+            [Bet, Bet] | [Pass, Bet, Bet] => showdown(2.0),
+            // Whoever passed facing the bet folded and loses their ante
+            [Bet, Pass] => if player == 0 { 1.0 } else { -1.0 },
+            [Pass, Bet, Pass] => if player == 1 { 1.0 } else { -1.0 },
+            history => panic!("{:?} is not terminal", history),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use KuhnAction::*;
+
+    fn play(cards: [u8; 2], history: &[KuhnAction]) -> KuhnState {
+        let mut state = Kuhn.apply(&Kuhn.root(), &Deal(cards[0], cards[1]));
+        for action in history {
+            state = Kuhn.apply(&state, action);
+        }
+        state
+    }
+
+    #[test]
+    fn test_rules() {
+        assert_eq!(Kuhn.turn(&Kuhn.root()), Turn::Chance);
+        assert_eq!(Kuhn.chance_outcomes(&Kuhn.root()).len(), 6);
+        assert_eq!(Kuhn.turn(&play([0, 2], &[Pass])), Turn::Player(1));
+        assert_eq!(Kuhn.turn(&play([0, 2], &[Pass, Bet])), Turn::Player(0));
+        assert_eq!(Kuhn.info_set_key(&play([0, 2], &[Pass, Bet])), "J|pb");
+        assert_eq!(Kuhn.public_state(&play([2, 0], &[Pass, Bet])), "pb");
+
+        assert_eq!(Kuhn.utility(&play([0, 2], &[Pass, Pass]), 0), -1.0);
+        assert_eq!(Kuhn.utility(&play([2, 0], &[Bet, Bet]), 0), 2.0);
+        assert_eq!(Kuhn.utility(&play([0, 2], &[Bet, Pass]), 1), -1.0);
+        assert_eq!(Kuhn.utility(&play([2, 1], &[Pass, Bet, Pass]), 0), -1.0);
+    }
+}
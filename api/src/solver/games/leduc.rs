@@ -0,0 +1,210 @@
+//! Leduc hold'em
+//!
+//! Six cards (two each of J, Q, K), antes of 1 and one private card each.
+//! Two betting rounds with raises of 2 then 4 and at most two raises per
+//! round; a public card is dealt between them. A pair with the public card
+//! wins, otherwise the higher card; equal cards split the pot.
+
+use crate::solver::game::{Game, Turn};
+
+/// Bets and raises allowed per round
+const MAX_RAISES: usize = 2;
+
+/// Leduc hold'em
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Leduc;
+
+/// A betting action or a dealt card (0..6, rank `card / 2`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeducAction {
+    Fold,
+    /// Check or call
+    Call,
+    /// Bet or raise
+    Raise,
+    Deal(u8),
+}
+
+/// Cards dealt and actions taken
+#[derive(Debug, Clone, Default)]
+pub struct LeducState {
+    /// Private cards of players 0 and 1, in the order dealt
+    pub private: Vec<u8>,
+    pub public: Option<u8>,
+    /// Betting actions of each round
+    pub rounds: Vec<Vec<LeducAction>>,
+    /// Chips each player has put in
+    pub contributions: [u32; 2],
+    pub folded: Option<usize>,
+}
+
+impl LeducState {
+    fn round(&self) -> &[LeducAction] {
+        self.rounds.last().map_or(&[], |r| r.as_slice())
+    }
+
+    /// A round is over once someone calls after the first action
+    fn round_over(&self) -> bool {
+        let round = self.round();
+        round.len() >= 2 && round.last() == Some(&LeducAction::Call)
+    }
+
+    fn raises(&self) -> usize {
+        self.round().iter().filter(|&&a| a == LeducAction::Raise).count()
+    }
+}
+
+impl Game for Leduc {
+    type State = LeducState;
+    type Action = LeducAction;
+
+    fn root(&self) -> LeducState {
+        LeducState {
+            contributions: [1, 1],
+            ..LeducState::default()
+        }
+    }
+
+    fn turn(&self, state: &LeducState) -> Turn {
+        if state.folded.is_some() || (state.round_over() && state.public.is_some()) {
+            Turn::Terminal
+        } else if state.private.len() < 2 || state.round_over() {
+            Turn::Chance
+        } else {
+            Turn::Player(state.round().len() % 2)
+        }
+    }
+
+    fn actions(&self, state: &LeducState) -> Vec<LeducAction> {
+        let mut actions = Vec::with_capacity(3);
+        if state.contributions[0] != state.contributions[1] {
+            actions.push(LeducAction::Fold);
+        }
+        actions.push(LeducAction::Call);
+        if state.raises() < MAX_RAISES {
+            actions.push(LeducAction::Raise);
+        }
+        actions
+    }
+
+    fn chance_outcomes(&self, state: &LeducState) -> Vec<(LeducAction, f64)> {
+        let left: Vec<u8> = (0..6)
+            .filter(|c| !state.private.contains(c) && state.public != Some(*c))
+            .collect();
+        let p = 1.0 / left.len() as f64;
+        left.into_iter().map(|c| (LeducAction::Deal(c), p)).collect()
+    }
+
+    fn apply(&self, state: &LeducState, action: &LeducAction) -> LeducState {
+        let mut next = state.clone();
+        match *action {
+            LeducAction::Deal(card) if next.private.len() < 2 => {
+                next.private.push(card);
+                if next.private.len() == 2 {
+                    next.rounds.push(Vec::new());
+                }
+            }
+            LeducAction::Deal(card) => {
+                next.public = Some(card);
+                next.rounds.push(Vec::new());
+            }
+            action => {
+                let player = state.round().len() % 2;
+                let raise = if state.public.is_some() { 4 } else { 2 };
+                match action {
+                    LeducAction::Fold => next.folded = Some(player),
+                    LeducAction::Call => next.contributions[player] = state.contributions[1 - player],
+                    _ => next.contributions[player] = state.contributions[1 - player] + raise,
+                }
+                next.rounds.last_mut().expect("betting happens in a round").push(action);
+            }
+        }
+        next
+    }
+
+    fn info_set_key(&self, state: &LeducState) -> String {
+        let player = state.round().len() % 2;
+        format!("{}{}", rank(state.private[player]), self.public_state(state))
+    }
+
+    fn public_state(&self, state: &LeducState) -> String {
+        let rounds: Vec<String> = state
+            .rounds
+            .iter()
+            .map(|round| {
+                round
+                    .iter()
+                    .map(|a| match a {
+                        LeducAction::Fold => 'f',
+                        LeducAction::Call => 'c',
+                        _ => 'r',
+                    })
+                    .collect()
+            })
+            .collect();
+        format!("{}|{}", state.public.map_or(String::new(), |c| rank(c).to_string()), rounds.join("/"))
+    }
+
+    fn utility(&self, state: &LeducState, player: usize) -> f64 {
+        let won = state.contributions[1 - player] as f64;
+        let lost = state.contributions[player] as f64;
+        if let Some(folder) = state.folded {
+            return if folder == player { -lost } else { won };
+        }
+
+        let public = state.public.expect("showdowns have a public card") / 2;
+        // Pairs rank above every high card
+        let strength = |p: usize| {
+            let rank = state.private[p] / 2;
+            if rank == public { 10 + rank } else { rank }
+        };
+        match strength(player).cmp(&strength(1 - player)) {
+            std::cmp::Ordering::Greater => won,
+            std::cmp::Ordering::Less => -lost,
+            std::cmp::Ordering::Equal => 0.0,
+        }
+    }
+}
+
+/// Rank letter of a card
+fn rank(card: u8) -> char {
+    "JQK".as_bytes()[card as usize / 2] as char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use LeducAction::*;
+
+    fn play(actions: &[LeducAction]) -> LeducState {
+        actions.iter().fold(Leduc.root(), |state, a| Leduc.apply(&state, a))
+    }
+
+    #[test]
+    fn test_rules() {
+        let dealt = play(&[Deal(0), Deal(4)]);
+        assert_eq!(Leduc.turn(&dealt), Turn::Player(0));
+        assert_eq!(Leduc.actions(&dealt), vec![Call, Raise]);
+        assert_eq!(Leduc.info_set_key(&play(&[Deal(0), Deal(4), Raise])), "K|r");
+
+        // Two raises cap the round; calling deals the public card
+        let capped = play(&[Deal(0), Deal(4), Raise, Raise]);
+        assert_eq!(Leduc.actions(&capped), vec![Fold, Call]);
+        assert_eq!(capped.contributions, [3, 5]);
+        let flop = Leduc.apply(&capped, &Call);
+        assert_eq!(Leduc.turn(&flop), Turn::Chance);
+        assert_eq!(Leduc.chance_outcomes(&flop).len(), 4);
+
+        // J pairs the public J and beats the K; raises are 4 on the second round
+        let river = play(&[Deal(0), Deal(4), Raise, Call, Deal(1), Raise, Raise, Call]);
+        assert_eq!(Leduc.turn(&river), Turn::Terminal);
+        assert_eq!(river.contributions, [11, 11]);
+        assert_eq!(Leduc.utility(&river, 0), 11.0);
+        assert_eq!(Leduc.info_set_key(&play(&[Deal(0), Deal(4), Raise, Call, Deal(1)])), "JJ|rc/");
+        assert_eq!(Leduc.public_state(&play(&[Deal(0), Deal(4), Raise, Call, Deal(1)])), "J|rc/");
+
+        let folded = play(&[Deal(0), Deal(4), Call, Raise, Fold]);
+        assert_eq!(Leduc.turn(&folded), Turn::Terminal);
+        assert_eq!(Leduc.utility(&folded, 1), 1.0);
+    }
+}
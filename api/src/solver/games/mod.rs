@@ -0,0 +1,10 @@
+//! Small reference games
+//!
+//! Their equilibria are known, so they check the CFR update rules without
+//! the cost of hold'em.
+
+pub mod kuhn;
+pub mod leduc;
+
+pub use kuhn::Kuhn;
+pub use leduc::Leduc;
@@ -0,0 +1,142 @@
+//! Made-hand categories of hole cards on a board
+//!
+//! A hand only gets a category its hole cards improve the board to: two
+//! pair on a double-paired board, or a straight on a board straight, counts
+//! as high card. Made hands are named by their best five cards; single
+//! pairs are placed by the board card the hole cards pair, so "top pair" on
+//! A-K-7 is any ace.
+
+use super::cards::{Card, Rank};
+use super::hand_eval::{HandEvaluator, HandStrength};
+use crate::models::HandCategory;
+
+/// Worst strength of each category from straight flush to one pair
+const CATEGORY_BOUNDS: [(HandStrength, HandCategory); 8] = [
+    (10, HandCategory::StraightFlush),
+    (166, HandCategory::Quads),
+    (322, HandCategory::FullHouse),
+    (1599, HandCategory::Flush),
+    (1609, HandCategory::Straight),
+    (2467, HandCategory::Trips),
+    (3325, HandCategory::TwoPair),
+    (6185, HandCategory::WeakPair),
+];
+
+/// Category of hole cards on a flop, turn or river board
+pub fn hand_category(evaluator: &HandEvaluator, hole: [Card; 2], board: &[Card]) -> HandCategory {
+    let mut cards = hole.to_vec();
+    cards.extend_from_slice(board);
+    let category = made_hand(evaluator.evaluate_best(&cards));
+    if level(category) >= level(board_hand(evaluator, board)) {
+        return HandCategory::HighCard;
+    }
+
+    let pocket_pair = hole[0].rank() == hole[1].rank();
+    let set = pocket_pair && board.iter().any(|c| c.rank() == hole[0].rank());
+    match category {
+        HandCategory::Trips if set => HandCategory::Set,
+        HandCategory::WeakPair => pair(hole, board),
+        category => category,
+    }
+}
+
+/// Category of a hand's best five cards, with one pair as `WeakPair`
+fn made_hand(strength: HandStrength) -> HandCategory {
+    CATEGORY_BOUNDS
+        .iter()
+        .find(|&&(worst, _)| strength <= worst)
+        .map_or(HandCategory::HighCard, |&(_, category)| category)
+}
+
+/// What the board makes on its own; short boards can only pair up
+fn board_hand(evaluator: &HandEvaluator, board: &[Card]) -> HandCategory {
+    if board.len() >= 5 {
+        return made_hand(evaluator.evaluate_best(board));
+    }
+    let mut counts = [0; 13];
+    for card in board {
+        counts[card.rank() as usize] += 1;
+    }
+    let pairs = counts.iter().filter(|&&n| n == 2).count();
+    match counts.iter().max() {
+        Some(4) => HandCategory::Quads,
+        Some(3) => HandCategory::Trips,
+        _ if pairs == 2 => HandCategory::TwoPair,
+        _ if pairs == 1 => HandCategory::WeakPair,
+        _ => HandCategory::HighCard,
+    }
+}
+
+/// Position of a made-hand category, lower is better
+fn level(category: HandCategory) -> usize {
+    CATEGORY_BOUNDS
+        .iter()
+        .position(|&(_, c)| c == category)
+        .unwrap_or(CATEGORY_BOUNDS.len())
+}
+
+/// Place a pair the hole cards make by the board card it pairs
+fn pair(hole: [Card; 2], board: &[Card]) -> HandCategory {
+    // Distinct board ranks, highest first
+    let mut ranks: Vec<Rank> = board.iter().map(|c| c.rank()).collect();
+    ranks.sort_unstable_by(|a, b| b.cmp(a));
+    ranks.dedup();
+
+    if hole[0].rank() == hole[1].rank() {
+        return if hole[0].rank() > ranks[0] {
+            HandCategory::Overpair
+        } else {
+            HandCategory::WeakPair
+        };
+    }
+    match ranks.iter().position(|&r| hole.iter().any(|c| c.rank() == r)) {
+        Some(0) => HandCategory::TopPair,
+        Some(1) => HandCategory::SecondPair,
+        _ => HandCategory::WeakPair,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::cards::parse_board;
+
+    fn category(hole: &str, board: &str) -> HandCategory {
+        let hole = parse_board(hole).unwrap().try_into().unwrap();
+        hand_category(&HandEvaluator::new(), hole, &parse_board(board).unwrap())
+    }
+
+    #[test]
+    fn test_pairs() {
+        let flop = "Ah Kd 7c";
+        assert_eq!(category("As Qd", flop), HandCategory::TopPair);
+        assert_eq!(category("Ks Qd", flop), HandCategory::SecondPair);
+        assert_eq!(category("7s 6s", flop), HandCategory::WeakPair);
+        assert_eq!(category("Qs Qd", flop), HandCategory::WeakPair);
+        assert_eq!(category("Qs Jd", flop), HandCategory::HighCard);
+        assert_eq!(category("Qs Qd", "Jh 8d 2c"), HandCategory::Overpair);
+
+        // The board pair alone is not a made hand
+        assert_eq!(category("Qs Jd", "Ah Ad 7c"), HandCategory::HighCard);
+    }
+
+    #[test]
+    fn test_made_hands() {
+        assert_eq!(category("7s 7d", "Ah Kd 7c"), HandCategory::Set);
+        assert_eq!(category("As Qd", "Ah Ad 7c"), HandCategory::Trips);
+        assert_eq!(category("As Kh", "Ah Kd 7c"), HandCategory::TwoPair);
+        assert_eq!(category("Qs Jd", "Ah Kd Tc 2s"), HandCategory::Straight);
+        assert_eq!(category("Qd Jd", "Ad Kd 2d 7s 9c"), HandCategory::Flush);
+        assert_eq!(category("7s 7d", "Ah 7c Ad"), HandCategory::FullHouse);
+    }
+
+    #[test]
+    fn test_hole_cards_must_improve_the_board() {
+        assert_eq!(category("Qs Jd", "Ah Ad 7c 7d 2s"), HandCategory::HighCard);
+        assert_eq!(category("Ks 7s", "Ah Ad 7c 7d 2s"), HandCategory::FullHouse);
+        assert_eq!(category("2c 3d", "Ah Kd Qc Js Ts"), HandCategory::HighCard);
+        assert_eq!(category("2c 3d", "Ac Kc 8c 4c 9c"), HandCategory::HighCard);
+        assert_eq!(category("Ks 7d", "Ah Ad 7c"), HandCategory::TwoPair);
+        assert_eq!(category("As Qd", "Ah Kd Tc Js 2c"), HandCategory::Straight);
+    }
+}
@@ -0,0 +1,307 @@
+//! The 169 strategically distinct starting hand classes
+//!
+//! Classes are indexed in the usual 13x13 grid order: row-major from the
+//! top-left, `AA` first and `22` last, suited hands above the diagonal and
+//! offsuit hands below it.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::cards::{generate_all_combos, Card, Combo, Rank, Suit};
+use super::range::Range;
+use crate::models::HandClassStrategy;
+
+/// Number of hand classes
+pub const NUM_HAND_CLASSES: usize = 169;
+
+/// A starting hand class such as `AA`, `AKs` or `AKo`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HandClass {
+    pub high: Rank,
+    pub low: Rank,
+    pub suited: bool,
+}
+
+impl HandClass {
+    /// Create a hand class (ranks may be given in either order)
+    pub fn new(rank1: Rank, rank2: Rank, suited: bool) -> Self {
+        HandClass {
+            high: rank1.max(rank2),
+            low: rank1.min(rank2),
+            suited: suited && rank1 != rank2,
+        }
+    }
+
+    /// Class of a two-card combo
+    pub fn of_combo(combo: &Combo) -> Self {
+        HandClass::new(
+            combo.card1.rank(),
+            combo.card2.rank(),
+            combo.card1.suit() == combo.card2.suit(),
+        )
+    }
+
+    /// Check if this is a pocket pair
+    pub fn is_pair(&self) -> bool {
+        self.high == self.low
+    }
+
+    /// Grid index (0-168)
+    pub fn index(&self) -> usize {
+        let high = 12 - self.high as usize;
+        let low = 12 - self.low as usize;
+        if self.suited {
+            high * 13 + low
+        } else {
+            low * 13 + high
+        }
+    }
+
+    /// Hand class at a grid index
+    pub fn from_index(index: usize) -> Option<Self> {
+        if index >= NUM_HAND_CLASSES {
+            return None;
+        }
+        let ranks = Rank::all();
+        let row = ranks[12 - index / 13];
+        let col = ranks[12 - index % 13];
+        Some(HandClass::new(row, col, row > col))
+    }
+
+    /// All 169 classes in grid order
+    pub fn all() -> Vec<HandClass> {
+        (0..NUM_HAND_CLASSES)
+            .filter_map(HandClass::from_index)
+            .collect()
+    }
+
+    /// Number of combos in this class before card removal (6, 4 or 12)
+    pub fn num_combos(&self) -> u32 {
+        if self.is_pair() {
+            6
+        } else if self.suited {
+            4
+        } else {
+            12
+        }
+    }
+
+    /// IDs of all combos belonging to this class
+    pub fn combos(&self) -> Vec<u16> {
+        generate_all_combos()
+            .iter()
+            .filter(|combo| HandClass::of_combo(combo) == *self)
+            .map(|combo| combo.id)
+            .collect()
+    }
+}
+
+impl fmt::Display for HandClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_pair() {
+            write!(f, "{}{}", self.high, self.low)
+        } else {
+            let modifier = if self.suited { 's' } else { 'o' };
+            write!(f, "{}{}{}", self.high, self.low, modifier)
+        }
+    }
+}
+
+impl FromStr for HandClass {
+    type Err = String;
+
+    /// Parse a class like "AA", "AKs" or "AKo"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().collect();
+        if chars.len() < 2 || chars.len() > 3 {
+            return Err(format!("Invalid hand class: '{}'", s));
+        }
+
+        let rank1 = Rank::from_char(chars[0]).ok_or_else(|| format!("Invalid rank: '{}'", chars[0]))?;
+        let rank2 = Rank::from_char(chars[1]).ok_or_else(|| format!("Invalid rank: '{}'", chars[1]))?;
+
+        match (rank1 == rank2, chars.get(2)) {
+            (true, None) => Ok(HandClass::new(rank1, rank2, false)),
+            (false, Some('s' | 'S')) => Ok(HandClass::new(rank1, rank2, true)),
+            (false, Some('o' | 'O')) => Ok(HandClass::new(rank1, rank2, false)),
+            _ => Err(format!("Invalid hand class: '{}'", s)),
+        }
+    }
+}
+
+/// Solver output for one combo, the input to class aggregation
+#[derive(Debug, Clone)]
+pub struct ComboResult {
+    pub combo: Combo,
+    /// Range weight reaching the node
+    pub weight: f64,
+    pub strategy: Vec<f64>,
+    /// Expected value in bb
+    pub ev: f64,
+}
+
+/// Flush suit that makes a combo strategically distinct on this board
+///
+/// A combo is flush-relevant when its cards of some suit make a flush with
+/// the board or, before the river, a flush draw.
+pub fn flush_suit(combo: &Combo, board: &[Card]) -> Option<Suit> {
+    let needed = if board.len() >= 5 { 5 } else { 4 };
+    Suit::all()
+        .into_iter()
+        .filter_map(|suit| {
+            let held = combo.cards().iter().filter(|c| c.suit() == suit).count();
+            let total = held + board.iter().filter(|c| c.suit() == suit).count();
+            (held > 0 && total >= needed).then_some((total, suit))
+        })
+        .max()
+        .map(|(_, suit)| suit)
+}
+
+/// Class weights of a range (sum of combo frequencies per class), in grid order
+pub fn class_weights(range: &Range) -> Vec<f64> {
+    let all_combos = generate_all_combos();
+    let mut weights = vec![0.0; NUM_HAND_CLASSES];
+    for (combo_id, frequency) in range.get_combos() {
+        weights[HandClass::of_combo(&all_combos[combo_id as usize]).index()] += frequency;
+    }
+    weights
+}
+
+/// Running totals for one class subgroup
+#[derive(Default)]
+struct ClassTotals {
+    num_combos: u32,
+    weight: f64,
+    strategy: Vec<f64>,
+    ev: f64,
+}
+
+/// Aggregate per-combo results by hand class
+///
+/// Strategies and EVs are averaged weighted by each combo's range weight.
+/// On boards with flush draws each class is split into the combos holding
+/// the flush suit and the rest. Results are in grid order.
+pub fn aggregate_by_class(results: &[ComboResult], board: &[Card]) -> Vec<HandClassStrategy> {
+    let mut groups: BTreeMap<(usize, Option<Suit>), ClassTotals> = BTreeMap::new();
+    for result in results {
+        let key = (
+            HandClass::of_combo(&result.combo).index(),
+            flush_suit(&result.combo, board),
+        );
+        let totals = groups.entry(key).or_default();
+        totals.strategy.resize(result.strategy.len(), 0.0);
+        totals.num_combos += 1;
+        totals.weight += result.weight;
+        totals.ev += result.weight * result.ev;
+        for (total, p) in totals.strategy.iter_mut().zip(&result.strategy) {
+            *total += result.weight * p;
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, totals)| totals.weight > 0.0)
+        .map(|((index, suit), totals)| HandClassStrategy {
+            hand: HandClass::from_index(index).unwrap().to_string(),
+            flush_suit: suit.map(|s| s.to_string()),
+            num_combos: totals.num_combos,
+            weight: totals.weight,
+            strategy: totals.strategy.iter().map(|p| p / totals.weight).collect(),
+            ev: totals.ev / totals.weight,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::cards::parse_board;
+
+    #[test]
+    fn test_grid_order() {
+        let all = HandClass::all();
+        assert_eq!(all.len(), 169);
+        assert_eq!(all[0].to_string(), "AA");
+        assert_eq!(all[1].to_string(), "AKs");
+        assert_eq!(all[13].to_string(), "AKo");
+        assert_eq!(all[14].to_string(), "KK");
+        assert_eq!(all[168].to_string(), "22");
+
+        for (i, class) in all.iter().enumerate() {
+            assert_eq!(class.index(), i);
+        }
+    }
+
+    #[test]
+    fn test_combo_counts() {
+        let total: u32 = HandClass::all().iter().map(|c| c.num_combos()).sum();
+        assert_eq!(total, 1326);
+
+        let aks: HandClass = "AKs".parse().unwrap();
+        assert_eq!(aks.combos().len(), 4);
+        let ako: HandClass = "KAo".parse().unwrap();
+        assert_eq!(ako.to_string(), "AKo");
+        assert_eq!(ako.combos().len(), 12);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("AAs".parse::<HandClass>().is_err());
+        assert!("AK".parse::<HandClass>().is_err());
+        assert!("AKx".parse::<HandClass>().is_err());
+    }
+
+    fn result(hand: &str, weight: f64, strategy: Vec<f64>, ev: f64) -> ComboResult {
+        ComboResult {
+            combo: hand.parse().unwrap(),
+            weight,
+            strategy,
+            ev,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_by_class() {
+        let board = parse_board("2c 7d 9s").unwrap();
+        let results = vec![
+            result("AhKh", 1.0, vec![1.0, 0.0], 2.0),
+            result("AsKs", 0.5, vec![0.0, 1.0], 5.0),
+            result("AhKd", 1.0, vec![0.5, 0.5], 1.0),
+            result("QhQd", 0.0, vec![1.0, 0.0], 0.0),
+        ];
+        let classes = aggregate_by_class(&results, &board);
+
+        // Rainbow board: no flush splits, zero-weight classes dropped
+        assert_eq!(classes.len(), 2);
+        let aks = &classes[0];
+        assert_eq!(aks.hand, "AKs");
+        assert_eq!(aks.flush_suit, None);
+        assert_eq!(aks.num_combos, 2);
+        assert!((aks.weight - 1.5).abs() < 1e-9);
+        assert!((aks.strategy[0] - 2.0 / 3.0).abs() < 1e-9);
+        assert!((aks.ev - 3.0).abs() < 1e-9);
+        assert_eq!(classes[1].hand, "AKo");
+    }
+
+    #[test]
+    fn test_flush_suit_split() {
+        let board = parse_board("2h 7h 9s").unwrap();
+        assert_eq!(flush_suit(&"AhKh".parse().unwrap(), &board), Some(Suit::Hearts));
+        assert_eq!(flush_suit(&"AhKd".parse().unwrap(), &board), None);
+
+        let results = vec![
+            result("AhKh", 1.0, vec![1.0], 0.0),
+            result("AsKs", 1.0, vec![1.0], 0.0),
+            result("AdKd", 1.0, vec![1.0], 0.0),
+        ];
+        let classes = aggregate_by_class(&results, &board);
+        assert_eq!(classes.len(), 2);
+        assert_eq!((classes[0].flush_suit.as_deref(), classes[0].num_combos), (None, 2));
+        assert_eq!((classes[1].flush_suit.as_deref(), classes[1].num_combos), (Some("h"), 1));
+
+        // On a three-flush board a single card of the suit is a draw
+        let board = parse_board("2h 7h 9h").unwrap();
+        assert_eq!(flush_suit(&"AhKd".parse().unwrap(), &board), Some(Suit::Hearts));
+    }
+}
@@ -1,10 +1,20 @@
 //! Hand evaluation for poker hands
+//!
+//! Hands are ranked 1 (royal flush) to 7462 (7-5-4-3-2 offsuit), one rank
+//! per distinct hand value, as in the usual Cactus Kev numbering. The rank
+//! table is built once from every distinct five-card rank pattern.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
 
 use super::cards::Card;
 
 /// Hand strength value (lower is better)
 pub type HandStrength = u16;
 
+/// Number of distinct five-card hand values
+pub const NUM_HAND_RANKS: usize = 7462;
+
 /// Hand evaluator
 pub struct HandEvaluator;
 
@@ -15,15 +25,56 @@ impl HandEvaluator {
     }
 
     /// Evaluate a 5-card hand
-    pub fn evaluate_5cards(&self, _cards: [Card; 5]) -> HandStrength {
-        // TODO: Implement hand evaluation
-        0
+    pub fn evaluate_5cards(&self, cards: [Card; 5]) -> HandStrength {
+        let flush = cards.iter().all(|c| c.suit() == cards[0].suit());
+        let ranks = cards.map(|c| c.rank() as u8);
+        rank_table()[&hand_key(ranks, flush)]
     }
 
     /// Evaluate a 7-card hand (5 cards from board + 2 hole cards)
-    pub fn evaluate_7cards(&self, _cards: [Card; 7]) -> HandStrength {
-        // TODO: Implement 7-card evaluation
-        0
+    pub fn evaluate_7cards(&self, cards: [Card; 7]) -> HandStrength {
+        let mut best = HandStrength::MAX;
+        for skip1 in 0..7 {
+            for skip2 in skip1 + 1..7 {
+                let mut hand = [cards[0]; 5];
+                let mut n = 0;
+                for (i, &card) in cards.iter().enumerate() {
+                    if i != skip1 && i != skip2 {
+                        hand[n] = card;
+                        n += 1;
+                    }
+                }
+                best = best.min(self.evaluate_5cards(hand));
+            }
+        }
+        best
+    }
+
+    /// Best five-card strength of five to seven cards
+    pub fn evaluate_best(&self, cards: &[Card]) -> HandStrength {
+        assert!((5..=7).contains(&cards.len()), "need 5 to 7 cards, got {}", cards.len());
+        let mut best = HandStrength::MAX;
+        // Every five-card subset as a bit mask over the cards
+        for mask in 0u32..1 << cards.len() {
+            if mask.count_ones() != 5 {
+                continue;
+            }
+            let mut hand = [cards[0]; 5];
+            let kept = cards.iter().enumerate().filter(|&(i, _)| mask & 1 << i != 0);
+            for (slot, (_, &card)) in hand.iter_mut().zip(kept) {
+                *slot = card;
+            }
+            best = best.min(self.evaluate_5cards(hand));
+        }
+        best
+    }
+
+    /// Evaluate hole cards on a full five-card board
+    pub fn evaluate_hand(&self, hole: [Card; 2], board: &[Card]) -> HandStrength {
+        let mut cards = [hole[0]; 7];
+        cards[..2].copy_from_slice(&hole);
+        cards[2..].copy_from_slice(&board[..5]);
+        self.evaluate_7cards(cards)
     }
 }
 
@@ -32,3 +83,158 @@ impl Default for HandEvaluator {
         Self::new()
     }
 }
+
+/// Comparable value of a five-card rank pattern (higher is better)
+///
+/// The hand category sits above five 4-bit tiebreak ranks, ordered by group
+/// size then rank (so a full house compares trips before the pair).
+fn hand_key(ranks: [u8; 5], flush: bool) -> u32 {
+    let mut counts = [0u8; 13];
+    for &r in &ranks {
+        counts[r as usize] += 1;
+    }
+    let mut groups: Vec<(u8, u8)> = (0..13u8)
+        .rev()
+        .filter(|&r| counts[r as usize] > 0)
+        .map(|r| (counts[r as usize], r))
+        .collect();
+    groups.sort_by(|a, b| b.cmp(a));
+
+    // Highest card of a straight (the five in a wheel)
+    let straight_high = if groups.len() == 5 {
+        let high = groups[0].1;
+        let low = groups[4].1;
+        if high - low == 4 {
+            Some(high)
+        } else if high == 12 && groups[1].1 == 3 {
+            Some(3)
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    let category = match (straight_high, flush, groups[0].0, groups.get(1).map(|g| g.0)) {
+        (Some(_), true, _, _) => 8,
+        (_, _, 4, _) => 7,
+        (_, _, 3, Some(2)) => 6,
+        (_, true, _, _) => 5,
+        (Some(_), _, _, _) => 4,
+        (_, _, 3, _) => 3,
+        (_, _, 2, Some(2)) => 2,
+        (_, _, 2, _) => 1,
+        _ => 0,
+    };
+
+    let tiebreak = match straight_high {
+        Some(high) if category == 8 || category == 4 => (high as u32) << 16,
+        _ => groups
+            .iter()
+            .enumerate()
+            .fold(0, |key, (i, &(_, r))| key | (r as u32) << (16 - 4 * i)),
+    };
+    (category << 20) | tiebreak
+}
+
+/// Hand key to rank (1 = best) for every distinct five-card hand value
+fn rank_table() -> &'static HashMap<u32, HandStrength> {
+    static TABLE: OnceLock<HashMap<u32, HandStrength>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut keys = Vec::with_capacity(NUM_HAND_RANKS);
+        let mut ranks = [0u8; 5];
+        // Rank multisets in non-increasing order, at most four of a rank
+        fn visit(ranks: &mut [u8; 5], depth: usize, max: u8, keys: &mut Vec<u32>) {
+            if depth == 5 {
+                keys.push(hand_key(*ranks, false));
+                let distinct = ranks.windows(2).all(|w| w[0] != w[1]);
+                if distinct {
+                    keys.push(hand_key(*ranks, true));
+                }
+                return;
+            }
+            for r in 0..=max {
+                let same = ranks[..depth].iter().filter(|&&x| x == r).count();
+                if same < 4 {
+                    ranks[depth] = r;
+                    visit(ranks, depth + 1, r, keys);
+                }
+            }
+        }
+        visit(&mut ranks, 0, 12, &mut keys);
+
+        keys.sort_unstable_by(|a, b| b.cmp(a));
+        keys.dedup();
+        keys.into_iter()
+            .enumerate()
+            .map(|(i, key)| (key, i as HandStrength + 1))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::cards::parse_board;
+
+    fn eval5(s: &str) -> HandStrength {
+        let cards = parse_board(s).unwrap();
+        HandEvaluator::new().evaluate_5cards(cards.try_into().unwrap())
+    }
+
+    #[test]
+    fn test_rank_table_size() {
+        assert_eq!(rank_table().len(), NUM_HAND_RANKS);
+    }
+
+    #[test]
+    fn test_known_ranks() {
+        assert_eq!(eval5("AsKsQsJsTs"), 1);
+        assert_eq!(eval5("5h4h3h2hAh"), 10); // steel wheel, worst straight flush
+        assert_eq!(eval5("AsAhAdAcKs"), 11); // best quads
+        assert_eq!(eval5("7s5h4d3c2s"), NUM_HAND_RANKS as HandStrength);
+    }
+
+    #[test]
+    fn test_category_order() {
+        let hands = [
+            "KsQsJsTs9s", // straight flush
+            "2s2h2d2cAs", // quads
+            "3s3h3dAsAh", // full house
+            "As9s7s5s3s", // flush
+            "AsKhQdJcTs", // straight
+            "5s4h3d2cAs", // wheel
+            "QsQhQd3c2s", // trips
+            "KsKhQdQc2s", // two pair
+            "AsAh4d3c2s", // pair
+            "AsKhQdJc9s", // high card
+        ];
+        let ranks: Vec<_> = hands.iter().map(|h| eval5(h)).collect();
+        assert!(ranks.windows(2).all(|w| w[0] < w[1]), "{:?}", ranks);
+    }
+
+    #[test]
+    fn test_seven_cards() {
+        let evaluator = HandEvaluator::new();
+        let board = parse_board("Ah Kd Qc 7s 2h").unwrap();
+        let hole = |s: &str| -> [Card; 2] { parse_board(s).unwrap().try_into().unwrap() };
+
+        let straight = evaluator.evaluate_hand(hole("JsTs"), &board);
+        let set = evaluator.evaluate_hand(hole("7h7d"), &board);
+        let top_pair = evaluator.evaluate_hand(hole("AsJd"), &board);
+        assert!(straight < set && set < top_pair);
+        assert_eq!(straight, eval5("AhKdQcJsTs"));
+
+        let mut cards = hole("JsTs").to_vec();
+        cards.extend(&board);
+        assert_eq!(evaluator.evaluate_best(&cards), straight);
+        assert_eq!(evaluator.evaluate_best(&cards[..6]), eval5("AhKdQcJsTs"));
+
+        // Board plays: both hands chop
+        let board = parse_board("As Ks Qs Js Ts").unwrap();
+        assert_eq!(
+            evaluator.evaluate_hand(hole("2c3d"), &board),
+            evaluator.evaluate_hand(hole("4h5h"), &board)
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! No-limit hold'em as a [`Game`]
+//!
+//! A [`GameTree`] with the two ranges at its root. The first chance event
+//! deals each player a combo, weighted by range and without shared cards;
+//! after that the game follows the tree, dealing turn and river cards
+//! neither hand holds. Info set keys are those of
+//! [`CFRSolver`](super::CFRSolver), so the two share regret and strategy
+//! tables.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::cards::{Card, Combo};
+use super::cfr::{history_key, info_set_key, range_hands, showdown_strengths};
+use super::game::{Game, Turn};
+use super::game_state::Action;
+use super::game_tree::{GameNode, GameTree};
+use super::hand_eval::{HandEvaluator, HandStrength};
+use super::rng::{sample_index, Rng};
+use super::utils::chips_to_bb;
+use crate::models::Player;
+
+/// Hold'em over a game tree
+pub struct HoldemGame<'a> {
+    tree: &'a GameTree,
+    /// Each player's combos with their range weight
+    hands: [Vec<(Combo, f64)>; 2],
+    evaluator: HandEvaluator,
+    /// Hand strengths by river board
+    strengths: RefCell<HashMap<Vec<Card>, [Vec<HandStrength>; 2]>>,
+}
+
+/// A tree node and the hands dealt, as indices into each player's combos
+#[derive(Debug, Clone, Copy)]
+pub struct HoldemState<'a> {
+    pub node: &'a GameNode,
+    pub dealt: Option<[usize; 2]>,
+}
+
+/// Dealing the hands or following one of a node's children
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldemAction {
+    Hands([usize; 2]),
+    Child(usize),
+}
+
+impl<'a> HoldemGame<'a> {
+    pub fn new(tree: &'a GameTree) -> Self {
+        HoldemGame {
+            tree,
+            hands: range_hands(&tree.root.state),
+            evaluator: HandEvaluator::new(),
+            strengths: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Combo dealt to `player`
+    ///
+    /// # Panics
+    ///
+    /// Before the hands are dealt.
+    pub fn combo(&self, state: &HoldemState, player: usize) -> Combo {
+        let dealt = state.dealt.expect("hands are dealt first");
+        self.hands[player][dealt[player]].0
+    }
+
+    /// Range weight of each player's combos, zero where the board blocks them
+    fn weights(&self) -> [Vec<f64>; 2] {
+        let board = &self.tree.root.state.board;
+        [0, 1].map(|p| {
+            self.hands[p]
+                .iter()
+                .map(|(combo, w)| if combo.is_blocked_by(board) { 0.0 } else { *w })
+                .collect()
+        })
+    }
+
+    fn clash(&self, dealt: [usize; 2]) -> bool {
+        self.hands[0][dealt[0]].0.is_blocked_by(&self.hands[1][dealt[1]].0.cards())
+    }
+
+    /// Children dealing a card neither hand holds
+    fn open_cards(&self, state: &HoldemState) -> Vec<usize> {
+        let held = [0, 1].map(|p| self.combo(state, p));
+        state
+            .node
+            .children
+            .iter()
+            .enumerate()
+            .filter(|(_, (action, _))| match action {
+                Action::Deal(card) => !held.iter().any(|combo| combo.is_blocked_by(&[*card])),
+                _ => false,
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+impl<'a> Game for HoldemGame<'a> {
+    type State = HoldemState<'a>;
+    type Action = HoldemAction;
+
+    fn root(&self) -> HoldemState<'a> {
+        HoldemState {
+            node: &self.tree.root,
+            dealt: None,
+        }
+    }
+
+    fn turn(&self, state: &HoldemState) -> Turn {
+        let node = state.node;
+        if state.dealt.is_none() || node.is_chance() {
+            Turn::Chance
+        } else if node.is_terminal || node.is_leaf {
+            Turn::Terminal
+        } else {
+            Turn::Player(node.state.to_act as usize)
+        }
+    }
+
+    fn actions(&self, state: &HoldemState) -> Vec<HoldemAction> {
+        (0..state.node.children.len()).map(HoldemAction::Child).collect()
+    }
+
+    fn chance_outcomes(&self, state: &HoldemState) -> Vec<(HoldemAction, f64)> {
+        if state.dealt.is_some() {
+            let open = self.open_cards(state);
+            let p = 1.0 / open.len() as f64;
+            return open.into_iter().map(|i| (HoldemAction::Child(i), p)).collect();
+        }
+
+        let weights = self.weights();
+        let mut outcomes = Vec::new();
+        for (i, w0) in weights[0].iter().enumerate().filter(|(_, &w)| w > 0.0) {
+            for (j, w1) in weights[1].iter().enumerate().filter(|(_, &w)| w > 0.0) {
+                if !self.clash([i, j]) {
+                    outcomes.push((HoldemAction::Hands([i, j]), w0 * w1));
+                }
+            }
+        }
+        let total: f64 = outcomes.iter().map(|(_, w)| w).sum();
+        for (_, w) in &mut outcomes {
+            *w /= total;
+        }
+        outcomes
+    }
+
+    /// Deals hands by rejection rather than listing every pair
+    fn sample_chance(&self, state: &HoldemState, rng: &mut Rng) -> Option<HoldemAction> {
+        if state.dealt.is_some() {
+            let open = self.open_cards(state);
+            return (!open.is_empty()).then(|| HoldemAction::Child(open[rng.below(open.len())]));
+        }
+
+        let weights = self.weights();
+        if weights.iter().any(|w| w.iter().all(|&w| w <= 0.0)) {
+            return None;
+        }
+        (0..1000)
+            .map(|_| [0, 1].map(|p| sample_index(&weights[p], rng)))
+            .find(|&dealt| !self.clash(dealt))
+            .map(HoldemAction::Hands)
+    }
+
+    fn apply(&self, state: &HoldemState<'a>, action: &HoldemAction) -> HoldemState<'a> {
+        match *action {
+            HoldemAction::Hands(dealt) => HoldemState {
+                dealt: Some(dealt),
+                ..*state
+            },
+            HoldemAction::Child(i) => HoldemState {
+                node: &state.node.children[i].1,
+                ..*state
+            },
+        }
+    }
+
+    fn info_set_key(&self, state: &HoldemState) -> String {
+        let p = state.node.state.to_act as usize;
+        info_set_key(&state.node.state, self.combo(state, p).id)
+    }
+
+    fn public_state(&self, state: &HoldemState) -> String {
+        history_key(&state.node.state)
+    }
+
+    /// Chips won back minus chips put in, in bb; the starting pot and rake
+    /// mean the two payoffs don't sum to zero
+    ///
+    /// # Panics
+    ///
+    /// At the leaves of depth-limited trees, which are valued over whole
+    /// ranges rather than single hands.
+    fn utility(&self, state: &HoldemState, player: usize) -> f64 {
+        let node = state.node;
+        assert!(!node.is_leaf, "hold'em games need a full tree");
+        let pot = chips_to_bb(node.state.pot);
+        let invested = node.state.put_in().map(chips_to_bb);
+        let rake = &node.state.rake;
+
+        if let Some(folder) = node.state.folded() {
+            return rake.fold_payoffs(pot, invested, folder, true)[player];
+        }
+        let dealt = state.dealt.expect("hands are dealt first");
+        let mut cache = self.strengths.borrow_mut();
+        let strengths = showdown_strengths(&mut cache, &self.hands, &self.evaluator, &node.state.board);
+        // Lower strengths are better hands
+        let winner = match strengths[0][dealt[0]].cmp(&strengths[1][dealt[1]]) {
+            std::cmp::Ordering::Less => Some(Player::OOP),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(Player::IP),
+        };
+        rake.showdown_payoffs(pot, invested, winner)[player]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BetSizes, SolveRequest};
+    use crate::solver::{CFRSolver, GameCfr, GameState};
+
+    fn tree(board: &str, oop_range: &str, ip_range: &str) -> GameTree {
+        let state = GameState::from_request(&SolveRequest {
+            player: Player::OOP,
+            board: board.to_string(),
+            effective_stack: 100.0,
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: 10.0,
+            bet_sizes: Some(BetSizes {
+                oop_bet: "a".to_string(),
+                oop_raise: "a".to_string(),
+                ip_bet: "a".to_string(),
+                ip_raise: "a".to_string(),
+            }),
+            root_bet: None,
+            betting_history: None,
+            oop_range: Some(oop_range.to_string()),
+            ip_range: Some(ip_range.to_string()),
+            rake: None,
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        })
+        .unwrap();
+        GameTree::build(state, 10)
+    }
+
+    #[test]
+    fn test_deals() {
+        let tree = tree("Ah Kd Qc 7s", "JsTs,AsAc", "AsQs");
+        let game = HoldemGame::new(&tree);
+        // AsAc clashes with AsQs, leaving one pair
+        let deals = game.chance_outcomes(&game.root());
+        assert_eq!(deals.len(), 1);
+        let dealt = game.apply(&game.root(), &deals[0].0);
+        assert_eq!(game.turn(&dealt), Turn::Player(0));
+        assert_eq!(game.combo(&dealt, 0).to_string(), "JsTs");
+        assert_eq!(game.info_set_key(&dealt), format!("|{}", game.combo(&dealt, 0).id));
+
+        // Check, check: 44 turn cards miss both hands
+        let turn = game.apply(&game.apply(&dealt, &HoldemAction::Child(0)), &HoldemAction::Child(0));
+        assert_eq!(game.turn(&turn), Turn::Chance);
+        assert_eq!(game.chance_outcomes(&turn).len(), 44);
+    }
+
+    #[test]
+    fn test_matches_cfr_solver() {
+        // Same keys and payoffs: the generic best response agrees with the
+        // vectorized one on CFRSolver's strategy
+        let mut solver = CFRSolver::new(tree("Ah Kd Qc 7s 2h", "JsTs,3d3c,AdKc", "AsQs,KsKh"));
+        solver.solve(50);
+        let mut cfr = GameCfr::new(HoldemGame::new(&solver.tree));
+        cfr.strategy_sum = solver.strategy_sum.clone();
+        let generic = cfr.exploitability();
+        let vectorized = solver.exploitability();
+        assert!((generic - vectorized).abs() < 1e-6, "{} vs {}", generic, vectorized);
+    }
+}
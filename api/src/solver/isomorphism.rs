@@ -0,0 +1,136 @@
+//! Suit isomorphism
+//!
+//! Spots that differ only by a relabelling of suits have the same solution
+//! up to that relabelling. A board's canonical permutation maps it to the
+//! smallest relabelled form (flop sorted, then turn and river in order), so
+//! isomorphic spots share one stored solve.
+
+use super::cards::{Card, Combo, Suit};
+
+/// A relabelling of the four suits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuitPermutation([Suit; 4]);
+
+impl SuitPermutation {
+    /// The permutation that leaves every suit unchanged
+    pub fn identity() -> Self {
+        SuitPermutation(Suit::all())
+    }
+
+    /// All 24 permutations
+    pub fn all() -> Vec<Self> {
+        let suits = Suit::all();
+        let mut result = Vec::with_capacity(24);
+        for a in 0..4 {
+            for b in (0..4).filter(|&b| b != a) {
+                for c in (0..4).filter(|&c| c != a && c != b) {
+                    let d = 6 - a - b - c;
+                    result.push(SuitPermutation([suits[a], suits[b], suits[c], suits[d]]));
+                }
+            }
+        }
+        result
+    }
+
+    /// Permutation mapping `board` to its canonical form
+    pub fn canonical_for(board: &[Card]) -> Self {
+        Self::all()
+            .into_iter()
+            .min_by_key(|p| p.canonical_key(board))
+            .unwrap()
+    }
+
+    /// Permutation mapping `board` to its canonical form, breaking ties
+    /// between suits the board does not distinguish by the hole cards
+    pub fn canonical_for_hand(board: &[Card], hole: &[Card]) -> Self {
+        Self::all()
+            .into_iter()
+            .min_by_key(|p| {
+                let mut hole: Vec<u8> = hole.iter().map(|&c| 51 - p.apply_card(c).value()).collect();
+                hole.sort_unstable();
+                (p.canonical_key(board), hole)
+            })
+            .unwrap()
+    }
+
+    /// Board relabelled by this permutation, with the flop sorted high to low
+    pub fn apply_board(&self, board: &[Card]) -> Vec<Card> {
+        let mut mapped: Vec<Card> = board.iter().map(|&c| self.apply_card(c)).collect();
+        let flop = mapped.len().min(3);
+        mapped[..flop].sort_by(|a, b| b.cmp(a));
+        mapped
+    }
+
+    /// Relabel one suit
+    pub fn apply_suit(&self, suit: Suit) -> Suit {
+        self.0[suit as usize]
+    }
+
+    /// Relabel one card
+    pub fn apply_card(&self, card: Card) -> Card {
+        Card::new(card.rank(), self.apply_suit(card.suit()))
+    }
+
+    /// Relabel a combo
+    pub fn apply_combo(&self, combo: &Combo) -> Combo {
+        Combo::from_cards(self.apply_card(combo.card1), self.apply_card(combo.card2))
+    }
+
+    /// The permutation undoing this one
+    pub fn inverse(&self) -> Self {
+        let mut inverse = Suit::all();
+        for suit in Suit::all() {
+            inverse[self.apply_suit(suit) as usize] = suit;
+        }
+        SuitPermutation(inverse)
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    /// Ordering key of the relabelled board (ties broken towards identity)
+    fn canonical_key(&self, board: &[Card]) -> Vec<u8> {
+        // Negate so that higher cards sort first within the key
+        self.apply_board(board).iter().map(|c| 51 - c.value()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::cards::parse_board;
+
+    #[test]
+    fn test_isomorphic_boards_share_canonical_form() {
+        let a = parse_board("Ah Kh 2c 9d").unwrap();
+        let b = parse_board("Ks As 2h 9c").unwrap();
+        let ca = SuitPermutation::canonical_for(&a).apply_board(&a);
+        let cb = SuitPermutation::canonical_for(&b).apply_board(&b);
+        assert_eq!(ca, cb);
+
+        // Different turn suit relationship is not isomorphic
+        let c = parse_board("Ah Kh 2c 9h").unwrap();
+        let cc = SuitPermutation::canonical_for(&c).apply_board(&c);
+        assert_ne!(ca, cc);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let board = parse_board("Qd 7c 3h").unwrap();
+        let perm = SuitPermutation::canonical_for(&board);
+        let combo: Combo = "AhKd".parse().unwrap();
+        assert_eq!(perm.inverse().apply_combo(&perm.apply_combo(&combo)), combo);
+        assert!(SuitPermutation::identity().is_identity());
+    }
+
+    #[test]
+    fn test_canonical_for_hand() {
+        // Spades and diamonds are interchangeable on this board; the hand decides
+        let board = parse_board("Ah Kh 2c").unwrap();
+        let a = SuitPermutation::canonical_for_hand(&board, &parse_board("Qs Jd").unwrap());
+        let b = SuitPermutation::canonical_for_hand(&board, &parse_board("Qd Js").unwrap());
+        assert_eq!(a.apply_board(&board), b.apply_board(&board));
+        assert_eq!(a.apply_card("Qs".parse().unwrap()), b.apply_card("Qd".parse().unwrap()));
+    }
+}
@@ -0,0 +1,211 @@
+//! Monte Carlo CFR
+//!
+//! Sampling variants of CFR that touch only part of the game per iteration,
+//! written against [`Game`] and used by both [`GameCfr`](super::GameCfr) and
+//! [`CFRSolver`](super::CFRSolver) (through
+//! [`HoldemGame`](super::holdem_game::HoldemGame)):
+//!
+//! - chance sampling walks every combo and action full width but deals one
+//!   turn and river card per chance node (see the CFR pass);
+//! - external sampling deals one hand to each player and one card per
+//!   chance node, samples the opponent's actions and explores all of the
+//!   traverser's;
+//! - outcome sampling follows a single sampled path, exploring the
+//!   traverser's actions with probability [`EXPLORATION`] and correcting by
+//!   importance weights.
+//!
+//! Sampled regrets are noisy, so they get rough strategies on big trees
+//! quickly rather than the exact ones full-width CFR+ converges to.
+
+use super::game::{Game, Turn};
+use super::game_cfr::Walk;
+use super::rng::sample_index;
+
+/// Share of outcome sampling's traverser choices made uniformly at random
+pub const EXPLORATION: f64 = 0.6;
+
+/// How a CFR iteration covers the tree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Sampling {
+    /// Every combo, action and card (CFR+)
+    #[default]
+    FullWidth,
+    /// Every combo and action, one card per chance node
+    Chance,
+    /// One hand per player; the traverser's actions in full, one of the
+    /// opponent's and one card per chance node
+    External,
+    /// One hand per player and a single path through the tree
+    Outcome,
+}
+
+impl<G: Game> Walk<'_, G> {
+    /// External sampling: the traverser's actions in full, one sampled
+    /// opponent action and chance outcome
+    pub fn external(&mut self, state: &G::State) -> f64 {
+        match self.game.turn(state) {
+            Turn::Terminal => self.game.utility(state, self.traverser),
+            Turn::Chance => {
+                let Some(outcome) = self.game.sample_chance(state, self.rng) else {
+                    return 0.0;
+                };
+                self.external(&self.game.apply(state, &outcome))
+            }
+            Turn::Player(p) => {
+                let key = self.game.info_set_key(state);
+                let actions = self.game.actions(state);
+                let n = actions.len();
+                let strategy = self.strategy(&key, n);
+
+                if p != self.traverser {
+                    // The opponent's strategy is averaged where their play is sampled
+                    let weight = self.iteration as f64;
+                    let sums = self.strategy_sum.entry(key).or_insert_with(|| vec![0.0; n]);
+                    for (sum, s) in sums.iter_mut().zip(&strategy) {
+                        *sum += weight * s;
+                    }
+                    let a = sample_index(&strategy, self.rng);
+                    return self.external(&self.game.apply(state, &actions[a]));
+                }
+
+                let values: Vec<f64> = actions
+                    .iter()
+                    .map(|action| self.external(&self.game.apply(state, action)))
+                    .collect();
+                let value: f64 = values.iter().zip(&strategy).map(|(v, s)| v * s).sum();
+                let regrets = self.regrets.entry(key).or_insert_with(|| vec![0.0; n]);
+                for (regret, v) in regrets.iter_mut().zip(&values) {
+                    *regret += v - value;
+                }
+                value
+            }
+        }
+    }
+
+    /// Outcome sampling along one path
+    ///
+    /// `reach` is the traverser's reach and `opponent_reach` the opponent's,
+    /// `sample` the probability of sampling the path so far. Returns the
+    /// importance-weighted utility and both players' reach from this state
+    /// to the sampled terminal.
+    pub fn outcome(&mut self, state: &G::State, reach: f64, opponent_reach: f64, sample: f64) -> (f64, f64) {
+        match self.game.turn(state) {
+            Turn::Terminal => (self.game.utility(state, self.traverser) / sample, 1.0),
+            Turn::Chance => {
+                // Chance is sampled with its own probabilities, which cancel out
+                let Some(outcome) = self.game.sample_chance(state, self.rng) else {
+                    return (0.0, 0.0);
+                };
+                self.outcome(&self.game.apply(state, &outcome), reach, opponent_reach, sample)
+            }
+            Turn::Player(p) => {
+                let key = self.game.info_set_key(state);
+                let actions = self.game.actions(state);
+                let n = actions.len();
+                let strategy = self.strategy(&key, n);
+
+                if p != self.traverser {
+                    let a = sample_index(&strategy, self.rng);
+                    let next = self.game.apply(state, &actions[a]);
+                    let (utility, tail) =
+                        self.outcome(&next, reach, opponent_reach * strategy[a], sample * strategy[a]);
+                    return (utility, tail * strategy[a]);
+                }
+
+                let explore: Vec<f64> = strategy
+                    .iter()
+                    .map(|s| EXPLORATION / n as f64 + (1.0 - EXPLORATION) * s)
+                    .collect();
+                let a = sample_index(&explore, self.rng);
+                let next = self.game.apply(state, &actions[a]);
+                let (utility, tail) = self.outcome(&next, reach * strategy[a], opponent_reach, sample * explore[a]);
+
+                let weight = utility * opponent_reach;
+                let regrets = self.regrets.entry(key.clone()).or_insert_with(|| vec![0.0; n]);
+                for (b, regret) in regrets.iter_mut().enumerate() {
+                    *regret += match b == a {
+                        true => weight * tail * (1.0 - strategy[a]),
+                        false => -weight * tail * strategy[a],
+                    };
+                }
+                let weight = self.iteration as f64 * reach / sample;
+                let sums = self.strategy_sum.entry(key).or_insert_with(|| vec![0.0; n]);
+                for (sum, s) in sums.iter_mut().zip(&strategy) {
+                    *sum += weight * s;
+                }
+                (utility, tail * strategy[a])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BetSizes, Player, SolveRequest};
+    use crate::solver::{CFRSolver, GameState, GameTree};
+
+    fn tree(board: &str, oop_range: &str, ip_range: &str) -> GameTree {
+        let state = GameState::from_request(&SolveRequest {
+            player: Player::OOP,
+            board: board.to_string(),
+            effective_stack: 100.0,
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: 10.0,
+            bet_sizes: Some(BetSizes {
+                oop_bet: "a".to_string(),
+                oop_raise: "a".to_string(),
+                ip_bet: "a".to_string(),
+                ip_raise: "a".to_string(),
+            }),
+            root_bet: None,
+            betting_history: None,
+            oop_range: Some(oop_range.to_string()),
+            ip_range: Some(ip_range.to_string()),
+            rake: None,
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        })
+        .unwrap();
+        GameTree::build(state, 10)
+    }
+
+    /// Exploitability in bb after `iterations` with a sampling scheme
+    fn exploitability(tree: GameTree, sampling: Sampling, iterations: usize) -> f64 {
+        let mut solver = CFRSolver::new(tree).with_sampling(sampling, 7);
+        solver.solve(iterations);
+        solver.exploitability()
+    }
+
+    #[test]
+    fn test_river_variants_converge() {
+        // Nuts or air against a bluff catcher; uniform play is over 1bb exploitable
+        for (sampling, iterations, target) in [
+            (Sampling::Chance, 300, 0.05),
+            (Sampling::External, 30000, 0.3),
+            (Sampling::Outcome, 20000, 0.75),
+        ] {
+            let solved = exploitability(tree("Ah Kd Qc 7s 2h", "JsTs,3d3c", "AsQs"), sampling, iterations);
+            assert!(solved < target, "{:?}: {}", sampling, solved);
+        }
+    }
+
+    #[test]
+    fn test_chance_sampling_deals_cards() {
+        // The turn is dealt: sampled cards stand in for all 46
+        let uniform = exploitability(tree("Ah Kd Qc 7s", "JsTs,3d3c", "AsQs"), Sampling::Chance, 0);
+        let solved = exploitability(tree("Ah Kd Qc 7s", "JsTs,3d3c", "AsQs"), Sampling::Chance, 3000);
+        assert!(solved < uniform / 10.0, "{} -> {}", uniform, solved);
+    }
+
+    #[test]
+    fn test_deal() {
+        let tree = tree("Ah Kd Qc 7s 2h", "AsAd,AsAc", "AcAd");
+        let mut solver = CFRSolver::new(tree).with_sampling(Sampling::External, 1);
+        // AsAd and AsAc both clash with AcAd: nothing can be dealt, nothing is learned
+        solver.solve(10);
+        assert!(solver.regret_sum.is_empty());
+    }
+}
@@ -1,15 +1,39 @@
-/// CFR Solver implementation for No-Limit Hold'em poker
-///
-/// This module contains the core poker solver using Counterfactual Regret Minimization (CFR)
-/// to compute Nash equilibrium strategies.
+//! CFR Solver implementation for No-Limit Hold'em poker
+//!
+//! This module contains the core poker solver using Counterfactual Regret Minimization (CFR)
+//! to compute Nash equilibrium strategies.
 
 pub mod cards;
 pub mod range;
 pub mod bet_sizing;
 pub mod game_state;
 pub mod hand_eval;
+pub mod hand_category;
 pub mod game_tree;
 pub mod cfr;
+pub mod checkpoint;
+pub mod mccfr;
+pub mod game;
+pub mod game_cfr;
+pub mod games;
+pub mod holdem_game;
+pub mod node_lock;
+pub mod warm_start;
+pub mod hand_class;
+pub mod isomorphism;
+pub mod equity;
+pub mod preflop;
+pub mod rake;
+pub mod solved_tree;
+pub mod bytes;
+pub mod strategy_file;
+pub mod texture;
+pub mod value_net;
+pub mod features;
+pub mod deep_cfr;
+pub mod datagen;
+pub mod trainer;
+pub mod rng;
 pub mod utils;
 
 // Re-export commonly used types
@@ -20,11 +44,36 @@ pub use game_state::{GameState, Street, Action};
 pub use hand_eval::{HandEvaluator, HandStrength};
 pub use game_tree::{GameTree, GameNode};
 pub use cfr::CFRSolver;
+pub use checkpoint::{Checkpoint, CheckpointConfig};
+pub use mccfr::Sampling;
+pub use game::{Game, Turn};
+pub use game_cfr::GameCfr;
+pub use holdem_game::HoldemGame;
+pub use deep_cfr::{DeepCfrConfig, DeepCfrSolver};
+pub use features::FeatureEncoder;
+pub use value_net::ValueNetwork;
+pub use datagen::{DataGenConfig, DatasetWriter, Sample};
+pub use trainer::TrainConfig;
+pub use rng::Rng;
+pub use node_lock::NodeLocks;
+pub use warm_start::SolverTables;
+pub use isomorphism::SuitPermutation;
+pub use rake::Rake;
+pub use texture::BoardTexture;
+pub use solved_tree::{SolvedNode, SolvedNodeKind, SolvedTree};
+pub use strategy_file::StrategyFile;
+pub use hand_class::{aggregate_by_class, class_weights, ComboResult, HandClass};
+pub use equity::EquityTable;
+pub use preflop::{PreflopConfig, PreflopSolver};
+pub use utils::{Chips, CHIPS_PER_BB};
+
+use std::sync::Arc;
 
 use crate::{
     error::AppError,
-    models::{SolveRequest, SolveResponse},
+    models::{ActionInfo, HandStrategy, SolveRequest, SolveResponse},
 };
+use utils::chips_to_bb;
 
 /// Solver configuration
 #[derive(Debug, Clone)]
@@ -33,6 +82,12 @@ pub struct SolverConfig {
     pub iterations: usize,
     /// Maximum tree depth (action limit)
     pub max_depth: usize,
+    pub algorithm: Algorithm,
+    /// How tabular CFR iterations cover the tree
+    pub sampling: Sampling,
+    /// Write tabular solves to a checkpoint file as they run, and resume
+    /// from it if it exists
+    pub checkpoint: Option<CheckpointConfig>,
 }
 
 impl Default for SolverConfig {
@@ -40,34 +95,550 @@ impl Default for SolverConfig {
         Self {
             iterations: 100,
             max_depth: 20,
+            algorithm: Algorithm::Tabular,
+            sampling: Sampling::FullWidth,
+            checkpoint: None,
         }
     }
 }
 
+/// How strategies are computed
+#[derive(Debug, Clone, Default)]
+pub enum Algorithm {
+    /// Full-width CFR+ with regrets stored per info set ([`CFRSolver`])
+    #[default]
+    Tabular,
+    /// Sampled traversals and neural regrets ([`DeepCfrSolver`]); nothing is
+    /// stored per node, so it scales to trees too big for the tabular solver
+    DeepCfr(DeepCfrConfig),
+}
+
+/// Called between chunks of a tabular solve with the iterations still to
+/// run; returning `false` stops the solve
+pub type ProgressFn = dyn Fn(&mut CFRSolver, usize) -> bool + Send + Sync;
+
 /// Main solver orchestrator
 pub struct Solver {
-    evaluator: HandEvaluator,
     config: SolverConfig,
+    /// Values at street boundaries; without it trees are built in full
+    value_network: Option<Arc<ValueNetwork>>,
+    /// SHA-256 of the network's weights file, naming it in `settings_key`
+    network_sha256: Option<String>,
+    /// Iterations between progress calls, and the callback
+    progress: Option<(usize, Arc<ProgressFn>)>,
 }
 
 impl Solver {
     /// Create a new solver with the given configuration
     pub fn new(config: SolverConfig) -> Self {
         Self {
-            evaluator: HandEvaluator::new(),
             config,
+            value_network: None,
+            network_sha256: None,
+            progress: None,
         }
     }
 
+    /// Stop trees at the next street and value them with a network
+    pub fn with_value_network(mut self, network: Arc<ValueNetwork>) -> Self {
+        self.value_network = Some(network);
+        self
+    }
+
+    /// Check if trees stop at the next street instead of being built in full
+    pub fn has_value_network(&self) -> bool {
+        self.value_network.is_some()
+    }
+
+    /// Name the value network by the SHA-256 of its weights file
+    pub fn with_network_sha256(mut self, sha256: String) -> Self {
+        self.network_sha256 = Some(sha256);
+        self
+    }
+
+    /// Everything besides the request that decides what a solve returns:
+    /// the iterations, depth, algorithm, sampling and value network
+    pub fn settings_key(&self) -> String {
+        let SolverConfig {
+            iterations,
+            max_depth,
+            algorithm,
+            sampling,
+            checkpoint: _,
+        } = &self.config;
+        let network = match (&self.value_network, &self.network_sha256) {
+            (None, _) => "none",
+            (Some(_), Some(sha256)) => sha256,
+            (Some(_), None) => "unnamed",
+        };
+        format!("{}|{}|{:?}|{:?}|{}", iterations, max_depth, algorithm, sampling, network)
+    }
+
+    /// Checkpoint tabular solves as they run, resuming from the file if it exists
+    pub fn with_checkpoint(mut self, checkpoint: CheckpointConfig) -> Self {
+        self.config.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Report on tabular solves every `every` iterations, stopping them
+    /// when the callback returns `false`
+    ///
+    /// Subgames past a depth-limited leaf are reported on the same way once
+    /// the request's tree is solved, each counting its own iterations. Only
+    /// the request's tree is checkpointed.
+    pub fn with_progress(mut self, every: usize, progress: Arc<ProgressFn>) -> Self {
+        self.progress = Some((every, progress));
+        self
+    }
+
     /// Solve a poker scenario and return the equilibrium strategy
-    pub fn solve(&self, _request: &SolveRequest) -> Result<SolveResponse, AppError> {
-        // TODO: Implement full solving pipeline
-        // 1. Parse request inputs
-        // 2. Build initial game state
-        // 3. Build game tree
-        // 4. Run CFR iterations
-        // 5. Extract and format strategies
-
-        Err(AppError::Internal("Solver not yet implemented".to_string()))
+    ///
+    /// With a value network the tabular tree stops where the next street is
+    /// dealt, so flop solves only hold the flop betting in memory. External
+    /// and outcome sampling, like Deep CFR, play every street out and ignore
+    /// the value network.
+    pub fn solve(&self, request: &SolveRequest) -> Result<SolveResponse, AppError> {
+        self.solve_from(request, None).map(|(response, _)| response)
+    }
+
+    /// Solve starting from the tables of a previous solve of a similar spot
+    ///
+    /// Also returns the solver of the request's tree, before any subgame
+    /// the betting history runs on into, for the solve's warm start tables
+    /// and strategy tree (tabular solves only; Deep CFR keeps neither and
+    /// can't be warm started).
+    pub fn solve_from(
+        &self,
+        request: &SolveRequest,
+        warm_start: Option<&SolverTables>,
+    ) -> Result<(SolveResponse, Option<CFRSolver>), AppError> {
+        let state = GameState::from_request(request).map_err(AppError::ValidationError)?;
+        let locks = match &request.node_locks {
+            Some(locks) => NodeLocks::from_request(locks, &state.board).map_err(AppError::ValidationError)?,
+            None => NodeLocks::default(),
+        };
+
+        let board = state.board.clone();
+        let history = request.betting_history.as_deref().unwrap_or_default();
+        let mut root = None;
+        let (node_state, actions, results) = match &self.config.algorithm {
+            Algorithm::Tabular => {
+                let network = match self.config.sampling {
+                    Sampling::External | Sampling::Outcome => None,
+                    _ => self.value_network.as_ref(),
+                };
+                let mut cfr = self.tabular_solver(state, network);
+                let mut past_leaves = cfr.lock_nodes(&locks).map_err(AppError::ValidationError)?;
+                // Iterations count on from the warm start's
+                let target = warm_start.map_or(0, |t| t.iteration) + self.config.iterations;
+                match &self.config.checkpoint {
+                    Some(checkpoint) if checkpoint.path.exists() => {
+                        // The checkpoint already holds any warm start
+                        let saved = Checkpoint::load(&checkpoint.path).map_err(AppError::Internal)?;
+                        cfr.restore(saved).map_err(AppError::ValidationError)?;
+                    }
+                    _ => {
+                        if let Some(tables) = warm_start {
+                            tables.warm_start(&mut cfr);
+                        }
+                    }
+                }
+                self.run(&mut cfr, target, self.config.checkpoint.as_ref())?;
+
+                // A history running past a depth-limited leaf continues in the
+                // next street's subgame, solved for the ranges that reach it
+                let mut history = history.to_vec();
+                let mut subgame: Option<CFRSolver> = None;
+                while let Some((next, rest, locks)) = subgame
+                    .as_ref()
+                    .unwrap_or(&cfr)
+                    .subgame_after(&history, &past_leaves)
+                    .map_err(AppError::ValidationError)?
+                {
+                    let mut next = self.tabular_solver(next, network);
+                    past_leaves = next.lock_nodes(&locks).map_err(AppError::ValidationError)?;
+                    self.run(&mut next, self.config.iterations, None)?;
+                    subgame = Some(next);
+                    history = rest;
+                }
+
+                let solved = subgame.as_mut().unwrap_or(&mut cfr);
+                let (node, results) = solved.node_result(&history).map_err(AppError::ValidationError)?;
+                let found = (node.state.clone(), node.actions(), results);
+                root = Some(cfr);
+                found
+            }
+            Algorithm::DeepCfr(config) => {
+                if warm_start.is_some() {
+                    return Err(AppError::ValidationError(
+                        "Deep CFR cannot be warm started".to_string(),
+                    ));
+                }
+                if !locks.nodes.is_empty() {
+                    return Err(AppError::ValidationError(
+                        "Node locks are not supported by Deep CFR".to_string(),
+                    ));
+                }
+                let mut deep = DeepCfrSolver::new(state, self.config.max_depth, config.clone())
+                    .map_err(AppError::ValidationError)?;
+                deep.solve(self.config.iterations).map_err(AppError::ValidationError)?;
+                deep.node_result(history).map_err(AppError::ValidationError)?
+            }
+        };
+        if node_state.to_act != request.player {
+            return Err(AppError::ValidationError(format!(
+                "{:?} is not the player to act at this node",
+                request.player
+            )));
+        }
+
+        let actions = action_frequencies(&node_state, &actions, &results);
+        let total_weight: f64 = results.iter().map(|r| r.weight).sum();
+        let ev = (total_weight > 0.0)
+            .then(|| results.iter().map(|r| r.weight * r.ev).sum::<f64>() / total_weight);
+        let response = SolveResponse {
+            player: request.player,
+            board: request.board.clone(),
+            pot: chips_to_bb(node_state.pot),
+            effective_stack: chips_to_bb(node_state.effective_stack()),
+            num_combos: results.len(),
+            actions,
+            combos: results
+                .iter()
+                .map(|r| HandStrategy {
+                    hand: r.combo.to_string(),
+                    hand_id: r.combo.id as u32,
+                    strategy: r.strategy.clone(),
+                })
+                .collect(),
+            ev,
+            hand_classes: request
+                .group_by_class
+                .then(|| aggregate_by_class(&results, &board)),
+            solve_id: None,
+        };
+        Ok((response, root))
+    }
+}
+
+/// Actions at a node with how often the range takes each, weighting combos
+/// by their reach
+pub fn action_frequencies(state: &GameState, actions: &[Action], results: &[ComboResult]) -> Vec<ActionInfo> {
+    let mut actions: Vec<_> = actions
+        .iter()
+        .filter_map(|a| a.to_action_info(state.pot))
+        .collect();
+    let total_weight: f64 = results.iter().map(|r| r.weight).sum();
+    if total_weight > 0.0 {
+        for (a, action) in actions.iter_mut().enumerate() {
+            action.frequency = results.iter().map(|r| r.weight * r.strategy[a]).sum::<f64>() / total_weight;
+        }
+    }
+    actions
+}
+
+impl Solver {
+    /// Tabular solver over the tree from `state`, which stops where the
+    /// next street is dealt if a network values it
+    fn tabular_solver(&self, state: GameState, network: Option<&Arc<ValueNetwork>>) -> CFRSolver {
+        let tree = match (network, state.street.next()) {
+            (Some(_), Some(next)) => GameTree::build_depth_limited(state, self.config.max_depth, next),
+            _ => GameTree::build(state, self.config.max_depth),
+        };
+        let cfr = CFRSolver::new(tree).with_sampling(self.config.sampling, 0);
+        match network {
+            Some(network) => cfr.with_value_network(network.clone()),
+            None => cfr,
+        }
+    }
+
+    /// Run a tabular solve to `target` iterations, in chunks between
+    /// checkpoints and progress reports
+    fn run(&self, cfr: &mut CFRSolver, target: usize, checkpoint: Option<&CheckpointConfig>) -> Result<(), AppError> {
+        let chunk = [
+            checkpoint.map(|c| c.every),
+            self.progress.as_ref().map(|&(every, _)| every),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(target);
+        while cfr.iteration < target {
+            cfr.solve(chunk.max(1).min(target - cfr.iteration));
+            if let Some(checkpoint) = checkpoint {
+                cfr.checkpoint().save(&checkpoint.path).map_err(AppError::Internal)?;
+            }
+            if let Some((_, progress)) = &self.progress {
+                let remaining = target.saturating_sub(cfr.iteration);
+                if !progress(cfr, remaining) {
+                    return Err(AppError::ServiceUnavailable("Solve was cancelled".to_string()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ActionType, BetSizes, HistoryAction, LockRule, NodeLock, Player};
+    use std::sync::Mutex;
+    use value_net::{Dense, INPUT_SIZE, OUTPUT_SIZE};
+
+    fn request(board: &str) -> SolveRequest {
+        SolveRequest {
+            player: Player::OOP,
+            board: board.to_string(),
+            effective_stack: 50.0,
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: 10.0,
+            bet_sizes: Some(BetSizes {
+                oop_bet: "50".to_string(),
+                oop_raise: "a".to_string(),
+                ip_bet: "50".to_string(),
+                ip_raise: "a".to_string(),
+            }),
+            root_bet: None,
+            betting_history: None,
+            oop_range: Some("AA,KK".to_string()),
+            ip_range: Some("QQ,JJ".to_string()),
+            rake: None,
+            node_locks: None,
+            group_by_class: true,
+            warm_start_from: None,
+        }
+    }
+
+    #[test]
+    fn test_solve_river() {
+        let config = SolverConfig {
+            iterations: 50,
+            max_depth: 4,
+            ..SolverConfig::default()
+        };
+        let response = Solver::new(config).solve(&request("Ah Kd 7c 5s 2h")).unwrap();
+        assert_eq!(response.num_combos, 6); // 3 AA + 3 KK
+        assert_eq!(response.actions.len(), 2); // check, bet 50%
+        let total: f64 = response.actions.iter().map(|a| a.frequency).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert_eq!(response.hand_classes.unwrap().len(), 2);
+
+        let mut req = request("Ah Kd 7c 5s 2h");
+        req.player = Player::IP;
+        assert!(Solver::new(SolverConfig::default()).solve(&req).is_err());
+    }
+
+    /// Network giving each player half the pot
+    fn half_pot_network() -> Arc<ValueNetwork> {
+        let mut output = Dense::zeros(1, OUTPUT_SIZE);
+        output.biases.fill(0.5);
+        Arc::new(ValueNetwork::new(vec![Dense::zeros(INPUT_SIZE, 1), output]).unwrap())
+    }
+
+    fn step(order: u32, position: Player, action: ActionType, card: Option<&str>) -> HistoryAction {
+        HistoryAction {
+            order,
+            position,
+            action,
+            amount_percent: None,
+            card: card.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_solve_flop_with_value_network() {
+        let solver = Solver::new(SolverConfig {
+            iterations: 20,
+            max_depth: 4,
+            ..SolverConfig::default()
+        })
+        .with_value_network(half_pot_network());
+
+        let response = solver.solve(&request("Ah Kd 7c")).unwrap();
+        assert_eq!(response.num_combos, 6);
+    }
+
+    #[test]
+    fn test_solve_past_depth_limited_leaf() {
+        let solver = Solver::new(SolverConfig {
+            iterations: 20,
+            max_depth: 4,
+            ..SolverConfig::default()
+        })
+        .with_value_network(half_pot_network());
+
+        // The flop tree stops at the turn; the turn is solved on its own
+        let mut req = request("Ah Kd 7c");
+        req.betting_history = Some(vec![
+            step(1, Player::OOP, ActionType::Check, None),
+            step(2, Player::IP, ActionType::Check, None),
+            step(3, Player::OOP, ActionType::Deal, Some("2c")),
+            step(4, Player::OOP, ActionType::Check, None),
+        ]);
+        req.player = Player::IP;
+        let response = solver.solve(&req).unwrap();
+        assert_eq!(response.num_combos, 12); // QQ and JJ
+        assert_eq!(response.actions.len(), 2);
+
+        // The turn card can't be one already dealt
+        req.betting_history.as_mut().unwrap()[2].card = Some("Kd".to_string());
+        assert!(solver.solve(&req).is_err());
+    }
+
+    #[test]
+    fn test_progress_in_subgames() {
+        // Root tree then turn subgame, 20 iterations each in chunks of 5
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let progress: Arc<ProgressFn> = {
+            let calls = Arc::clone(&calls);
+            Arc::new(move |_, remaining| {
+                calls.lock().unwrap().push(remaining);
+                remaining > 0 || calls.lock().unwrap().len() < 8
+            })
+        };
+        let solver = Solver::new(SolverConfig {
+            iterations: 20,
+            max_depth: 4,
+            ..SolverConfig::default()
+        })
+        .with_value_network(half_pot_network())
+        .with_progress(5, progress);
+
+        let mut req = request("Ah Kd 7c");
+        req.betting_history = Some(vec![
+            step(1, Player::OOP, ActionType::Check, None),
+            step(2, Player::IP, ActionType::Check, None),
+            step(3, Player::OOP, ActionType::Deal, Some("2c")),
+            step(4, Player::OOP, ActionType::Check, None),
+        ]);
+        req.player = Player::IP;
+
+        // Cancelled at the end of the subgame
+        assert!(matches!(solver.solve(&req), Err(AppError::ServiceUnavailable(_))));
+        assert_eq!(*calls.lock().unwrap(), [15, 10, 5, 0, 15, 10, 5, 0]);
+    }
+
+    #[test]
+    fn test_lock_past_depth_limited_leaf() {
+        let solver = Solver::new(SolverConfig {
+            iterations: 20,
+            max_depth: 4,
+            ..SolverConfig::default()
+        })
+        .with_value_network(half_pot_network());
+
+        // IP is locked into checking back the 2c turn, which is solved as a
+        // subgame of its own
+        let history = vec![
+            step(1, Player::OOP, ActionType::Check, None),
+            step(2, Player::IP, ActionType::Check, None),
+            step(3, Player::OOP, ActionType::Deal, Some("2c")),
+            step(4, Player::OOP, ActionType::Check, None),
+        ];
+        let mut req = request("Ah Kd 7c");
+        req.player = Player::IP;
+        req.betting_history = Some(history.clone());
+        req.node_locks = Some(vec![NodeLock {
+            betting_history: history.clone(),
+            combos: vec![],
+            rules: vec![LockRule {
+                hands: String::new(),
+                category: None,
+                action: ActionType::Check,
+                amount_percent: None,
+                frequency: 1.0,
+            }],
+        }]);
+        let response = solver.solve(&req).unwrap();
+        assert!((response.actions[0].frequency - 1.0).abs() < 1e-9);
+
+        // A lock on another turn is never solved
+        req.node_locks.as_mut().unwrap()[0].betting_history[2].card = Some("3c".to_string());
+        let err = solver.solve(&req).unwrap_err();
+        assert!(err.to_string().contains("off the line of the betting history"), "{}", err);
+    }
+
+    #[test]
+    fn test_solve_external_sampling() {
+        let config = SolverConfig {
+            iterations: 2000,
+            sampling: Sampling::External,
+            ..SolverConfig::default()
+        };
+        let response = Solver::new(config).solve(&request("Ah Kd 7c 5s 2h")).unwrap();
+        assert_eq!(response.num_combos, 6);
+        let total: f64 = response.actions.iter().map(|a| a.frequency).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_solve_from_previous() {
+        let solver = Solver::new(SolverConfig {
+            iterations: 50,
+            max_depth: 4,
+            ..SolverConfig::default()
+        });
+        let (_, cfr) = solver.solve_from(&request("Ah Kd 7c 5s 2h"), None).unwrap();
+        let tables = SolverTables::capture(&cfr.unwrap());
+        assert_eq!(tables.iteration, 50);
+
+        let mut req = request("Ah Kd 7c 5s 2h");
+        req.bet_sizes.as_mut().unwrap().oop_bet = "60".to_string();
+        let (response, cfr) = solver.solve_from(&req, Some(&tables)).unwrap();
+        assert_eq!(response.num_combos, 6);
+        assert_eq!(cfr.unwrap().iteration, 100);
+    }
+
+    #[test]
+    fn test_solve_resumes_from_checkpoint() {
+        let path = std::env::temp_dir().join(format!("solver-checkpoint-{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = |iterations| SolverConfig {
+            iterations,
+            max_depth: 4,
+            sampling: Sampling::External,
+            checkpoint: Some(CheckpointConfig {
+                path: path.clone(),
+                every: 7,
+            }),
+            ..SolverConfig::default()
+        };
+        let req = request("Ah Kd 7c 5s 2h");
+
+        // Stopped after 20 of 40 iterations, then resumed
+        Solver::new(config(20)).solve(&req).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap().iteration, 20);
+        let (resumed, _) = Solver::new(config(40)).solve_from(&req, None).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap().iteration, 40);
+        std::fs::remove_file(&path).unwrap();
+
+        let (uninterrupted, _) = Solver::new(config(40)).solve_from(&req, None).unwrap();
+        let strategies = |r: &SolveResponse| r.combos.iter().map(|c| c.strategy.clone()).collect::<Vec<_>>();
+        assert_eq!(strategies(&resumed), strategies(&uninterrupted));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_solve_deep_cfr() {
+        let config = SolverConfig {
+            iterations: 2,
+            max_depth: 2,
+            algorithm: Algorithm::DeepCfr(DeepCfrConfig {
+                traversals: 20,
+                hidden: vec![8],
+                train_steps: 20,
+                batch_size: 16,
+                ev_samples: 2,
+                ..DeepCfrConfig::default()
+            }),
+            ..SolverConfig::default()
+        };
+        let response = Solver::new(config).solve(&request("Ah Kd 7c 5s 2h")).unwrap();
+        assert_eq!(response.num_combos, 6);
+        assert_eq!(response.actions.len(), 2);
+        let total: f64 = response.actions.iter().map(|a| a.frequency).sum();
+        assert!((total - 1.0).abs() < 1e-9);
     }
 }
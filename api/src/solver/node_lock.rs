@@ -0,0 +1,394 @@
+//! Node locking: fixing a player's strategy at chosen nodes
+//!
+//! Locks are given either as explicit per-combo action probabilities or as
+//! rules locking one action's frequency for a hand group (Pio range syntax),
+//! optionally narrowed to a made-hand category on the node's board.
+//! Locked frequencies are enforced during CFR, so the opponent converges to
+//! a best adjustment around them.
+
+use std::collections::HashMap;
+
+use super::cards::{Card, Combo};
+use super::game_state::{find_action, Action};
+use super::hand_category::hand_category;
+use super::hand_eval::HandEvaluator;
+use super::range::Range;
+use super::utils::Chips;
+use crate::models::{ActionType, HistoryAction, NodeLock};
+
+/// Strategy of a locked combo at a node: `Some(p)` = fixed probability,
+/// `None` = solved freely
+pub type LockedStrategy = Vec<Option<f64>>;
+
+/// Tolerance for strategy sums
+const EPSILON: f64 = 1e-6;
+
+/// Lock on a single combo before it is matched against the node's actions
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComboConstraint {
+    /// Full strategy in the node's action order
+    Strategy(Vec<f64>),
+    /// Fixed frequencies for some actions: (type, percent of pot, frequency)
+    Actions(Vec<(ActionType, Option<f64>, f64)>),
+}
+
+impl ComboConstraint {
+    /// Match the constraint against the actions available at a node
+    pub fn resolve(&self, actions: &[Action], pot: Chips, to_call: Chips) -> Result<LockedStrategy, String> {
+        match self {
+            ComboConstraint::Strategy(strategy) => {
+                if strategy.len() != actions.len() {
+                    return Err(format!(
+                        "Locked strategy has {} entries but the node has {} actions",
+                        strategy.len(),
+                        actions.len()
+                    ));
+                }
+                Ok(strategy.iter().map(|&p| Some(p)).collect())
+            }
+            ComboConstraint::Actions(rules) => {
+                let mut locked = vec![None; actions.len()];
+                for (action_type, amount_percent, frequency) in rules {
+                    let index = find_action(actions, action_type, *amount_percent, None, pot, to_call)
+                        .ok_or_else(|| {
+                            format!(
+                                "Locked action {:?} is not available at this node",
+                                action_type
+                            )
+                        })?;
+                    locked[index] = Some(*frequency);
+                }
+
+                let total: f64 = locked.iter().flatten().sum();
+                if total > 1.0 + EPSILON {
+                    return Err(format!("Locked frequencies sum to {:.3} (> 1.0)", total));
+                }
+                Ok(locked)
+            }
+        }
+    }
+}
+
+/// Locks for all combos at one node
+#[derive(Debug, Clone)]
+pub struct LockedNode {
+    /// Betting actions leading to the node
+    pub history: Vec<HistoryAction>,
+    /// Constraint per combo ID
+    pub combos: HashMap<u16, ComboConstraint>,
+}
+
+/// All node locks of a solve
+#[derive(Debug, Clone, Default)]
+pub struct NodeLocks {
+    pub nodes: Vec<LockedNode>,
+}
+
+impl NodeLocks {
+    /// Parse and validate node locks from the API model
+    pub fn from_request(locks: &[NodeLock], board: &[Card]) -> Result<Self, String> {
+        let nodes = locks
+            .iter()
+            .map(|lock| parse_node_lock(lock, board))
+            .collect::<Result<_, _>>()?;
+        Ok(NodeLocks { nodes })
+    }
+
+    /// Check if there are no locks
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+/// Parse a single node lock
+fn parse_node_lock(lock: &NodeLock, board: &[Card]) -> Result<LockedNode, String> {
+    let mut combos: HashMap<u16, ComboConstraint> = HashMap::new();
+    let board = node_board(lock, board)?;
+    let board = board.as_slice();
+    let evaluator = HandEvaluator::new();
+
+    // Rules first, in order: later rules override earlier ones for the same action
+    for rule in &lock.rules {
+        if !(0.0..=1.0).contains(&rule.frequency) {
+            return Err(format!(
+                "Lock frequency must be 0.0-1.0, got {}",
+                rule.frequency
+            ));
+        }
+        if matches!(rule.action, ActionType::Bet | ActionType::Raise)
+            && rule.amount_percent.is_none()
+        {
+            return Err(format!(
+                "Lock rule for '{}' needs amount_percent",
+                rule.hands
+            ));
+        }
+        if matches!(rule.action, ActionType::Deal) {
+            return Err("Deal actions cannot be locked".to_string());
+        }
+
+        let range = match rule.hands.trim() {
+            "" => Range::full(),
+            hands => Range::parse(hands)?,
+        };
+        let range = range.filter_blocked(board);
+        for (combo_id, _) in range.get_combos() {
+            if let Some(category) = rule.category {
+                let combo = Combo::from_id(combo_id).ok_or("Invalid combo ID")?;
+                if hand_category(&evaluator, combo.cards(), board) != category {
+                    continue;
+                }
+            }
+            let entry = combos
+                .entry(combo_id)
+                .or_insert_with(|| ComboConstraint::Actions(Vec::new()));
+            if let ComboConstraint::Actions(rules) = entry {
+                rules.retain(|(action, pct, _)| {
+                    !(*action == rule.action && *pct == rule.amount_percent)
+                });
+                rules.push((rule.action.clone(), rule.amount_percent, rule.frequency));
+            }
+        }
+    }
+
+    // Explicit combo strategies take precedence over rules
+    for combo_lock in &lock.combos {
+        let combo: Combo = combo_lock.hand.parse()?;
+        if combo.is_blocked_by(board) {
+            return Err(format!(
+                "Locked hand '{}' is blocked by the board",
+                combo_lock.hand
+            ));
+        }
+        if combo_lock
+            .strategy
+            .iter()
+            .any(|&p| !(0.0..=1.0).contains(&p))
+        {
+            return Err(format!(
+                "Locked strategy for '{}' has probabilities outside 0.0-1.0",
+                combo_lock.hand
+            ));
+        }
+        let total: f64 = combo_lock.strategy.iter().sum();
+        if (total - 1.0).abs() > EPSILON {
+            return Err(format!(
+                "Locked strategy for '{}' sums to {:.3} (expected 1.0)",
+                combo_lock.hand, total
+            ));
+        }
+        combos.insert(
+            combo.id,
+            ComboConstraint::Strategy(combo_lock.strategy.clone()),
+        );
+    }
+
+    Ok(LockedNode {
+        history: lock.betting_history.clone(),
+        combos,
+    })
+}
+
+/// Board at a locked node: the solve's board plus any cards dealt on the way
+fn node_board(lock: &NodeLock, board: &[Card]) -> Result<Vec<Card>, String> {
+    let mut board = board.to_vec();
+    for action in &lock.betting_history {
+        if let (ActionType::Deal, Some(card)) = (&action.action, &action.card) {
+            board.push(card.parse()?);
+        }
+    }
+    Ok(board)
+}
+
+/// Combine a solver strategy with a lock
+///
+/// Locked actions keep their fixed probability; the remaining mass is
+/// spread over the free actions in proportion to `strategy`.
+pub fn apply_lock(strategy: &[f64], lock: &[Option<f64>]) -> Vec<f64> {
+    let locked_total: f64 = lock.iter().flatten().sum();
+    let free_total: f64 = strategy
+        .iter()
+        .zip(lock)
+        .filter(|(_, l)| l.is_none())
+        .map(|(p, _)| p)
+        .sum();
+    let num_free = lock.iter().filter(|l| l.is_none()).count();
+
+    // Nothing left to solve: renormalise the locked frequencies
+    if num_free == 0 {
+        return lock
+            .iter()
+            .map(|l| {
+                if locked_total > 0.0 {
+                    l.unwrap_or(0.0) / locked_total
+                } else {
+                    1.0 / lock.len() as f64
+                }
+            })
+            .collect();
+    }
+
+    let remaining = (1.0 - locked_total).max(0.0);
+    strategy
+        .iter()
+        .zip(lock)
+        .map(|(&p, l)| match l {
+            Some(fixed) => *fixed,
+            None if free_total > 0.0 => remaining * p / free_total,
+            None => remaining / num_free as f64,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ComboLock, HandCategory, LockRule, Player};
+    use crate::solver::cards::parse_board;
+
+    fn rule(hands: &str, action: ActionType, pct: Option<f64>, frequency: f64) -> LockRule {
+        LockRule {
+            hands: hands.to_string(),
+            category: None,
+            action,
+            amount_percent: pct,
+            frequency,
+        }
+    }
+
+    #[test]
+    fn test_rule_lock() {
+        let board = parse_board("Ah Kd Qc").unwrap();
+        let lock = NodeLock {
+            betting_history: vec![],
+            combos: vec![],
+            rules: vec![rule("AA", ActionType::Check, None, 1.0)],
+        };
+
+        let locks = NodeLocks::from_request(&[lock], &board).unwrap();
+        assert_eq!(locks.nodes.len(), 1);
+        // Ah on board leaves 3 AA combos
+        assert_eq!(locks.nodes[0].combos.len(), 3);
+    }
+
+    #[test]
+    fn test_category_rule() {
+        // Always check top pair, on the turn after check, check
+        let board = parse_board("Ah Kd 7c").unwrap();
+        let history_action = |order, position, action, card: Option<&str>| HistoryAction {
+            order,
+            position,
+            action,
+            amount_percent: None,
+            card: card.map(str::to_string),
+        };
+        let lock = NodeLock {
+            betting_history: vec![
+                history_action(1, Player::OOP, ActionType::Check, None),
+                history_action(2, Player::IP, ActionType::Check, None),
+                history_action(3, Player::OOP, ActionType::Deal, Some("Qs")),
+            ],
+            combos: vec![],
+            rules: vec![LockRule {
+                category: Some(HandCategory::TopPair),
+                ..rule("", ActionType::Check, None, 1.0)
+            }],
+        };
+
+        let locks = NodeLocks::from_request(&[lock], &board).unwrap();
+        let locked = &locks.nodes[0].combos;
+        let combo = |hand: &str| hand.parse::<Combo>().unwrap().id;
+        assert!(locked.contains_key(&combo("AsJd")));
+        assert!(!locked.contains_key(&combo("KsJd")));
+        // The dealt Qs blocks AsQs, and AQ now makes two pair
+        assert!(!locked.contains_key(&combo("AsQs")));
+        assert!(!locked.contains_key(&combo("AdQd")));
+        assert!(locked.values().all(|c| *c == ComboConstraint::Actions(vec![(ActionType::Check, None, 1.0)])));
+    }
+
+    #[test]
+    fn test_combo_lock_overrides_rules() {
+        let board = parse_board("Ah Kd Qc").unwrap();
+        let lock = NodeLock {
+            betting_history: vec![],
+            combos: vec![ComboLock {
+                hand: "AsAd".to_string(),
+                strategy: vec![0.5, 0.5],
+            }],
+            rules: vec![rule("AA", ActionType::Check, None, 1.0)],
+        };
+
+        let locks = NodeLocks::from_request(&[lock], &board).unwrap();
+        let combo: Combo = "AsAd".parse().unwrap();
+        assert_eq!(
+            locks.nodes[0].combos[&combo.id],
+            ComboConstraint::Strategy(vec![0.5, 0.5])
+        );
+    }
+
+    #[test]
+    fn test_invalid_locks() {
+        let board = parse_board("Ah Kd Qc").unwrap();
+        let blocked = NodeLock {
+            betting_history: vec![],
+            combos: vec![ComboLock {
+                hand: "AhAd".to_string(),
+                strategy: vec![1.0],
+            }],
+            rules: vec![],
+        };
+        assert!(NodeLocks::from_request(&[blocked], &board).is_err());
+
+        let bad_sum = NodeLock {
+            betting_history: vec![],
+            combos: vec![ComboLock {
+                hand: "AsAd".to_string(),
+                strategy: vec![0.5, 0.2],
+            }],
+            rules: vec![],
+        };
+        assert!(NodeLocks::from_request(&[bad_sum], &board).is_err());
+
+        let missing_size = NodeLock {
+            betting_history: vec![],
+            combos: vec![],
+            rules: vec![rule("AA", ActionType::Bet, None, 1.0)],
+        };
+        assert!(NodeLocks::from_request(&[missing_size], &board).is_err());
+    }
+
+    #[test]
+    fn test_resolve_constraint() {
+        let actions = vec![
+            Action::Check,
+            Action::Bet(7),
+            Action::Bet(13),
+            Action::AllIn(100),
+        ];
+        let constraint = ComboConstraint::Actions(vec![
+            (ActionType::Check, None, 0.5),
+            (ActionType::Bet, Some(67.0), 0.25),
+        ]);
+
+        let locked = constraint.resolve(&actions, 2000, 0).unwrap();
+        assert_eq!(locked, vec![Some(0.5), None, Some(0.25), None]);
+
+        let too_long = ComboConstraint::Strategy(vec![0.5, 0.5]);
+        assert!(too_long.resolve(&actions, 2000, 0).is_err());
+    }
+
+    #[test]
+    fn test_apply_lock() {
+        let strategy = vec![0.1, 0.6, 0.3];
+
+        // Locked check at 50%, rest split 2:1
+        let result = apply_lock(&strategy, &[Some(0.5), None, None]);
+        assert!((result[0] - 0.5).abs() < 1e-9);
+        assert!((result[1] - 1.0 / 3.0).abs() < 1e-9);
+        assert!((result[2] - 1.0 / 6.0).abs() < 1e-9);
+
+        // Fully locked
+        let result = apply_lock(&strategy, &[Some(1.0), Some(0.0), Some(0.0)]);
+        assert_eq!(result, vec![1.0, 0.0, 0.0]);
+    }
+}
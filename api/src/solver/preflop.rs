@@ -0,0 +1,792 @@
+//! Heads-up preflop game model
+//!
+//! Preflop trees are solved over the 169 hand classes with CFR+. Showdown
+//! terminals use a precomputed class-vs-class equity table, so a call that
+//! closes the action is valued as if the caller realised all of its equity,
+//! and card removal between the two hands is ignored.
+
+use super::cfr::regret_matching;
+use super::equity::EquityTable;
+use super::game_state::Action;
+use super::hand_class::{class_weights, HandClass, NUM_HAND_CLASSES};
+use super::rake::Rake;
+use super::range::Range;
+use super::utils::{bb_to_chips, chips_to_bb, parse_bb_amount, Chips, MAX_BB};
+use crate::models::{
+    ActionInfo, ActionTypeResponse, HandClassStrategy, Player, PreflopPosition, PreflopSizes,
+    PreflopSolveRequest, PreflopSolveResponse,
+};
+
+/// Player index of the small blind
+const SB: usize = 0;
+/// Player index of the big blind
+const BB: usize = 1;
+
+/// A preflop raise size
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreflopSize {
+    /// Raise to a fixed amount
+    Chips(Chips),
+    /// Raise to a multiple of the current bet (e.g. 3x)
+    Multiple(f64),
+    /// All-in
+    AllIn,
+}
+
+/// Parse a preflop size string like "2.5, 3x, a"
+fn parse_preflop_sizes(s: &str) -> Result<Vec<PreflopSize>, String> {
+    let mut sizes = Vec::new();
+
+    for token in s.split(',') {
+        let token = token.trim();
+        if token.is_empty() {
+            continue;
+        }
+
+        let size = if token.eq_ignore_ascii_case("a") || token.eq_ignore_ascii_case("allin") {
+            PreflopSize::AllIn
+        } else if let Some(multiple) = token.strip_suffix(['x', 'X']) {
+            let value = multiple
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid raise multiple: '{}'", token))?;
+            if value <= 1.0 {
+                return Err(format!("Raise multiple must be greater than 1, got {}", token));
+            }
+            PreflopSize::Multiple(value)
+        } else {
+            let value = token
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid raise size: '{}' (expected bb, 'x' multiple or 'a')", token))?;
+            PreflopSize::Chips(parse_bb_amount(value, "Raise size")?)
+        };
+        sizes.push(size);
+    }
+
+    if sizes.is_empty() {
+        return Err("Raise size string cannot be empty".to_string());
+    }
+
+    Ok(sizes)
+}
+
+/// Preflop game configuration
+#[derive(Debug, Clone)]
+pub struct PreflopConfig {
+    /// Starting stack of each player
+    pub stack: Chips,
+    pub small_blind: Chips,
+    pub big_blind: Chips,
+    /// Ante posted by each player
+    pub ante: Chips,
+    /// Button straddle posted by the SB instead of the small blind
+    pub straddle: Option<Chips>,
+    /// Raise sizes for open, 3-bet, 4-bet and 5-bet+
+    pub sizes: [Vec<PreflopSize>; 4],
+    /// Fraction of the stack at which raises become all-in
+    pub allin_threshold: f64,
+    pub rake: Rake,
+}
+
+impl PreflopConfig {
+    /// Parse the preflop configuration from API model
+    pub fn from_request(request: &PreflopSolveRequest) -> Result<Self, String> {
+        let stack = parse_bb_amount(request.effective_stack, "Effective stack")?;
+        let small_blind = parse_bb_amount(request.small_blind, "Small blind")?;
+        let big_blind = parse_bb_amount(request.big_blind, "Big blind")?;
+        if small_blind > big_blind {
+            return Err("Small blind cannot be larger than the big blind".to_string());
+        }
+
+        let ante = match request.ante {
+            ante if ante < 0.0 || !ante.is_finite() => {
+                return Err(format!("Ante must be non-negative, got {}", ante));
+            }
+            ante if ante > MAX_BB => {
+                return Err(format!("Ante must be at most {}bb, got {}", MAX_BB, ante));
+            }
+            ante => bb_to_chips(ante),
+        };
+
+        let straddle = request
+            .straddle
+            .map(|s| parse_bb_amount(s, "Straddle"))
+            .transpose()?;
+        if straddle.is_some_and(|s| s <= big_blind) {
+            return Err("Straddle must be larger than the big blind".to_string());
+        }
+
+        let largest_post = straddle.unwrap_or(big_blind);
+        if stack <= ante + largest_post {
+            return Err("Stacks must be larger than the blinds and antes".to_string());
+        }
+
+        if !(request.allin_threshold > 0.0 && request.allin_threshold <= 100.0) {
+            return Err(format!(
+                "All-in threshold must be in (0, 100], got {}",
+                request.allin_threshold
+            ));
+        }
+
+        let sizes = request.sizes.clone().unwrap_or_default();
+        let PreflopSizes {
+            open,
+            three_bet,
+            four_bet,
+            five_bet,
+        } = &sizes;
+
+        let rake = match &request.rake {
+            Some(config) => Rake::from_config(config)?,
+            None => Rake::none(),
+        };
+
+        Ok(PreflopConfig {
+            stack,
+            small_blind,
+            big_blind,
+            ante,
+            straddle,
+            sizes: [
+                parse_preflop_sizes(open)?,
+                parse_preflop_sizes(three_bet)?,
+                parse_preflop_sizes(four_bet)?,
+                parse_preflop_sizes(five_bet)?,
+            ],
+            allin_threshold: request.allin_threshold / 100.0,
+            rake,
+        })
+    }
+
+    /// Most a player can bet in total (stack behind the ante)
+    fn max_bet(&self) -> Chips {
+        self.stack - self.ante
+    }
+}
+
+/// Kind of a preflop tree node
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreflopNodeKind {
+    /// Player to act (0 = SB, 1 = BB)
+    Decision(usize),
+    /// A player folded
+    Fold(usize),
+    /// Action closed; hands are compared by equity
+    Showdown,
+}
+
+/// A node in the preflop tree
+#[derive(Debug, Clone)]
+pub struct PreflopNode {
+    pub kind: PreflopNodeKind,
+    /// Total chips put in by each player (blinds, antes and bets)
+    pub invested: [Chips; 2],
+    pub actions: Vec<Action>,
+    pub children: Vec<usize>,
+}
+
+impl PreflopNode {
+    /// Pot size at this node
+    pub fn pot(&self) -> Chips {
+        self.invested[0] + self.invested[1]
+    }
+}
+
+/// Betting state while building the tree
+#[derive(Debug, Clone)]
+struct BettingState {
+    bets: [Chips; 2],
+    to_act: usize,
+    acted: [bool; 2],
+    raises: usize,
+}
+
+/// Preflop game tree (node 0 is the root)
+#[derive(Debug, Clone)]
+pub struct PreflopTree {
+    pub nodes: Vec<PreflopNode>,
+}
+
+impl PreflopTree {
+    /// Build the tree for a configuration
+    pub fn build(config: &PreflopConfig) -> Self {
+        let max_bet = config.max_bet();
+        let state = BettingState {
+            bets: [
+                config.straddle.unwrap_or(config.small_blind).min(max_bet),
+                config.big_blind.min(max_bet),
+            ],
+            // With a button straddle the BB acts first
+            to_act: if config.straddle.is_some() { BB } else { SB },
+            acted: [false, false],
+            raises: 0,
+        };
+
+        let mut tree = PreflopTree { nodes: Vec::new() };
+        tree.add_decision(config, state);
+        tree
+    }
+
+    /// Follow a path like "r2.5/r7.5/c" from the root
+    pub fn find(&self, path: &str) -> Result<usize, String> {
+        let steps = self.find_steps(path)?;
+        Ok(steps
+            .last()
+            .map(|&(node, action)| self.nodes[node].children[action])
+            .unwrap_or(0))
+    }
+
+    /// Follow a path from the root, returning each (node, action index) taken
+    pub fn find_steps(&self, path: &str) -> Result<Vec<(usize, usize)>, String> {
+        let mut node = 0;
+        let mut steps = Vec::new();
+
+        for token in path.split('/').map(str::trim).filter(|t| !t.is_empty()) {
+            let actions = &self.nodes[node].actions;
+            let index = match token {
+                "f" => actions.iter().position(|a| *a == Action::Fold),
+                "x" => actions.iter().position(|a| *a == Action::Check),
+                "c" => actions.iter().position(|a| *a == Action::Call),
+                "a" => actions.iter().position(|a| matches!(a, Action::AllIn(_))),
+                _ => {
+                    let amount = token
+                        .strip_prefix('r')
+                        .and_then(|bb| bb.parse::<f64>().ok())
+                        .ok_or_else(|| format!("Invalid action '{}' in path", token))?;
+                    let target = bb_to_chips(amount);
+                    actions.iter().position(|a| {
+                        matches!(a, Action::Raise(to) | Action::AllIn(to) if *to == target)
+                    })
+                }
+            }
+            .ok_or_else(|| format!("Action '{}' is not available in the tree", token))?;
+
+            steps.push((node, index));
+            node = self.nodes[node].children[index];
+        }
+
+        Ok(steps)
+    }
+
+    fn add_node(&mut self, kind: PreflopNodeKind, invested: [Chips; 2]) -> usize {
+        self.nodes.push(PreflopNode {
+            kind,
+            invested,
+            actions: Vec::new(),
+            children: Vec::new(),
+        });
+        self.nodes.len() - 1
+    }
+
+    fn add_decision(&mut self, config: &PreflopConfig, state: BettingState) -> usize {
+        let invested = [state.bets[0] + config.ante, state.bets[1] + config.ante];
+        let player = state.to_act;
+        let index = self.add_node(PreflopNodeKind::Decision(player), invested);
+
+        let actions = available_actions(config, &state);
+        let mut children = Vec::with_capacity(actions.len());
+        for action in &actions {
+            let child = self.add_child(config, &state, action);
+            children.push(child);
+        }
+
+        self.nodes[index].actions = actions;
+        self.nodes[index].children = children;
+        index
+    }
+
+    fn add_child(&mut self, config: &PreflopConfig, state: &BettingState, action: &Action) -> usize {
+        let player = state.to_act;
+        let opponent = 1 - player;
+        let mut next = state.clone();
+        next.acted[player] = true;
+        next.to_act = opponent;
+
+        match action {
+            Action::Fold => {
+                let invested = [state.bets[0] + config.ante, state.bets[1] + config.ante];
+                return self.add_node(PreflopNodeKind::Fold(player), invested);
+            }
+            Action::Call => next.bets[player] = state.bets[opponent],
+            Action::Raise(to) | Action::AllIn(to) => {
+                next.bets[player] = *to;
+                next.acted[opponent] = false;
+                next.raises += 1;
+            }
+            _ => {}
+        }
+
+        // Action closes once both players acted at the current bet
+        if next.acted[opponent] {
+            let invested = [next.bets[0] + config.ante, next.bets[1] + config.ante];
+            self.add_node(PreflopNodeKind::Showdown, invested)
+        } else {
+            self.add_decision(config, next)
+        }
+    }
+}
+
+/// Actions available to the player to act
+fn available_actions(config: &PreflopConfig, state: &BettingState) -> Vec<Action> {
+    let player = state.to_act;
+    let current = state.bets[1 - player];
+    let max_bet = config.max_bet();
+
+    let mut actions = if current > state.bets[player] {
+        vec![Action::Fold, Action::Call]
+    } else {
+        vec![Action::Check]
+    };
+
+    // No raising once either player is all-in
+    if current >= max_bet || state.bets[player] >= max_bet {
+        return actions;
+    }
+
+    let mut raises: Vec<Chips> = config.sizes[state.raises.min(3)]
+        .iter()
+        .map(|size| {
+            let to = match size {
+                PreflopSize::Chips(to) => *to,
+                PreflopSize::Multiple(m) => (current as f64 * m).round() as Chips,
+                PreflopSize::AllIn => max_bet,
+            };
+            if to as f64 >= config.allin_threshold * config.stack as f64 {
+                max_bet
+            } else {
+                to.min(max_bet)
+            }
+        })
+        .filter(|&to| to > current)
+        .collect();
+    raises.sort_unstable();
+    raises.dedup();
+
+    actions.extend(raises.into_iter().map(|to| {
+        if to == max_bet {
+            Action::AllIn(to)
+        } else {
+            Action::Raise(to)
+        }
+    }));
+    actions
+}
+
+/// CFR+ solver for the preflop tree
+pub struct PreflopSolver<'a> {
+    pub tree: PreflopTree,
+    pub config: PreflopConfig,
+    equity: &'a EquityTable,
+    /// Initial class weights [SB, BB]
+    weights: [Vec<f64>; 2],
+    /// Per node: cumulative regrets, action-major (action * 169 + class)
+    regret_sum: Vec<Vec<f64>>,
+    /// Per node: cumulative strategy, same layout as `regret_sum`
+    strategy_sum: Vec<Vec<f64>>,
+    pub iteration: usize,
+}
+
+impl<'a> PreflopSolver<'a> {
+    /// Create a solver for a configuration and ranges [SB, BB]
+    pub fn new(config: PreflopConfig, ranges: [&Range; 2], equity: &'a EquityTable) -> Self {
+        let tree = PreflopTree::build(&config);
+        let tables: Vec<Vec<f64>> = tree
+            .nodes
+            .iter()
+            .map(|node| vec![0.0; node.actions.len() * NUM_HAND_CLASSES])
+            .collect();
+
+        PreflopSolver {
+            tree,
+            config,
+            equity,
+            weights: [class_weights(ranges[0]), class_weights(ranges[1])],
+            regret_sum: tables.clone(),
+            strategy_sum: tables,
+            iteration: 0,
+        }
+    }
+
+    /// Run CFR+ iterations
+    pub fn solve(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.iteration += 1;
+            for traverser in [SB, BB] {
+                let reach = self.weights.clone();
+                self.cfr(0, traverser, &reach);
+            }
+        }
+    }
+
+    /// Current strategy at a decision node (action-major)
+    fn current_strategy(&self, node: usize) -> Vec<f64> {
+        strategy_from(&self.regret_sum[node], self.tree.nodes[node].actions.len(), regret_matching)
+    }
+
+    /// Average strategy at a decision node (action-major)
+    pub fn average_strategy(&self, node: usize) -> Vec<f64> {
+        strategy_from(&self.strategy_sum[node], self.tree.nodes[node].actions.len(), |sums| {
+            let total: f64 = sums.iter().sum();
+            if total > 0.0 {
+                sums.iter().map(|s| s / total).collect()
+            } else {
+                vec![1.0 / sums.len() as f64; sums.len()]
+            }
+        })
+    }
+
+    /// One CFR+ pass; returns the traverser's counterfactual values per class
+    fn cfr(&mut self, node: usize, traverser: usize, reach: &[Vec<f64>; 2]) -> Vec<f64> {
+        let player = match self.tree.nodes[node].kind {
+            PreflopNodeKind::Decision(player) => player,
+            _ => return self.terminal_values(node, traverser, &reach[1 - traverser]),
+        };
+
+        let strategy = self.current_strategy(node);
+        let children = self.tree.nodes[node].children.clone();
+        let mut values = vec![0.0; NUM_HAND_CLASSES];
+        let mut action_values = Vec::with_capacity(children.len());
+
+        for (a, &child) in children.iter().enumerate() {
+            let probs = &strategy[a * NUM_HAND_CLASSES..(a + 1) * NUM_HAND_CLASSES];
+            let mut child_reach = reach.clone();
+            for (r, p) in child_reach[player].iter_mut().zip(probs) {
+                *r *= p;
+            }
+
+            let child_values = self.cfr(child, traverser, &child_reach);
+            if player == traverser {
+                for (i, v) in values.iter_mut().enumerate() {
+                    *v += probs[i] * child_values[i];
+                }
+                action_values.push(child_values);
+            } else {
+                for (v, cv) in values.iter_mut().zip(&child_values) {
+                    *v += cv;
+                }
+            }
+        }
+
+        if player == traverser {
+            let weight = self.iteration as f64;
+            let regrets = &mut self.regret_sum[node];
+            let sums = &mut self.strategy_sum[node];
+            for (a, child_values) in action_values.iter().enumerate() {
+                for i in 0..NUM_HAND_CLASSES {
+                    let k = a * NUM_HAND_CLASSES + i;
+                    // CFR+: regrets floored at zero, linear strategy averaging
+                    regrets[k] = (regrets[k] + child_values[i] - values[i]).max(0.0);
+                    sums[k] += weight * reach[player][i] * strategy[k];
+                }
+            }
+        }
+
+        values
+    }
+
+    /// Values of the average strategy for `traverser` below `node`
+    fn evaluate(&self, node: usize, traverser: usize, reach: &[Vec<f64>; 2]) -> Vec<f64> {
+        let player = match self.tree.nodes[node].kind {
+            PreflopNodeKind::Decision(player) => player,
+            _ => return self.terminal_values(node, traverser, &reach[1 - traverser]),
+        };
+
+        let strategy = self.average_strategy(node);
+        let mut values = vec![0.0; NUM_HAND_CLASSES];
+        for (a, &child) in self.tree.nodes[node].children.iter().enumerate() {
+            let probs = &strategy[a * NUM_HAND_CLASSES..(a + 1) * NUM_HAND_CLASSES];
+            let mut child_reach = reach.clone();
+            for (r, p) in child_reach[player].iter_mut().zip(probs) {
+                *r *= p;
+            }
+
+            let child_values = self.evaluate(child, traverser, &child_reach);
+            for i in 0..NUM_HAND_CLASSES {
+                values[i] += if player == traverser {
+                    probs[i] * child_values[i]
+                } else {
+                    child_values[i]
+                };
+            }
+        }
+        values
+    }
+
+    /// Traverser's values at a terminal node, weighted by opponent reach
+    fn terminal_values(&self, node: usize, traverser: usize, opponent_reach: &[f64]) -> Vec<f64> {
+        let node = &self.tree.nodes[node];
+        let pot = chips_to_bb(node.pot());
+        let invested = node.invested.map(chips_to_bb);
+        let opponent_total: f64 = opponent_reach.iter().sum();
+        let players = [Player::OOP, Player::IP];
+
+        match node.kind {
+            PreflopNodeKind::Fold(folder) => {
+                let payoff = self.config.rake.fold_payoffs(pot, invested, players[folder], false)[traverser];
+                vec![payoff * opponent_total; NUM_HAND_CLASSES]
+            }
+            PreflopNodeKind::Showdown => {
+                // Equity-weighted mix of winning and losing the pot
+                let rake = &self.config.rake;
+                let win = rake.showdown_payoffs(pot, invested, Some(players[traverser]))[traverser];
+                let lose = rake.showdown_payoffs(pot, invested, Some(players[1 - traverser]))[traverser];
+                self.equity
+                    .weighted_equities(opponent_reach)
+                    .into_iter()
+                    .map(|eq| win * eq + lose * (opponent_total - eq))
+                    .collect()
+            }
+            PreflopNodeKind::Decision(_) => unreachable!("decision nodes are not terminal"),
+        }
+    }
+
+    /// Strategy and EVs of the acting player at the node reached by `path`
+    pub fn node_result(&self, path: &str) -> Result<PreflopSolveResponse, String> {
+        let steps = self.tree.find_steps(path)?;
+        let target = self.tree.find(path)?;
+        let player = match self.tree.nodes[target].kind {
+            PreflopNodeKind::Decision(player) => player,
+            _ => return Err(format!("Path '{}' leads to a terminal node", path)),
+        };
+
+        // Reach probabilities of both players at the target node
+        let mut reach = self.weights.clone();
+        for (node, a) in steps {
+            if let PreflopNodeKind::Decision(actor) = self.tree.nodes[node].kind {
+                let strategy = self.average_strategy(node);
+                for (i, r) in reach[actor].iter_mut().enumerate() {
+                    *r *= strategy[a * NUM_HAND_CLASSES + i];
+                }
+            }
+        }
+
+        let values = self.evaluate(target, player, &reach);
+        let opponent_total: f64 = reach[1 - player].iter().sum();
+        let strategy = self.average_strategy(target);
+        let num_actions = self.tree.nodes[target].actions.len();
+
+        let hands: Vec<HandClassStrategy> = HandClass::all()
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.weights[player][*i] > 0.0)
+            .map(|(i, class)| HandClassStrategy {
+                hand: class.to_string(),
+                flush_suit: None,
+                num_combos: class.num_combos(),
+                weight: reach[player][i],
+                strategy: (0..num_actions)
+                    .map(|a| strategy[a * NUM_HAND_CLASSES + i])
+                    .collect(),
+                ev: if opponent_total > 0.0 {
+                    values[i] / opponent_total
+                } else {
+                    0.0
+                },
+            })
+            .collect();
+
+        let total_weight: f64 = hands.iter().map(|h| h.weight).sum();
+        let actions = self.tree.nodes[target]
+            .actions
+            .iter()
+            .enumerate()
+            .map(|(a, action)| {
+                let mut info = preflop_action_info(action, self.tree.nodes[target].pot());
+                if total_weight > 0.0 {
+                    info.frequency =
+                        hands.iter().map(|h| h.weight * h.strategy[a]).sum::<f64>() / total_weight;
+                }
+                info
+            })
+            .collect();
+
+        Ok(PreflopSolveResponse {
+            player: if player == SB {
+                PreflopPosition::SB
+            } else {
+                PreflopPosition::BB
+            },
+            pot: chips_to_bb(self.tree.nodes[target].pot()),
+            actions,
+            hands,
+        })
+    }
+}
+
+/// Apply a per-class strategy function to action-major tables
+fn strategy_from(table: &[f64], num_actions: usize, f: impl Fn(&[f64]) -> Vec<f64>) -> Vec<f64> {
+    let mut strategy = vec![0.0; table.len()];
+    for i in 0..NUM_HAND_CLASSES {
+        let values: Vec<f64> = (0..num_actions)
+            .map(|a| table[a * NUM_HAND_CLASSES + i])
+            .collect();
+        for (a, p) in f(&values).into_iter().enumerate() {
+            strategy[a * NUM_HAND_CLASSES + i] = p;
+        }
+    }
+    strategy
+}
+
+/// Describe a preflop action for the API
+fn preflop_action_info(action: &Action, pot: Chips) -> ActionInfo {
+    let (name, action_type, amount) = match action {
+        Action::Fold => ("Fold".to_string(), ActionTypeResponse::Fold, 0),
+        Action::Check => ("Check".to_string(), ActionTypeResponse::Check, 0),
+        Action::Call => ("Call".to_string(), ActionTypeResponse::Call, 0),
+        Action::Raise(to) => (
+            format!("Raise to {}bb", chips_to_bb(*to)),
+            ActionTypeResponse::Raise,
+            *to,
+        ),
+        Action::AllIn(to) => ("All-in".to_string(), ActionTypeResponse::Allin, *to),
+        Action::Bet(_) | Action::Deal(_) => unreachable!("not a preflop action"),
+    };
+
+    ActionInfo {
+        name,
+        action_type,
+        amount_big_blinds: chips_to_bb(amount),
+        amount_percent: amount as f64 * 100.0 / pot.max(1) as f64,
+        frequency: 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> PreflopSolveRequest {
+        PreflopSolveRequest {
+            effective_stack: 20.0,
+            small_blind: 0.5,
+            big_blind: 1.0,
+            ante: 0.0,
+            straddle: None,
+            sizes: Some(PreflopSizes {
+                open: "2.5".to_string(),
+                three_bet: "3x".to_string(),
+                four_bet: "a".to_string(),
+                five_bet: "a".to_string(),
+            }),
+            allin_threshold: 67.0,
+            sb_range: None,
+            bb_range: None,
+            rake: None,
+            path: String::new(),
+            iterations: None,
+        }
+    }
+
+    /// Equity table where the higher top rank always wins
+    fn high_card_equity() -> EquityTable {
+        EquityTable::from_fn(|hero, villain| match hero.high.cmp(&villain.high) {
+            std::cmp::Ordering::Greater => 1.0,
+            std::cmp::Ordering::Less => 0.0,
+            std::cmp::Ordering::Equal => 0.5,
+        })
+    }
+
+    #[test]
+    fn test_parse_preflop_sizes() {
+        let sizes = parse_preflop_sizes("2.5, 3x, a").unwrap();
+        assert_eq!(
+            sizes,
+            vec![
+                PreflopSize::Chips(250),
+                PreflopSize::Multiple(3.0),
+                PreflopSize::AllIn
+            ]
+        );
+        assert!(parse_preflop_sizes("").is_err());
+        assert!(parse_preflop_sizes("0.5x").is_err());
+        assert!(parse_preflop_sizes("abc").is_err());
+    }
+
+    #[test]
+    fn test_root_actions() {
+        let config = PreflopConfig::from_request(&request()).unwrap();
+        let tree = PreflopTree::build(&config);
+        let root = &tree.nodes[0];
+
+        assert_eq!(root.kind, PreflopNodeKind::Decision(SB));
+        assert_eq!(root.pot(), 150);
+        assert_eq!(root.actions, vec![Action::Fold, Action::Call, Action::Raise(250)]);
+
+        // 3-bet to 7.5bb, 4-bet is all-in
+        let three_bet = tree.find("r2.5/r7.5").unwrap();
+        assert_eq!(tree.nodes[three_bet].actions, vec![Action::Fold, Action::Call, Action::AllIn(2000)]);
+
+        // SB limp gives the BB the option
+        let limp = tree.find("c").unwrap();
+        assert_eq!(tree.nodes[limp].kind, PreflopNodeKind::Decision(BB));
+        assert_eq!(tree.nodes[limp].actions[0], Action::Check);
+        let checked = tree.find("c/x").unwrap();
+        assert_eq!(tree.nodes[checked].kind, PreflopNodeKind::Showdown);
+    }
+
+    #[test]
+    fn test_allin_threshold() {
+        let mut req = request();
+        req.effective_stack = 10.0;
+        let config = PreflopConfig::from_request(&req).unwrap();
+        let tree = PreflopTree::build(&config);
+
+        // 3x of 2.5bb = 7.5bb is over 67% of 10bb, so it becomes all-in
+        let node = tree.find("r2.5").unwrap();
+        assert_eq!(tree.nodes[node].actions, vec![Action::Fold, Action::Call, Action::AllIn(1000)]);
+    }
+
+    #[test]
+    fn test_straddle_and_antes() {
+        let mut req = request();
+        req.straddle = Some(2.0);
+        req.ante = 0.1;
+        let config = PreflopConfig::from_request(&req).unwrap();
+        let tree = PreflopTree::build(&config);
+        let root = &tree.nodes[0];
+
+        assert_eq!(root.kind, PreflopNodeKind::Decision(BB));
+        assert_eq!(root.invested, [210, 110]);
+
+        let mut req = request();
+        req.straddle = Some(0.5);
+        assert!(PreflopConfig::from_request(&req).is_err());
+    }
+
+    #[test]
+    fn test_solve_push_fold() {
+        // At 10bb AA never folds and 22 (losing to every other top
+        // rank) must fold facing a shove
+        let mut req = request();
+        req.effective_stack = 10.0;
+        req.sizes = Some(PreflopSizes {
+            open: "a".to_string(),
+            three_bet: "a".to_string(),
+            four_bet: "a".to_string(),
+            five_bet: "a".to_string(),
+        });
+        let config = PreflopConfig::from_request(&req).unwrap();
+        let equity = high_card_equity();
+        let full = Range::full();
+        let mut solver = PreflopSolver::new(config, [&full, &full], &equity);
+        solver.solve(200);
+
+        let root = solver.node_result("").unwrap();
+        assert_eq!(root.player, PreflopPosition::SB);
+        assert_eq!(root.hands.len(), 169);
+        let aa = &root.hands[0];
+        assert_eq!(aa.hand, "AA");
+        assert!(aa.strategy[0] < 0.01, "AA never folds: {:?}", aa.strategy);
+        assert!(aa.ev > 0.0);
+
+        let facing = solver.node_result("a").unwrap();
+        assert_eq!(facing.player, PreflopPosition::BB);
+        let deuces = facing.hands.iter().find(|h| h.hand == "22").unwrap();
+        assert!(deuces.strategy[0] > 0.99, "22 folds: {:?}", deuces.strategy);
+
+        for hand in &facing.hands {
+            let sum: f64 = hand.strategy.iter().sum();
+            assert!((sum - 1.0).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Rake modelling and terminal payoffs
+//!
+//! Terminal payoffs are returned per player as net chips won relative to
+//! what that player put into the pot. The pot may hold dead money from
+//! before the spot, so payoffs only sum to zero when it doesn't and there
+//! is no rake; with rake the winner's share is reduced by the rake taken
+//! from the pot.
+
+use crate::models::{Player, RakeConfig};
+
+/// Rake structure
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rake {
+    /// Fraction of the pot taken (0.05 = 5%)
+    pub fraction: f64,
+    /// Maximum rake per pot in bb (`f64::INFINITY` = uncapped)
+    pub cap: f64,
+    /// Pots that end preflop are not raked
+    pub no_flop_no_drop: bool,
+}
+
+impl Rake {
+    /// No rake (zero-sum payoffs)
+    pub fn none() -> Self {
+        Rake {
+            fraction: 0.0,
+            cap: 0.0,
+            no_flop_no_drop: true,
+        }
+    }
+
+    /// Parse rake from API model
+    pub fn from_config(config: &RakeConfig) -> Result<Self, String> {
+        if !(0.0..=100.0).contains(&config.percent) {
+            return Err(format!("Rake percent must be 0-100, got {}", config.percent));
+        }
+
+        let cap = match config.cap_big_blinds {
+            Some(cap) if cap < 0.0 => {
+                return Err(format!("Rake cap must be non-negative, got {}", cap));
+            }
+            Some(cap) => cap,
+            None => f64::INFINITY,
+        };
+
+        Ok(Rake {
+            fraction: config.percent / 100.0,
+            cap,
+            no_flop_no_drop: config.no_flop_no_drop,
+        })
+    }
+
+    /// Check if this rake takes anything at all
+    pub fn is_zero(&self) -> bool {
+        self.fraction == 0.0 || self.cap == 0.0
+    }
+
+    /// Rake taken from a pot of the given size
+    pub fn amount(&self, pot: f64, saw_flop: bool) -> f64 {
+        if !saw_flop && self.no_flop_no_drop {
+            return 0.0;
+        }
+        (pot * self.fraction).min(self.cap)
+    }
+
+    /// What's left of a pot of the given size once the rake is taken
+    fn won(&self, pot: f64, saw_flop: bool) -> f64 {
+        if self.is_zero() {
+            pot
+        } else {
+            pot - self.amount(pot, saw_flop)
+        }
+    }
+
+    /// Payoffs [OOP, IP] when `folder` folds
+    ///
+    /// `pot` is the whole pot including any dead money; `invested` is what
+    /// each player put into it.
+    pub fn fold_payoffs(&self, pot: f64, invested: [f64; 2], folder: Player, saw_flop: bool) -> [f64; 2] {
+        let won = self.won(pot, saw_flop);
+
+        match folder {
+            Player::OOP => [-invested[0], won - invested[1]],
+            Player::IP => [won - invested[0], -invested[1]],
+        }
+    }
+
+    /// Payoffs [OOP, IP] at showdown; `winner` is `None` for a split pot
+    ///
+    /// Showdowns always happen after the flop, so the rake always applies.
+    pub fn showdown_payoffs(&self, pot: f64, invested: [f64; 2], winner: Option<Player>) -> [f64; 2] {
+        let won = self.won(pot, true);
+
+        match winner {
+            Some(Player::OOP) => [won - invested[0], -invested[1]],
+            Some(Player::IP) => [-invested[0], won - invested[1]],
+            None => [won / 2.0 - invested[0], won / 2.0 - invested[1]],
+        }
+    }
+}
+
+impl Default for Rake {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(percent: f64, cap: Option<f64>, no_flop_no_drop: bool) -> RakeConfig {
+        RakeConfig {
+            percent,
+            cap_big_blinds: cap,
+            no_flop_no_drop,
+        }
+    }
+
+    #[test]
+    fn test_no_rake_is_zero_sum() {
+        let rake = Rake::none();
+        let payoffs = rake.showdown_payoffs(60.0, [30.0, 30.0], Some(Player::OOP));
+        assert_eq!(payoffs, [30.0, -30.0]);
+
+        let payoffs = rake.fold_payoffs(35.0, [10.0, 25.0], Player::OOP, true);
+        assert_eq!(payoffs, [-10.0, 10.0]);
+    }
+
+    #[test]
+    fn test_rake_with_cap() {
+        let rake = Rake::from_config(&config(5.0, Some(3.0), true)).unwrap();
+
+        // 5% of 20bb = 1bb (under cap)
+        assert!((rake.amount(20.0, true) - 1.0).abs() < 1e-9);
+        // 5% of 100bb = 5bb, capped at 3bb
+        assert!((rake.amount(100.0, true) - 3.0).abs() < 1e-9);
+
+        let payoffs = rake.showdown_payoffs(100.0, [50.0, 50.0], Some(Player::IP));
+        assert_eq!(payoffs, [-50.0, 47.0]);
+    }
+
+    #[test]
+    fn test_split_pot_rake() {
+        let rake = Rake::from_config(&config(5.0, None, true)).unwrap();
+        let payoffs = rake.showdown_payoffs(20.0, [10.0, 10.0], None);
+        assert!((payoffs[0] + 0.5).abs() < 1e-9);
+        assert!((payoffs[1] + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_no_flop_no_drop() {
+        let rake = Rake::from_config(&config(5.0, Some(3.0), true)).unwrap();
+        assert_eq!(rake.fold_payoffs(4.0, [1.0, 3.0], Player::OOP, false), [-1.0, 1.0]);
+
+        let raked = Rake::from_config(&config(5.0, Some(3.0), false)).unwrap();
+        let payoffs = raked.fold_payoffs(4.0, [1.0, 3.0], Player::OOP, false);
+        assert!((payoffs[1] - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_dead_money_goes_to_the_winner() {
+        // 10bb starting pot, then 20bb each
+        let rake = Rake::none();
+        assert_eq!(rake.showdown_payoffs(50.0, [20.0, 20.0], Some(Player::OOP)), [30.0, -20.0]);
+        assert_eq!(rake.fold_payoffs(30.0, [20.0, 0.0], Player::IP, true), [10.0, 0.0]);
+
+        let raked = Rake::from_config(&config(5.0, Some(3.0), true)).unwrap();
+        let payoffs = raked.showdown_payoffs(50.0, [20.0, 20.0], None);
+        assert!((payoffs[0] - 3.75).abs() < 1e-9);
+        assert!((payoffs[1] - 3.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_invalid_rake() {
+        assert!(Rake::from_config(&config(-1.0, None, true)).is_err());
+        assert!(Rake::from_config(&config(101.0, None, true)).is_err());
+        assert!(Rake::from_config(&config(5.0, Some(-1.0), true)).is_err());
+    }
+}
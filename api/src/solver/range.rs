@@ -5,11 +5,14 @@
 //! - Ranges: "JJ-99", "AQs-ATs"
 //! - Frequencies: "QQ:0.5", "AA:0.75"
 //! - Plus notation: "22+", "A2s+", "ATo+"
+//! - Specific combos: "AhKh", "AsKd:0.5"
 //! - Combinations: "AA,KK,QQ,JJ-99,AQs-ATs"
 
 use std::collections::HashMap;
+use std::fmt;
 
 use super::cards::{Card, Combo, Rank, generate_all_combos};
+use super::hand_class::{HandClass, NUM_HAND_CLASSES};
 
 /// A hand range with combo frequencies
 #[derive(Debug, Clone)]
@@ -26,6 +29,16 @@ impl Range {
         }
     }
 
+    /// Create a range containing every combo at full frequency
+    pub fn full() -> Self {
+        Range {
+            combos: generate_all_combos()
+                .iter()
+                .map(|combo| (combo.id, 1.0))
+                .collect(),
+        }
+    }
+
     /// Parse a range from PioSOLVER syntax
     ///
     /// Examples:
@@ -111,6 +124,62 @@ impl Range {
     pub fn get_frequency(&self, combo_id: u16) -> f64 {
         self.combos.get(&combo_id).copied().unwrap_or(0.0)
     }
+
+    /// Set the frequency of a combo (zero removes it)
+    pub fn set_frequency(&mut self, combo_id: u16, frequency: f64) {
+        if frequency > 0.0 {
+            self.combos.insert(combo_id, frequency);
+        } else {
+            self.combos.remove(&combo_id);
+        }
+    }
+
+    /// Number of combos weighted by frequency
+    pub fn total_weight(&self) -> f64 {
+        self.combos.values().sum()
+    }
+}
+
+/// Normalised Pio syntax: one entry per hand class in grid order
+///
+/// Classes whose combos all share a frequency are written as the class
+/// (e.g. `AKs:0.5`), others combo by combo. Zero-frequency combos are dropped.
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let all_combos = generate_all_combos();
+        let mut classes: Vec<Vec<(u16, f64)>> = vec![Vec::new(); NUM_HAND_CLASSES];
+        for (&id, &frequency) in &self.combos {
+            if frequency > 0.0 {
+                classes[HandClass::of_combo(&all_combos[id as usize]).index()].push((id, frequency));
+            }
+        }
+
+        let mut entries = Vec::new();
+        for (index, mut combos) in classes.into_iter().enumerate() {
+            let class = HandClass::from_index(index).unwrap();
+            combos.sort_by_key(|&(id, _)| id);
+            let uniform = combos.len() == class.num_combos() as usize
+                && combos.iter().all(|&(_, freq)| freq == combos[0].1);
+            if uniform {
+                entries.push(with_frequency(class.to_string(), combos[0].1));
+            } else {
+                entries.extend(
+                    combos
+                        .iter()
+                        .map(|&(id, freq)| with_frequency(all_combos[id as usize].to_string(), freq)),
+                );
+            }
+        }
+        write!(f, "{}", entries.join(","))
+    }
+}
+
+fn with_frequency(hand: String, frequency: f64) -> String {
+    if frequency == 1.0 {
+        hand
+    } else {
+        format!("{}:{}", hand, frequency)
+    }
 }
 
 impl Default for Range {
@@ -124,8 +193,8 @@ fn parse_hand_pattern(s: &str) -> Result<Vec<u16>, String> {
     let all_combos = generate_all_combos();
 
     // Check for plus notation (e.g., "22+", "A2s+", "ATo+")
-    if s.ends_with('+') {
-        return parse_plus_notation(&s[..s.len() - 1]);
+    if let Some(base) = s.strip_suffix('+') {
+        return parse_plus_notation(base);
     }
 
     // Check for range (e.g., "JJ-99", "AQs-ATs")
@@ -133,6 +202,13 @@ fn parse_hand_pattern(s: &str) -> Result<Vec<u16>, String> {
         return parse_range_notation(s);
     }
 
+    // Specific combo (e.g., "AhKh")
+    if s.len() == 4 {
+        if let Ok(combo) = s.parse::<Combo>() {
+            return Ok(vec![combo.id]);
+        }
+    }
+
     // Single hand (e.g., "AA", "AKs", "AKo")
     parse_single_hand(s, &all_combos)
 }
@@ -396,6 +472,13 @@ mod tests {
         assert_eq!(range.len(), 0);
     }
 
+    #[test]
+    fn test_full_range() {
+        let range = Range::full();
+        assert_eq!(range.len(), 1326);
+        assert_eq!(range.get_frequency(1325), 1.0);
+    }
+
     #[test]
     fn test_invalid_frequency() {
         assert!(Range::parse("AA:1.5").is_err()); // > 1.0
@@ -408,4 +491,22 @@ mod tests {
         assert!(Range::parse("A").is_err());
         assert!(Range::parse("AKx").is_err());
     }
+
+    #[test]
+    fn test_parse_specific_combos() {
+        let range = Range::parse("AhKh,AsKd:0.5").unwrap();
+        assert_eq!(range.len(), 2);
+        assert_eq!(range.total_weight(), 1.5);
+    }
+
+    #[test]
+    fn test_normalised_display() {
+        let range = Range::parse("KK,AA:0.5,JJ-TT,AKs,AsKs:0.25,QQ:0").unwrap();
+        assert_eq!(range.to_string(), "AA:0.5,AsKs:0.25,AhKh,AdKd,AcKc,KK,JJ,TT");
+
+        // Normalised output parses back to the same range
+        let reparsed = Range::parse(&range.to_string()).unwrap();
+        assert_eq!(reparsed.to_string(), range.to_string());
+        assert_eq!(reparsed.total_weight(), 3.0 + 18.0 + 3.25);
+    }
 }
@@ -0,0 +1,305 @@
+//! Solved game trees
+//!
+//! A flat, index-linked tree holding the strategy and EVs of the acting
+//! player at every decision node. This is what gets written to strategy
+//! files and browsed node by node.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::cards::{Card, Combo};
+use super::game_state::{find_action, Action};
+use super::isomorphism::SuitPermutation;
+use super::utils::Chips;
+use crate::models::{ActionType, Player};
+
+/// What happens at a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolvedNodeKind {
+    /// A player chooses an action
+    Decision(Player),
+    /// A card is dealt
+    Chance,
+    /// Fold or showdown
+    Terminal,
+}
+
+/// One node of a solved tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolvedNode {
+    pub kind: SolvedNodeKind,
+    /// Pot including bets on the current street
+    pub pot: Chips,
+    /// Effective stack behind
+    pub stack: Chips,
+    /// Chips the player to act needs to call (0 unless facing a bet)
+    pub to_call: Chips,
+    /// Actions (or dealt cards) leading to `children`, in the same order
+    pub actions: Vec<Action>,
+    /// Child node indices
+    pub children: Vec<u32>,
+    /// Combo-major strategy of the acting player: `strategy[c * actions + a]`
+    pub strategy: Vec<f32>,
+    /// EV of each of the acting player's combos in bb
+    pub evs: Vec<f32>,
+}
+
+impl SolvedNode {
+    /// Strategy of the combo at `index` in the acting player's combo list
+    pub fn combo_strategy(&self, index: usize) -> &[f32] {
+        let n = self.actions.len();
+        &self.strategy[index * n..(index + 1) * n]
+    }
+
+    /// Follow action `index`: scale the acting player's reach by its
+    /// probability, or add a dealt card and zero the combos it blocks
+    pub fn advance(&self, index: usize, combos: &[Vec<u16>; 2], weights: &mut [Vec<f64>; 2], board: &mut Vec<Card>) {
+        match (self.kind, &self.actions[index]) {
+            (SolvedNodeKind::Decision(player), _) => {
+                let p = player as usize;
+                for (c, weight) in weights[p].iter_mut().enumerate() {
+                    *weight *= self.combo_strategy(c)[index] as f64;
+                }
+            }
+            (_, Action::Deal(card)) => {
+                board.push(*card);
+                for (combos, weights) in combos.iter().zip(weights.iter_mut()) {
+                    for (&id, weight) in combos.iter().zip(weights.iter_mut()) {
+                        if Combo::from_id(id).is_some_and(|c| c.is_blocked_by(&[*card])) {
+                            *weight = 0.0;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A solved tree; node 0 is the root and children come after their parent
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolvedTree {
+    pub board: Vec<Card>,
+    /// Combo IDs in each player's range, indexed [OOP, IP]
+    pub combos: [Vec<u16>; 2],
+    /// Range weight of each combo in `combos`
+    pub weights: [Vec<f32>; 2],
+    pub nodes: Vec<SolvedNode>,
+}
+
+impl SolvedTree {
+    /// Combo list of the player acting at a node (empty if nobody acts)
+    pub fn acting_combos(&self, node: &SolvedNode) -> &[u16] {
+        match node.kind {
+            SolvedNodeKind::Decision(Player::OOP) => &self.combos[0],
+            SolvedNodeKind::Decision(Player::IP) => &self.combos[1],
+            _ => &[],
+        }
+    }
+
+    /// Check that indices and table sizes are consistent
+    pub fn validate(&self) -> Result<(), String> {
+        if self.combos.iter().zip(&self.weights).any(|(c, w)| c.len() != w.len()) {
+            return Err("Range weights do not match the combo lists".to_string());
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            if node.actions.len() != node.children.len() {
+                return Err(format!("Node {} has {} actions but {} children", i, node.actions.len(), node.children.len()));
+            }
+            if let Some(&child) = node.children.iter().find(|&&c| c as usize >= self.nodes.len() || c as usize <= i) {
+                return Err(format!("Node {} has invalid child {}", i, child));
+            }
+
+            let combos = self.acting_combos(node).len();
+            if node.strategy.len() != combos * node.actions.len() || node.evs.len() != combos {
+                return Err(format!("Node {} has strategy or EV tables of the wrong size", i));
+            }
+        }
+        Ok(())
+    }
+
+    /// Relabel the suits of the board, combos and dealt cards
+    pub fn relabel(&self, permutation: &SuitPermutation) -> SolvedTree {
+        let mut tree = self.clone();
+        tree.board = self.board.iter().map(|&c| permutation.apply_card(c)).collect();
+        for combos in &mut tree.combos {
+            for id in combos.iter_mut() {
+                if let Some(combo) = Combo::from_id(*id) {
+                    *id = permutation.apply_combo(&combo).id;
+                }
+            }
+        }
+        for node in &mut tree.nodes {
+            for action in &mut node.actions {
+                if let Action::Deal(card) = action {
+                    *card = permutation.apply_card(*card);
+                }
+            }
+        }
+        tree
+    }
+}
+
+/// One step of a node path such as `x/b33/r50/c/Td`
+///
+/// `x` check, `c` call, `f` fold, `a` all-in, `b<pct>` / `r<pct>` bet or
+/// raise sized like bet sizes (a raise adds that percentage of the pot
+/// after calling), or a dealt card.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathStep {
+    pub action: ActionType,
+    pub amount_percent: Option<f64>,
+    pub card: Option<Card>,
+}
+
+impl PathStep {
+    fn new(action: ActionType) -> Self {
+        PathStep {
+            action,
+            amount_percent: None,
+            card: None,
+        }
+    }
+
+    /// Index of the matching action at a node
+    pub fn find_in(&self, node: &SolvedNode) -> Option<usize> {
+        find_action(&node.actions, &self.action, self.amount_percent, self.card, node.pot, node.to_call)
+    }
+
+    /// Path step for an action taken at a node
+    pub fn of(action: &Action, node: &SolvedNode) -> Self {
+        let percent = action.size_percent(node.pot, node.to_call);
+        match action {
+            Action::Fold => PathStep::new(ActionType::Fold),
+            Action::Check => PathStep::new(ActionType::Check),
+            Action::Call => PathStep::new(ActionType::Call),
+            Action::Bet(_) => PathStep {
+                amount_percent: Some(percent.round()),
+                ..PathStep::new(ActionType::Bet)
+            },
+            Action::Raise(_) => PathStep {
+                amount_percent: Some(percent.round()),
+                ..PathStep::new(ActionType::Raise)
+            },
+            Action::AllIn(_) => PathStep::new(ActionType::Allin),
+            Action::Deal(card) => PathStep {
+                card: Some(*card),
+                ..PathStep::new(ActionType::Deal)
+            },
+        }
+    }
+}
+
+impl FromStr for PathStep {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sized = |action: ActionType, pct: &str| -> Result<PathStep, String> {
+            let pct: f64 = pct
+                .parse()
+                .ok()
+                .filter(|p: &f64| p.is_finite() && *p > 0.0)
+                .ok_or_else(|| format!("Invalid size in path step '{}'", s))?;
+            Ok(PathStep {
+                amount_percent: Some(pct),
+                ..PathStep::new(action)
+            })
+        };
+
+        match s {
+            "x" => Ok(PathStep::new(ActionType::Check)),
+            "c" => Ok(PathStep::new(ActionType::Call)),
+            "f" => Ok(PathStep::new(ActionType::Fold)),
+            "a" => Ok(PathStep::new(ActionType::Allin)),
+            _ if s.len() == 2 && s.parse::<Card>().is_ok() => Ok(PathStep {
+                card: s.parse().ok(),
+                ..PathStep::new(ActionType::Deal)
+            }),
+            _ if s.starts_with('b') => sized(ActionType::Bet, &s[1..]),
+            _ if s.starts_with('r') => sized(ActionType::Raise, &s[1..]),
+            _ => Err(format!(
+                "Invalid path step '{}' (expected x, c, f, a, b<pct>, r<pct> or a card)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PathStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.action, self.amount_percent, self.card) {
+            (ActionType::Bet, Some(pct), _) => write!(f, "b{}", pct),
+            (ActionType::Raise, Some(pct), _) => write!(f, "r{}", pct),
+            (ActionType::Deal, _, Some(card)) => write!(f, "{}", card),
+            (ActionType::Check, _, _) => write!(f, "x"),
+            (ActionType::Call, _, _) => write!(f, "c"),
+            (ActionType::Fold, _, _) => write!(f, "f"),
+            (ActionType::Allin, _, _) => write!(f, "a"),
+            _ => write!(f, "?"),
+        }
+    }
+}
+
+/// Parse a '/'-separated node path; the empty path is the root
+pub fn parse_path(path: &str) -> Result<Vec<PathStep>, String> {
+    path.split('/')
+        .filter(|step| !step.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_path() {
+        let steps = parse_path("x/b33/r50/c/Td").unwrap();
+        assert_eq!(steps.len(), 5);
+        assert_eq!(steps[1].amount_percent, Some(33.0));
+        assert_eq!(steps[4].card, Some("Td".parse().unwrap()));
+        assert_eq!(
+            steps.iter().map(|s| s.to_string()).collect::<Vec<_>>().join("/"),
+            "x/b33/r50/c/Td"
+        );
+
+        assert!(parse_path("").unwrap().is_empty());
+        assert!(parse_path("x/b/c").is_err());
+        assert!(parse_path("x/q").is_err());
+    }
+
+    #[test]
+    fn test_path_step_matches_closest_size() {
+        let node = SolvedNode {
+            kind: SolvedNodeKind::Decision(Player::OOP),
+            pot: 1000,
+            stack: 10000,
+            to_call: 0,
+            actions: vec![Action::Check, Action::Bet(330), Action::Bet(750), Action::AllIn(10000)],
+            children: vec![1, 2, 3, 4],
+            strategy: vec![],
+            evs: vec![],
+        };
+        assert_eq!("b33".parse::<PathStep>().unwrap().find_in(&node), Some(1));
+        assert_eq!("b70".parse::<PathStep>().unwrap().find_in(&node), Some(2));
+        assert_eq!("a".parse::<PathStep>().unwrap().find_in(&node), Some(3));
+        assert_eq!("c".parse::<PathStep>().unwrap().find_in(&node), None);
+        assert_eq!(PathStep::of(&node.actions[2], &node).to_string(), "b75");
+
+        // Facing 330 into 1000, a raise of 1160 calls 330 and adds half the
+        // 1660 pot after calling
+        let facing = SolvedNode {
+            kind: SolvedNodeKind::Decision(Player::IP),
+            pot: 1330,
+            stack: 9670,
+            to_call: 330,
+            actions: vec![Action::Fold, Action::Call, Action::Raise(1160), Action::AllIn(9670)],
+            children: vec![5, 6, 7, 8],
+            strategy: vec![],
+            evs: vec![],
+        };
+        let step = PathStep::of(&facing.actions[2], &facing);
+        assert_eq!(step.to_string(), "r50");
+        assert_eq!(step.find_in(&facing), Some(2));
+    }
+}
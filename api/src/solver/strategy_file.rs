@@ -0,0 +1,612 @@
+//! Compact binary strategy files
+//!
+//! Layout (all integers little-endian):
+//!
+//! ```text
+//! Header, 32 bytes, never compressed
+//!   0   8  magic "DPCFRTRE"
+//!   8   2  format version (currently 1)
+//!   10  2  flags: bit 0 = body is zlib-compressed,
+//!                 bit 1 = 16-bit probabilities (otherwise 8-bit)
+//!   12  4  CRC-32 of the body as stored
+//!   16  8  body length as stored
+//!   24  8  body length uncompressed
+//!
+//! Body
+//!   u8 board card count, then one u8 per card (rank * 4 + suit)
+//!   2x (OOP range, then IP range):
+//!     u16 combo count, then u16 combo IDs, then f32 range weights
+//!   u32 node count, then one 28-byte record per node:
+//!     u8  kind (0 = OOP acts, 1 = IP acts, 2 = chance, 3 = terminal)
+//!     u8  action count
+//!     u16 reserved (0)
+//!     u32 pot in chips
+//!     u32 effective stack in chips
+//!     u32 index of the node's first entry in the action table
+//!     u64 index of the node's first probability in the strategy section
+//!     u32 index of the node's first EV in the EV table
+//!   u32 action count, then one 9-byte record per action:
+//!     u8  type (0 fold, 1 check, 2 call, 3 bet, 4 raise, 5 all-in, 6 deal)
+//!     u32 amount in chips, or the card value for deals
+//!     u32 child node index
+//!   u64 probability count, then one u8 or u16 per probability, combo-major
+//!   u32 EV count, then one f32 per EV (bb)
+//! ```
+//!
+//! Node records are fixed-size, so an uncompressed file can be memory-mapped
+//! and single nodes decoded without reading the rest of the tree.
+//! Probabilities are quantised to `2^8 - 1` or `2^16 - 1` steps and
+//! renormalised per combo when read.
+
+use std::borrow::Cow;
+use std::io::{Read, Write};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+use super::bytes::Reader;
+use super::cards::Card;
+use super::game_state::Action;
+use super::solved_tree::{PathStep, SolvedNode, SolvedNodeKind, SolvedTree};
+use crate::models::Player;
+
+/// File magic
+pub const MAGIC: &[u8; 8] = b"DPCFRTRE";
+/// Current format version
+pub const FORMAT_VERSION: u16 = 1;
+
+const HEADER_LEN: usize = 32;
+const NODE_RECORD_LEN: usize = 28;
+const ACTION_RECORD_LEN: usize = 9;
+const FLAG_COMPRESSED: u16 = 1;
+const FLAG_WIDE: u16 = 2;
+/// Largest expansion of a zlib stream
+const MAX_ZLIB_RATIO: usize = 1032;
+
+/// Encoding options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Compress the body with zlib (the file can then no longer be read in place)
+    pub compress: bool,
+    /// Store probabilities in 16 bits instead of 8
+    pub wide: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions {
+            compress: false,
+            wide: true,
+        }
+    }
+}
+
+/// Encode a solved tree
+pub fn write(tree: &SolvedTree, options: WriteOptions) -> Result<Vec<u8>, String> {
+    tree.validate()?;
+
+    let mut body = Vec::new();
+    body.push(tree.board.len() as u8);
+    body.extend(tree.board.iter().map(|c| c.value()));
+    for (combos, weights) in tree.combos.iter().zip(&tree.weights) {
+        body.extend((combos.len() as u16).to_le_bytes());
+        for id in combos {
+            body.extend(id.to_le_bytes());
+        }
+        for weight in weights {
+            body.extend(weight.to_le_bytes());
+        }
+    }
+
+    // Node table, with running offsets into the action, strategy and EV sections
+    body.extend((tree.nodes.len() as u32).to_le_bytes());
+    let (mut action_index, mut prob_index, mut ev_index) = (0u32, 0u64, 0u32);
+    for node in &tree.nodes {
+        body.push(match node.kind {
+            SolvedNodeKind::Decision(Player::OOP) => 0,
+            SolvedNodeKind::Decision(Player::IP) => 1,
+            SolvedNodeKind::Chance => 2,
+            SolvedNodeKind::Terminal => 3,
+        });
+        body.push(node.actions.len() as u8);
+        body.extend(0u16.to_le_bytes());
+        body.extend(node.pot.to_le_bytes());
+        body.extend(node.stack.to_le_bytes());
+        body.extend(action_index.to_le_bytes());
+        body.extend(prob_index.to_le_bytes());
+        body.extend(ev_index.to_le_bytes());
+        action_index += node.actions.len() as u32;
+        prob_index += node.strategy.len() as u64;
+        ev_index += node.evs.len() as u32;
+    }
+
+    body.extend(action_index.to_le_bytes());
+    for node in &tree.nodes {
+        for (action, &child) in node.actions.iter().zip(&node.children) {
+            let (kind, value) = encode_action(action);
+            body.push(kind);
+            body.extend(value.to_le_bytes());
+            body.extend(child.to_le_bytes());
+        }
+    }
+
+    body.extend(prob_index.to_le_bytes());
+    for node in &tree.nodes {
+        for &p in &node.strategy {
+            let p = p.clamp(0.0, 1.0) as f64;
+            if options.wide {
+                body.extend(((p * u16::MAX as f64).round() as u16).to_le_bytes());
+            } else {
+                body.push((p * u8::MAX as f64).round() as u8);
+            }
+        }
+    }
+
+    body.extend(ev_index.to_le_bytes());
+    for node in &tree.nodes {
+        for ev in &node.evs {
+            body.extend(ev.to_le_bytes());
+        }
+    }
+
+    let raw_len = body.len() as u64;
+    let mut flags = 0;
+    if options.wide {
+        flags |= FLAG_WIDE;
+    }
+    if options.compress {
+        flags |= FLAG_COMPRESSED;
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&body).map_err(|e| e.to_string())?;
+        body = encoder.finish().map_err(|e| e.to_string())?;
+    }
+
+    let mut file = Vec::with_capacity(HEADER_LEN + body.len());
+    file.extend(MAGIC);
+    file.extend(FORMAT_VERSION.to_le_bytes());
+    file.extend(flags.to_le_bytes());
+    file.extend(crc32fast::hash(&body).to_le_bytes());
+    file.extend((body.len() as u64).to_le_bytes());
+    file.extend(raw_len.to_le_bytes());
+    file.extend(body);
+    Ok(file)
+}
+
+fn encode_action(action: &Action) -> (u8, u32) {
+    match action {
+        Action::Fold => (0, 0),
+        Action::Check => (1, 0),
+        Action::Call => (2, 0),
+        Action::Bet(amount) => (3, *amount),
+        Action::Raise(amount) => (4, *amount),
+        Action::AllIn(amount) => (5, *amount),
+        Action::Deal(card) => (6, card.value() as u32),
+    }
+}
+
+fn decode_action(kind: u8, value: u32) -> Result<Action, String> {
+    Ok(match kind {
+        0 => Action::Fold,
+        1 => Action::Check,
+        2 => Action::Call,
+        3 => Action::Bet(value),
+        4 => Action::Raise(value),
+        5 => Action::AllIn(value),
+        6 => Action::Deal(
+            u8::try_from(value)
+                .ok()
+                .and_then(Card::from_value)
+                .ok_or_else(|| format!("Invalid dealt card {}", value))?,
+        ),
+        _ => return Err(format!("Invalid action type {}", kind)),
+    })
+}
+
+/// A parsed strategy file
+///
+/// Borrows the input when the body is uncompressed, so nodes can be decoded
+/// lazily from a memory-mapped file.
+#[derive(Debug)]
+pub struct StrategyFile<'a> {
+    body: Cow<'a, [u8]>,
+    wide: bool,
+    board: Vec<Card>,
+    combos: [Vec<u16>; 2],
+    weights: [Vec<f32>; 2],
+    node_count: usize,
+    nodes_at: usize,
+    actions_at: usize,
+    probs_at: usize,
+    evs_at: usize,
+}
+
+impl<'a> StrategyFile<'a> {
+    /// Check the header and checksum and locate the body sections
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_LEN || &bytes[..8] != MAGIC {
+            return Err("Not a strategy file".to_string());
+        }
+        let mut header = Reader::new(&bytes[8..HEADER_LEN], "Strategy file");
+        let version = header.u16()?;
+        if version != FORMAT_VERSION {
+            return Err(format!("Unsupported strategy file version {}", version));
+        }
+        let flags = header.u16()?;
+        let checksum = header.u32()?;
+        let stored_len = header.u64()? as usize;
+        let raw_len = header.u64()? as usize;
+
+        let stored = bytes
+            .get(HEADER_LEN..HEADER_LEN.saturating_add(stored_len))
+            .ok_or("Strategy file is truncated")?;
+        if crc32fast::hash(stored) != checksum {
+            return Err("Strategy file checksum mismatch".to_string());
+        }
+
+        let body = if flags & FLAG_COMPRESSED != 0 {
+            // The header is not checksummed: preallocate no more than zlib
+            // can expand the stored body to, and stop reading past `raw_len`
+            let mut raw = Vec::with_capacity(raw_len.min(stored.len().saturating_mul(MAX_ZLIB_RATIO)));
+            ZlibDecoder::new(stored)
+                .take((raw_len as u64).saturating_add(1))
+                .read_to_end(&mut raw)
+                .map_err(|e| format!("Cannot decompress strategy file: {}", e))?;
+            Cow::Owned(raw)
+        } else {
+            Cow::Borrowed(stored)
+        };
+        if body.len() != raw_len {
+            return Err("Strategy file body has the wrong length".to_string());
+        }
+
+        let mut file = StrategyFile {
+            body,
+            wide: flags & FLAG_WIDE != 0,
+            board: Vec::new(),
+            combos: [Vec::new(), Vec::new()],
+            weights: [Vec::new(), Vec::new()],
+            node_count: 0,
+            nodes_at: 0,
+            actions_at: 0,
+            probs_at: 0,
+            evs_at: 0,
+        };
+        file.index()?;
+        Ok(file)
+    }
+
+    /// Read the fixed sections and record where the variable ones start
+    fn index(&mut self) -> Result<(), String> {
+        let mut r = Reader::new(&self.body, "Strategy file");
+        let board_len = r.u8()? as usize;
+        self.board = (0..board_len)
+            .map(|_| r.u8().and_then(|v| Card::from_value(v).ok_or_else(|| format!("Invalid card {}", v))))
+            .collect::<Result<_, _>>()?;
+        for player in 0..2 {
+            let n = r.u16()? as usize;
+            self.combos[player] = (0..n).map(|_| r.u16()).collect::<Result<_, _>>()?;
+            self.weights[player] = (0..n).map(|_| r.f32()).collect::<Result<_, _>>()?;
+        }
+
+        self.node_count = r.u32()? as usize;
+        self.nodes_at = r.pos();
+        r.skip(self.node_count, NODE_RECORD_LEN)?;
+        let action_count = r.u32()? as usize;
+        self.actions_at = r.pos();
+        r.skip(action_count, ACTION_RECORD_LEN)?;
+        let prob_count = r.u64()? as usize;
+        self.probs_at = r.pos();
+        r.skip(prob_count, if self.wide { 2 } else { 1 })?;
+        let ev_count = r.u32()? as usize;
+        self.evs_at = r.pos();
+        r.skip(ev_count, 4)?;
+        Ok(())
+    }
+
+    pub fn board(&self) -> &[Card] {
+        &self.board
+    }
+
+    pub fn combos(&self) -> &[Vec<u16>; 2] {
+        &self.combos
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /// Decode one node
+    pub fn node(&self, index: usize) -> Result<SolvedNode, String> {
+        if index >= self.node_count {
+            return Err(format!("Node {} out of range", index));
+        }
+        let mut r = Reader::at(&self.body, self.nodes_at + index * NODE_RECORD_LEN, "Strategy file");
+        let kind = match r.u8()? {
+            0 => SolvedNodeKind::Decision(Player::OOP),
+            1 => SolvedNodeKind::Decision(Player::IP),
+            2 => SolvedNodeKind::Chance,
+            3 => SolvedNodeKind::Terminal,
+            k => return Err(format!("Invalid node kind {}", k)),
+        };
+        let num_actions = r.u8()? as usize;
+        r.u16()?;
+        let pot = r.u32()?;
+        let stack = r.u32()?;
+        let first_action = r.u32()? as usize;
+        let first_prob = r.u64()? as usize;
+        let first_ev = r.u32()? as usize;
+
+        let mut actions = Vec::with_capacity(num_actions);
+        let mut children = Vec::with_capacity(num_actions);
+        let mut r = Reader::at(&self.body, self.actions_at + first_action * ACTION_RECORD_LEN, "Strategy file");
+        for _ in 0..num_actions {
+            let kind = r.u8()?;
+            let value = r.u32()?;
+            actions.push(decode_action(kind, value)?);
+            children.push(r.u32()?);
+        }
+
+        let num_combos = match kind {
+            SolvedNodeKind::Decision(Player::OOP) => self.combos[0].len(),
+            SolvedNodeKind::Decision(Player::IP) => self.combos[1].len(),
+            _ => 0,
+        };
+
+        let strategy = self.read_strategy(first_prob, num_combos, num_actions)?;
+        let mut r = Reader::at(&self.body, self.evs_at + first_ev * 4, "Strategy file");
+        let evs = (0..num_combos).map(|_| r.f32()).collect::<Result<_, _>>()?;
+
+        // Not stored: calling adds exactly the amount to call to the pot
+        let to_call = match actions.iter().position(|a| *a == Action::Call) {
+            Some(i) => self.node_pot(children[i] as usize)?.saturating_sub(pot),
+            None => 0,
+        };
+
+        Ok(SolvedNode {
+            kind,
+            pot,
+            stack,
+            to_call,
+            actions,
+            children,
+            strategy,
+            evs,
+        })
+    }
+
+    /// Pot of a node, without decoding the rest of it
+    fn node_pot(&self, index: usize) -> Result<u32, String> {
+        if index >= self.node_count {
+            return Err(format!("Node {} out of range", index));
+        }
+        Reader::at(&self.body, self.nodes_at + index * NODE_RECORD_LEN + 4, "Strategy file").u32()
+    }
+
+    /// Follow a path from the root, tracking dealt cards and each combo's reach
+    pub fn find(&self, path: &[PathStep]) -> Result<PathNode, String> {
+        let mut node = self.node(0)?;
+        let mut steps = Vec::with_capacity(path.len());
+        let mut board = self.board.clone();
+        let mut weights = self
+            .weights
+            .clone()
+            .map(|w| w.iter().map(|&x| x as f64).collect::<Vec<f64>>());
+
+        for (depth, step) in path.iter().enumerate() {
+            let index = step
+                .find_in(&node)
+                .ok_or_else(|| format!("Action {} ({}) is not available in the tree", depth + 1, step))?;
+            node.advance(index, &self.combos, &mut weights, &mut board);
+            steps.push(PathStep::of(&node.actions[index], &node));
+            node = self.node(node.children[index] as usize)?;
+        }
+
+        Ok(PathNode {
+            node,
+            path: steps,
+            board,
+            weights,
+        })
+    }
+
+    /// Dequantise a node's strategy, renormalising each combo
+    fn read_strategy(&self, first: usize, combos: usize, actions: usize) -> Result<Vec<f32>, String> {
+        let width = if self.wide { 2 } else { 1 };
+        // `first` comes from the file, so it may point anywhere
+        let pos = first.saturating_mul(width).saturating_add(self.probs_at);
+        let mut r = Reader::at(&self.body, pos, "Strategy file");
+        let mut strategy = Vec::with_capacity(combos * actions);
+        for _ in 0..combos {
+            let raw = (0..actions)
+                .map(|_| if self.wide { r.u16().map(|v| v as f32) } else { r.u8().map(|v| v as f32) })
+                .collect::<Result<Vec<f32>, _>>()?;
+            let total: f32 = raw.iter().sum();
+            if total > 0.0 {
+                strategy.extend(raw.iter().map(|v| v / total));
+            } else {
+                strategy.extend(std::iter::repeat_n(1.0 / actions as f32, actions));
+            }
+        }
+        Ok(strategy)
+    }
+
+    /// Decode the whole tree
+    pub fn to_tree(&self) -> Result<SolvedTree, String> {
+        Ok(SolvedTree {
+            board: self.board.clone(),
+            combos: self.combos.clone(),
+            weights: self.weights.clone(),
+            nodes: (0..self.node_count).map(|i| self.node(i)).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// A node reached by following a path from the root
+#[derive(Debug, Clone)]
+pub struct PathNode {
+    pub node: SolvedNode,
+    /// Steps of the actions actually taken, with sizes as available in the tree
+    pub path: Vec<PathStep>,
+    /// Board including the cards dealt along the path
+    pub board: Vec<Card>,
+    /// Range weight times own reach probability of each combo, indexed [OOP, IP]
+    pub weights: [Vec<f64>; 2],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::cards::parse_board;
+    use crate::solver::solved_tree::parse_path;
+
+    /// Root where OOP checks or bets 50 into a 100 pot, IP then checks or folds
+    fn tree() -> SolvedTree {
+        SolvedTree {
+            board: parse_board("Ah Kd Qc").unwrap(),
+            combos: [vec![0, 5, 9], vec![100, 200]],
+            weights: [vec![1.0, 0.5, 1.0], vec![1.0, 0.25]],
+            nodes: vec![
+                SolvedNode {
+                    kind: SolvedNodeKind::Decision(Player::OOP),
+                    pot: 100,
+                    stack: 9950,
+                    to_call: 0,
+                    actions: vec![Action::Check, Action::Bet(50)],
+                    children: vec![1, 2],
+                    strategy: vec![0.25, 0.75, 1.0, 0.0, 0.5, 0.5],
+                    evs: vec![1.5, -0.25, 3.0],
+                },
+                SolvedNode {
+                    kind: SolvedNodeKind::Terminal,
+                    pot: 100,
+                    stack: 9950,
+                    to_call: 0,
+                    actions: vec![],
+                    children: vec![],
+                    strategy: vec![],
+                    evs: vec![],
+                },
+                SolvedNode {
+                    kind: SolvedNodeKind::Decision(Player::IP),
+                    pot: 150,
+                    stack: 9925,
+                    to_call: 50,
+                    actions: vec![Action::Fold, Action::Call],
+                    children: vec![3, 4],
+                    strategy: vec![0.1, 0.9, 0.6, 0.4],
+                    evs: vec![0.0, 2.0],
+                },
+                SolvedNode {
+                    kind: SolvedNodeKind::Terminal,
+                    pot: 150,
+                    stack: 9925,
+                    to_call: 0,
+                    actions: vec![],
+                    children: vec![],
+                    strategy: vec![],
+                    evs: vec![],
+                },
+                SolvedNode {
+                    kind: SolvedNodeKind::Chance,
+                    pot: 200,
+                    stack: 9900,
+                    to_call: 0,
+                    actions: vec![Action::Deal("2s".parse().unwrap())],
+                    children: vec![5],
+                    strategy: vec![],
+                    evs: vec![],
+                },
+                SolvedNode {
+                    kind: SolvedNodeKind::Terminal,
+                    pot: 200,
+                    stack: 9900,
+                    to_call: 0,
+                    actions: vec![],
+                    children: vec![],
+                    strategy: vec![],
+                    evs: vec![],
+                },
+            ],
+        }
+    }
+
+    fn assert_close(a: &SolvedTree, b: &SolvedTree, tolerance: f32) {
+        assert_eq!(a.board, b.board);
+        assert_eq!((&a.combos, &a.weights), (&b.combos, &b.weights));
+        for (x, y) in a.nodes.iter().zip(&b.nodes) {
+            assert_eq!((x.kind, x.pot, &x.actions, &x.children), (y.kind, y.pot, &y.actions, &y.children));
+            assert_eq!((x.stack, &x.evs), (y.stack, &y.evs));
+            for (p, q) in x.strategy.iter().zip(&y.strategy) {
+                assert!((p - q).abs() < tolerance, "{} vs {}", p, q);
+            }
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_all_options() {
+        let tree = tree();
+        for compress in [false, true] {
+            for wide in [false, true] {
+                let bytes = write(&tree, WriteOptions { compress, wide }).unwrap();
+                let decoded = StrategyFile::parse(&bytes).unwrap().to_tree().unwrap();
+                assert_close(&tree, &decoded, if wide { 1e-4 } else { 1e-2 });
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_single_node() {
+        let bytes = write(&tree(), WriteOptions::default()).unwrap();
+        let file = StrategyFile::parse(&bytes).unwrap();
+        assert_eq!(file.node_count(), 6);
+
+        let node = file.node(2).unwrap();
+        assert_eq!(node.kind, SolvedNodeKind::Decision(Player::IP));
+        assert_eq!(node.actions, vec![Action::Fold, Action::Call]);
+        assert!((node.combo_strategy(1)[0] - 0.6).abs() < 1e-4);
+        assert!(file.node(6).is_err());
+    }
+
+    #[test]
+    fn test_find_tracks_reach_and_board() {
+        let bytes = write(&tree(), WriteOptions::default()).unwrap();
+        let file = StrategyFile::parse(&bytes).unwrap();
+
+        let found = file.find(&parse_path("b50/c/2s").unwrap()).unwrap();
+        assert_eq!(found.node.kind, SolvedNodeKind::Terminal);
+        assert_eq!(found.board.len(), 4);
+        // OOP bet with weights 0.75, 0, 0.5; IP called with 0.9, 0.4
+        let expected = [vec![0.75, 0.0, 0.5], vec![0.9, 0.1]];
+        for (weights, expected) in found.weights.iter().zip(&expected) {
+            for (w, e) in weights.iter().zip(expected) {
+                assert!((w - e).abs() < 1e-3, "{} vs {}", w, e);
+            }
+        }
+
+        assert!(file.find(&parse_path("x/c").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_corrupt_files() {
+        let mut bytes = write(&tree(), WriteOptions::default()).unwrap();
+        assert!(StrategyFile::parse(&bytes[..20]).is_err());
+
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        assert_eq!(
+            StrategyFile::parse(&bytes).unwrap_err(),
+            "Strategy file checksum mismatch"
+        );
+
+        bytes[8] = 99;
+        assert!(StrategyFile::parse(&bytes).unwrap_err().contains("version"));
+
+        // An uncompressed length the body can't have is not allocated
+        let options = WriteOptions { compress: true, wide: false };
+        let mut bytes = write(&tree(), options).unwrap();
+        bytes[24..32].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            StrategyFile::parse(&bytes).unwrap_err(),
+            "Strategy file body has the wrong length"
+        );
+    }
+}
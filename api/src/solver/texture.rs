@@ -0,0 +1,183 @@
+//! Board textures and canonical flop subsets
+//!
+//! Flops that differ only by a permutation of suits are strategically
+//! identical, leaving 1755 canonical flops out of 22100. Named subsets filter
+//! those by texture for batch solving.
+
+use std::collections::BTreeSet;
+
+use super::cards::{Card, Rank, Suit};
+use super::isomorphism::SuitPermutation;
+
+/// Number of strategically distinct flops
+pub const NUM_CANONICAL_FLOPS: usize = 1755;
+
+/// Suit distribution of a board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuitTexture {
+    /// No two cards share a suit
+    Rainbow,
+    /// At most two cards of any suit
+    TwoTone,
+    /// Three or more cards of one suit
+    Monotone,
+}
+
+impl SuitTexture {
+    /// Texture label as used in batch reports
+    pub fn label(self) -> &'static str {
+        match self {
+            SuitTexture::Rainbow => "rainbow",
+            SuitTexture::TwoTone => "two_tone",
+            SuitTexture::Monotone => "monotone",
+        }
+    }
+}
+
+/// Coarse texture of a board used to group batch results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardTexture {
+    pub suits: SuitTexture,
+    pub paired: bool,
+    pub high: Rank,
+}
+
+impl BoardTexture {
+    /// Classify a (non-empty) board
+    pub fn of(board: &[Card]) -> Self {
+        let max_suit = Suit::all()
+            .iter()
+            .map(|&s| board.iter().filter(|c| c.suit() == s).count())
+            .max()
+            .unwrap_or(0);
+        let suits = match max_suit {
+            0 | 1 => SuitTexture::Rainbow,
+            2 => SuitTexture::TwoTone,
+            _ => SuitTexture::Monotone,
+        };
+
+        let ranks: BTreeSet<Rank> = board.iter().map(|c| c.rank()).collect();
+        BoardTexture {
+            suits,
+            paired: ranks.len() < board.len(),
+            high: ranks.iter().next_back().copied().unwrap_or(Rank::Two),
+        }
+    }
+
+    /// Labels this board is reported under: suits, pairing and high card
+    pub fn labels(&self) -> Vec<String> {
+        vec![
+            self.suits.label().to_string(),
+            if self.paired { "paired" } else { "unpaired" }.to_string(),
+            format!("{}-high", self.high),
+        ]
+    }
+}
+
+/// All 1755 canonical flops, highest cards first
+pub fn canonical_flops() -> Vec<[Card; 3]> {
+    let mut flops = BTreeSet::new();
+    for a in 0..52u8 {
+        for b in (a + 1)..52 {
+            for c in (b + 1)..52 {
+                let cards = [a, b, c].map(|v| Card::from_value(v).unwrap());
+                flops.insert(canonicalize(cards));
+            }
+        }
+    }
+
+    let mut flops: Vec<[Card; 3]> = flops.into_iter().collect();
+    flops.reverse();
+    flops
+}
+
+/// Number of boards suit-isomorphic to `board`, itself included
+///
+/// 24 for a rainbow flop, 12 for two-tone or paired and 4 for monotone or
+/// trips, so weighting canonical flops by it counts every raw flop once.
+pub fn isomorphism_count(board: &[Card]) -> usize {
+    let boards: BTreeSet<Vec<Card>> = SuitPermutation::all()
+        .iter()
+        .map(|permutation| {
+            let mut mapped = permutation.apply_board(board);
+            mapped.sort_unstable();
+            mapped
+        })
+        .collect();
+    boards.len()
+}
+
+/// Canonical suit relabelling of a flop, cards sorted high to low
+fn canonicalize(cards: [Card; 3]) -> [Card; 3] {
+    let mapped = SuitPermutation::canonical_for(&cards).apply_board(&cards);
+    [mapped[0], mapped[1], mapped[2]]
+}
+
+/// Named flop subset: `all`, `rainbow`, `two_tone`, `monotone` or `paired`
+pub fn flop_subset(name: &str) -> Result<Vec<[Card; 3]>, String> {
+    let filter: fn(&BoardTexture) -> bool = match name {
+        "all" => |_| true,
+        "rainbow" => |t| t.suits == SuitTexture::Rainbow,
+        "two_tone" => |t| t.suits == SuitTexture::TwoTone,
+        "monotone" => |t| t.suits == SuitTexture::Monotone,
+        "paired" => |t| t.paired,
+        _ => {
+            return Err(format!(
+                "Unknown flop subset '{}' (expected all, rainbow, two_tone, monotone or paired)",
+                name
+            ))
+        }
+    };
+
+    Ok(canonical_flops()
+        .into_iter()
+        .filter(|flop| filter(&BoardTexture::of(flop)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::cards::parse_board;
+
+    #[test]
+    fn test_canonical_flops() {
+        let flops = canonical_flops();
+        assert_eq!(flops.len(), NUM_CANONICAL_FLOPS);
+
+        // Suit-isomorphic flops map to the same canonical flop
+        let a = canonicalize(parse_board("Ah Kh 2c").unwrap().try_into().unwrap());
+        let b = canonicalize(parse_board("2d Ks As").unwrap().try_into().unwrap());
+        assert_eq!(a, b);
+
+        let raw: usize = flops.iter().map(|flop| isomorphism_count(flop)).sum();
+        assert_eq!(raw, 22100);
+        assert_eq!(isomorphism_count(&parse_board("Ah Kd Qc").unwrap()), 24);
+        assert_eq!(isomorphism_count(&parse_board("7s 7d 2c").unwrap()), 12);
+        assert_eq!(isomorphism_count(&parse_board("Jh 8h 3h").unwrap()), 4);
+    }
+
+    #[test]
+    fn test_board_texture() {
+        let texture = BoardTexture::of(&parse_board("Ah Kd Qc").unwrap());
+        assert_eq!(texture.suits, SuitTexture::Rainbow);
+        assert_eq!(texture.labels(), vec!["rainbow", "unpaired", "A-high"]);
+
+        let texture = BoardTexture::of(&parse_board("9h 9s 4h").unwrap());
+        assert_eq!(texture.labels(), vec!["two_tone", "paired", "9-high"]);
+
+        let texture = BoardTexture::of(&parse_board("Jc 8c 3c").unwrap());
+        assert_eq!(texture.suits, SuitTexture::Monotone);
+    }
+
+    #[test]
+    fn test_flop_subsets() {
+        let counts: Vec<usize> = ["rainbow", "two_tone", "monotone"]
+            .iter()
+            .map(|name| flop_subset(name).unwrap().len())
+            .collect();
+        assert_eq!(counts.iter().sum::<usize>(), NUM_CANONICAL_FLOPS);
+        assert_eq!(flop_subset("monotone").unwrap().len(), 286);
+        assert!(flop_subset("turns").is_err());
+    }
+}
@@ -0,0 +1,390 @@
+//! Value network training
+//!
+//! Fits the MLP of [`ValueNetwork`] to solver-generated samples on the CPU:
+//! masked mean squared error on the pot shares, minibatch gradients by
+//! backpropagation and Adam updates. A slice of the samples is held out to
+//! report validation loss after every epoch.
+
+use super::datagen::Sample;
+use super::rng::Rng;
+use super::value_net::{forward_layers, Dense, ValueNetwork, INPUT_SIZE, OUTPUT_SIZE};
+
+/// Training hyperparameters
+#[derive(Debug, Clone)]
+pub struct TrainConfig {
+    /// Hidden layer sizes; empty trains a linear model
+    pub hidden: Vec<usize>,
+    pub epochs: usize,
+    pub batch_size: usize,
+    pub learning_rate: f64,
+    /// Share of the samples held out for validation
+    pub validation_fraction: f64,
+    /// Seeds weight initialisation, the validation split and shuffling
+    pub seed: u64,
+}
+
+impl Default for TrainConfig {
+    fn default() -> Self {
+        Self {
+            hidden: vec![256, 256],
+            epochs: 20,
+            batch_size: 32,
+            learning_rate: 1e-3,
+            validation_fraction: 0.1,
+            seed: 0,
+        }
+    }
+}
+
+/// Losses after one epoch
+#[derive(Debug, Clone, Copy)]
+pub struct EpochReport {
+    /// 1-based epoch number
+    pub epoch: usize,
+    /// Mean loss over the epoch's training batches
+    pub train_loss: f64,
+    /// Loss on the held-out samples (none if nothing was held out)
+    pub validation_loss: Option<f64>,
+}
+
+/// Train a value network, calling `on_epoch` after each epoch
+pub fn train(
+    samples: &[Sample],
+    config: &TrainConfig,
+    mut on_epoch: impl FnMut(&EpochReport),
+) -> Result<(ValueNetwork, Vec<EpochReport>), String> {
+    if samples.is_empty() {
+        return Err("No training samples".to_string());
+    }
+    if let Some(sample) = samples
+        .iter()
+        .find(|s| s.input.len() != INPUT_SIZE || s.target.len() != OUTPUT_SIZE || s.mask.len() != OUTPUT_SIZE)
+    {
+        return Err(format!(
+            "Sample maps {} inputs to {} outputs (expected {} to {})",
+            sample.input.len(),
+            sample.target.len(),
+            INPUT_SIZE,
+            OUTPUT_SIZE
+        ));
+    }
+
+    let mut rng = Rng::new(config.seed);
+    let mut order: Vec<usize> = (0..samples.len()).collect();
+    rng.shuffle(&mut order);
+    let held_out = ((samples.len() as f64 * config.validation_fraction) as usize).min(samples.len() - 1);
+    let (validation, mut training) = {
+        let (v, t) = order.split_at(held_out);
+        (v.to_vec(), t.to_vec())
+    };
+
+    let mut sizes = vec![INPUT_SIZE];
+    sizes.extend(&config.hidden);
+    sizes.push(OUTPUT_SIZE);
+    let mut network = ValueNetwork::new(init_layers(&sizes, &mut rng))?;
+    let mut adam = Adam::new(&network.layers, config.learning_rate);
+
+    let mut reports = Vec::with_capacity(config.epochs);
+    for epoch in 1..=config.epochs {
+        rng.shuffle(&mut training);
+        let mut total = 0.0;
+        let mut batches = 0;
+        for batch in training.chunks(config.batch_size.max(1)) {
+            let examples: Vec<Example> = batch.iter().map(|&i| Example::of(&samples[i])).collect();
+            total += batch_step(&mut network.layers, &mut adam, &examples);
+            batches += 1;
+        }
+
+        let report = EpochReport {
+            epoch,
+            train_loss: total / batches.max(1) as f64,
+            validation_loss: (!validation.is_empty()).then(|| {
+                let held: Vec<&Sample> = validation.iter().map(|&i| &samples[i]).collect();
+                loss(&network, &held)
+            }),
+        };
+        on_epoch(&report);
+        reports.push(report);
+    }
+    Ok((network, reports))
+}
+
+/// Mean squared error over the unmasked targets of some samples
+pub fn loss(network: &ValueNetwork, samples: &[&Sample]) -> f64 {
+    let examples: Vec<Example> = samples.iter().map(|s| Example::of(s)).collect();
+    examples_loss(&network.layers, &examples)
+}
+
+/// One training target for an MLP
+pub(crate) struct Example<'a> {
+    pub input: &'a [f32],
+    pub target: &'a [f32],
+    /// Targets that count towards the loss
+    pub mask: &'a [bool],
+    /// Weight of the example in the loss
+    pub weight: f32,
+}
+
+impl<'a> Example<'a> {
+    fn of(sample: &'a Sample) -> Self {
+        Example {
+            input: &sample.input,
+            target: &sample.target,
+            mask: &sample.mask,
+            weight: 1.0,
+        }
+    }
+
+    /// Weight of the example times its number of unmasked targets
+    fn mass(&self) -> f64 {
+        self.weight as f64 * self.mask.iter().filter(|&&m| m).count() as f64
+    }
+}
+
+/// Weighted mean squared error over the unmasked targets of some examples
+pub(crate) fn examples_loss(layers: &[Dense], examples: &[Example]) -> f64 {
+    let mass: f64 = examples.iter().map(Example::mass).sum();
+    if mass <= 0.0 {
+        return 0.0;
+    }
+    let total: f64 = examples
+        .iter()
+        .map(|example| {
+            let output = forward_layers(layers, example.input);
+            let error: f64 = output
+                .iter()
+                .zip(example.target)
+                .zip(example.mask)
+                .filter(|(_, &m)| m)
+                .map(|((y, t), _)| ((y - t) as f64).powi(2))
+                .sum();
+            example.weight as f64 * error
+        })
+        .sum();
+    total / mass
+}
+
+/// One Adam step on a minibatch; returns the batch loss before the step
+pub(crate) fn batch_step(layers: &mut [Dense], adam: &mut Adam, batch: &[Example]) -> f64 {
+    let mass: f64 = batch.iter().map(Example::mass).sum();
+    if mass <= 0.0 {
+        return 0.0;
+    }
+    let mut grads: Vec<Dense> = layers.iter().map(|l| Dense::zeros(l.inputs, l.outputs)).collect();
+    let loss = batch.iter().map(|example| backward(layers, example, mass, &mut grads)).sum();
+    adam.step(layers, &grads);
+    loss
+}
+
+/// He-initialised layers with the given sizes, input first
+pub(crate) fn init_layers(sizes: &[usize], rng: &mut Rng) -> Vec<Dense> {
+    sizes.windows(2).map(|w| he_init(w[0], w[1], rng)).collect()
+}
+
+/// He-initialised layer for ReLU networks
+fn he_init(inputs: usize, outputs: usize, rng: &mut Rng) -> Dense {
+    let scale = (2.0 / inputs as f64).sqrt();
+    let mut layer = Dense::zeros(inputs, outputs);
+    for w in &mut layer.weights {
+        *w = (rng.normal() * scale) as f32;
+    }
+    layer
+}
+
+/// Add one example's gradient of the batch loss to `grads`
+///
+/// The batch loss is the weighted squared error over unmasked targets
+/// divided by `mass`, the batch's total weight of unmasked targets. Returns
+/// this example's share of it.
+fn backward(layers: &[Dense], example: &Example, mass: f64, grads: &mut [Dense]) -> f64 {
+    // Inputs to every layer, after ReLU for the hidden ones
+    let mut activations = vec![example.input.to_vec()];
+    for (i, layer) in layers.iter().enumerate() {
+        let mut x = layer.forward(activations.last().unwrap());
+        if i + 1 < layers.len() {
+            x.iter_mut().for_each(|v| *v = v.max(0.0));
+        }
+        activations.push(x);
+    }
+
+    let output = activations.pop().unwrap();
+    let scale = example.weight / mass as f32;
+    let mut loss = 0.0;
+    let mut delta: Vec<f32> = output
+        .iter()
+        .zip(example.target)
+        .zip(example.mask)
+        .map(|((y, t), &m)| {
+            if !m {
+                return 0.0;
+            }
+            loss += ((y - t) as f64).powi(2);
+            2.0 * (y - t) * scale
+        })
+        .collect();
+
+    for (l, layer) in layers.iter().enumerate().rev() {
+        let input = &activations[l];
+        let grad = &mut grads[l];
+        for (o, &d) in delta.iter().enumerate() {
+            if d == 0.0 {
+                continue;
+            }
+            grad.biases[o] += d;
+            let row = &mut grad.weights[o * layer.inputs..(o + 1) * layer.inputs];
+            for (g, &x) in row.iter_mut().zip(input) {
+                *g += d * x;
+            }
+        }
+        if l == 0 {
+            break;
+        }
+
+        // Through the weights, then the ReLU of the previous layer
+        let mut previous = vec![0.0; layer.inputs];
+        for (o, &d) in delta.iter().enumerate() {
+            if d == 0.0 {
+                continue;
+            }
+            let row = &layer.weights[o * layer.inputs..(o + 1) * layer.inputs];
+            for (p, &w) in previous.iter_mut().zip(row) {
+                *p += d * w;
+            }
+        }
+        for (p, &x) in previous.iter_mut().zip(input) {
+            if x <= 0.0 {
+                *p = 0.0;
+            }
+        }
+        delta = previous;
+    }
+    loss * scale as f64
+}
+
+/// Adam optimiser state
+pub(crate) struct Adam {
+    learning_rate: f64,
+    step: i32,
+    /// First and second moment estimates, shaped like the layers
+    m: Vec<Dense>,
+    v: Vec<Dense>,
+}
+
+impl Adam {
+    const BETA1: f64 = 0.9;
+    const BETA2: f64 = 0.999;
+    const EPSILON: f64 = 1e-8;
+
+    pub(crate) fn new(layers: &[Dense], learning_rate: f64) -> Self {
+        let zeros = || layers.iter().map(|l| Dense::zeros(l.inputs, l.outputs)).collect();
+        Adam {
+            learning_rate,
+            step: 0,
+            m: zeros(),
+            v: zeros(),
+        }
+    }
+
+    fn step(&mut self, layers: &mut [Dense], grads: &[Dense]) {
+        self.step += 1;
+        let lr = self.learning_rate * (1.0 - Self::BETA2.powi(self.step)).sqrt() / (1.0 - Self::BETA1.powi(self.step));
+        for (((layer, grad), m), v) in layers.iter_mut().zip(grads).zip(&mut self.m).zip(&mut self.v) {
+            let params = layer.weights.iter_mut().chain(layer.biases.iter_mut());
+            let grads = grad.weights.iter().chain(&grad.biases);
+            let moments = m.weights.iter_mut().chain(m.biases.iter_mut());
+            let squares = v.weights.iter_mut().chain(v.biases.iter_mut());
+            for (((param, &g), m), v) in params.zip(grads).zip(moments).zip(squares) {
+                let g = g as f64;
+                *m = (Self::BETA1 * *m as f64 + (1.0 - Self::BETA1) * g) as f32;
+                *v = (Self::BETA2 * *v as f64 + (1.0 - Self::BETA2) * g * g) as f32;
+                *param -= (lr * *m as f64 / ((*v as f64).sqrt() + Self::EPSILON)) as f32;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::value_net::NUM_COMBOS;
+
+    /// Index of the pot ratio input
+    const POT_INPUT: usize = INPUT_SIZE - 2 - 2 * NUM_COMBOS;
+
+    /// Samples whose first combo's share is a linear function of one input
+    fn linear_samples(n: usize) -> Vec<Sample> {
+        let mut rng = Rng::new(11);
+        (0..n)
+            .map(|_| {
+                let x = rng.next_f64() as f32;
+                let mut input = vec![0.0; INPUT_SIZE];
+                input[POT_INPUT] = x;
+                let mut target = vec![0.0; OUTPUT_SIZE];
+                target[0] = 0.2 + 0.6 * x;
+                target[NUM_COMBOS] = 0.8 - 0.6 * x;
+                let mut mask = vec![false; OUTPUT_SIZE];
+                mask[0] = true;
+                mask[NUM_COMBOS] = true;
+                Sample { input, target, mask }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_gradient_matches_finite_difference() {
+        let samples = linear_samples(1);
+        let mut rng = Rng::new(2);
+        let layers = vec![he_init(INPUT_SIZE, 3, &mut rng), he_init(3, OUTPUT_SIZE, &mut rng)];
+        let mut network = ValueNetwork::new(layers).unwrap();
+        network.layers[0].biases = vec![0.1, 0.2, 0.3];
+
+        let mut grads: Vec<Dense> = network.layers.iter().map(|l| Dense::zeros(l.inputs, l.outputs)).collect();
+        backward(&network.layers, &Example::of(&samples[0]), 2.0, &mut grads);
+
+        // Loss of one sample is the mean over its two unmasked targets
+        let eps = 1e-2;
+        for (l, w) in [(0, POT_INPUT), (0, 2 * INPUT_SIZE + POT_INPUT), (1, 0), (1, NUM_COMBOS * 3 + 1)] {
+            let original = network.layers[l].weights[w];
+            network.layers[l].weights[w] = original + eps;
+            let up = loss(&network, &[&samples[0]]);
+            network.layers[l].weights[w] = original - eps;
+            let down = loss(&network, &[&samples[0]]);
+            network.layers[l].weights[w] = original;
+
+            let numeric = (up - down) / (2.0 * eps as f64);
+            let analytic = grads[l].weights[w] as f64;
+            assert!((numeric - analytic).abs() < 1e-3, "layer {} weight {}: {} vs {}", l, w, numeric, analytic);
+        }
+    }
+
+    #[test]
+    fn test_train_reduces_loss() {
+        let samples = linear_samples(40);
+        let config = TrainConfig {
+            hidden: vec![4],
+            epochs: 30,
+            batch_size: 8,
+            learning_rate: 1e-2,
+            validation_fraction: 0.25,
+            seed: 1,
+        };
+        let mut seen = 0;
+        let (network, reports) = train(&samples, &config, |_| seen += 1).unwrap();
+        assert_eq!(seen, 30);
+
+        let first = reports[0].validation_loss.unwrap();
+        let last = reports[29].validation_loss.unwrap();
+        assert!(last < first / 4.0, "validation loss {} -> {}", first, last);
+        assert!(last < 0.01, "validation loss {}", last);
+        assert!(ValueNetwork::parse(&network.to_bytes()).is_ok());
+    }
+
+    #[test]
+    fn test_train_rejects_bad_samples() {
+        let config = TrainConfig::default();
+        assert!(train(&[], &config, |_| {}).is_err());
+        let mut samples = linear_samples(2);
+        samples[1].target.pop();
+        assert!(train(&samples, &config, |_| {}).is_err());
+    }
+}
@@ -1,3 +1,66 @@
 //! Utility functions for the solver
 
-// Placeholder for future utility functions
+/// Chip amount in hundredths of a big blind (centi-bb)
+///
+/// All amounts inside the solver use this unit so that fractional sizes
+/// (2.5bb opens, 33% of 5bb) are represented exactly to 0.01bb.
+pub type Chips = u32;
+
+/// Number of chips in one big blind
+pub const CHIPS_PER_BB: Chips = 100;
+
+/// Largest amount the API accepts, in bb
+///
+/// A pot and both stacks at this size still add up to well under
+/// `Chips::MAX`, so chip arithmetic can't overflow.
+pub const MAX_BB: f64 = 1_000_000.0;
+
+/// Convert big blinds to chips (rounded to the nearest centi-bb)
+pub fn bb_to_chips(bb: f64) -> Chips {
+    (bb * CHIPS_PER_BB as f64).round().max(0.0) as Chips
+}
+
+/// Convert chips to big blinds
+pub fn chips_to_bb(chips: Chips) -> f64 {
+    chips as f64 / CHIPS_PER_BB as f64
+}
+
+/// Parse a positive big-blind amount from the API into chips
+pub fn parse_bb_amount(bb: f64, name: &str) -> Result<Chips, String> {
+    if !bb.is_finite() || bb <= 0.0 {
+        return Err(format!("{} must be a positive number of bb, got {}", name, bb));
+    }
+    if bb > MAX_BB {
+        return Err(format!("{} must be at most {}bb, got {}", name, MAX_BB, bb));
+    }
+
+    let chips = bb_to_chips(bb);
+    if chips == 0 {
+        return Err(format!("{} must be at least 0.01bb, got {}", name, bb));
+    }
+    Ok(chips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chip_conversion() {
+        assert_eq!(bb_to_chips(2.5), 250);
+        assert_eq!(bb_to_chips(100.0), 10000);
+        assert_eq!(bb_to_chips(0.333), 33);
+        assert_eq!(chips_to_bb(165), 1.65);
+    }
+
+    #[test]
+    fn test_parse_bb_amount() {
+        assert_eq!(parse_bb_amount(5.5, "Pot"), Ok(550));
+        assert!(parse_bb_amount(0.0, "Pot").is_err());
+        assert!(parse_bb_amount(-1.0, "Pot").is_err());
+        assert!(parse_bb_amount(0.001, "Pot").is_err());
+        assert!(parse_bb_amount(f64::NAN, "Pot").is_err());
+        assert_eq!(parse_bb_amount(MAX_BB, "Pot"), Ok(100_000_000));
+        assert!(parse_bb_amount(21_474_837.0, "Pot").is_err());
+    }
+}
@@ -0,0 +1,307 @@
+//! Value network for depth-limited solving
+//!
+//! A fully connected network (ReLU hidden layers, linear output) evaluated
+//! on the CPU. Given the public state at a street boundary and both players'
+//! ranges it predicts, for every combo, the share of the pot that combo
+//! expects to win from there on. Depth-limited trees use it at their leaves.
+//!
+//! Weights file format (little-endian):
+//! magic `DPCFRVNN`, u16 format version, u16 feature version, u32 layer
+//! count, then per layer u32 inputs, u32 outputs, `outputs * inputs` f32
+//! weights (row per output) and `outputs` f32 biases.
+
+use std::path::Path;
+
+use super::bytes::Reader;
+use super::cards::{Card, Combo};
+use super::features::FeatureEncoder;
+use super::utils::Chips;
+
+pub use super::features::{FEATURE_VERSION, NUM_COMBOS};
+
+/// Magic bytes at the start of a weights file
+pub const MAGIC: &[u8; 8] = b"DPCFRVNN";
+
+/// Version of the weights file layout
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Input size in the current feature version: board, pot and stack ratios,
+/// both ranges
+pub const INPUT_SIZE: usize = super::features::VALUE_INPUT_SIZE;
+
+/// Output size: a value per combo for each player [OOP, IP]
+pub const OUTPUT_SIZE: usize = 2 * NUM_COMBOS;
+
+/// A fully connected layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dense {
+    pub inputs: usize,
+    pub outputs: usize,
+    /// Row-major, one row of `inputs` weights per output
+    pub weights: Vec<f32>,
+    pub biases: Vec<f32>,
+}
+
+impl Dense {
+    /// Layer with all weights and biases zero
+    pub fn zeros(inputs: usize, outputs: usize) -> Self {
+        Dense {
+            inputs,
+            outputs,
+            weights: vec![0.0; inputs * outputs],
+            biases: vec![0.0; outputs],
+        }
+    }
+
+    /// Affine transform of one input vector
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        self.weights
+            .chunks_exact(self.inputs)
+            .zip(&self.biases)
+            .map(|(row, bias)| bias + row.iter().zip(input).map(|(w, x)| w * x).sum::<f32>())
+            .collect()
+    }
+}
+
+/// Value network
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueNetwork {
+    pub layers: Vec<Dense>,
+    /// Version of the input encoding the network was trained on
+    pub feature_version: u16,
+}
+
+impl ValueNetwork {
+    /// Network on the current feature version from its layers, checking the
+    /// shapes chain up
+    pub fn new(layers: Vec<Dense>) -> Result<Self, String> {
+        Self::with_feature_version(layers, FEATURE_VERSION)
+    }
+
+    /// Network trained on an older (or the current) feature version
+    pub fn with_feature_version(layers: Vec<Dense>, feature_version: u16) -> Result<Self, String> {
+        let input_size = FeatureEncoder::new(feature_version)?.value_input_size();
+        let (Some(first), Some(last)) = (layers.first(), layers.last()) else {
+            return Err("A value network needs at least one layer".to_string());
+        };
+        if first.inputs != input_size || last.outputs != OUTPUT_SIZE {
+            return Err(format!(
+                "Value network maps {} inputs to {} outputs (expected {} to {} for feature version {})",
+                first.inputs, last.outputs, input_size, OUTPUT_SIZE, feature_version
+            ));
+        }
+        for (i, layer) in layers.iter().enumerate() {
+            if layer.weights.len() != layer.inputs * layer.outputs || layer.biases.len() != layer.outputs {
+                return Err(format!("Layer {} has inconsistent weight sizes", i));
+            }
+            if let Some(next) = layers.get(i + 1) {
+                if next.inputs != layer.outputs {
+                    return Err(format!(
+                        "Layer {} outputs {} values but layer {} takes {}",
+                        i,
+                        layer.outputs,
+                        i + 1,
+                        next.inputs
+                    ));
+                }
+            }
+        }
+        Ok(ValueNetwork {
+            layers,
+            feature_version,
+        })
+    }
+
+    /// Load weights from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("Cannot read value network '{}': {}", path.display(), e))?;
+        Self::parse(&bytes)
+    }
+
+    /// Parse a weights file
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader::new(bytes, "Value network file");
+        if reader.take(8)? != MAGIC {
+            return Err("Not a value network file".to_string());
+        }
+        let version = reader.u16()?;
+        if version != FORMAT_VERSION {
+            return Err(format!("Unsupported value network format version {}", version));
+        }
+        let feature_version = reader.u16()?;
+        FeatureEncoder::new(feature_version)
+            .map_err(|e| format!("Value network was trained on features it cannot be fed: {}", e))?;
+
+        let num_layers = reader.u32()? as usize;
+        let mut layers = Vec::with_capacity(num_layers.min(64));
+        for _ in 0..num_layers {
+            let inputs = reader.u32()? as usize;
+            let outputs = reader.u32()? as usize;
+            let weights = reader.f32s(inputs.saturating_mul(outputs))?;
+            let biases = reader.f32s(outputs)?;
+            layers.push(Dense {
+                inputs,
+                outputs,
+                weights,
+                biases,
+            });
+        }
+        if !reader.is_done() {
+            return Err("Trailing bytes after the last layer".to_string());
+        }
+        Self::with_feature_version(layers, feature_version)
+    }
+
+    /// Serialise to the weights file format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend(FORMAT_VERSION.to_le_bytes());
+        bytes.extend(self.feature_version.to_le_bytes());
+        bytes.extend((self.layers.len() as u32).to_le_bytes());
+        for layer in &self.layers {
+            bytes.extend((layer.inputs as u32).to_le_bytes());
+            bytes.extend((layer.outputs as u32).to_le_bytes());
+            for x in layer.weights.iter().chain(&layer.biases) {
+                bytes.extend(x.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Total number of weights and biases
+    pub fn num_parameters(&self) -> usize {
+        self.layers.iter().map(|l| l.weights.len() + l.biases.len()).sum()
+    }
+
+    /// Run the network on an encoded input
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        forward_layers(&self.layers, input)
+    }
+
+    /// Predicted pot share of every combo for each player [OOP, IP]
+    ///
+    /// `ranges` are reach weights indexed by combo ID, as are the shares.
+    pub fn predict(&self, board: &[Card], pot: Chips, stack: Chips, ranges: [&[f64]; 2]) -> [Vec<f64>; 2] {
+        let encoder = FeatureEncoder::new(self.feature_version).expect("checked when the network was built");
+        let (input, permutation) = encoder.encode_public_state(board, pot, stack, ranges);
+        let output = self.forward(&input);
+        // Outputs are indexed by the relabelled combos
+        let mapped: Vec<usize> = (0..NUM_COMBOS as u16)
+            .map(|id| permutation.apply_combo(&Combo::from_id(id).unwrap()).id as usize)
+            .collect();
+        [0, 1].map(|p| mapped.iter().map(|&m| output[p * NUM_COMBOS + m] as f64).collect())
+    }
+}
+
+/// Run an MLP: ReLU after every layer but the last
+pub fn forward_layers(layers: &[Dense], input: &[f32]) -> Vec<f32> {
+    let mut x = input.to_vec();
+    for (i, layer) in layers.iter().enumerate() {
+        x = layer.forward(&x);
+        if i + 1 < layers.len() {
+            x.iter_mut().for_each(|v| *v = v.max(0.0));
+        }
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::cards::parse_board;
+    use crate::solver::isomorphism::SuitPermutation;
+
+    /// Network predicting a constant pot share for every combo
+    fn constant_network(share: f32) -> ValueNetwork {
+        let mut output = Dense::zeros(4, OUTPUT_SIZE);
+        output.biases = vec![share; OUTPUT_SIZE];
+        ValueNetwork::new(vec![Dense::zeros(INPUT_SIZE, 4), output]).unwrap()
+    }
+
+    #[test]
+    fn test_forward() {
+        let hidden = Dense {
+            inputs: 2,
+            outputs: 2,
+            weights: vec![1.0, 1.0, -1.0, 0.0],
+            biases: vec![0.0, 0.5],
+        };
+        let output = Dense {
+            inputs: 2,
+            outputs: 1,
+            weights: vec![2.0, 1.0],
+            biases: vec![0.25],
+        };
+        // Shapes don't match the value network, so run the layers directly
+        let x = hidden.forward(&[1.0, 2.0]);
+        assert_eq!(x, vec![3.0, -0.5]);
+        let relu: Vec<f32> = x.iter().map(|v| v.max(0.0)).collect();
+        assert_eq!(output.forward(&relu), vec![6.25]);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut network = constant_network(0.5);
+        network.layers[0].weights[7] = -1.5;
+        let parsed = ValueNetwork::parse(&network.to_bytes()).unwrap();
+        assert_eq!(parsed, network);
+        assert_eq!(parsed.num_parameters(), INPUT_SIZE * 4 + 4 + 4 * OUTPUT_SIZE + OUTPUT_SIZE);
+    }
+
+    #[test]
+    fn test_predict() {
+        let network = constant_network(0.5);
+        let board = parse_board("Ah Kd Qc 7s").unwrap();
+        let range = vec![1.0; NUM_COMBOS];
+        let [oop, ip] = network.predict(&board, 2000, 10000, [&range, &range]);
+        assert_eq!(oop.len(), NUM_COMBOS);
+        assert!(oop.iter().chain(&ip).all(|&v| v == 0.5));
+    }
+
+    #[test]
+    fn test_predict_maps_canonical_outputs() {
+        // Only the output of one canonical combo is non-zero
+        let board = parse_board("2c 5c 9h").unwrap();
+        let combo: Combo = "AcKc".parse().unwrap();
+        let canonical = SuitPermutation::canonical_for(&board).apply_combo(&combo);
+        let mut output = Dense::zeros(1, OUTPUT_SIZE);
+        output.biases[canonical.id as usize] = 1.0;
+        let network = ValueNetwork::new(vec![Dense::zeros(INPUT_SIZE, 1), output]).unwrap();
+
+        let range = vec![1.0; NUM_COMBOS];
+        let [oop, _] = network.predict(&board, 1000, 5000, [&range, &range]);
+        assert_eq!(oop[combo.id as usize], 1.0);
+        assert_eq!(oop.iter().sum::<f64>(), 1.0);
+    }
+
+    #[test]
+    fn test_old_feature_version() {
+        let v1_inputs = FeatureEncoder::new(1).unwrap().value_input_size();
+        let network = ValueNetwork::with_feature_version(vec![Dense::zeros(v1_inputs, OUTPUT_SIZE)], 1).unwrap();
+        let parsed = ValueNetwork::parse(&network.to_bytes()).unwrap();
+        assert_eq!(parsed.feature_version, 1);
+
+        // Old networks still predict, on the identity permutation
+        let board = parse_board("Ah Kd Qc").unwrap();
+        let range = vec![1.0; NUM_COMBOS];
+        assert_eq!(parsed.predict(&board, 1000, 5000, [&range, &range])[0].len(), NUM_COMBOS);
+        assert!(ValueNetwork::with_feature_version(vec![Dense::zeros(INPUT_SIZE, OUTPUT_SIZE)], 1).is_err());
+    }
+
+    #[test]
+    fn test_invalid_files() {
+        let bytes = constant_network(0.5).to_bytes();
+        assert!(ValueNetwork::parse(&bytes[..bytes.len() - 1]).is_err());
+        assert!(ValueNetwork::parse(b"NOTANETWORK").is_err());
+
+        let mut wrong_features = bytes.clone();
+        wrong_features[10] = 99;
+        assert!(ValueNetwork::parse(&wrong_features).is_err());
+
+        assert!(ValueNetwork::new(vec![Dense::zeros(3, OUTPUT_SIZE)]).is_err());
+        assert!(ValueNetwork::new(vec![Dense::zeros(INPUT_SIZE, 4), Dense::zeros(5, OUTPUT_SIZE)]).is_err());
+    }
+}
@@ -0,0 +1,313 @@
+//! Warm starts from a previous solve
+//!
+//! A finished solve's regrets and strategy sums are saved as a tree that
+//! mirrors its game tree. A new solve of a similar spot (one bet size
+//! changed, a range tweaked) walks its own tree alongside the saved one and
+//! seeds every node it can reach by the same action path, so it starts near
+//! the old equilibrium instead of from uniform play.
+//!
+//! Actions are matched exactly where possible, otherwise to the remaining
+//! action of the same kind with the closest amount; dealt cards only match
+//! exactly. Subtrees without a match start cold.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::cards::{Card, Combo};
+use super::cfr::{info_set_key, CFRSolver};
+use super::game_state::Action;
+use super::game_tree::GameNode;
+use super::isomorphism::SuitPermutation;
+use super::utils::Chips;
+
+/// Regrets and strategy sums of a solve by action path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolverTables {
+    /// Iterations run, so linear averaging carries on with the right weights
+    pub iteration: usize,
+    root: TableNode,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TableNode {
+    actions: Vec<TableAction>,
+    /// Regrets and strategy sums of the acting player's combos
+    combos: Vec<ComboTables>,
+    children: Vec<TableNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ComboTables {
+    combo: u16,
+    regrets: Vec<f64>,
+    strategy: Vec<f64>,
+}
+
+/// Serialisable copy of an [`Action`]; cards by their value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum TableAction {
+    Fold,
+    Check,
+    Call,
+    Bet(Chips),
+    Raise(Chips),
+    AllIn(Chips),
+    Deal(u8),
+}
+
+impl From<&Action> for TableAction {
+    fn from(action: &Action) -> Self {
+        match *action {
+            Action::Fold => TableAction::Fold,
+            Action::Check => TableAction::Check,
+            Action::Call => TableAction::Call,
+            Action::Bet(amount) => TableAction::Bet(amount),
+            Action::Raise(amount) => TableAction::Raise(amount),
+            Action::AllIn(amount) => TableAction::AllIn(amount),
+            Action::Deal(card) => TableAction::Deal(card.value()),
+        }
+    }
+}
+
+impl TableAction {
+    /// Distance to another action, or `None` if they can't stand in for each other
+    fn distance(self, other: TableAction) -> Option<Chips> {
+        use TableAction::*;
+        match (self, other) {
+            (Bet(a), Bet(b)) | (Raise(a), Raise(b)) | (AllIn(a), AllIn(b)) => Some(a.abs_diff(b)),
+            (a, b) => (a == b).then_some(0),
+        }
+    }
+}
+
+impl SolverTables {
+    /// Save the tables of a solver
+    pub fn capture(solver: &CFRSolver) -> Self {
+        SolverTables {
+            iteration: solver.iteration,
+            root: capture_node(solver, &solver.tree.root),
+        }
+    }
+
+    /// Seed a fresh solver with these tables, node by node along matching
+    /// action paths
+    ///
+    /// Combos outside the new ranges are skipped, and new actions without
+    /// a counterpart start with zero regret.
+    pub fn warm_start(&self, solver: &mut CFRSolver) {
+        let mut seed = Seed {
+            hands: &solver.hands,
+            regret_sum: &mut solver.regret_sum,
+            strategy_sum: &mut solver.strategy_sum,
+        };
+        seed.node(&solver.tree.root, &self.root);
+        solver.iteration = self.iteration;
+    }
+
+    /// Relabel the suits of combos and dealt cards
+    pub fn relabel(&self, permutation: &SuitPermutation) -> Self {
+        let mut tables = self.clone();
+        if !permutation.is_identity() {
+            relabel_node(&mut tables.root, permutation);
+        }
+        tables
+    }
+}
+
+fn capture_node(solver: &CFRSolver, node: &GameNode) -> TableNode {
+    let mut table = TableNode {
+        actions: node.children.iter().map(|(action, _)| action.into()).collect(),
+        children: node.children.iter().map(|(_, child)| capture_node(solver, child)).collect(),
+        ..TableNode::default()
+    };
+    if node.is_terminal || node.is_leaf || node.is_chance() {
+        return table;
+    }
+
+    let p = node.state.to_act as usize;
+    for (combo, _) in &solver.hands[p] {
+        let key = info_set_key(&node.state, combo.id);
+        let (Some(regrets), Some(strategy)) = (solver.regret_sum.get(&key), solver.strategy_sum.get(&key)) else {
+            continue;
+        };
+        table.combos.push(ComboTables {
+            combo: combo.id,
+            regrets: regrets.clone(),
+            strategy: strategy.clone(),
+        });
+    }
+    table
+}
+
+/// Tables being written into a solver
+struct Seed<'a> {
+    hands: &'a [Vec<(Combo, f64)>; 2],
+    regret_sum: &'a mut HashMap<String, Vec<f64>>,
+    strategy_sum: &'a mut HashMap<String, Vec<f64>>,
+}
+
+impl Seed<'_> {
+    fn node(&mut self, node: &GameNode, table: &TableNode) {
+        let actions: Vec<TableAction> = node.children.iter().map(|(action, _)| action.into()).collect();
+        let matches = match_actions(&actions, &table.actions);
+
+        if !(node.is_terminal || node.is_leaf || node.is_chance()) {
+            let p = node.state.to_act as usize;
+            for combo in &table.combos {
+                if !self.hands[p].iter().any(|(c, _)| c.id == combo.combo) {
+                    continue;
+                }
+                let mapped = |values: &[f64]| -> Vec<f64> {
+                    matches.iter().map(|m| m.map_or(0.0, |j| values[j])).collect()
+                };
+                let key = info_set_key(&node.state, combo.combo);
+                self.regret_sum.insert(key.clone(), mapped(&combo.regrets));
+                self.strategy_sum.insert(key, mapped(&combo.strategy));
+            }
+        }
+
+        for ((_, child), m) in node.children.iter().zip(&matches) {
+            if let Some(j) = *m {
+                self.node(child, &table.children[j]);
+            }
+        }
+    }
+}
+
+/// Saved action standing in for each new one, each used at most once
+///
+/// Exact matches are taken first, then the closest of the same kind.
+fn match_actions(new: &[TableAction], old: &[TableAction]) -> Vec<Option<usize>> {
+    let mut matches: Vec<Option<usize>> = new.iter().map(|a| old.iter().position(|b| a == b)).collect();
+    let mut used: Vec<bool> = vec![false; old.len()];
+    for j in matches.iter().flatten() {
+        used[*j] = true;
+    }
+    for (i, action) in new.iter().enumerate() {
+        if matches[i].is_some() {
+            continue;
+        }
+        let closest = old
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| !used[*j])
+            .filter_map(|(j, b)| Some((j, action.distance(*b)?)))
+            .min_by_key(|&(_, d)| d);
+        if let Some((j, _)) = closest {
+            used[j] = true;
+            matches[i] = Some(j);
+        }
+    }
+    matches
+}
+
+fn relabel_node(node: &mut TableNode, permutation: &SuitPermutation) {
+    for action in &mut node.actions {
+        if let TableAction::Deal(value) = action {
+            if let Some(card) = Card::from_value(*value) {
+                *value = permutation.apply_card(card).value();
+            }
+        }
+    }
+    for combo in &mut node.combos {
+        if let Some(c) = Combo::from_id(combo.combo) {
+            combo.combo = permutation.apply_combo(&c).id;
+        }
+    }
+    for child in &mut node.children {
+        relabel_node(child, permutation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BetSizes, Player, SolveRequest};
+    use crate::solver::{GameState, GameTree};
+
+    fn solver(bet: &str, ip_range: &str) -> CFRSolver {
+        let state = GameState::from_request(&SolveRequest {
+            player: Player::OOP,
+            board: "Ah Kd Qc 7s 2h".to_string(),
+            effective_stack: 100.0,
+            oop_stack: None,
+            ip_stack: None,
+            starting_pot: 10.0,
+            bet_sizes: Some(BetSizes {
+                oop_bet: bet.to_string(),
+                oop_raise: "a".to_string(),
+                ip_bet: bet.to_string(),
+                ip_raise: "a".to_string(),
+            }),
+            root_bet: None,
+            betting_history: None,
+            oop_range: Some("JsTs,3d3c,AdKc,QdQh".to_string()),
+            ip_range: Some(ip_range.to_string()),
+            rake: None,
+            node_locks: None,
+            group_by_class: false,
+            warm_start_from: None,
+        })
+        .unwrap();
+        CFRSolver::new(GameTree::build(state, 10))
+    }
+
+    #[test]
+    fn test_match_actions() {
+        use TableAction::*;
+        let old = [Check, Bet(500), Bet(1000), AllIn(10000)];
+        assert_eq!(
+            match_actions(&[Check, Bet(600), AllIn(10000)], &old),
+            vec![Some(0), Some(1), Some(3)]
+        );
+        // Each saved action stands in once; folds never match bets
+        assert_eq!(match_actions(&[Bet(1000), Bet(900), Bet(800)], &old), vec![Some(2), Some(1), None]);
+        assert_eq!(match_actions(&[Fold, Deal(3)], &[Call, Deal(4)]), vec![None, None]);
+    }
+
+    #[test]
+    fn test_warm_start_converges_faster() {
+        let mut previous = solver("50", "AsQs,KsKh,8c8d");
+        previous.solve(300);
+        let tables = SolverTables::capture(&previous);
+        assert_eq!(tables.iteration, 300);
+
+        // One bet size changed: the old bets stand in for the new ones
+        let mut cold = solver("60", "AsQs,KsKh,8c8d");
+        cold.solve(30);
+        let mut warm = solver("60", "AsQs,KsKh,8c8d");
+        tables.warm_start(&mut warm);
+        assert!(!warm.regret_sum.is_empty());
+        warm.solve(30);
+        let (cold, warm) = (cold.exploitability(), warm.exploitability());
+        assert!(warm < cold / 2.0, "warm {} vs cold {}", warm, cold);
+    }
+
+    #[test]
+    fn test_relabel_roundtrip() {
+        let mut previous = solver("50", "AsQs,KsKh");
+        previous.solve(20);
+        let tables = SolverTables::capture(&previous);
+        let permutation = SuitPermutation::canonical_for(&previous.tree.root.state.board);
+        let json = serde_json::to_vec(&tables.relabel(&permutation)).unwrap();
+        let restored: SolverTables = serde_json::from_slice(&json).unwrap();
+        let restored = restored.relabel(&permutation.inverse());
+
+        let mut warm = solver("50", "AsQs,KsKh");
+        restored.warm_start(&mut warm);
+        // Same tables under the same keys, up to float formatting
+        let pairs = [
+            (&previous.regret_sum, &warm.regret_sum),
+            (&previous.strategy_sum, &warm.strategy_sum),
+        ];
+        for (saved, seeded) in pairs {
+            assert_eq!(saved.len(), seeded.len());
+            for (key, values) in saved {
+                for (a, b) in values.iter().zip(&seeded[key]) {
+                    assert!((a - b).abs() < 1e-9, "{}: {} vs {}", key, a, b);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,451 @@
+//! Persistent strategy store
+//!
+//! Solves are saved one file per spot in a local directory, keyed by a
+//! canonical hash of the request. Requests that differ only by a suit
+//! relabelling share a key; stored results are kept in canonical suits and
+//! relabelled back on lookup. Each solve may also keep its full strategy
+//! tree in a binary strategy file next to it, memory-mapped for browsing,
+//! and its solver tables for warm starting similar solves.
+//! Old solves are evicted by age and total size.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    models::{SolveRequest, SolveResponse},
+    solver::{
+        cards::{parse_board, Combo},
+        strategy_file::{self, WriteOptions},
+        Card, Range, SolvedTree, SolverTables, Suit, SuitPermutation,
+    },
+};
+
+/// Version of the stored file layout
+const STORE_VERSION: u32 = 1;
+
+/// Canonical identity of a spot
+#[derive(Debug, Clone)]
+pub struct SpotKey {
+    /// Hex SHA-256 of the canonical request
+    pub hash: String,
+    /// Suit relabelling from the request to the canonical spot
+    pub permutation: SuitPermutation,
+}
+
+impl SpotKey {
+    /// Compute the key of a request solved with the given solver settings
+    ///
+    /// Covers everything that changes the returned strategy: board (after
+    /// suit isomorphism), ranges, stacks, pot, bet sizes, root bet, rake,
+    /// node locks and the requested node, and the solver's settings and
+    /// value network (see `Solver::settings_key`), so a reloaded model or
+    /// new iteration count doesn't serve old solves.
+    pub fn of(request: &SolveRequest, settings: &str) -> Result<Self, String> {
+        let board = parse_board(&request.board)?;
+        let permutation = SuitPermutation::canonical_for(&board);
+
+        let mut canonical = request.clone();
+        // Where a solve started doesn't change what it converges to
+        canonical.warm_start_from = None;
+        canonical.board = join_cards(&permutation.apply_board(&board));
+        canonical.bet_sizes = Some(canonical.bet_sizes.unwrap_or_default());
+        canonical.oop_range = Some(canonical_range(request.oop_range.as_deref(), &permutation)?);
+        canonical.ip_range = Some(canonical_range(request.ip_range.as_deref(), &permutation)?);
+        for action in canonical.betting_history.iter_mut().flatten() {
+            relabel_card(&mut action.card, &permutation)?;
+        }
+        for lock in canonical.node_locks.iter_mut().flatten() {
+            for action in &mut lock.betting_history {
+                relabel_card(&mut action.card, &permutation)?;
+            }
+            for combo in &mut lock.combos {
+                let cards = parse_board(&combo.hand)?;
+                combo.hand = join_cards(&cards.iter().map(|&c| permutation.apply_card(c)).collect::<Vec<_>>());
+            }
+            for rule in &mut lock.rules {
+                rule.hands = canonical_range(Some(&rule.hands), &permutation)?;
+            }
+        }
+
+        let bytes = serde_json::to_vec(&canonical).map_err(|e| e.to_string())?;
+        let mut hasher = Sha256::new();
+        hasher.update(settings.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(&bytes);
+        let hash = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        Ok(SpotKey { hash, permutation })
+    }
+}
+
+/// Combo weights of a range in canonical suits, by combo ID
+///
+/// Ranges can name single suited combos, so their combos are relabelled
+/// like the board.
+fn canonical_range(range: Option<&str>, permutation: &SuitPermutation) -> Result<String, String> {
+    let range = match range {
+        Some(s) => Range::parse(s)?,
+        None => Range::full(),
+    };
+    let mut combos: Vec<(u16, f64)> = range
+        .get_combos()
+        .into_iter()
+        .filter_map(|(id, freq)| Some((permutation.apply_combo(&Combo::from_id(id)?).id, freq)))
+        .collect();
+    combos.sort_by_key(|&(id, _)| id);
+    Ok(combos
+        .iter()
+        .map(|(id, freq)| format!("{}:{}", id, freq))
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+fn relabel_card(card: &mut Option<String>, permutation: &SuitPermutation) -> Result<(), String> {
+    if let Some(s) = card {
+        *s = permutation.apply_card(s.parse::<Card>()?).to_string();
+    }
+    Ok(())
+}
+
+/// Space-separated card list
+pub fn join_cards(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Relabel the suits of a solve result
+pub fn relabel_response(response: &SolveResponse, permutation: &SuitPermutation) -> SolveResponse {
+    let mut relabelled = response.clone();
+    if permutation.is_identity() {
+        return relabelled;
+    }
+
+    if let Ok(board) = parse_board(&response.board) {
+        let board: Vec<Card> = board.iter().map(|&c| permutation.apply_card(c)).collect();
+        relabelled.board = join_cards(&board);
+    }
+    for combo in &mut relabelled.combos {
+        // Keep the card order of the original notation
+        if let Ok(cards) = parse_board(&combo.hand) {
+            if let [a, b] = cards[..] {
+                let (a, b) = (permutation.apply_card(a), permutation.apply_card(b));
+                combo.hand = format!("{}{}", a, b);
+                combo.hand_id = Combo::from_cards(a, b).id as u32;
+            }
+        }
+    }
+    for class in relabelled.hand_classes.iter_mut().flatten() {
+        if let Some(suit) = &class.flush_suit {
+            if let Some(s) = suit.chars().next().and_then(Suit::from_char) {
+                class.flush_suit = Some(permutation.apply_suit(s).to_string());
+            }
+        }
+    }
+    relabelled
+}
+
+/// Stored file contents
+#[derive(Serialize, Deserialize)]
+struct StoredSolve {
+    version: u32,
+    key: String,
+    response: SolveResponse,
+}
+
+/// Directory of stored solves with size and age limits
+#[derive(Debug, Clone)]
+pub struct StrategyStore {
+    dir: PathBuf,
+    max_bytes: u64,
+    max_age: Duration,
+}
+
+impl StrategyStore {
+    /// Open (creating if needed) a store directory
+    pub fn open(dir: impl Into<PathBuf>, max_bytes: u64, max_age: Duration) -> Result<Self, String> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Cannot create strategy store '{}': {}", dir.display(), e))?;
+        Ok(StrategyStore {
+            dir,
+            max_bytes,
+            max_age,
+        })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    fn tree_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.tree", key))
+    }
+
+    fn tables_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.tables", key))
+    }
+
+    /// Keys are hex hashes; anything else (e.g. from a URL) is never a file name
+    fn is_valid_key(key: &str) -> bool {
+        !key.is_empty() && key.chars().all(|c| c.is_ascii_hexdigit())
+    }
+
+    /// Stored result for a key (in canonical suits), if present and not expired
+    pub fn get(&self, key: &str) -> Option<SolveResponse> {
+        if !Self::is_valid_key(key) {
+            return None;
+        }
+        let path = self.path(key);
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+        if self.is_expired(modified) {
+            self.remove(key);
+            return None;
+        }
+
+        let stored: StoredSolve = serde_json::from_slice(&fs::read(&path).ok()?).ok()?;
+        (stored.version == STORE_VERSION && stored.key == key).then_some(stored.response)
+    }
+
+    /// Save a result (in canonical suits), then evict to stay within limits
+    pub fn put(&self, key: &str, response: &SolveResponse) -> Result<(), String> {
+        let stored = StoredSolve {
+            version: STORE_VERSION,
+            key: key.to_string(),
+            response: response.clone(),
+        };
+        let bytes = serde_json::to_vec(&stored).map_err(|e| e.to_string())?;
+        self.write(key, "json", &bytes)
+    }
+
+    /// Save the strategy tree of a solve (in canonical suits)
+    pub fn put_tree(&self, key: &str, tree: &SolvedTree) -> Result<(), String> {
+        let bytes = strategy_file::write(tree, WriteOptions::default())?;
+        self.write(key, "tree", &bytes)
+    }
+
+    /// Memory-map the strategy file of a stored solve
+    ///
+    /// Parse the result with `StrategyFile::parse`.
+    pub fn tree(&self, key: &str) -> Option<Mmap> {
+        if !Self::is_valid_key(key) || self.get(key).is_none() {
+            return None;
+        }
+        let file = fs::File::open(self.tree_path(key)).ok()?;
+        // SAFETY: files are only ever replaced by rename and removed, never
+        // modified in place, so the mapped contents cannot change
+        unsafe { Mmap::map(&file) }.ok()
+    }
+
+    /// Save the solver tables of a solve (in canonical suits) for warm starts
+    pub fn put_tables(&self, key: &str, tables: &SolverTables) -> Result<(), String> {
+        let bytes = serde_json::to_vec(tables).map_err(|e| e.to_string())?;
+        self.write(key, "tables", &bytes)
+    }
+
+    /// Solver tables of a stored solve (in canonical suits), if it kept any
+    pub fn tables(&self, key: &str) -> Option<SolverTables> {
+        if !Self::is_valid_key(key) || self.get(key).is_none() {
+            return None;
+        }
+        serde_json::from_slice(&fs::read(self.tables_path(key)).ok()?).ok()
+    }
+
+    /// Write then rename so readers never see a partial file
+    fn write(&self, key: &str, extension: &str, bytes: &[u8]) -> Result<(), String> {
+        let tmp = self.dir.join(format!("{}.{}.tmp", key, extension));
+        fs::write(&tmp, bytes)
+            .and_then(|_| fs::rename(&tmp, self.dir.join(format!("{}.{}", key, extension))))
+            .map_err(|e| format!("Cannot write stored solve: {}", e))?;
+
+        self.evict();
+        Ok(())
+    }
+
+    /// Remove a solve with its tree and tables
+    fn remove(&self, key: &str) {
+        let _ = fs::remove_file(self.path(key));
+        let _ = fs::remove_file(self.tree_path(key));
+        let _ = fs::remove_file(self.tables_path(key));
+    }
+
+    /// Remove expired solves, then the oldest ones until under the size limit
+    ///
+    /// A solve, its tree and its tables are evicted together.
+    pub fn evict(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+
+        // Newest modification time and total size per key
+        let mut solves: HashMap<String, (SystemTime, u64)> = HashMap::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !matches!(path.extension().and_then(|e| e.to_str()), Some("json" | "tree" | "tables")) {
+                continue;
+            }
+            let (Some(key), Ok(meta)) = (path.file_stem().and_then(|s| s.to_str()), entry.metadata()) else {
+                continue;
+            };
+            let Ok(modified) = meta.modified() else {
+                continue;
+            };
+            let solve = solves.entry(key.to_string()).or_insert((modified, 0));
+            solve.0 = solve.0.max(modified);
+            solve.1 += meta.len();
+        }
+
+        let mut solves: Vec<(String, SystemTime, u64)> = solves
+            .into_iter()
+            .filter_map(|(key, (modified, len))| {
+                if self.is_expired(modified) {
+                    self.remove(&key);
+                    return None;
+                }
+                Some((key, modified, len))
+            })
+            .collect();
+
+        solves.sort_by_key(|(_, modified, _)| *modified);
+        let mut total: u64 = solves.iter().map(|(_, _, len)| len).sum();
+        for (key, _, len) in solves {
+            if total <= self.max_bytes {
+                break;
+            }
+            self.remove(&key);
+            total -= len;
+        }
+    }
+
+    fn is_expired(&self, modified: SystemTime) -> bool {
+        modified.elapsed().is_ok_and(|age| age > self.max_age)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::solve::solve_request;
+    use crate::solver::{Solver, SolverConfig};
+
+    fn request(board: &str) -> SolveRequest {
+        serde_json::from_value(serde_json::json!({
+            "player": "OOP",
+            "board": board,
+            "effective_stack": 100,
+            "starting_pot": 20,
+            "oop_range": "AA,KK",
+            "ip_range": "TT,99"
+        }))
+        .unwrap()
+    }
+
+    fn solve(req: &SolveRequest) -> SolveResponse {
+        let solver = Solver::new(SolverConfig {
+            iterations: 10,
+            max_depth: 2,
+            ..SolverConfig::default()
+        });
+        solve_request(req, &solver).unwrap()
+    }
+
+    fn temp_store(name: &str, max_bytes: u64) -> StrategyStore {
+        let dir = std::env::temp_dir().join(format!("strategy-store-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        StrategyStore::open(dir, max_bytes, Duration::from_secs(3600)).unwrap()
+    }
+
+    #[test]
+    fn test_isomorphic_requests_share_key() {
+        let a = SpotKey::of(&request("Ah Kh 2c"), "settings").unwrap();
+        let b = SpotKey::of(&request("Ks 2d As"), "settings").unwrap();
+        assert_eq!(a.hash, b.hash);
+
+        let c = SpotKey::of(&request("Ah Kd 2c"), "settings").unwrap();
+        assert_ne!(a.hash, c.hash);
+
+        let mut req = request("Ah Kh 2c");
+        req.warm_start_from = Some(c.hash.clone());
+        assert_eq!(SpotKey::of(&req, "settings").unwrap().hash, a.hash);
+        req.starting_pot = 25.0;
+        assert_ne!(SpotKey::of(&req, "settings").unwrap().hash, a.hash);
+
+        // Single combos in ranges and lock rules follow the suits of the board
+        let with_combo = |board: &str, combo: &str| {
+            let mut req = request(board);
+            req.oop_range = Some(format!("AA,{}", combo));
+            req.node_locks = Some(vec![serde_json::from_value(serde_json::json!({
+                "rules": [{"hands": combo, "action": "check"}]
+            }))
+            .unwrap()]);
+            SpotKey::of(&req, "settings").unwrap().hash
+        };
+        assert_eq!(with_combo("Ah Kh 2c", "QhJh"), with_combo("Ks 2d As", "QsJs"));
+        assert_ne!(with_combo("Ah Kh 2c", "QhJh"), with_combo("Ah Kh 2c", "QcJc"));
+
+        // Other solver settings or another network give another solve
+        let other = SpotKey::of(&request("Ah Kh 2c"), "other settings").unwrap();
+        assert_ne!(other.hash, a.hash);
+    }
+
+    #[test]
+    fn test_store_roundtrip() {
+        let store = temp_store("roundtrip", u64::MAX);
+        let req = request("Qd Jd 4c 8s 2h");
+        let key = SpotKey::of(&req, "settings").unwrap();
+        let response = solve(&req);
+
+        assert!(store.get(&key.hash).is_none());
+        store
+            .put(&key.hash, &relabel_response(&response, &key.permutation))
+            .unwrap();
+
+        let stored = store.get(&key.hash).unwrap();
+        let restored = relabel_response(&stored, &key.permutation.inverse());
+        assert_eq!(restored.combos[0].hand, response.combos[0].hand);
+        assert_eq!(restored.board, response.board);
+    }
+
+    #[test]
+    fn test_tables_roundtrip() {
+        let store = temp_store("tables", u64::MAX);
+        let mut req = request("Ah Kd 7c 5s 2h");
+        req.ip_range = Some("QQ".to_string());
+        let key = SpotKey::of(&req, "settings").unwrap();
+        let solver = Solver::new(SolverConfig {
+            iterations: 10,
+            max_depth: 2,
+            ..SolverConfig::default()
+        });
+        let (response, cfr) = solver.solve_from(&req, None).unwrap();
+        let tables = SolverTables::capture(&cfr.unwrap()).relabel(&key.permutation);
+
+        store.put_tables(&key.hash, &tables).unwrap();
+        // Tables only count alongside their solve
+        assert!(store.tables(&key.hash).is_none());
+        store.put(&key.hash, &relabel_response(&response, &key.permutation)).unwrap();
+        assert_eq!(store.tables(&key.hash).unwrap().iteration, 10);
+    }
+
+    #[test]
+    fn test_eviction_by_size() {
+        let store = temp_store("evict", 1);
+        let req = request("Ah Kd Qc 7s 2h");
+        let key = SpotKey::of(&req, "settings").unwrap();
+        store.put(&key.hash, &solve(&req)).unwrap();
+
+        // Over the size limit, so the file is evicted right away
+        assert!(store.get(&key.hash).is_none());
+    }
+}
@@ -1,2 +1,49 @@
 // Common test utilities
 // This file can be extended with shared test helpers
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use deeppdcfr_mock_server::config::Config;
+use deeppdcfr_mock_server::model_registry::ModelRegistry;
+use deeppdcfr_mock_server::solver::value_net::{Dense, ValueNetwork, INPUT_SIZE, OUTPUT_SIZE};
+
+/// Server configuration with small trees and few iterations, so tests solve quickly
+pub fn test_config() -> Config {
+    Config {
+        solver_iterations: 10,
+        solver_max_depth: 2,
+        ..Config::default()
+    }
+}
+
+/// Registry with a tiny value network that gives each player half the pot,
+/// so flop and turn trees stop where the next card is dealt
+pub fn test_models() -> ModelRegistry {
+    static PATH: OnceLock<PathBuf> = OnceLock::new();
+    let path = PATH.get_or_init(|| {
+        let mut output = Dense::zeros(1, OUTPUT_SIZE);
+        output.biases.fill(0.5);
+        let network = ValueNetwork::new(vec![Dense::zeros(INPUT_SIZE, 1), output]).unwrap();
+        let path = std::env::temp_dir().join(format!("test-value-{}.net", std::process::id()));
+        std::fs::write(&path, network.to_bytes()).unwrap();
+        path
+    });
+    ModelRegistry::open(Some(path.to_string_lossy().into_owned())).unwrap()
+}
+
+/// Build the full application (routes, CORS, Swagger) the same way `main` does
+#[macro_export]
+macro_rules! init_app {
+    () => {
+        actix_web::test::init_service(
+            actix_web::App::new()
+                .wrap(deeppdcfr_mock_server::create_cors())
+                .service(deeppdcfr_mock_server::create_swagger())
+                .app_data(actix_web::web::Data::new(common::test_config()))
+                .app_data(actix_web::web::Data::new(common::test_models()))
+                .configure(deeppdcfr_mock_server::configure_app),
+        )
+        .await
+    };
+}
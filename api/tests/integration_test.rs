@@ -1,40 +1,175 @@
-use axum::{
-    body::Body,
-    http::{Request, StatusCode},
-};
-use deeppdcfr_mock_server::create_app;
+mod common;
+
+use actix_web::{http::StatusCode, test};
 use serde_json::json;
-use tower::util::ServiceExt; // for `oneshot` and `ready`
 
-#[tokio::test]
+#[actix_web::test]
 async fn test_health_endpoint() {
-    let app = create_app();
-
-    let response = app
-        .oneshot(
-            Request::builder()
-                .uri("/health")
-                .body(Body::empty())
-                .unwrap(),
-        )
-        .await
-        .unwrap();
+    let app = test::init_service(actix_web::App::new().configure(deeppdcfr_mock_server::configure_app)).await;
+
+    let response =
+        test::call_service(&app, test::TestRequest::get().uri("/health").to_request()).await;
 
     assert_eq!(response.status(), StatusCode::OK);
 
-    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
-        .await
-        .unwrap();
-    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let body: serde_json::Value = test::read_body_json(response).await;
 
     assert_eq!(body["status"], "ok");
-    assert_eq!(body["model_loaded"], true);
+    assert_eq!(body["model_loaded"], false);
+    assert_eq!(body["tabular_only"], true);
+    assert_eq!(body["models"], json!([]));
     assert_eq!(body["version"], "0.1.0");
 }
 
-#[tokio::test]
+#[actix_web::test]
+async fn test_health_with_value_network() {
+    use deeppdcfr_mock_server::model_registry::ModelRegistry;
+    use deeppdcfr_mock_server::solver::value_net::{Dense, ValueNetwork, INPUT_SIZE, OUTPUT_SIZE};
+
+    let dir = std::env::temp_dir().join(format!("health-models-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("value.net");
+    let network =
+        ValueNetwork::new(vec![Dense::zeros(INPUT_SIZE, 2), Dense::zeros(2, OUTPUT_SIZE)]).unwrap();
+    std::fs::write(&path, network.to_bytes()).unwrap();
+
+    let models = ModelRegistry::open(Some(path.to_string_lossy().into_owned())).unwrap();
+    let config = deeppdcfr_mock_server::config::Config {
+        admin_token: Some("s3cret".to_string()),
+        ..common::test_config()
+    };
+    let app = test::init_service(
+        actix_web::App::new()
+            .app_data(actix_web::web::Data::new(config))
+            .app_data(actix_web::web::Data::new(models))
+            .configure(deeppdcfr_mock_server::configure_app),
+    )
+    .await;
+
+    let response =
+        test::call_service(&app, test::TestRequest::get().uri("/health").to_request()).await;
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["model_loaded"], true);
+    assert_eq!(body["tabular_only"], false);
+    assert_eq!(body["models"][0]["kind"], "value_network");
+    assert_eq!(body["models"][0]["feature_version"], 2);
+    assert_eq!(body["models"][0]["num_parameters"], network.num_parameters());
+    let hash = body["models"][0]["sha256"].as_str().unwrap().to_string();
+
+    // Swap in new weights through the admin endpoint, which needs the token
+    let mut retrained = network.clone();
+    retrained.layers[1].biases.fill(0.5);
+    std::fs::write(&path, retrained.to_bytes()).unwrap();
+    for token in [None, Some("Bearer wrong"), Some("s3cret")] {
+        let mut request = test::TestRequest::post().uri("/v1/admin/models/reload");
+        if let Some(token) = token {
+            request = request.insert_header(("authorization", token));
+        }
+        let response = test::call_service(&app, request.to_request()).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/admin/models/reload")
+            .insert_header(("authorization", "Bearer s3cret"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_ne!(body["models"][0]["sha256"], hash);
+
+    // A broken file is rejected and the loaded network kept
+    std::fs::write(&path, b"broken").unwrap();
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/admin/models/reload")
+            .insert_header(("authorization", "Bearer s3cret"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let response =
+        test::call_service(&app, test::TestRequest::get().uri("/health").to_request()).await;
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["model_loaded"], true);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[actix_web::test]
+async fn test_reload_without_value_network() {
+    let config = deeppdcfr_mock_server::config::Config {
+        admin_token: Some("s3cret".to_string()),
+        ..common::test_config()
+    };
+    let app = test::init_service(
+        actix_web::App::new()
+            .app_data(actix_web::web::Data::new(config))
+            .configure(deeppdcfr_mock_server::configure_app),
+    )
+    .await;
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/admin/models/reload")
+            .insert_header(("authorization", "Bearer s3cret"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Without ADMIN_TOKEN the endpoint is disabled
+    let app = init_app!();
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/admin/models/reload")
+            .insert_header(("authorization", "Bearer "))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_flop_solve_needs_value_network() {
+    let app = test::init_service(
+        actix_web::App::new()
+            .app_data(actix_web::web::Data::new(common::test_config()))
+            .configure(deeppdcfr_mock_server::configure_app),
+    )
+    .await;
+
+    let solve = |board: &str| {
+        test::TestRequest::post()
+            .uri("/v1/solve")
+            .set_json(json!({
+                "player": "OOP",
+                "board": board,
+                "effective_stack": 100,
+                "starting_pot": 20,
+                "oop_range": "AA",
+                "ip_range": "KK"
+            }))
+            .to_request()
+    };
+
+    // The full flop tree is left to the jobs queue
+    let response = test::call_service(&app, solve("Ah Kd Qc")).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert!(body["message"].as_str().unwrap().contains("/v1/jobs"), "{}", body);
+
+    let response = test::call_service(&app, solve("Ah Kd Qc 7s 2h")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
 async fn test_solve_endpoint() {
-    let app = create_app();
+    let app = init_app!();
 
     let request_body = json!({
         "player": "OOP",
@@ -43,31 +178,26 @@ async fn test_solve_endpoint() {
         "starting_pot": 20
     });
 
-    let response = app
-        .oneshot(
-            Request::builder()
-                .method("POST")
-                .uri("/v1/solve")
-                .header("content-type", "application/json")
-                .body(Body::from(serde_json::to_string(&request_body).unwrap()))
-                .unwrap(),
-        )
-        .await
-        .unwrap();
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/solve")
+            .set_json(&request_body)
+            .to_request(),
+    )
+    .await;
 
     assert_eq!(response.status(), StatusCode::OK);
 
-    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
-        .await
-        .unwrap();
-    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let body: serde_json::Value = test::read_body_json(response).await;
 
     // Verify response structure
     assert_eq!(body["player"], "OOP");
     assert_eq!(body["board"], "Ah Kd Qc");
-    assert_eq!(body["pot"], 20);
-    assert_eq!(body["effective_stack"], 100);
-    assert_eq!(body["num_combos"], 46);
+    assert_eq!(body["pot"], 20.0);
+    assert_eq!(body["effective_stack"], 100.0);
+    // Every combo not blocked by the board
+    assert_eq!(body["num_combos"], 1176);
 
     // Verify actions array
     let actions = body["actions"].as_array().unwrap();
@@ -76,39 +206,36 @@ async fn test_solve_endpoint() {
     assert_eq!(actions[1]["name"], "Bet 33%");
     assert_eq!(actions[2]["name"], "Bet 67%");
     assert_eq!(actions[3]["name"], "All-in");
+    let total: f64 = actions.iter().map(|a| a["frequency"].as_f64().unwrap()).sum();
+    assert!((total - 1.0).abs() < 1e-9);
 
     // Verify combos array
     let combos = body["combos"].as_array().unwrap();
-    assert_eq!(combos.len(), 46);
+    assert_eq!(combos.len(), 1176);
 
-    // Verify first combo structure
-    let first_combo = &combos[0];
-    assert_eq!(first_combo["hand"], "AcAd");
-    assert_eq!(first_combo["hand_id"], 1320);
-    let strategy = first_combo["strategy"].as_array().unwrap();
-    assert_eq!(strategy.len(), 4);
-
-    // Verify strategies sum to 1.0
-    let sum: f64 = strategy.iter().map(|v| v.as_f64().unwrap()).sum();
-    assert!((sum - 1.0).abs() < 0.001);
+    // Every strategy sums to 1.0
+    for combo in combos {
+        let strategy = combo["strategy"].as_array().unwrap();
+        assert_eq!(strategy.len(), 4);
+        let sum: f64 = strategy.iter().map(|v| v.as_f64().unwrap()).sum();
+        assert!((sum - 1.0).abs() < 0.001, "{}", combo["hand"]);
+    }
 }
 
-#[tokio::test]
+#[actix_web::test]
 async fn test_cors_headers() {
-    let app = create_app();
-
-    let response = app
-        .oneshot(
-            Request::builder()
-                .method("OPTIONS")
-                .uri("/health")
-                .header("origin", "http://example.com")
-                .header("access-control-request-method", "GET")
-                .body(Body::empty())
-                .unwrap(),
-        )
-        .await
-        .unwrap();
+    let app = init_app!();
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::default()
+            .method(actix_web::http::Method::OPTIONS)
+            .uri("/health")
+            .insert_header(("origin", "http://example.com"))
+            .insert_header(("access-control-request-method", "GET"))
+            .to_request(),
+    )
+    .await;
 
     // CORS should allow the request
     assert!(response.status().is_success() || response.status() == StatusCode::NO_CONTENT);
@@ -117,19 +244,12 @@ async fn test_cors_headers() {
     assert!(headers.contains_key("access-control-allow-origin"));
 }
 
-#[tokio::test]
+#[actix_web::test]
 async fn test_swagger_ui_accessible() {
-    let app = create_app();
-
-    let response = app
-        .oneshot(
-            Request::builder()
-                .uri("/docs/")
-                .body(Body::empty())
-                .unwrap(),
-        )
-        .await
-        .unwrap();
+    let app = init_app!();
+
+    let response =
+        test::call_service(&app, test::TestRequest::get().uri("/docs/").to_request()).await;
 
     // Swagger UI should be accessible (returns HTML or redirects)
     assert!(
@@ -137,3 +257,970 @@ async fn test_swagger_ui_accessible() {
         "Swagger UI should be accessible"
     );
 }
+
+#[actix_web::test]
+async fn test_solve_rejects_invalid_node_lock() {
+    let app = init_app!();
+
+    // AhAd is blocked by the Ah on the board
+    let request_body = json!({
+        "player": "OOP",
+        "board": "Ah Kd Qc",
+        "effective_stack": 100,
+        "starting_pot": 20,
+        "node_locks": [{
+            "betting_history": [],
+            "combos": [{ "hand": "AhAd", "strategy": [1.0, 0.0, 0.0, 0.0] }]
+        }]
+    });
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/solve")
+            .set_json(&request_body)
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["error"], "validation_error");
+}
+
+#[actix_web::test]
+async fn test_solve_asymmetric_stacks_facing_bet() {
+    let app = init_app!();
+
+    let request_body = json!({
+        "player": "OOP",
+        "board": "Ah Kd Qc",
+        "effective_stack": 100,
+        "oop_stack": 60.5,
+        "ip_stack": 150,
+        "starting_pot": 5.5,
+        "root_bet": { "position": "IP", "amount": 1.8 }
+    });
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/solve")
+            .set_json(&request_body)
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["pot"], 7.3);
+    assert_eq!(body["effective_stack"], 60.5);
+}
+
+#[actix_web::test]
+async fn test_preflop_requires_equity_table() {
+    let app = init_app!();
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/solve/preflop")
+            .set_json(json!({ "effective_stack": 10 }))
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[actix_web::test]
+async fn test_preflop_solve_endpoint() {
+    use deeppdcfr_mock_server::solver::EquityTable;
+
+    // Synthetic table where the higher top card always wins
+    let equity = EquityTable::from_fn(|hero, villain| match hero.high.cmp(&villain.high) {
+        std::cmp::Ordering::Greater => 1.0,
+        std::cmp::Ordering::Less => 0.0,
+        std::cmp::Ordering::Equal => 0.5,
+    });
+    let app = test::init_service(
+        actix_web::App::new()
+            .app_data(actix_web::web::Data::new(equity))
+            .configure(deeppdcfr_mock_server::configure_app),
+    )
+    .await;
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/solve/preflop")
+            .set_json(json!({
+                "effective_stack": 10,
+                "ante": 0.1,
+                "iterations": 50
+            }))
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["player"], "SB");
+    assert_eq!(body["hands"].as_array().unwrap().len(), 169);
+    assert_eq!(body["hands"][0]["hand"], "AA");
+
+    // An invalid path is rejected
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/solve/preflop")
+            .set_json(json!({ "effective_stack": 10, "path": "q" }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+async fn test_solve_group_by_class() {
+    let app = init_app!();
+
+    let request_body = json!({
+        "player": "OOP",
+        "board": "Ah Kd Qc",
+        "effective_stack": 100,
+        "starting_pot": 20,
+        "oop_range": "AA,AKs,AKo,KK,QQ:0.5,JJ-99,AQs-ATs,KQs",
+        "group_by_class": true
+    });
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/solve")
+            .set_json(&request_body)
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(response).await;
+    let classes = body["hand_classes"].as_array().unwrap();
+    assert_eq!(classes[0]["hand"], "AA");
+    assert_eq!(classes[0]["num_combos"], 3);
+
+    let qq = classes.iter().find(|c| c["hand"] == "QQ").unwrap();
+    assert_eq!(qq["weight"], 1.5);
+}
+
+#[actix_web::test]
+async fn test_solve_job_lifecycle() {
+    let jobs = deeppdcfr_mock_server::create_job_manager(
+        &common::test_config(),
+        None,
+        Some(actix_web::web::Data::new(common::test_models())),
+    );
+    let app = test::init_service(
+        actix_web::App::new()
+            .app_data(actix_web::web::Data::new(jobs))
+            .configure(deeppdcfr_mock_server::configure_app),
+    )
+    .await;
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/jobs")
+            .set_json(json!({
+                "player": "OOP",
+                "board": "Ah Kd Qc",
+                "effective_stack": 100,
+                "starting_pot": 20,
+                "oop_range": "AA,KK,QQ",
+                "ip_range": "JJ,TT,99"
+            }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    let uri = format!("/v1/jobs/{}", body["job_id"].as_str().unwrap());
+
+    // Poll until the job completes
+    let mut body = serde_json::Value::Null;
+    for _ in 0..500 {
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        body = test::read_body_json(response).await;
+        if body["status"] == "completed" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(body["status"], "completed");
+    assert_eq!(body["result"]["pot"], 20.0);
+
+    // Deleting a finished job forgets it
+    let response =
+        test::call_service(&app, test::TestRequest::delete().uri(&uri).to_request()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = test::call_service(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_solve_job_progress_and_cancel() {
+    use deeppdcfr_mock_server::models::{JobStatus, SolveRequest};
+
+    let config = deeppdcfr_mock_server::config::Config {
+        solver_iterations: 1_000_000,
+        job_progress_every: 2,
+        job_workers: 1,
+        ..common::test_config()
+    };
+    let jobs = deeppdcfr_mock_server::create_job_manager(
+        &config,
+        None,
+        Some(actix_web::web::Data::new(common::test_models())),
+    );
+    let request: SolveRequest = serde_json::from_value(json!({
+        "player": "OOP",
+        "board": "Ah Kd Qc 7s 2h",
+        "effective_stack": 100,
+        "starting_pot": 20,
+        "oop_range": "AA,KK",
+        "ip_range": "TT,99"
+    }))
+    .unwrap();
+
+    // Progress comes from the solver loop while the job runs
+    let first = jobs.submit(request.clone()).unwrap().job_id;
+    let mut job = jobs.get(&first).unwrap();
+    for _ in 0..500 {
+        if job.progress.iteration > 0 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        job = jobs.get(&first).unwrap();
+    }
+    assert_eq!(job.status, JobStatus::Running);
+    assert!(job.progress.iteration > 0 && job.progress.iteration < 1_000_000);
+    assert!(job.progress.exploitability.unwrap() >= 0.0);
+
+    // Cancelling stops the solve, freeing the only worker for the next job
+    jobs.cancel(&first).unwrap();
+    let second = jobs.submit(request).unwrap().job_id;
+    let mut job = jobs.get(&second).unwrap();
+    for _ in 0..500 {
+        if job.status == JobStatus::Running {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        job = jobs.get(&second).unwrap();
+    }
+    assert_eq!(job.status, JobStatus::Running);
+    jobs.cancel(&second).unwrap();
+}
+
+#[actix_web::test]
+async fn test_solve_job_resumes_from_checkpoint() {
+    use deeppdcfr_mock_server::jobs::JobManager;
+    use deeppdcfr_mock_server::models::{JobStatus, SolveRequest};
+    use deeppdcfr_mock_server::solver::CheckpointConfig;
+
+    let dir = std::env::temp_dir().join(format!("job-checkpoints-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = deeppdcfr_mock_server::config::Config {
+        job_checkpoint_dir: Some(dir.to_string_lossy().into_owned()),
+        job_checkpoint_every: 5,
+        ..common::test_config()
+    };
+    let models = common::test_models();
+    let request: SolveRequest = serde_json::from_value(json!({
+        "player": "OOP",
+        "board": "Ah Kd Qc 7s 2h",
+        "effective_stack": 100,
+        "starting_pot": 20,
+        "oop_range": "AA,KK",
+        "ip_range": "TT,99"
+    }))
+    .unwrap();
+
+    // A run that got further than this job would go before the server stopped
+    let path = deeppdcfr_mock_server::job_checkpoint_path(&dir, &request);
+    std::fs::create_dir_all(&dir).unwrap();
+    let earlier = deeppdcfr_mock_server::config::Config {
+        solver_iterations: 20,
+        ..config.clone()
+    };
+    let expected = deeppdcfr_mock_server::api::solve::request_solver(&earlier, Some(&models))
+        .with_checkpoint(CheckpointConfig { path: path.clone(), every: 5 })
+        .solve(&request)
+        .unwrap();
+    assert!(path.exists());
+
+    let jobs: JobManager =
+        deeppdcfr_mock_server::create_job_manager(&config, None, Some(actix_web::web::Data::new(models)));
+    let id = jobs.submit(request).unwrap().job_id;
+    let mut job = jobs.get(&id).unwrap();
+    for _ in 0..500 {
+        if job.status == JobStatus::Completed {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        job = jobs.get(&id).unwrap();
+    }
+    assert_eq!(job.status, JobStatus::Completed);
+
+    // The job carries on from the checkpoint's 20 iterations instead of
+    // solving its own 10 from scratch, then removes the checkpoint
+    let result = job.result.unwrap();
+    for (got, want) in result.combos.iter().zip(&expected.combos) {
+        assert_eq!(got.strategy, want.strategy);
+    }
+    assert!(!path.exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[actix_web::test]
+async fn test_solve_job_events() {
+    use actix_web::body::MessageBody;
+    use deeppdcfr_mock_server::jobs::{JobManager, SolveFn};
+    use std::sync::{mpsc, Arc, Mutex};
+
+    // The solve waits after each progress report until the test lets it
+    // go on, so the stream sees every snapshot however fast the solve is
+    let config = deeppdcfr_mock_server::config::Config {
+        solver_iterations: 40,
+        ..common::test_config()
+    };
+    let models = common::test_models();
+    let (proceed, wait) = mpsc::channel::<()>();
+    let wait = Arc::new(Mutex::new(wait));
+    let solve: Arc<SolveFn> = Arc::new(move |req, control| {
+        let report = deeppdcfr_mock_server::job_progress(control, config.solver_iterations);
+        let wait = Arc::clone(&wait);
+        let solver = deeppdcfr_mock_server::api::solve::request_solver(&config, Some(&models)).with_progress(
+            2,
+            Arc::new(move |cfr, remaining| {
+                let keep_going = report(cfr, remaining);
+                // Runs free once the test drops its sender
+                let _ = wait.lock().unwrap().recv();
+                keep_going
+            }),
+        );
+        deeppdcfr_mock_server::api::solve::solve_stored(req, None, &solver)
+    });
+    let app = test::init_service(
+        actix_web::App::new()
+            .app_data(actix_web::web::Data::new(JobManager::new(1, 4, 40, solve)))
+            .configure(deeppdcfr_mock_server::configure_app),
+    )
+    .await;
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/jobs")
+            .set_json(json!({
+                "player": "OOP",
+                "board": "Ah Kd Qc",
+                "effective_stack": 100,
+                "starting_pot": 20,
+                "oop_range": "AA,KK,QQ",
+                "ip_range": "JJ,TT,99"
+            }))
+            .to_request(),
+    )
+    .await;
+    let body: serde_json::Value = test::read_body_json(response).await;
+    let uri = format!("/v1/jobs/{}/events", body["job_id"].as_str().unwrap());
+
+    let response = test::call_service(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+
+    // Each chunk of the stream is one event
+    let mut stream = Box::pin(response.into_body());
+    let mut next_event = async || {
+        let chunk = std::future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await;
+        chunk.map(|c| String::from_utf8(c.unwrap().to_vec()).unwrap())
+    };
+
+    // Snapshots from the running solve: iterations rise, each with the
+    // root strategy so far
+    let mut progress: Vec<serde_json::Value> = Vec::new();
+    while progress.len() < 3 {
+        let event = next_event().await.unwrap();
+        let data = event.strip_prefix("event: progress\ndata: ").unwrap();
+        let snapshot: serde_json::Value = serde_json::from_str(data.trim_end()).unwrap();
+        let iteration = snapshot["iteration"].as_u64().unwrap();
+        if iteration > progress.last().map_or(0, |p| p["iteration"].as_u64().unwrap()) {
+            progress.push(snapshot);
+            proceed.send(()).unwrap();
+        }
+    }
+    for snapshot in &progress {
+        let actions = snapshot["root_actions"].as_array().unwrap();
+        let total: f64 = actions.iter().map(|a| a["frequency"].as_f64().unwrap()).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+    drop(proceed);
+
+    // The stream ends with the full solve result
+    let mut last = String::new();
+    while let Some(event) = next_event().await {
+        last = event;
+    }
+    let last = last.trim_end();
+    assert!(last.starts_with("event: result\ndata: "));
+    let result: serde_json::Value =
+        serde_json::from_str(last.trim_start_matches("event: result\ndata: ")).unwrap();
+    assert_eq!(result["num_combos"], 9);
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/v1/jobs/job-999/events").to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_solve_batch() {
+    let app = init_app!();
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/solve/batch")
+            .set_json(json!({
+                "player": "OOP",
+                "boards": ["Ah Kd Qc", "7s 7d 2c", "Jh 8h 3h"],
+                "effective_stack": 100,
+                "starting_pot": 20,
+                "oop_range": "AA,KK,QQ",
+                "ip_range": "JJ,TT,99"
+            }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["num_boards"], 3);
+    assert_eq!(body["boards"][1]["board"], "7s 7d 2c");
+    assert_eq!(body["boards"][1]["textures"], json!(["rainbow", "paired", "7-high"]));
+
+    let textures = body["textures"].as_array().unwrap();
+    assert_eq!(textures[0]["texture"], "all");
+    assert_eq!(textures[0]["num_boards"], 3);
+    let total: f64 = textures[0]["actions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["frequency"].as_f64().unwrap())
+        .sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    let monotone = textures.iter().find(|t| t["texture"] == "monotone").unwrap();
+    assert_eq!(monotone["num_boards"], 1);
+
+    // Overpairs have a positive EV on every board, averaged per texture
+    // over the raw flops each board stands for
+    let evs: Vec<f64> = body["boards"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["ev"].as_f64().unwrap())
+        .collect();
+    assert!(evs.iter().all(|&ev| ev > 0.0), "{:?}", evs);
+    let weights: Vec<u64> = body["boards"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["weight"].as_u64().unwrap())
+        .collect();
+    assert_eq!(weights, vec![24, 12, 4]);
+    let average = textures[0]["average_ev"].as_f64().unwrap();
+    assert!((average - (24.0 * evs[0] + 12.0 * evs[1] + 4.0 * evs[2]) / 40.0).abs() < 1e-9);
+    assert_eq!(monotone["average_ev"].as_f64(), Some(evs[2]));
+
+    // Named flop subsets expand to canonical flops
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/solve/batch")
+            .set_json(json!({
+                "player": "OOP",
+                "flop_subset": "monotone",
+                "effective_stack": 100,
+                "starting_pot": 20,
+                "bet_sizes": { "oop_bet": "a", "oop_raise": "a", "ip_bet": "a", "ip_raise": "a" },
+                "oop_range": "AA,KK,QQ",
+                "ip_range": "JJ,TT,99"
+            }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["num_boards"], 286);
+
+    // Invalid boards reject the whole batch
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/solve/batch")
+            .set_json(json!({
+                "player": "OOP",
+                "boards": ["Ah Kd Qc", "Ah Ah 2c"],
+                "effective_stack": 100,
+                "starting_pot": 20
+            }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+async fn test_solve_served_from_store() {
+    use deeppdcfr_mock_server::store::StrategyStore;
+
+    let dir = std::env::temp_dir().join(format!("strategy-store-it-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let store = actix_web::web::Data::new(
+        StrategyStore::open(&dir, u64::MAX, std::time::Duration::from_secs(3600)).unwrap(),
+    );
+    let app = test::init_service(
+        actix_web::App::new()
+            .app_data(actix_web::web::Data::new(common::test_config()))
+            .app_data(actix_web::web::Data::new(common::test_models()))
+            .app_data(store.clone())
+            .configure(deeppdcfr_mock_server::configure_app),
+    )
+    .await;
+
+    let mut ids = Vec::new();
+    for board in ["Ah Kh Qc", "Ah Kh Qc", "Ad Kd Qs"] {
+        let response = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/v1/solve")
+                .set_json(json!({
+                    "player": "OOP",
+                    "board": board,
+                    "effective_stack": 100,
+                    "starting_pot": 20
+                }))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(response).await;
+        assert_eq!(body["board"], board);
+        ids.push(body["solve_id"].as_str().unwrap().to_string());
+    }
+
+    // Repeated and suit-isomorphic requests share one stored solve
+    assert_eq!(ids[0], ids[1]);
+    assert_eq!(ids[0], ids[2]);
+    // One solve with its strategy tree and solver tables
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 3);
+    let cold = store.tables(&ids[0]).unwrap().iteration;
+
+    // A nearby spot can warm start from a stored solve, not from an unknown one
+    for (from, status) in [(ids[0].as_str(), StatusCode::OK), ("abc123", StatusCode::NOT_FOUND)] {
+        let response = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/v1/solve")
+                .set_json(json!({
+                    "player": "OOP",
+                    "board": "Ah Kh Qc",
+                    "effective_stack": 100,
+                    "starting_pot": 22,
+                    "warm_start_from": from
+                }))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), status);
+        if status == StatusCode::OK {
+            // The warm solve carries on from the stored solve's iterations
+            let body: serde_json::Value = test::read_body_json(response).await;
+            let warm = store.tables(body["solve_id"].as_str().unwrap()).unwrap().iteration;
+            assert_eq!(warm, 2 * cold);
+        }
+    }
+}
+
+#[actix_web::test]
+async fn test_warm_start_without_store() {
+    let app = init_app!();
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/solve")
+            .set_json(json!({
+                "player": "OOP",
+                "board": "Ah Kh Qc",
+                "effective_stack": 100,
+                "starting_pot": 20,
+                "warm_start_from": "abc123"
+            }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+async fn test_browse_stored_solve_node() {
+    use deeppdcfr_mock_server::store::StrategyStore;
+
+    let dir = std::env::temp_dir().join(format!("strategy-store-nodes-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let store = StrategyStore::open(&dir, u64::MAX, std::time::Duration::from_secs(3600)).unwrap();
+    let app = test::init_service(
+        actix_web::App::new()
+            .app_data(actix_web::web::Data::new(common::test_config()))
+            .app_data(actix_web::web::Data::new(common::test_models()))
+            .app_data(actix_web::web::Data::new(store))
+            .configure(deeppdcfr_mock_server::configure_app),
+    )
+    .await;
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/solve")
+            .set_json(json!({
+                "player": "OOP",
+                "board": "Ah Kd Qc",
+                "effective_stack": 100,
+                "starting_pot": 20,
+                "oop_range": "AA,KK,QQ"
+            }))
+            .to_request(),
+    )
+    .await;
+    let solved: serde_json::Value = test::read_body_json(response).await;
+    let id = solved["solve_id"].as_str().unwrap();
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::get().uri(&format!("/v1/solves/{}/node", id)).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let root: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(root["node_type"], "decision");
+    assert_eq!(root["path"], "");
+    // Only combos in the range reach the node: 3 each of AA, KK and QQ
+    assert_eq!(root["node"]["num_combos"], 9);
+    let paths: Vec<&str> = root["children"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, vec!["x", "b33", "b67", "a"]);
+    let frequencies: f64 = root["children"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["frequency"].as_f64().unwrap())
+        .sum();
+    assert!((frequencies - 1.0).abs() < 1e-3);
+
+    // Sizes match the closest bet in the tree
+    let response = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/v1/solves/{}/node?path=b30", id))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let node: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(node["path"], "b33");
+    assert_eq!(node["node_type"], "decision");
+    assert_eq!(node["node"]["player"], "IP");
+    let strategies = node["node"]["combos"].as_array().unwrap();
+    assert!(!strategies.is_empty());
+    for combo in strategies {
+        let total: f64 = combo["strategy"].as_array().unwrap().iter().map(|p| p.as_f64().unwrap()).sum();
+        assert!((total - 1.0).abs() < 1e-3);
+    }
+
+    // Deeper nodes resolve too, down to the end of the street
+    let response = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/v1/solves/{}/node?path=x/b33/r50", id))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let node: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(node["node_type"], "decision");
+    assert_eq!(node["node"]["player"], "IP");
+    let response = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/v1/solves/{}/node?path=x/b33/f", id))
+            .to_request(),
+    )
+    .await;
+    let node: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(node["node_type"], "terminal");
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/v1/solves/{}/node?path=c", id))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/v1/solves/0123abcd/node").to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    // The same node as Pio-style CSV
+    let response = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/v1/solves/{}/export", id))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("content-type").unwrap(), "text/csv");
+    let csv = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("Hand,Weight,EV,Check,Bet 33%,Bet 67%,All-in"));
+    assert_eq!(lines.count(), 9);
+}
+
+#[actix_web::test]
+async fn test_import_pio_config() {
+    let app = init_app!();
+
+    let script = "set_board AhKdQc\nset_pot 0 0 20\nset_eff_stack 100\nset_range OOP AA,KK\nset_bet_sizes OOP,flop,bet,50\n";
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/import/pio?player=IP")
+            .insert_header(("content-type", "text/plain"))
+            .set_payload(script)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["player"], "IP");
+    assert_eq!(body["board"], "Ah Kd Qc");
+    assert_eq!(body["bet_sizes"]["oop_bet"], "50");
+    assert_eq!(body["oop_range"], "AA,KK");
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/import/pio")
+            .insert_header(("content-type", "text/plain"))
+            .set_payload("set_board AhKdQc")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+async fn test_review_hand_histories() {
+    let app = init_app!();
+
+    let histories = "\
+PokerStars Hand #245871234567: Hold'em No Limit ($0.50/$1.00 USD) - 2024/03/01 20:15:00 ET
+Table 'Alcor II' 6-max Seat #3 is the button
+Seat 1: Villain A ($104.50 in chips)
+Seat 2: Hero ($100 in chips)
+Seat 3: btn_player ($87.25 in chips)
+Villain A: posts small blind $0.50
+Hero: posts big blind $1
+*** HOLE CARDS ***
+Dealt to Hero [Ah Kd]
+btn_player: raises $1.50 to $2.50
+Villain A: folds
+Hero: calls $1.50
+*** FLOP *** [Ac 7d 2h]
+Hero: checks
+btn_player: bets $3
+Hero: calls $3
+*** TURN *** [Ac 7d 2h] [9s]
+Hero: checks
+btn_player: checks
+*** RIVER *** [Ac 7d 2h 9s] [4c]
+Hero: bets $8
+btn_player: folds
+Uncalled bet ($8) returned to Hero
+*** SUMMARY ***
+
+PokerStars Hand #245871234568: Hold'em No Limit ($0.50/$1.00 USD) - 2024/03/01 20:16:00 ET
+Table 'Alcor II' 6-max Seat #1 is the button
+Seat 1: Villain A ($104.50 in chips)
+Seat 2: Hero ($100 in chips)
+Hero: posts small blind $0.50
+Villain A: posts big blind $1
+*** HOLE CARDS ***
+Dealt to Hero [7c 2d]
+Hero: folds
+Uncalled bet ($0.50) returned to Villain A
+*** SUMMARY ***
+";
+
+    let response = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/hand-history/review")
+            .insert_header(("content-type", "text/plain"))
+            .set_payload(histories)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["num_hands"], 2);
+
+    let hand = &body["hands"][0];
+    assert_eq!(hand["hand_id"], "245871234567");
+    assert_eq!(hand["hero_position"], "OOP");
+    let decisions = hand["decisions"].as_array().unwrap();
+    assert_eq!(decisions.len(), 4);
+    assert_eq!(decisions[1]["action_taken"]["action"], "call");
+    assert_eq!(decisions[3]["board"], "Ac 7d 2h 9s 4c");
+    assert_eq!(decisions[3]["request"]["betting_history"].as_array().unwrap().len(), 7);
+    let errors: Vec<&serde_json::Value> = decisions.iter().map(|d| &d["error"]).collect();
+    assert!(errors.iter().all(|e| e.is_null()), "{:?}", errors);
+    // The solver's strategy for hero's own hand at every decision
+    for decision in decisions {
+        let strategy = decision["hero_strategy"].as_array().unwrap();
+        assert_eq!(strategy.len(), decision["actions"].as_array().unwrap().len());
+        let sum: f64 = strategy.iter().map(|p| p.as_f64().unwrap()).sum();
+        assert!((sum - 1.0).abs() < 1e-6);
+    }
+
+    assert_eq!(body["hands"][1]["error"], "The hand ended before the flop");
+}
+
+#[actix_web::test]
+async fn test_cli_solve_show_and_range() {
+    use std::process::Command;
+
+    let cli = env!("CARGO_BIN_EXE_deeppdcfr-cli");
+    let dir = std::env::temp_dir().join(format!("deeppdcfr-cli-it-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let request = dir.join("request.json");
+    let tree = dir.join("spot.tree");
+    std::fs::write(
+        &request,
+        json!({
+            "player": "OOP",
+            "board": "Ah Kd Qc 7s",
+            "effective_stack": 100,
+            "starting_pot": 20,
+            "oop_range": "AA,KK,QQ",
+            "ip_range": "JJ,TT,AKs"
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    // Without a value network the turn is solved through the river
+    let output = Command::new(cli)
+        .args(["solve", "--iterations", "20", "--max-depth", "2", "--request"])
+        .arg(&request)
+        .arg("--out")
+        .arg(&tree)
+        .env_remove("VALUE_NETWORK_PATH")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Wrote "));
+
+    let output = Command::new(cli).arg("show").arg(&tree).arg("--csv").output().unwrap();
+    assert!(output.status.success());
+    let csv = String::from_utf8(output.stdout).unwrap();
+    assert!(csv.starts_with("Hand,Weight,EV,Check,Bet 33%,Bet 67%,All-in\n"));
+
+    // Every node of the tree is stored, down through the river card
+    let output = Command::new(cli).arg("show").arg(&tree).args(["--path", "x/b33/r50/c"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Chance node"));
+    let output = Command::new(cli)
+        .arg("show")
+        .arg(&tree)
+        .args(["--path", "x/b33/r50/c/Td"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Board Ah Kd Qc 7s Td"), "{}", stdout);
+    assert!(stdout.contains("OOP to act"), "{}", stdout);
+    let output = Command::new(cli).arg("show").arg(&tree).args(["--path", "b67/f"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Terminal node"));
+
+    let output = Command::new(cli).args(["range", "AA,KK:0.5", "--board", "As"]).output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("AhAd,AhAc,AdAc,KK:0.5\n"), "{}", stdout);
+    assert!(stdout.contains("9 combos (6.0 weighted)"));
+
+    let output = Command::new(cli).args(["range", "ZZ"]).output().unwrap();
+    assert!(!output.status.success());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[actix_web::test]
+async fn test_cli_datagen_and_train() {
+    use std::process::Command;
+
+    let cli = env!("CARGO_BIN_EXE_deeppdcfr-cli");
+    let dir = std::env::temp_dir().join(format!("deeppdcfr-cli-train-{}", std::process::id()));
+    let data = dir.join("data");
+    let weights = dir.join("value.net");
+
+    let output = Command::new(cli)
+        .args(["datagen", "--samples", "3", "--shard-size", "2", "--density", "0.01"])
+        .args(["--bets", "a", "--max-depth", "1", "--max-iterations", "20", "--seed", "4", "--out"])
+        .arg(&data)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("now holds 2 shards"));
+
+    let output = Command::new(cli)
+        .args(["train", "--hidden", "4", "--epochs", "2", "--validation", "0.34", "--data"])
+        .arg(&data)
+        .arg("--out")
+        .arg(&weights)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Loaded 3 samples"));
+    assert!(stdout.contains("Epoch   2: train loss"), "{}", stdout);
+    assert!(deeppdcfr_mock_server::solver::ValueNetwork::load(&weights).is_ok());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}